@@ -1,4 +1,4 @@
-use super::{event::AbiEvent, function::AbiFunction};
+use super::{error::AbiError, event::AbiEvent, function::AbiFunction};
 
 use serde::{ser::SerializeSeq, Serialize, Serializer};
 
@@ -9,11 +9,16 @@ pub struct AbiContract {
 
     /// Events emitted from the contract.
     events: Vec<AbiEvent>,
+
+    /// Custom errors the contract may revert with.
+    errors: Vec<AbiError>,
 }
 
 impl Serialize for AbiContract {
     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
-        let mut seq = s.serialize_seq(Some(self.funcs.len() + self.events.len()))?;
+        let mut seq = s.serialize_seq(Some(
+            self.funcs.len() + self.events.len() + self.errors.len(),
+        ))?;
         for func in &self.funcs {
             seq.serialize_element(func)?;
         }
@@ -22,12 +27,32 @@ impl Serialize for AbiContract {
             seq.serialize_element(event)?;
         }
 
+        for error in &self.errors {
+            seq.serialize_element(error)?;
+        }
+
         seq.end()
     }
 }
 
 impl AbiContract {
-    pub fn new(funcs: Vec<AbiFunction>, events: Vec<AbiEvent>) -> Self {
-        Self { funcs, events }
+    pub fn new(funcs: Vec<AbiFunction>, events: Vec<AbiEvent>, errors: Vec<AbiError>) -> Self {
+        Self {
+            funcs,
+            events,
+            errors,
+        }
+    }
+
+    pub fn funcs(&self) -> &[AbiFunction] {
+        &self.funcs
+    }
+
+    pub fn events(&self) -> &[AbiEvent] {
+        &self.events
+    }
+
+    pub fn errors(&self) -> &[AbiError] {
+        &self.errors
     }
 }
@@ -0,0 +1,105 @@
+use super::types::{AbiTupleField, AbiType};
+
+use fe_common::utils::keccak;
+use serde::Serialize;
+
+/// A custom error, i.e. a struct that is used as `revert` data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AbiError {
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    pub name: String,
+    pub inputs: Vec<AbiTupleField>,
+}
+
+impl AbiError {
+    pub fn new(name: String, fields: Vec<(String, AbiType)>) -> Self {
+        let inputs = fields
+            .into_iter()
+            .map(|(name, ty)| AbiTupleField::new(name, ty))
+            .collect();
+
+        Self {
+            ty: "error",
+            name,
+            inputs,
+        }
+    }
+
+    pub fn signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name,
+            self.inputs
+                .iter()
+                .map(|input| input.ty.selector_type_name())
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+
+    /// Returns first 4 bytes of signature hash in hex.
+    pub fn selector_hex(&self) -> String {
+        keccak::partial(self.signature().as_bytes(), 4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_test::{assert_ser_tokens, Token};
+
+    fn test_error() -> AbiError {
+        AbiError::new(
+            "MyError".into(),
+            vec![
+                ("code".into(), AbiType::UInt(256)),
+                ("owner".into(), AbiType::Address),
+            ],
+        )
+    }
+
+    #[test]
+    fn serialize_error() {
+        let error = test_error();
+
+        assert_ser_tokens(
+            &error,
+            &[
+                Token::Struct {
+                    name: "AbiError",
+                    len: 3,
+                },
+                Token::Str("type"),
+                Token::Str("error"),
+                Token::String("name"),
+                Token::String("MyError"),
+                Token::Str("inputs"),
+                Token::Seq { len: Some(2) },
+                Token::Map { len: None },
+                Token::String("name"),
+                Token::String("code"),
+                Token::String("type"),
+                Token::String("uint256"),
+                Token::MapEnd,
+                Token::Map { len: None },
+                Token::String("name"),
+                Token::String("owner"),
+                Token::String("type"),
+                Token::String("address"),
+                Token::MapEnd,
+                Token::SeqEnd,
+                Token::StructEnd,
+            ],
+        )
+    }
+
+    #[test]
+    fn error_selector() {
+        let error = test_error();
+
+        assert_eq!(error.signature(), "MyError(uint256,address)");
+        assert_eq!(error.selector_hex(), "a251ed4d");
+    }
+}
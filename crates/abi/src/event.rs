@@ -1,7 +1,7 @@
 use super::types::AbiType;
 
 use fe_common::utils::keccak;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct AbiEvent {
@@ -60,7 +60,7 @@ impl AbiEventSignature {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AbiEventField {
     pub name: String,
     #[serde(flatten)]
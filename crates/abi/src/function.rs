@@ -1,11 +1,11 @@
 use fe_common::utils::keccak;
 
-use serde::Serialize;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 
 use super::types::AbiType;
 
 /// The mutability of a public function.
-#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum StateMutability {
     Pure,
@@ -30,35 +30,59 @@ impl StateMutability {
         // Check ABI conformity
         // See https://github.com/ethereum/fe/issues/558
         //
-        //              no self   |   self   |  mut self  |
-        //           ......................................
-        // no ctx    :    pure    |   view    |  payable  |
-        // ctx       :    view    |   view    |  payable  |
-        // mut ctx   :   payable  |  payable  |  payable  |
+        //              no self    |    self    |  mut self   |
+        //           .........................................
+        // no ctx    :    pure     |    view    | nonpayable  |
+        // ctx       :    view     |    view    | nonpayable  |
+        // mut ctx   : nonpayable  | nonpayable | nonpayable  |
+        //
+        // Functions only become `payable` when marked with the `#payable`
+        // attribute.
 
         match (self_, ctx) {
             (SelfParam::None, CtxParam::None) => StateMutability::Pure,
             (SelfParam::None, CtxParam::Imm) => StateMutability::View,
-            (SelfParam::None, CtxParam::Mut) => StateMutability::Payable,
+            (SelfParam::None, CtxParam::Mut) => StateMutability::Nonpayable,
             (SelfParam::Imm, CtxParam::None) => StateMutability::View,
             (SelfParam::Imm, CtxParam::Imm) => StateMutability::View,
-            (SelfParam::Imm, CtxParam::Mut) => StateMutability::Payable,
-            (SelfParam::Mut, _) => StateMutability::Payable,
+            (SelfParam::Imm, CtxParam::Mut) => StateMutability::Nonpayable,
+            (SelfParam::Mut, _) => StateMutability::Nonpayable,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AbiFunction {
-    #[serde(rename = "type")]
     func_type: AbiFunctionType,
     name: String,
     inputs: Vec<AbiFunctionParamInner>,
     outputs: Vec<AbiFunctionParamInner>,
-    #[serde(rename = "stateMutability")]
     state_mutability: StateMutability,
 }
 
+impl Serialize for AbiFunction {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        // `receive` and `fallback` entries have neither a name nor parameters.
+        if matches!(
+            self.func_type,
+            AbiFunctionType::Receive | AbiFunctionType::Fallback
+        ) {
+            let mut record = s.serialize_struct("AbiFunction", 2)?;
+            record.serialize_field("type", &self.func_type)?;
+            record.serialize_field("stateMutability", &self.state_mutability)?;
+            return record.end();
+        }
+
+        let mut record = s.serialize_struct("AbiFunction", 5)?;
+        record.serialize_field("type", &self.func_type)?;
+        record.serialize_field("name", &self.name)?;
+        record.serialize_field("inputs", &self.inputs)?;
+        record.serialize_field("outputs", &self.outputs)?;
+        record.serialize_field("stateMutability", &self.state_mutability)?;
+        record.end()
+    }
+}
+
 impl AbiFunction {
     pub fn new(
         func_type: AbiFunctionType,
@@ -87,6 +111,32 @@ impl AbiFunction {
     pub fn selector(&self) -> AbiFunctionSelector {
         AbiFunctionSelector::new(self)
     }
+
+    pub fn func_type(&self) -> AbiFunctionType {
+        self.func_type
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `(name, type)` of each parameter.
+    pub fn inputs(&self) -> impl Iterator<Item = (&str, &AbiType)> {
+        self.inputs
+            .iter()
+            .map(|param| (param.name.as_str(), &param.ty))
+    }
+
+    /// Returns `(name, type)` of each return value.
+    pub fn outputs(&self) -> impl Iterator<Item = (&str, &AbiType)> {
+        self.outputs
+            .iter()
+            .map(|param| (param.name.as_str(), &param.ty))
+    }
+
+    pub fn state_mutability(&self) -> &StateMutability {
+        &self.state_mutability
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -245,6 +295,38 @@ mod tests {
         )
     }
 
+    #[test]
+    fn serialize_receive() {
+        let func = AbiFunction::new(
+            AbiFunctionType::Receive,
+            "__receive__".into(),
+            vec![],
+            None,
+            StateMutability::Payable,
+        );
+
+        assert_ser_tokens(
+            &func,
+            &[
+                Token::Struct {
+                    name: "AbiFunction",
+                    len: 2,
+                },
+                Token::Str("type"),
+                Token::UnitVariant {
+                    name: "AbiFunctionType",
+                    variant: "receive",
+                },
+                Token::Str("stateMutability"),
+                Token::UnitVariant {
+                    name: "StateMutability",
+                    variant: "payable",
+                },
+                Token::StructEnd,
+            ],
+        )
+    }
+
     #[test]
     fn test_state_mutability() {
         assert_eq!(
@@ -257,7 +339,7 @@ mod tests {
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::None, CtxParam::Mut),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
 
         assert_eq!(
@@ -270,20 +352,20 @@ mod tests {
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Imm, CtxParam::Mut),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
 
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Mut, CtxParam::None),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Mut, CtxParam::Imm),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Mut, CtxParam::Mut),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
 
         let pure_func = test_func(StateMutability::Pure);
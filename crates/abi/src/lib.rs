@@ -1,4 +1,8 @@
 pub mod contract;
+pub mod error;
 pub mod event;
 pub mod function;
+pub mod rust_bindings;
+pub mod solidity;
+pub mod storage_layout;
 pub mod types;
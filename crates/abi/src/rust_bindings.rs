@@ -0,0 +1,559 @@
+//! Generates Rust bindings for an [`AbiContract`], built on `ethabi`.
+//!
+//! Each function gets a `{Name}Call` struct that encodes its calldata and
+//! decodes its return data, each event gets a `{Name}Event` struct that decodes
+//! a raw log, and tuples are mapped to plain structs.
+
+use super::{
+    contract::AbiContract,
+    event::AbiEvent,
+    function::{AbiFunction, AbiFunctionType},
+    solidity::struct_name,
+    types::AbiType,
+};
+
+/// Rust keywords that can't be used as raw identifiers.
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+const INVALID_DATA: &str = "ethabi::Error::InvalidData";
+
+/// Returns the source of a Rust module with bindings for `contract`.
+pub fn bindings(name: &str, contract: &AbiContract) -> String {
+    let mut writer = BindingsWriter::default();
+
+    let funcs: Vec<_> = contract
+        .funcs()
+        .iter()
+        .filter(|func| {
+            matches!(
+                func.func_type(),
+                AbiFunctionType::Function | AbiFunctionType::Constructor
+            )
+        })
+        .map(|func| writer.call(func))
+        .collect();
+
+    let events: Vec<_> = contract
+        .events()
+        .iter()
+        .filter(|event| !event.anonymous)
+        .map(|event| writer.event(event))
+        .collect();
+
+    let mut items = writer.structs;
+    items.extend(funcs);
+    items.extend(events);
+
+    format!(
+        "// Rust bindings for the `{name}` contract.\n\
+         // Generated by `fe build --emit rust-bindings`; do not edit.\n\
+         \n\
+         #[allow(unused_imports)]\n\
+         use ethabi::{{Address, Hash, Int, ParamType, Token, Uint}};\n\
+         \n\
+         {}",
+        items.join("\n")
+    )
+}
+
+#[derive(Default)]
+struct BindingsWriter {
+    /// Tuple types that were given a struct, and the struct name.
+    tuples: Vec<(AbiType, String)>,
+
+    /// The definitions of the structs in `tuples`.
+    structs: Vec<String>,
+
+    /// The names of all generated structs.
+    names: Vec<String>,
+}
+
+impl BindingsWriter {
+    fn call(&mut self, func: &AbiFunction) -> String {
+        let is_constructor = func.func_type() == AbiFunctionType::Constructor;
+        let struct_name = if is_constructor {
+            self.unique_name("ConstructorCall")
+        } else {
+            self.unique_name(&format!("{}Call", struct_name(func.name())))
+        };
+
+        let fields: Vec<_> = func
+            .inputs()
+            .enumerate()
+            .map(|(idx, (name, ty))| {
+                let rust_ty = self.rust_type(ty, name);
+                (field_name(name, idx), ty, rust_ty)
+            })
+            .collect();
+
+        let tokens = fields
+            .iter()
+            .map(|(name, ty, _)| into_token(ty, &format!("self.{name}")))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut methods = vec![];
+        if is_constructor {
+            methods.push(format!(
+                "    /// Returns the deployment code for the contract `bytecode`.\n    \
+                     pub fn encode(self, bytecode: &[u8]) -> Vec<u8> {{\n        \
+                         let mut data = bytecode.to_vec();\n        \
+                         data.extend(ethabi::encode(&[{tokens}]));\n        \
+                         data\n    \
+                     }}\n"
+            ));
+        } else {
+            let selector = func
+                .selector()
+                .selector_raw()
+                .iter()
+                .map(|byte| format!("0x{byte:02x}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            methods.push(format!("    pub const SELECTOR: [u8; 4] = [{selector}];\n"));
+            methods.push(format!(
+                "    /// Returns the calldata for this call.\n    \
+                     pub fn encode(self) -> Vec<u8> {{\n        \
+                         let mut data = Self::SELECTOR.to_vec();\n        \
+                         data.extend(ethabi::encode(&[{tokens}]));\n        \
+                         data\n    \
+                     }}\n"
+            ));
+        }
+
+        if let Some((_, ret_ty)) = func.outputs().next() {
+            let hint = format!("{}_result", func.name());
+            let rust_ty = self.rust_type(ret_ty, &hint);
+            let param_ty = param_type(ret_ty);
+            let value = self.decode_token(ret_ty, &next_token("tokens"));
+            // The decoded value is already a `Result`, so return it directly
+            // rather than through `Ok(value?)`.
+            let value = value
+                .strip_suffix('?')
+                .expect("decoded value doesn't end with `?`");
+            methods.push(format!(
+                "    /// Decodes the data returned by this call.\n    \
+                     pub fn decode_output(data: &[u8]) -> Result<{rust_ty}, ethabi::Error> {{\n        \
+                         let mut tokens = ethabi::decode(&[{param_ty}], data)?.into_iter();\n        \
+                         {value}\n    \
+                     }}\n"
+            ));
+        }
+
+        let doc = if is_constructor {
+            "/// Deploys the contract.".to_string()
+        } else {
+            format!("/// Calls `{}`.", func.selector().selector_signature())
+        };
+        format!(
+            "{doc}\n{}\nimpl {struct_name} {{\n{}}}\n",
+            struct_definition(&struct_name, &fields),
+            methods.join("\n")
+        )
+    }
+
+    fn event(&mut self, event: &AbiEvent) -> String {
+        let struct_name = self.unique_name(&format!("{}Event", struct_name(&event.name)));
+        let signature = event.signature();
+
+        let mut data_types = vec![];
+        let mut field_values = vec![];
+        let fields: Vec<_> = event
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(idx, input)| {
+                let name = field_name(&input.name, idx);
+                let topic = next_topic();
+                let (value, rust_ty) = if !input.indexed {
+                    data_types.push(param_type(&input.ty));
+                    let rust_ty = self.rust_type(&input.ty, &input.name);
+                    (self.decode_token(&input.ty, &next_token("tokens")), rust_ty)
+                } else if input.ty.is_static() {
+                    let decoded = format!(
+                        "ethabi::decode(&[{}], {topic}.as_bytes())?\
+                         .into_iter()\
+                         .next()\
+                         .ok_or({INVALID_DATA})?",
+                        param_type(&input.ty)
+                    );
+                    let rust_ty = self.rust_type(&input.ty, &input.name);
+                    (self.decode_token(&input.ty, &decoded), rust_ty)
+                } else {
+                    // Indexed reference types are logged as the hash of their encoding.
+                    (format!("*{topic}"), "Hash".to_string())
+                };
+                field_values.push(format!("            {name}: {value},\n"));
+                (name, &input.ty, rust_ty)
+            })
+            .collect();
+
+        let topic = signature
+            .hash_raw()
+            .iter()
+            .map(|byte| format!("0x{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "/// The `{}` event.\n\
+             {}\n\
+             impl {struct_name} {{\n    \
+                 pub const TOPIC: [u8; 32] = [{topic}];\n\
+             \n    \
+                 /// Decodes the event from a log emitted by the contract.\n    \
+                 pub fn decode(log: &ethabi::RawLog) -> Result<Self, ethabi::Error> {{\n        \
+                     if log.topics.first().map(|topic| topic.as_bytes()) != Some(&Self::TOPIC[..]) {{\n            \
+                         return Err({INVALID_DATA});\n        \
+                     }}\n        \
+                     let mut topics = log.topics.iter().skip(1);\n        \
+                     let mut tokens = ethabi::decode(&[{}], &log.data)?.into_iter();\n        \
+                     Ok(Self {{\n\
+             {}        \
+                     }})\n    \
+                 }}\n\
+             }}\n",
+            signature.signature(),
+            struct_definition(&struct_name, &fields),
+            data_types.join(", "),
+            field_values.concat(),
+        )
+    }
+
+    /// Returns the Rust type used for `ty`, defining a struct for it if it's a
+    /// tuple.
+    fn rust_type(&mut self, ty: &AbiType, hint: &str) -> String {
+        match ty {
+            AbiType::UInt(_) => "Uint".to_string(),
+            AbiType::Int(_) => "Int".to_string(),
+            AbiType::Address => "Address".to_string(),
+            AbiType::Bool => "bool".to_string(),
+            AbiType::Function | AbiType::Bytes => "Vec<u8>".to_string(),
+            AbiType::String => "String".to_string(),
+            AbiType::Array { elem_ty, .. } => {
+                if elem_ty.as_ref() == &AbiType::UInt(8) {
+                    "Vec<u8>".to_string()
+                } else {
+                    format!("Vec<{}>", self.rust_type(elem_ty, hint))
+                }
+            }
+            AbiType::Tuple(fields) => {
+                if let Some((_, name)) = self.tuples.iter().find(|(tuple, _)| tuple == ty) {
+                    return name.clone();
+                }
+
+                let fields: Vec<_> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, field)| {
+                        let rust_ty = self.rust_type(&field.ty, &field.name);
+                        (field_name(&field.name, idx), &field.ty, rust_ty)
+                    })
+                    .collect();
+
+                let name = self.unique_name(&struct_name(hint));
+
+                let tokens = fields
+                    .iter()
+                    .map(|(field, ty, _)| into_token(ty, &format!("self.{field}")))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let values = fields
+                    .iter()
+                    .map(|(field, ty, _)| {
+                        format!(
+                            "            {field}: {},\n",
+                            self.decode_token(ty, &next_token("tokens"))
+                        )
+                    })
+                    .collect::<String>();
+
+                self.structs.push(format!(
+                    "{}\n\
+                     impl {name} {{\n    \
+                         pub fn into_token(self) -> Token {{\n        \
+                             Token::Tuple(vec![{tokens}])\n    \
+                         }}\n\
+                     \n    \
+                         pub fn from_token(token: Token) -> Result<Self, ethabi::Error> {{\n        \
+                             let mut tokens = token.into_tuple().ok_or({INVALID_DATA})?.into_iter();\n        \
+                             Ok(Self {{\n\
+                     {values}        \
+                             }})\n    \
+                         }}\n\
+                     }}\n",
+                    struct_definition(&name, &fields),
+                ));
+                self.tuples.push((ty.clone(), name.clone()));
+                name
+            }
+        }
+    }
+
+    /// Returns `base_name`, with a numeric suffix if a struct with that name
+    /// was already generated, e.g. for overloaded functions.
+    fn unique_name(&mut self, base_name: &str) -> String {
+        let mut name = base_name.to_string();
+        let mut suffix = 1;
+        while self.names.contains(&name) {
+            suffix += 1;
+            name = format!("{base_name}{suffix}");
+        }
+        self.names.push(name.clone());
+        name
+    }
+
+    /// Returns an expression that converts the `ethabi::Token` expression
+    /// `token` into the Rust type of `ty`, returning early on a mismatch.
+    ///
+    /// Tuple types must have been passed to `rust_type` first.
+    fn decode_token(&self, ty: &AbiType, token: &str) -> String {
+        let into = |method: &str| format!("{token}.{method}().ok_or({INVALID_DATA})?");
+        match ty {
+            AbiType::UInt(_) => into("into_uint"),
+            AbiType::Int(_) => into("into_int"),
+            AbiType::Address => into("into_address"),
+            AbiType::Bool => into("into_bool"),
+            AbiType::Function => into("into_fixed_bytes"),
+            AbiType::Bytes => into("into_bytes"),
+            AbiType::String => into("into_string"),
+            AbiType::Array { elem_ty, .. } if elem_ty.as_ref() == &AbiType::UInt(8) => {
+                into("into_bytes")
+            }
+            AbiType::Array { elem_ty, .. } => format!(
+                "{}.into_iter().map(|elem| Ok({})).collect::<Result<Vec<_>, ethabi::Error>>()?",
+                into("into_fixed_array"),
+                self.decode_token(elem_ty, "elem")
+            ),
+            AbiType::Tuple(_) => {
+                let (_, name) = self
+                    .tuples
+                    .iter()
+                    .find(|(tuple, _)| tuple == ty)
+                    .expect("tuple has no struct");
+                format!("{name}::from_token({token})?")
+            }
+        }
+    }
+}
+
+fn struct_definition(name: &str, fields: &[(String, &AbiType, String)]) -> String {
+    let derive = "#[derive(Debug, Clone, PartialEq, Eq)]";
+    if fields.is_empty() {
+        format!("{derive}\npub struct {name};\n")
+    } else {
+        let fields = fields
+            .iter()
+            .map(|(name, _, rust_ty)| format!("    pub {name}: {rust_ty},\n"))
+            .collect::<String>();
+        format!("{derive}\npub struct {name} {{\n{fields}}}\n")
+    }
+}
+
+/// Returns an expression that converts `value` into an `ethabi::Token`.
+fn into_token(ty: &AbiType, value: &str) -> String {
+    match ty {
+        AbiType::UInt(_) => format!("Token::Uint({value})"),
+        AbiType::Int(_) => format!("Token::Int({value})"),
+        AbiType::Address => format!("Token::Address({value})"),
+        AbiType::Bool => format!("Token::Bool({value})"),
+        AbiType::Function => format!("Token::FixedBytes({value})"),
+        AbiType::Bytes => format!("Token::Bytes({value})"),
+        AbiType::String => format!("Token::String({value})"),
+        AbiType::Array { elem_ty, .. } if elem_ty.as_ref() == &AbiType::UInt(8) => {
+            format!("Token::Bytes({value})")
+        }
+        AbiType::Array { elem_ty, .. } => format!(
+            "Token::FixedArray({value}.into_iter().map(|elem| {}).collect())",
+            into_token(elem_ty, "elem")
+        ),
+        AbiType::Tuple(_) => format!("{value}.into_token()"),
+    }
+}
+
+/// Returns an `ethabi::ParamType` expression for `ty`.
+fn param_type(ty: &AbiType) -> String {
+    match ty {
+        AbiType::UInt(bits) => format!("ParamType::Uint({bits})"),
+        AbiType::Int(bits) => format!("ParamType::Int({bits})"),
+        AbiType::Address => "ParamType::Address".to_string(),
+        AbiType::Bool => "ParamType::Bool".to_string(),
+        AbiType::Function => "ParamType::FixedBytes(24)".to_string(),
+        AbiType::Bytes => "ParamType::Bytes".to_string(),
+        AbiType::String => "ParamType::String".to_string(),
+        AbiType::Array { elem_ty, .. } if elem_ty.as_ref() == &AbiType::UInt(8) => {
+            "ParamType::Bytes".to_string()
+        }
+        AbiType::Array { elem_ty, len } => format!(
+            "ParamType::FixedArray(Box::new({}), {len})",
+            param_type(elem_ty)
+        ),
+        AbiType::Tuple(fields) => format!(
+            "ParamType::Tuple(vec![{}])",
+            fields
+                .iter()
+                .map(|field| param_type(&field.ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Returns an expression that takes the next token from the iterator `tokens`.
+fn next_token(tokens: &str) -> String {
+    format!("{tokens}.next().ok_or({INVALID_DATA})?")
+}
+
+/// Returns an expression that takes the next topic from the iterator `topics`.
+fn next_topic() -> String {
+    format!("topics.next().ok_or({INVALID_DATA})?")
+}
+
+/// Returns a Rust identifier for a parameter or field, naming unnamed ones
+/// after their position.
+fn field_name(name: &str, idx: usize) -> String {
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field{idx}")
+    } else if NON_RAW_KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else if RUST_KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::AbiEventField,
+        function::{AbiFunction, StateMutability},
+        types::AbiTupleField,
+    };
+
+    fn test_contract() -> AbiContract {
+        let point = AbiType::Tuple(vec![
+            AbiTupleField::new("x".into(), AbiType::UInt(256)),
+            AbiTupleField::new("y".into(), AbiType::Int(8)),
+        ]);
+
+        let funcs = vec![
+            AbiFunction::new(
+                AbiFunctionType::Constructor,
+                "__init__".into(),
+                vec![("owner".into(), AbiType::Address)],
+                None,
+                StateMutability::Payable,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "get_point".into(),
+                vec![("type".into(), AbiType::UInt(8))],
+                Some(point.clone()),
+                StateMutability::View,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "set_points".into(),
+                vec![(
+                    "points".into(),
+                    AbiType::Array {
+                        elem_ty: point.into(),
+                        len: 2,
+                    },
+                )],
+                None,
+                StateMutability::Nonpayable,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "get_point".into(),
+                vec![],
+                Some(AbiType::Bool),
+                StateMutability::View,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "set_origin".into(),
+                vec![(
+                    "get_point_call".into(),
+                    AbiType::Tuple(vec![AbiTupleField::new("z".into(), AbiType::Bool)]),
+                )],
+                None,
+                StateMutability::Nonpayable,
+            ),
+        ];
+        let events = vec![AbiEvent::new(
+            "Moved".into(),
+            vec![
+                AbiEventField::new("by".into(), AbiType::Address, true),
+                AbiEventField::new("note".into(), AbiType::String, true),
+                AbiEventField::new("distance".into(), AbiType::UInt(256), false),
+            ],
+            false,
+        )];
+
+        AbiContract::new(funcs, events, vec![])
+    }
+
+    #[test]
+    fn generate_calls() {
+        let bindings = bindings("Foo", &test_contract());
+
+        assert!(bindings.starts_with("// Rust bindings for the `Foo` contract.\n"));
+        assert!(bindings.contains("pub struct ConstructorCall {\n    pub owner: Address,\n}\n"));
+        assert!(bindings.contains("pub struct GetPointCall {\n    pub r#type: Uint,\n}\n"));
+        assert!(bindings.contains(
+            "pub fn decode_output(data: &[u8]) -> Result<GetPointResult, ethabi::Error> {"
+        ));
+        assert!(bindings
+            .contains("pub struct SetPointsCall {\n    pub points: Vec<GetPointResult>,\n}\n"));
+        // The selector of `set_points((uint256,int8)[2])`.
+        assert!(bindings.contains("pub const SELECTOR: [u8; 4] = [0xbb, 0x8e, 0x8e, 0xa4];"));
+    }
+
+    #[test]
+    fn generate_events() {
+        let bindings = bindings("Foo", &test_contract());
+
+        assert!(bindings.contains(
+            "pub struct MovedEvent {\n    pub by: Address,\n    pub note: Hash,\n    pub distance: Uint,\n}\n"
+        ));
+        assert!(bindings.contains(
+            "let mut tokens = ethabi::decode(&[ParamType::Uint(256)], &log.data)?.into_iter();"
+        ));
+        assert!(bindings
+            .contains("            note: *topics.next().ok_or(ethabi::Error::InvalidData)?,\n"));
+    }
+
+    #[test]
+    fn unique_struct_names() {
+        let bindings = bindings("Foo", &test_contract());
+
+        // The overload of `get_point` and the tuple named after
+        // `get_point_call` must not reuse the name of `GetPointCall`.
+        assert!(bindings.contains("pub struct GetPointCall2;\n"));
+        assert!(bindings.contains("pub struct GetPointCall3 {\n    pub z: bool,\n}\n"));
+        assert!(bindings
+            .contains("pub struct SetOriginCall {\n    pub get_point_call: GetPointCall3,\n}\n"));
+    }
+
+    /// `tests/fixtures/foo_bindings.rs` is compiled and exercised by
+    /// `tests/rust_bindings.rs`, so it must match the generated code.
+    #[test]
+    fn compiled_bindings_are_current() {
+        assert_eq!(
+            bindings("Foo", &test_contract()),
+            include_str!("../tests/fixtures/foo_bindings.rs")
+        );
+    }
+}
@@ -0,0 +1,512 @@
+//! Renders an [`AbiContract`] as a Solidity interface, so that Fe contracts can
+//! be called from Solidity code.
+
+use super::{
+    contract::AbiContract,
+    function::{AbiFunctionType, StateMutability},
+    types::AbiType,
+};
+
+const SOLIDITY_VERSION: &str = "^0.8.4";
+
+/// Words that can't be used as identifiers in Solidity.
+const RESERVED_WORDS: &[&str] = &[
+    "abstract",
+    "after",
+    "alias",
+    "anonymous",
+    "apply",
+    "assembly",
+    "auto",
+    "break",
+    "byte",
+    "calldata",
+    "case",
+    "catch",
+    "constant",
+    "constructor",
+    "continue",
+    "contract",
+    "copyof",
+    "default",
+    "define",
+    "delete",
+    "do",
+    "else",
+    "emit",
+    "enum",
+    "error",
+    "event",
+    "external",
+    "fallback",
+    "false",
+    "final",
+    "for",
+    "function",
+    "if",
+    "immutable",
+    "implements",
+    "import",
+    "in",
+    "indexed",
+    "inline",
+    "interface",
+    "internal",
+    "is",
+    "let",
+    "library",
+    "macro",
+    "mapping",
+    "match",
+    "memory",
+    "modifier",
+    "mutable",
+    "new",
+    "null",
+    "of",
+    "override",
+    "partial",
+    "payable",
+    "pragma",
+    "private",
+    "promise",
+    "public",
+    "pure",
+    "receive",
+    "reference",
+    "relocatable",
+    "return",
+    "returns",
+    "revert",
+    "sealed",
+    "sizeof",
+    "static",
+    "storage",
+    "struct",
+    "super",
+    "supports",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "type",
+    "typedef",
+    "typeof",
+    "unchecked",
+    "using",
+    "var",
+    "view",
+    "virtual",
+    "while",
+    "address",
+    "bool",
+    "string",
+    "bytes",
+    "int",
+    "uint",
+    "fixed",
+    "ufixed",
+];
+
+/// Returns a Solidity interface named `name` declaring the functions, events
+/// and errors of `contract`.
+///
+/// Tuples are declared as structs inside the interface. As the ABI doesn't
+/// record struct names, they are named after the parameter or field they
+/// first appear in.
+///
+/// Renaming a function, event or error would change its selector, so items
+/// whose name is reserved in Solidity are left out and replaced by a comment.
+pub fn interface(name: &str, contract: &AbiContract) -> String {
+    let mut writer = InterfaceWriter::default();
+
+    let events: Vec<_> = contract
+        .events()
+        .iter()
+        .map(|event| {
+            if is_reserved(&event.name) {
+                return omitted("event", &event.name);
+            }
+            let params = event
+                .inputs
+                .iter()
+                .map(|input| {
+                    let ty = writer.type_name(&input.ty, &input.name);
+                    let indexed = if input.indexed { " indexed" } else { "" };
+                    format!("{ty}{indexed}{}", param_name(&input.name))
+                })
+                .collect::<Vec<_>>();
+            format!("event {}({});", event.name, params.join(", "))
+        })
+        .collect();
+
+    let errors: Vec<_> = contract
+        .errors()
+        .iter()
+        .map(|error| {
+            if is_reserved(&error.name) {
+                return omitted("error", &error.name);
+            }
+            let params = error
+                .inputs
+                .iter()
+                .map(|input| {
+                    let ty = writer.type_name(&input.ty, &input.name);
+                    format!("{ty}{}", param_name(&input.name))
+                })
+                .collect::<Vec<_>>();
+            format!("error {}({});", error.name, params.join(", "))
+        })
+        .collect();
+
+    let funcs: Vec<_> = contract
+        .funcs()
+        .iter()
+        // Constructors can't be declared in an interface.
+        .filter(|func| func.func_type() != AbiFunctionType::Constructor)
+        .map(|func| {
+            let mutability = match func.state_mutability() {
+                StateMutability::Pure => " pure",
+                StateMutability::View => " view",
+                StateMutability::Nonpayable => "",
+                StateMutability::Payable => " payable",
+            };
+            match func.func_type() {
+                AbiFunctionType::Receive => return "receive() external payable;".to_string(),
+                AbiFunctionType::Fallback => return format!("fallback() external{mutability};"),
+                _ => {}
+            }
+            if is_reserved(func.name()) {
+                return omitted("function", func.name());
+            }
+
+            let params = func
+                .inputs()
+                .map(|(name, ty)| writer.param(ty, name, "calldata"))
+                .collect::<Vec<_>>();
+            let returns = func
+                .outputs()
+                .map(|(name, ty)| {
+                    let hint = if name.is_empty() {
+                        format!("{}_result", func.name())
+                    } else {
+                        name.to_string()
+                    };
+                    let mut param = writer.param(ty, &hint, "memory");
+                    if name.is_empty() {
+                        param.truncate(param.len() - param_name(&hint).len());
+                    }
+                    param
+                })
+                .collect::<Vec<_>>();
+
+            let returns = if returns.is_empty() {
+                "".to_string()
+            } else {
+                format!(" returns ({})", returns.join(", "))
+            };
+            format!(
+                "function {}({}) external{mutability}{returns};",
+                func.name(),
+                params.join(", ")
+            )
+        })
+        .collect();
+
+    let structs: Vec<_> = writer
+        .structs
+        .iter()
+        .map(|(struct_name, fields)| {
+            let fields = fields
+                .iter()
+                .map(|(field_ty, field_name)| format!("        {field_ty} {field_name};\n"))
+                .collect::<String>();
+            format!("struct {struct_name} {{\n{fields}    }}")
+        })
+        .collect();
+
+    let sections = [structs, events, errors, funcs]
+        .into_iter()
+        .filter(|section| !section.is_empty())
+        .map(|section| {
+            section
+                .iter()
+                .map(|item| format!("    {item}\n"))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "// SPDX-License-Identifier: UNLICENSED\npragma solidity {SOLIDITY_VERSION};\n\ninterface {name} {{\n{}}}\n",
+        sections.join("\n")
+    )
+}
+
+#[derive(Default)]
+struct InterfaceWriter {
+    /// Struct names and their `(type, name)` fields, in definition order.
+    structs: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl InterfaceWriter {
+    /// Returns a function parameter declaration with a data location if
+    /// required.
+    fn param(&mut self, ty: &AbiType, name: &str, location: &str) -> String {
+        let ty_name = self.type_name(ty, name);
+        if is_reference_type(ty) {
+            format!("{ty_name} {location}{}", param_name(name))
+        } else {
+            format!("{ty_name}{}", param_name(name))
+        }
+    }
+
+    fn type_name(&mut self, ty: &AbiType, hint: &str) -> String {
+        match ty {
+            AbiType::Array { elem_ty, len } => {
+                format!("{}[{len}]", self.type_name(elem_ty, hint))
+            }
+            AbiType::Tuple(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| {
+                        let field_ty = self.type_name(&field.ty, &field.name);
+                        (field_ty, identifier(&field.name))
+                    })
+                    .collect();
+                self.define_struct(&struct_name(hint), fields)
+            }
+            _ => ty.selector_type_name(),
+        }
+    }
+
+    /// Defines a struct and returns its name. Structurally equal tuples share a
+    /// single definition.
+    fn define_struct(&mut self, name: &str, fields: Vec<(String, String)>) -> String {
+        if let Some((defined, _)) = self.structs.iter().find(|(_, f)| f == &fields) {
+            return defined.clone();
+        }
+
+        let mut unique_name = name.to_string();
+        let mut suffix = 1;
+        while self.structs.iter().any(|(n, _)| n == &unique_name) {
+            suffix += 1;
+            unique_name = format!("{name}{suffix}");
+        }
+
+        self.structs.push((unique_name.clone(), fields));
+        unique_name
+    }
+}
+
+fn is_reference_type(ty: &AbiType) -> bool {
+    matches!(
+        ty,
+        AbiType::String | AbiType::Bytes | AbiType::Array { .. } | AbiType::Tuple(_)
+    )
+}
+
+/// Returns a parameter name prefixed with a space, or nothing for an unnamed
+/// parameter.
+fn param_name(name: &str) -> String {
+    if name.is_empty() {
+        "".to_string()
+    } else {
+        format!(" {}", identifier(name))
+    }
+}
+
+fn identifier(name: &str) -> String {
+    if is_reserved(name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Returns `true` if `name` is a keyword or an elementary type name in
+/// Solidity.
+fn is_reserved(name: &str) -> bool {
+    let is_sized_type =
+        ["uint", "int", "bytes"]
+            .iter()
+            .any(|prefix| match name.strip_prefix(prefix) {
+                Some(bits) => bits.parse::<usize>().is_ok(),
+                None => false,
+            });
+    is_sized_type || RESERVED_WORDS.contains(&name)
+}
+
+fn omitted(kind: &str, name: &str) -> String {
+    format!("// {kind} `{name}` is omitted: its name is reserved in Solidity")
+}
+
+pub(crate) fn struct_name(hint: &str) -> String {
+    let name: String = hint
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("Tuple{name}")
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::AbiError,
+        event::{AbiEvent, AbiEventField},
+        function::AbiFunction,
+        types::AbiTupleField,
+    };
+
+    #[test]
+    fn render_interface() {
+        let point = AbiType::Tuple(vec![
+            AbiTupleField::new("x".into(), AbiType::UInt(256)),
+            AbiTupleField::new("y".into(), AbiType::UInt(256)),
+        ]);
+
+        let funcs = vec![
+            AbiFunction::new(
+                AbiFunctionType::Constructor,
+                "__init__".into(),
+                vec![("owner".into(), AbiType::Address)],
+                None,
+                StateMutability::Payable,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "get_point".into(),
+                vec![("idx".into(), AbiType::UInt(8))],
+                Some(point.clone()),
+                StateMutability::View,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "set_name".into(),
+                vec![("name".into(), AbiType::String), ("origin".into(), point)],
+                None,
+                StateMutability::Nonpayable,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Receive,
+                "__receive__".into(),
+                vec![],
+                None,
+                StateMutability::Payable,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Fallback,
+                "__fallback__".into(),
+                vec![],
+                None,
+                StateMutability::Nonpayable,
+            ),
+        ];
+        let events = vec![AbiEvent::new(
+            "Renamed".into(),
+            vec![
+                AbiEventField::new("from".into(), AbiType::Address, true),
+                AbiEventField::new("data".into(), AbiType::Bytes, false),
+            ],
+            false,
+        )];
+        let errors = vec![AbiError::new(
+            "NotOwner".into(),
+            vec![("caller".into(), AbiType::Address)],
+        )];
+        let contract = AbiContract::new(funcs, events, errors);
+
+        assert_eq!(
+            interface("IFoo", &contract),
+            "// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.4;
+
+interface IFoo {
+    struct GetPointResult {
+        uint256 x;
+        uint256 y;
+    }
+
+    event Renamed(address indexed from, bytes data);
+
+    error NotOwner(address caller);
+
+    function get_point(uint8 idx) external view returns (GetPointResult memory);
+    function set_name(string calldata name, GetPointResult calldata origin) external;
+    receive() external payable;
+    fallback() external;
+}
+"
+        );
+    }
+
+    #[test]
+    fn omit_reserved_names() {
+        let funcs = vec![
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "delete".into(),
+                vec![("key".into(), AbiType::UInt(256))],
+                None,
+                StateMutability::Nonpayable,
+            ),
+            AbiFunction::new(
+                AbiFunctionType::Function,
+                "digest".into(),
+                vec![("data".into(), AbiType::Bytes)],
+                Some(AbiType::Array {
+                    elem_ty: Box::new(AbiType::UInt(8)),
+                    len: 32,
+                }),
+                StateMutability::Pure,
+            ),
+        ];
+        let events = vec![AbiEvent::new(
+            "emit".into(),
+            vec![AbiEventField::new("value".into(), AbiType::UInt(8), false)],
+            false,
+        )];
+        let errors = vec![AbiError::new("revert".into(), vec![])];
+        let contract = AbiContract::new(funcs, events, errors);
+
+        assert_eq!(
+            interface("IFoo", &contract),
+            "// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.4;
+
+interface IFoo {
+    // event `emit` is omitted: its name is reserved in Solidity
+
+    // error `revert` is omitted: its name is reserved in Solidity
+
+    // function `delete` is omitted: its name is reserved in Solidity
+    function digest(bytes calldata data) external pure returns (uint8[32] memory);
+}
+"
+        );
+    }
+
+    #[test]
+    fn escape_identifiers() {
+        assert_eq!(identifier("value"), "value");
+        assert_eq!(identifier("address"), "address_");
+        assert_eq!(identifier("uint8"), "uint8_");
+        assert_eq!(identifier("bytes32"), "bytes32_");
+        assert_eq!(identifier("bytes_len"), "bytes_len");
+        assert_eq!(struct_name("my_point"), "MyPoint");
+        assert_eq!(struct_name("0"), "Tuple0");
+    }
+}
@@ -0,0 +1,411 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The storage layout of a contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageLayout {
+    pub contract: String,
+    /// The ERC-7201 namespace id of the contract, if any. The fields of the
+    /// contract are placed from the root slot of the namespace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub storage: Vec<StorageField>,
+}
+
+impl StorageLayout {
+    pub fn new(contract: String, namespace: Option<String>, storage: Vec<StorageField>) -> Self {
+        Self {
+            contract,
+            namespace,
+            storage,
+        }
+    }
+
+    /// Returns the changes of the `new` layout which are incompatible with
+    /// the storage written with this layout, e.g. when the implementation
+    /// behind a proxy is upgraded. Adding fields after the existing ones is
+    /// compatible.
+    pub fn incompatibilities(&self, new: &StorageLayout) -> Vec<LayoutIncompatibility> {
+        if self.namespace != new.namespace {
+            return vec![LayoutIncompatibility::NamespaceChanged {
+                old: self.namespace.clone(),
+                new: new.namespace.clone(),
+            }];
+        }
+
+        let mut incompatibilities = vec![];
+        compare_fields(&self.storage, &new.storage, "", &mut incompatibilities);
+        incompatibilities
+    }
+}
+
+/// A field stored in the storage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The slot where the field starts, in decimal.
+    pub slot: String,
+    /// The byte offset of the field from the least significant byte of the
+    /// slot, in the same way as solc's storage layout. Non-primitive fields
+    /// always start a new slot, so their offset is zero.
+    pub offset: usize,
+    /// The size of the field in bytes.
+    pub size: usize,
+    #[serde(flatten)]
+    pub encoding: StorageEncoding,
+}
+
+impl StorageField {
+    pub fn new(
+        name: String,
+        ty: String,
+        slot: String,
+        offset: usize,
+        size: usize,
+        encoding: StorageEncoding,
+    ) -> Self {
+        Self {
+            name,
+            ty,
+            slot,
+            offset,
+            size,
+            encoding,
+        }
+    }
+}
+
+/// How a field is laid out in the storage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "encoding", rename_all = "snake_case")]
+pub enum StorageEncoding {
+    /// A primitive value, an enum or a string stored in place.
+    Inplace,
+
+    /// A struct or a tuple whose members are stored in place.
+    Struct { members: Vec<StorageField> },
+
+    /// An array whose elements are stored in place. The element `i` starts
+    /// at the byte `i * stride` counted from the start of the array, and
+    /// `element` is the layout of the first element.
+    Array {
+        length: usize,
+        stride: usize,
+        element: Box<StorageField>,
+    },
+
+    /// A map whose values are stored at hashed locations.
+    ///
+    /// The value of `key` starts at the byte address `keccak256(k . p) &
+    /// ~0xff`, where `p` is the byte address of the map, i.e. its slot times
+    /// 32 modulo `2**256`, and `k` is `key` padded to 32 bytes. A key which
+    /// isn't a primitive is hashed with `keccak256` to get `k`. The slots in
+    /// `value` are relative to the slot of the value.
+    ///
+    /// In a namespaced contract, the bits of the namespace root slot above
+    /// the bit `251` are added to the slot of the value, as the byte address
+    /// can't represent them.
+    Mapping {
+        key: String,
+        value: Box<StorageField>,
+    },
+}
+
+/// A change of a storage layout which corrupts the storage written with the
+/// old layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutIncompatibility {
+    /// The namespace changed, so all the fields moved.
+    NamespaceChanged {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Removed {
+        field: String,
+    },
+    TypeChanged {
+        field: String,
+        old: String,
+        new: String,
+    },
+    /// The field starts at another slot or offset.
+    Moved {
+        field: String,
+        old: (String, usize),
+        new: (String, usize),
+    },
+    /// The distance in bytes between the elements of an array changed, e.g.
+    /// because its element type was marked with `#unpacked`.
+    StrideChanged {
+        field: String,
+        old: usize,
+        new: usize,
+    },
+}
+
+impl fmt::Display for LayoutIncompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let namespace = |namespace: &Option<String>| match namespace {
+            Some(namespace) => format!("`{namespace}`"),
+            None => "none".to_string(),
+        };
+
+        match self {
+            Self::NamespaceChanged { old, new } => write!(
+                f,
+                "the storage namespace changed from {} to {}",
+                namespace(old),
+                namespace(new)
+            ),
+            Self::Removed { field } => write!(f, "`{field}` was removed"),
+            Self::TypeChanged { field, old, new } => {
+                write!(f, "the type of `{field}` changed from `{old}` to `{new}`")
+            }
+            Self::Moved { field, old, new } => write!(
+                f,
+                "`{field}` moved from slot {} (offset {}) to slot {} (offset {})",
+                old.0, old.1, new.0, new.1
+            ),
+            Self::StrideChanged { field, old, new } => write!(
+                f,
+                "the elements of `{field}` are {new} bytes apart instead of {old}"
+            ),
+        }
+    }
+}
+
+fn compare_fields(
+    old: &[StorageField],
+    new: &[StorageField],
+    parent: &str,
+    incompatibilities: &mut Vec<LayoutIncompatibility>,
+) {
+    for old_field in old {
+        let path = format!("{parent}{}", old_field.name);
+        match new
+            .iter()
+            .find(|new_field| new_field.name == old_field.name)
+        {
+            Some(new_field) => compare_field(old_field, new_field, path, incompatibilities),
+            None => incompatibilities.push(LayoutIncompatibility::Removed { field: path }),
+        }
+    }
+}
+
+fn compare_field(
+    old: &StorageField,
+    new: &StorageField,
+    path: String,
+    incompatibilities: &mut Vec<LayoutIncompatibility>,
+) {
+    if old.ty != new.ty {
+        incompatibilities.push(LayoutIncompatibility::TypeChanged {
+            field: path,
+            old: old.ty.clone(),
+            new: new.ty.clone(),
+        });
+        return;
+    }
+
+    if old.slot != new.slot || old.offset != new.offset {
+        incompatibilities.push(LayoutIncompatibility::Moved {
+            field: path,
+            old: (old.slot.clone(), old.offset),
+            new: (new.slot.clone(), new.offset),
+        });
+        return;
+    }
+
+    // The layout of a struct can change without changing its name.
+    match (&old.encoding, &new.encoding) {
+        (StorageEncoding::Struct { members: old }, StorageEncoding::Struct { members: new }) => {
+            compare_fields(old, new, &format!("{path}."), incompatibilities)
+        }
+
+        (
+            StorageEncoding::Array {
+                stride: old_stride,
+                element: old,
+                ..
+            },
+            StorageEncoding::Array {
+                stride: new_stride,
+                element: new,
+                ..
+            },
+        ) => {
+            if old_stride != new_stride {
+                incompatibilities.push(LayoutIncompatibility::StrideChanged {
+                    field: path.clone(),
+                    old: *old_stride,
+                    new: *new_stride,
+                });
+            }
+            compare_field(old, new, format!("{path}[]"), incompatibilities)
+        }
+
+        (
+            StorageEncoding::Mapping { value: old, .. },
+            StorageEncoding::Mapping { value: new, .. },
+        ) => compare_field(old, new, format!("{path}[]"), incompatibilities),
+
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_test::{assert_tokens, Token};
+
+    #[test]
+    fn serialize_storage_layout() {
+        let value = StorageField::new(
+            "".into(),
+            "u8".into(),
+            "0".into(),
+            31,
+            1,
+            StorageEncoding::Inplace,
+        );
+        let map = StorageField::new(
+            "balances".into(),
+            "Map<address, u8>".into(),
+            "1".into(),
+            0,
+            32,
+            StorageEncoding::Mapping {
+                key: "address".into(),
+                value: Box::new(value),
+            },
+        );
+        let layout = StorageLayout::new("Foo".into(), None, vec![map]);
+
+        assert_tokens(
+            &layout,
+            &[
+                Token::Struct {
+                    name: "StorageLayout",
+                    len: 2,
+                },
+                Token::Str("contract"),
+                Token::String("Foo"),
+                Token::Str("storage"),
+                Token::Seq { len: Some(1) },
+                Token::Map { len: None },
+                Token::Str("name"),
+                Token::String("balances"),
+                Token::Str("type"),
+                Token::String("Map<address, u8>"),
+                Token::Str("slot"),
+                Token::String("1"),
+                Token::Str("offset"),
+                Token::U64(0),
+                Token::Str("size"),
+                Token::U64(32),
+                Token::Str("encoding"),
+                Token::Str("mapping"),
+                Token::Str("key"),
+                Token::String("address"),
+                Token::Str("value"),
+                Token::Map { len: None },
+                Token::Str("name"),
+                Token::String(""),
+                Token::Str("type"),
+                Token::String("u8"),
+                Token::Str("slot"),
+                Token::String("0"),
+                Token::Str("offset"),
+                Token::U64(31),
+                Token::Str("size"),
+                Token::U64(1),
+                Token::Str("encoding"),
+                Token::Str("inplace"),
+                Token::MapEnd,
+                Token::MapEnd,
+                Token::SeqEnd,
+                Token::StructEnd,
+            ],
+        )
+    }
+
+    #[test]
+    fn storage_layout_incompatibilities() {
+        let field = |name: &str, ty: &str, slot: &str, offset, encoding| {
+            StorageField::new(name.into(), ty.into(), slot.into(), offset, 1, encoding)
+        };
+        let point = |slot: &str, members| {
+            field(
+                "point",
+                "Point",
+                slot,
+                0,
+                StorageEncoding::Struct { members },
+            )
+        };
+
+        let old = StorageLayout::new(
+            "Foo".into(),
+            None,
+            vec![
+                field("a", "u8", "0", 31, StorageEncoding::Inplace),
+                field("b", "u8", "0", 30, StorageEncoding::Inplace),
+                point(
+                    "1",
+                    vec![
+                        field("x", "u8", "1", 31, StorageEncoding::Inplace),
+                        field("y", "u8", "1", 30, StorageEncoding::Inplace),
+                    ],
+                ),
+            ],
+        );
+
+        // Appending a field is compatible.
+        let mut new = old.clone();
+        new.storage
+            .push(field("c", "u8", "2", 31, StorageEncoding::Inplace));
+        assert!(old.incompatibilities(&new).is_empty());
+
+        let new = StorageLayout::new(
+            "Foo".into(),
+            None,
+            vec![
+                field("a", "u8", "0", 30, StorageEncoding::Inplace),
+                field("b", "bool", "0", 29, StorageEncoding::Inplace),
+                point(
+                    "1",
+                    vec![field("x", "u8", "1", 31, StorageEncoding::Inplace)],
+                ),
+            ],
+        );
+        assert_eq!(
+            old.incompatibilities(&new),
+            vec![
+                LayoutIncompatibility::Moved {
+                    field: "a".into(),
+                    old: ("0".into(), 31),
+                    new: ("0".into(), 30),
+                },
+                LayoutIncompatibility::TypeChanged {
+                    field: "b".into(),
+                    old: "u8".into(),
+                    new: "bool".into(),
+                },
+                LayoutIncompatibility::Removed {
+                    field: "point.y".into(),
+                },
+            ]
+        );
+
+        let mut new = old.clone();
+        new.namespace = Some("example.main".into());
+        assert_eq!(
+            old.incompatibilities(&new)[0].to_string(),
+            "the storage namespace changed from none to `example.main`"
+        );
+    }
+}
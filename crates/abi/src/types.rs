@@ -1,4 +1,10 @@
-use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::fmt;
+
+use serde::{
+    de::{self, IgnoredAny, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AbiType {
@@ -120,6 +126,62 @@ impl AbiType {
         }
     }
 
+    /// Parses a JSON ABI type name, e.g. `uint256`, `tuple[2]` or `string`.
+    /// `components` must be given when the type name refers to a tuple.
+    pub fn from_type_name(
+        type_name: &str,
+        components: Option<Vec<AbiTupleField>>,
+    ) -> Result<Self, String> {
+        if let Some(elem_name) = type_name.strip_suffix(']') {
+            let (elem_name, len) = elem_name
+                .rsplit_once('[')
+                .ok_or_else(|| format!("invalid ABI type `{type_name}`"))?;
+            if len.is_empty() {
+                return Err(format!(
+                    "dynamically sized array `{type_name}` is not supported"
+                ));
+            }
+            let len = len
+                .parse()
+                .map_err(|_| format!("invalid array length in ABI type `{type_name}`"))?;
+            let elem_ty = Self::from_type_name(elem_name, components)?;
+            return Ok(Self::Array {
+                elem_ty: elem_ty.into(),
+                len,
+            });
+        }
+
+        let int_bits = |bits: &str| -> Result<usize, String> {
+            if bits.is_empty() {
+                return Ok(256);
+            }
+            match bits.parse::<usize>() {
+                Ok(bits) if bits % 8 == 0 && (8..=256).contains(&bits) => Ok(bits),
+                _ => Err(format!("invalid integer width in ABI type `{type_name}`")),
+            }
+        };
+
+        match type_name {
+            "address" => Ok(Self::Address),
+            "bool" => Ok(Self::Bool),
+            "function" => Ok(Self::Function),
+            "bytes" => Ok(Self::Bytes),
+            "string" => Ok(Self::String),
+            "tuple" => components
+                .map(Self::Tuple)
+                .ok_or_else(|| "missing `components` for ABI type `tuple`".to_string()),
+            _ => {
+                if let Some(bits) = type_name.strip_prefix("uint") {
+                    Ok(Self::UInt(int_bits(bits)?))
+                } else if let Some(bits) = type_name.strip_prefix("int") {
+                    Ok(Self::Int(int_bits(bits)?))
+                } else {
+                    Err(format!("unsupported ABI type `{type_name}`"))
+                }
+            }
+        }
+    }
+
     fn serialize_component<S: SerializeMap>(&self, s: &mut S) -> Result<(), S::Error> {
         match self {
             Self::Tuple(entry) => s.serialize_entry("components", entry),
@@ -141,7 +203,44 @@ impl Serialize for AbiType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+impl<'de> Deserialize<'de> for AbiType {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct AbiTypeVisitor;
+
+        impl<'de> Visitor<'de> for AbiTypeVisitor {
+            type Value = AbiType;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "an ABI type with `type` and optional `components` entries"
+                )
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<AbiType, A::Error> {
+                let mut type_name: Option<String> = None;
+                let mut components = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "type" => type_name = Some(map.next_value()?),
+                        "components" => components = Some(map.next_value()?),
+                        // Other entries, e.g. `internalType`, don't affect the ABI type.
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                let type_name = type_name.ok_or_else(|| de::Error::missing_field("type"))?;
+                AbiType::from_type_name(&type_name, components).map_err(de::Error::custom)
+            }
+        }
+
+        d.deserialize_map(AbiTypeVisitor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AbiTupleField {
     pub name: String,
     #[serde(flatten)]
@@ -161,7 +260,7 @@ impl AbiTupleField {
 mod tests {
     use super::*;
 
-    use serde_test::{assert_ser_tokens, Token};
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, Token};
 
     #[test]
     fn primitive() {
@@ -371,4 +470,79 @@ mod tests {
             ],
         )
     }
+
+    #[test]
+    fn from_type_name() {
+        assert_eq!(
+            AbiType::from_type_name("uint", None),
+            Ok(AbiType::UInt(256))
+        );
+        assert_eq!(AbiType::from_type_name("int8", None), Ok(AbiType::Int(8)));
+        assert_eq!(
+            AbiType::from_type_name("address[3][2]", None),
+            Ok(AbiType::Array {
+                elem_ty: AbiType::Array {
+                    elem_ty: AbiType::Address.into(),
+                    len: 3
+                }
+                .into(),
+                len: 2
+            })
+        );
+
+        assert!(AbiType::from_type_name("uint7", None).is_err());
+        assert!(AbiType::from_type_name("uint264", None).is_err());
+        assert!(AbiType::from_type_name("uint256[]", None).is_err());
+        assert!(AbiType::from_type_name("bytes32", None).is_err());
+        assert!(AbiType::from_type_name("tuple", None).is_err());
+    }
+
+    #[test]
+    fn deserialize_tuple() {
+        let field1 = AbiTupleField::new("field1".into(), AbiType::UInt(16));
+        let field2 = AbiTupleField::new("field2".into(), AbiType::String);
+        let tuple_ty = AbiType::Tuple(vec![field1, field2]);
+
+        assert_de_tokens(
+            &tuple_ty,
+            &[
+                Token::Map { len: None },
+                Token::Str("internalType"),
+                Token::Str("struct Foo.Bar"),
+                Token::Str("type"),
+                Token::Str("tuple"),
+                Token::Str("components"),
+                Token::Seq { len: Some(2) },
+                // Field1.
+                Token::Map { len: None },
+                Token::Str("name"),
+                Token::Str("field1"),
+                Token::Str("type"),
+                Token::Str("uint16"),
+                Token::MapEnd,
+                // Field2.
+                Token::Map { len: None },
+                Token::Str("name"),
+                Token::Str("field2"),
+                Token::Str("type"),
+                Token::Str("string"),
+                Token::MapEnd,
+                Token::SeqEnd,
+                Token::MapEnd,
+            ],
+        )
+    }
+
+    #[test]
+    fn deserialize_unsupported() {
+        assert_de_tokens_error::<AbiType>(
+            &[
+                Token::Map { len: None },
+                Token::Str("type"),
+                Token::Str("bytes4"),
+                Token::MapEnd,
+            ],
+            "unsupported ABI type `bytes4`",
+        )
+    }
 }
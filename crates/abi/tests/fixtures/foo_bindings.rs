@@ -0,0 +1,162 @@
+// Rust bindings for the `Foo` contract.
+// Generated by `fe build --emit rust-bindings`; do not edit.
+
+#[allow(unused_imports)]
+use ethabi::{Address, Hash, Int, ParamType, Token, Uint};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetPointResult {
+    pub x: Uint,
+    pub y: Int,
+}
+
+impl GetPointResult {
+    pub fn into_token(self) -> Token {
+        Token::Tuple(vec![Token::Uint(self.x), Token::Int(self.y)])
+    }
+
+    pub fn from_token(token: Token) -> Result<Self, ethabi::Error> {
+        let mut tokens = token.into_tuple().ok_or(ethabi::Error::InvalidData)?.into_iter();
+        Ok(Self {
+            x: tokens.next().ok_or(ethabi::Error::InvalidData)?.into_uint().ok_or(ethabi::Error::InvalidData)?,
+            y: tokens.next().ok_or(ethabi::Error::InvalidData)?.into_int().ok_or(ethabi::Error::InvalidData)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetPointCall3 {
+    pub z: bool,
+}
+
+impl GetPointCall3 {
+    pub fn into_token(self) -> Token {
+        Token::Tuple(vec![Token::Bool(self.z)])
+    }
+
+    pub fn from_token(token: Token) -> Result<Self, ethabi::Error> {
+        let mut tokens = token.into_tuple().ok_or(ethabi::Error::InvalidData)?.into_iter();
+        Ok(Self {
+            z: tokens.next().ok_or(ethabi::Error::InvalidData)?.into_bool().ok_or(ethabi::Error::InvalidData)?,
+        })
+    }
+}
+
+/// Deploys the contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstructorCall {
+    pub owner: Address,
+}
+
+impl ConstructorCall {
+    /// Returns the deployment code for the contract `bytecode`.
+    pub fn encode(self, bytecode: &[u8]) -> Vec<u8> {
+        let mut data = bytecode.to_vec();
+        data.extend(ethabi::encode(&[Token::Address(self.owner)]));
+        data
+    }
+}
+
+/// Calls `get_point(uint8)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetPointCall {
+    pub r#type: Uint,
+}
+
+impl GetPointCall {
+    pub const SELECTOR: [u8; 4] = [0x62, 0xf3, 0x3e, 0x34];
+
+    /// Returns the calldata for this call.
+    pub fn encode(self) -> Vec<u8> {
+        let mut data = Self::SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[Token::Uint(self.r#type)]));
+        data
+    }
+
+    /// Decodes the data returned by this call.
+    pub fn decode_output(data: &[u8]) -> Result<GetPointResult, ethabi::Error> {
+        let mut tokens = ethabi::decode(&[ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Int(8)])], data)?.into_iter();
+        GetPointResult::from_token(tokens.next().ok_or(ethabi::Error::InvalidData)?)
+    }
+}
+
+/// Calls `set_points((uint256,int8)[2])`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetPointsCall {
+    pub points: Vec<GetPointResult>,
+}
+
+impl SetPointsCall {
+    pub const SELECTOR: [u8; 4] = [0xbb, 0x8e, 0x8e, 0xa4];
+
+    /// Returns the calldata for this call.
+    pub fn encode(self) -> Vec<u8> {
+        let mut data = Self::SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[Token::FixedArray(self.points.into_iter().map(|elem| elem.into_token()).collect())]));
+        data
+    }
+}
+
+/// Calls `get_point()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetPointCall2;
+
+impl GetPointCall2 {
+    pub const SELECTOR: [u8; 4] = [0x37, 0x00, 0x52, 0x4d];
+
+    /// Returns the calldata for this call.
+    pub fn encode(self) -> Vec<u8> {
+        let mut data = Self::SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[]));
+        data
+    }
+
+    /// Decodes the data returned by this call.
+    pub fn decode_output(data: &[u8]) -> Result<bool, ethabi::Error> {
+        let mut tokens = ethabi::decode(&[ParamType::Bool], data)?.into_iter();
+        tokens.next().ok_or(ethabi::Error::InvalidData)?.into_bool().ok_or(ethabi::Error::InvalidData)
+    }
+}
+
+/// Calls `set_origin((bool))`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetOriginCall {
+    pub get_point_call: GetPointCall3,
+}
+
+impl SetOriginCall {
+    pub const SELECTOR: [u8; 4] = [0x18, 0x69, 0x81, 0x99];
+
+    /// Returns the calldata for this call.
+    pub fn encode(self) -> Vec<u8> {
+        let mut data = Self::SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[self.get_point_call.into_token()]));
+        data
+    }
+}
+
+/// The `Moved(address,string,uint256)` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovedEvent {
+    pub by: Address,
+    pub note: Hash,
+    pub distance: Uint,
+}
+
+impl MovedEvent {
+    pub const TOPIC: [u8; 32] = [0x1a, 0x98, 0x9b, 0x57, 0x02, 0x45, 0x3f, 0x6f, 0xfc, 0xbd, 0x04, 0xe8, 0x52, 0x6b, 0xb5, 0x33, 0x99, 0x21, 0x7d, 0x8c, 0x05, 0xd8, 0xde, 0xf3, 0x80, 0x0d, 0xd0, 0xaa, 0xbd, 0x80, 0x76, 0x97];
+
+    /// Decodes the event from a log emitted by the contract.
+    pub fn decode(log: &ethabi::RawLog) -> Result<Self, ethabi::Error> {
+        if log.topics.first().map(|topic| topic.as_bytes()) != Some(&Self::TOPIC[..]) {
+            return Err(ethabi::Error::InvalidData);
+        }
+        let mut topics = log.topics.iter().skip(1);
+        let mut tokens = ethabi::decode(&[ParamType::Uint(256)], &log.data)?.into_iter();
+        Ok(Self {
+            by: ethabi::decode(&[ParamType::Address], topics.next().ok_or(ethabi::Error::InvalidData)?.as_bytes())?.into_iter().next().ok_or(ethabi::Error::InvalidData)?.into_address().ok_or(ethabi::Error::InvalidData)?,
+            note: *topics.next().ok_or(ethabi::Error::InvalidData)?,
+            distance: tokens.next().ok_or(ethabi::Error::InvalidData)?.into_uint().ok_or(ethabi::Error::InvalidData)?,
+        })
+    }
+}
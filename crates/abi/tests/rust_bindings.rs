@@ -0,0 +1,84 @@
+//! Compiles the Rust bindings generated for the test contract in
+//! `src/rust_bindings.rs` and checks that they round-trip ABI data.
+
+#[allow(dead_code)]
+mod foo {
+    include!("fixtures/foo_bindings.rs");
+}
+
+use ethabi::{Address, Hash, RawLog, Token};
+use foo::*;
+
+#[test]
+fn encode_calls() {
+    let point = GetPointResult {
+        x: 1.into(),
+        y: 2.into(),
+    };
+    let data = SetPointsCall {
+        points: vec![point.clone(), point],
+    }
+    .encode();
+    assert_eq!(&data[..4], &SetPointsCall::SELECTOR);
+    assert_eq!(data.len(), 4 + 4 * 32);
+
+    let data = SetOriginCall {
+        get_point_call: GetPointCall3 { z: true },
+    }
+    .encode();
+    assert_eq!(
+        data[4..],
+        ethabi::encode(&[Token::Tuple(vec![Token::Bool(true)])])
+    );
+    assert_ne!(GetPointCall::SELECTOR, GetPointCall2::SELECTOR);
+
+    let code = [0xfe];
+    let data = ConstructorCall {
+        owner: Address::repeat_byte(1),
+    }
+    .encode(&code);
+    assert_eq!(data[..1], code);
+    assert_eq!(data.len(), 1 + 32);
+}
+
+#[test]
+fn decode_outputs() {
+    let point = GetPointResult {
+        x: 3.into(),
+        y: 4.into(),
+    };
+    let data = ethabi::encode(&[point.clone().into_token()]);
+    assert_eq!(GetPointCall::decode_output(&data).unwrap(), point);
+
+    let data = ethabi::encode(&[Token::Bool(true)]);
+    assert!(GetPointCall2::decode_output(&data).unwrap());
+    assert!(GetPointCall::decode_output(&data).is_err());
+}
+
+#[test]
+fn decode_event() {
+    let by = Address::repeat_byte(0xaa);
+    let note = Hash::repeat_byte(0xbb);
+    let log = RawLog {
+        topics: vec![
+            Hash::from(MovedEvent::TOPIC),
+            Hash::from_slice(&ethabi::encode(&[Token::Address(by)])),
+            note,
+        ],
+        data: ethabi::encode(&[Token::Uint(7.into())]),
+    };
+    assert_eq!(
+        MovedEvent::decode(&log).unwrap(),
+        MovedEvent {
+            by,
+            note,
+            distance: 7.into(),
+        }
+    );
+
+    let log = RawLog {
+        topics: vec![Hash::zero()],
+        data: vec![],
+    };
+    assert!(MovedEvent::decode(&log).is_err());
+}
@@ -1,4 +1,5 @@
 use crate::namespace::types::Base;
+use fe_common::EvmVersion;
 use strum::{AsRefStr, EnumIter, EnumString};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumString, AsRefStr)]
@@ -70,8 +71,11 @@ pub enum Intrinsic {
     __mload,          // (p)
     __mstore,         // (p, v) -> ()
     __mstore8,        // (p, v) -> ()
+    __mcopy,          // (t, f, s) -> ()
     __sload,          // (p)
     __sstore,         // (p, v) -> ()
+    __tload,          // (p)
+    __tstore,         // (p, v) -> ()
     __msize,          // ()
     __gas,            // ()
     __address,        // ()
@@ -112,8 +116,11 @@ pub enum Intrinsic {
     __coinbase,       // ()
     __timestamp,      // ()
     __number,         // ()
+    __difficulty,     // ()
     __prevrandao,     // ()
     __gaslimit,       // ()
+    __blobhash,       // (i)
+    __blobbasefee,    // ()
 }
 
 impl Intrinsic {
@@ -123,18 +130,19 @@ impl Intrinsic {
             __stop | __basefee | __origin | __gasprice | __coinbase | __timestamp | __number
             | __prevrandao | __gaslimit | __pc | __msize | __gas | __address | __selfbalance
             | __caller | __callvalue | __calldatasize | __codesize | __returndatasize
-            | __invalid | __chainid => 0,
+            | __invalid | __chainid | __difficulty | __blobbasefee => 0,
 
             __not | __iszero | __pop | __mload | __balance | __sload | __calldataload
-            | __extcodesize | __extcodehash | __selfdestruct | __blockhash => 1,
+            | __extcodesize | __extcodehash | __selfdestruct | __blockhash | __tload
+            | __blobhash => 1,
 
             __add | __sub | __mul | __div | __sdiv | __mod | __smod | __exp | __lt | __gt
             | __slt | __sgt | __eq | __and | __or | __xor | __byte | __shl | __shr | __sar
             | __signextend | __keccak256 | __mstore | __mstore8 | __sstore | __return
-            | __revert | __log0 => 2,
+            | __revert | __log0 | __tstore => 2,
 
             __addmod | __mulmod | __calldatacopy | __codecopy | __returndatacopy | __create
-            | __log1 => 3,
+            | __log1 | __mcopy => 3,
             __extcodecopy | __create2 | __log2 => 4,
             __log3 => 5,
             __delegatecall | __staticcall | __log4 => 6,
@@ -147,8 +155,36 @@ impl Intrinsic {
         match self {
             __stop | __pop | __mstore | __mstore8 | __sstore | __calldatacopy | __codecopy
             | __extcodecopy | __returndatacopy | __return | __revert | __selfdestruct
-            | __invalid | __log0 | __log1 | __log2 | __log3 | __log4 => Base::Unit,
+            | __invalid | __log0 | __log1 | __log2 | __log3 | __log4 | __tstore | __mcopy => {
+                Base::Unit
+            }
             _ => Base::u256(),
         }
     }
+
+    /// Returns the EVM version which introduced the intrinsic.
+    pub fn introduced_in(&self) -> EvmVersion {
+        use Intrinsic::*;
+        match self {
+            __chainid | __selfbalance => EvmVersion::Istanbul,
+            __basefee => EvmVersion::London,
+            __prevrandao => EvmVersion::Paris,
+            __tload | __tstore | __mcopy | __blobhash | __blobbasefee => EvmVersion::Cancun,
+            _ => EvmVersion::Constantinople,
+        }
+    }
+
+    /// Returns the EVM version which removed the intrinsic, if any.
+    pub fn removed_in(&self) -> Option<EvmVersion> {
+        match self {
+            Intrinsic::__difficulty => Some(EvmVersion::Paris),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the intrinsic can be used when targeting `version`.
+    pub fn is_available(&self, version: EvmVersion) -> bool {
+        self.introduced_in() <= version
+            && !matches!(self.removed_in(), Some(removed) if removed <= version)
+    }
 }
@@ -2,3 +2,12 @@ pub const EMITTABLE_TRAIT_NAME: &str = "Emittable";
 pub const EMIT_FN_NAME: &str = "emit";
 pub const INDEXED: &str = "indexed";
 pub const MAX_INDEXED_EVENT_FIELDS: usize = 3;
+pub const PAYABLE: &str = "payable";
+pub const INLINE: &str = "inline";
+pub const NOINLINE: &str = "noinline";
+pub const UNPACKED: &str = "unpacked";
+pub const NAMESPACE: &str = "namespace";
+pub const TRANSIENT: &str = "transient";
+
+pub const EVM_VERSION_HINT: &str =
+    "Hint: the target EVM version can be set with `--evm-version` or `evm-version` in fe.toml";
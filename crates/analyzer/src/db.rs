@@ -14,7 +14,7 @@ use crate::{
     namespace::items::EnumVariantId,
 };
 use fe_common::db::{SourceDb, SourceDbStorage, Upcast, UpcastMut};
-use fe_common::{SourceFileId, Span};
+use fe_common::{EvmVersion, SourceFileId, Span};
 use fe_parser::ast;
 use indexmap::map::IndexMap;
 use smol_str::SmolStr;
@@ -70,6 +70,10 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
     // that just need the global entrypoint
     #[salsa::input]
     fn root_ingot(&self) -> IngotId;
+    // The EVM version which the compiled code targets. Calls to intrinsics
+    // which aren't available in it are rejected.
+    #[salsa::input]
+    fn evm_version(&self) -> EvmVersion;
 
     #[salsa::invoke(queries::ingots::ingot_modules)]
     fn ingot_modules(&self, ingot: IngotId) -> Rc<[ModuleId]>;
@@ -134,6 +138,10 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
     fn contract_init_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
     #[salsa::invoke(queries::contracts::contract_call_function)]
     fn contract_call_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
+    #[salsa::invoke(queries::contracts::contract_receive_function)]
+    fn contract_receive_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
+    #[salsa::invoke(queries::contracts::contract_fallback_function)]
+    fn contract_fallback_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
 
     #[salsa::invoke(queries::contracts::contract_all_fields)]
     fn contract_all_fields(&self, id: ContractId) -> Rc<[ContractFieldId]>;
@@ -219,12 +227,21 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
 }
 
 #[salsa::database(AnalyzerDbStorage, SourceDbStorage)]
-#[derive(Default)]
 pub struct TestDb {
     storage: salsa::Storage<TestDb>,
 }
 impl salsa::Database for TestDb {}
 
+impl Default for TestDb {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: salsa::Storage::default(),
+        };
+        db.set_evm_version(EvmVersion::default());
+        db
+    }
+}
+
 impl Upcast<dyn SourceDb> for TestDb {
     fn upcast(&self) -> &(dyn SourceDb + 'static) {
         self
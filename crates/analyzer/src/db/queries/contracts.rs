@@ -1,15 +1,20 @@
+use crate::builtins::Intrinsic;
+use crate::constants::EVM_VERSION_HINT;
 use crate::context::AnalyzerContext;
 use crate::db::{Analysis, AnalyzerDb};
+use crate::display::Displayable;
 use crate::errors;
 use crate::namespace::items::{
-    self, ContractFieldId, ContractId, DepGraph, DepGraphWrapper, DepLocality, FunctionId, Item,
-    TypeDef,
+    self, is_special_function_name, ContractFieldId, ContractId, DepGraph, DepGraphWrapper,
+    DepLocality, FunctionId, Item, TypeDef,
 };
 use crate::namespace::scopes::ItemScope;
 use crate::namespace::types::{self, Type};
 use crate::traversal::types::type_desc;
-use fe_common::diagnostics::Label;
+use fe_common::diagnostics::{Diagnostic, Label};
+use fe_common::Span;
 use fe_parser::ast;
+use fe_parser::node::Node;
 use indexmap::map::{Entry, IndexMap};
 use smol_str::SmolStr;
 use std::rc::Rc;
@@ -20,13 +25,16 @@ pub fn contract_all_functions(db: &dyn AnalyzerDb, contract: ContractId) -> Rc<[
     let module = contract.module(db);
     let body = &contract.data(db).ast.kind.body;
     body.iter()
-        .map(|stmt| match stmt {
-            ast::ContractStmt::Function(node) => db.intern_function(Rc::new(items::Function::new(
-                db,
-                node,
-                Some(Item::Type(TypeDef::Contract(contract))),
-                module,
-            ))),
+        .filter_map(|stmt| match stmt {
+            ast::ContractStmt::Function(node) => {
+                Some(db.intern_function(Rc::new(items::Function::new(
+                    db,
+                    node,
+                    Some(Item::Type(TypeDef::Contract(contract))),
+                    module,
+                ))))
+            }
+            ast::ContractStmt::Attribute(_) => None,
         })
         .collect()
 }
@@ -41,10 +49,21 @@ pub fn contract_function_map(
     for func in db.contract_all_functions(contract).iter() {
         let def = &func.data(db).ast;
         let def_name = def.name();
-        if def_name == "__init__" || def_name == "__call__" {
+        if is_special_function_name(def_name) {
             continue;
         }
 
+        if func.is_payable(db) && !func.is_public(db) {
+            scope.fancy_error(
+                "`#payable` can only be applied to public contract functions",
+                vec![Label::primary(
+                    func.name_span(db),
+                    "this function can't be called externally",
+                )],
+                vec!["Hint: Make the function public with `pub fn`".into()],
+            );
+        }
+
         if let Ok(Some(named_item)) = scope.resolve_name(def_name, func.name_span(db)) {
             scope.name_conflict_error(
                 "function",
@@ -184,26 +203,119 @@ pub fn contract_call_function(
     db: &dyn AnalyzerDb,
     contract: ContractId,
 ) -> Analysis<Option<FunctionId>> {
+    let (first_def, mut diagnostics) = contract_special_function(db, contract, "__call__");
+
+    if let Some((_id, init_span)) = first_def {
+        for func in db.contract_all_functions(contract).iter() {
+            let name = func.name(db);
+            if func.is_public(db) && !is_special_function_name(&name) {
+                diagnostics.push(errors::fancy_error(
+                    "`pub` not allowed if `__call__` is defined",
+                    vec![
+                        Label::primary(func.name_span(db), format!("`{name}` can't be public")),
+                        Label::secondary(init_span, "`__call__` defined here"),
+                    ],
+                    vec![
+                        "The `__call__` function replaces the default function dispatcher, which makes `pub` modifiers obsolete.".to_string(),
+                        "Hint: Remove the `pub` modifier or `__call__` function.".to_string(),
+                    ],
+                ));
+            }
+        }
+    }
+
+    Analysis {
+        value: first_def.map(|(id, _span)| id),
+        diagnostics: diagnostics.into(),
+    }
+}
+
+pub fn contract_receive_function(
+    db: &dyn AnalyzerDb,
+    contract: ContractId,
+) -> Analysis<Option<FunctionId>> {
+    contract_dispatcher_hook(db, contract, "__receive__")
+}
+
+pub fn contract_fallback_function(
+    db: &dyn AnalyzerDb,
+    contract: ContractId,
+) -> Analysis<Option<FunctionId>> {
+    contract_dispatcher_hook(db, contract, "__fallback__")
+}
+
+/// Looks up the `__receive__` or `__fallback__` function, which the
+/// dispatcher calls when no public function matches the call data.
+fn contract_dispatcher_hook(
+    db: &dyn AnalyzerDb,
+    contract: ContractId,
+    name: &str,
+) -> Analysis<Option<FunctionId>> {
+    let (first_def, mut diagnostics) = contract_special_function(db, contract, name);
+
+    if let Some((id, span)) = first_def {
+        let sig = id.signature(db);
+        if sig.params.len() > usize::from(sig.ctx_decl.is_some()) {
+            diagnostics.push(errors::fancy_error(
+                format!("`{name}` function has parameters"),
+                vec![Label::primary(
+                    id.data(db).ast.kind.sig.span,
+                    format!("`{name}` can only take `self` and `ctx`"),
+                )],
+                vec![format!("Example: `pub fn {name}(mut self, ctx: Context):`")],
+            ));
+        }
+
+        if let Some(call_fn) = contract.call_function(db) {
+            diagnostics.push(errors::fancy_error(
+                format!("`{name}` can't be used together with `__call__`"),
+                vec![
+                    Label::primary(span, format!("`{name}` defined here")),
+                    Label::secondary(call_fn.data(db).ast.span, "`__call__` defined here"),
+                ],
+                vec![format!(
+                    "The `__call__` function replaces the default function dispatcher, which is what calls `{name}`."
+                )],
+            ));
+        }
+    }
+
+    Analysis {
+        value: first_def.map(|(id, _span)| id),
+        diagnostics: diagnostics.into(),
+    }
+}
+
+/// Finds the definition of the special function `name`, reporting multiple
+/// definitions and a missing `pub` modifier.
+fn contract_special_function(
+    db: &dyn AnalyzerDb,
+    contract: ContractId,
+    name: &str,
+) -> (Option<(FunctionId, Span)>, Vec<Diagnostic>) {
     let all_fns = db.contract_all_functions(contract);
-    let mut call_fns = all_fns.iter().filter_map(|func| {
+    let mut special_fns = all_fns.iter().filter_map(|func| {
         let def = &func.data(db).ast;
-        (def.name() == "__call__").then_some((func, def.span))
+        (def.name() == name).then_some((*func, def.span))
     });
 
     let mut diagnostics = vec![];
 
-    let first_def = call_fns.next();
-    if let Some((_, dupe_span)) = call_fns.next() {
+    let first_def = special_fns.next();
+    if let Some((_, dupe_span)) = special_fns.next() {
         let mut labels = vec![
-            Label::primary(first_def.unwrap().1, "`__call__` first defined here"),
-            Label::secondary(dupe_span, "`__call__` redefined here"),
+            Label::primary(first_def.unwrap().1, format!("`{name}` first defined here")),
+            Label::secondary(dupe_span, format!("`{name}` redefined here")),
         ];
-        for (_, dupe_span) in call_fns {
-            labels.push(Label::secondary(dupe_span, "`__call__` redefined here"));
+        for (_, dupe_span) in special_fns {
+            labels.push(Label::secondary(
+                dupe_span,
+                format!("`{name}` redefined here"),
+            ));
         }
         diagnostics.push(errors::fancy_error(
             format!(
-                "`fn __call__()` is defined multiple times in `contract {}`",
+                "`fn {name}()` is defined multiple times in `contract {}`",
                 contract.name(db),
             ),
             labels,
@@ -212,43 +324,24 @@ pub fn contract_call_function(
     }
 
     if let Some((id, span)) = first_def {
-        // `__call__` must be `pub`.
+        // Special functions must be `pub`.
         // Return type is checked in `queries::functions::function_signature`.
         if !id.is_public(db) {
             diagnostics.push(errors::fancy_error(
-                "`__call__` function is not public",
-                vec![Label::primary(span, "`__call__` function must be public")],
+                format!("`{name}` function is not public"),
+                vec![Label::primary(
+                    span,
+                    format!("`{name}` function must be public"),
+                )],
                 vec![
                     "Hint: Add the `pub` modifier.".to_string(),
-                    "Example: `pub fn __call__():`".to_string(),
+                    format!("Example: `pub fn {name}():`"),
                 ],
             ));
         }
     }
 
-    if let Some((_id, init_span)) = first_def {
-        for func in all_fns.iter() {
-            let name = func.name(db);
-            if func.is_public(db) && name != "__init__" && name != "__call__" {
-                diagnostics.push(errors::fancy_error(
-                    "`pub` not allowed if `__call__` is defined",
-                    vec![
-                        Label::primary(func.name_span(db), format!("`{name}` can't be public")),
-                        Label::secondary(init_span, "`__call__` defined here"),
-                    ],
-                    vec![
-                        "The `__call__` function replaces the default function dispatcher, which makes `pub` modifiers obsolete.".to_string(),
-                        "Hint: Remove the `pub` modifier or `__call__` function.".to_string(),
-                    ],
-                ));
-            }
-        }
-    }
-
-    Analysis {
-        value: first_def.map(|(id, _span)| *id),
-        diagnostics: diagnostics.into(),
-    }
+    (first_def, diagnostics)
 }
 
 /// All field ids, including those with duplicate names
@@ -319,6 +412,9 @@ pub fn contract_field_type(
     if let Some(value_node) = &node.kind.value {
         scope.not_yet_implemented("contract field initial value assignment", value_node.span);
     }
+    if field.is_transient(db) {
+        check_transient_field(&mut scope, node, &typ);
+    }
 
     Analysis {
         value: typ,
@@ -326,6 +422,39 @@ pub fn contract_field_type(
     }
 }
 
+/// Transient fields are loaded and stored with single `tload` and `tstore`
+/// instructions, so they must have a primitive type.
+fn check_transient_field(
+    scope: &mut ItemScope,
+    node: &Node<ast::Field>,
+    typ: &Result<types::TypeId, errors::TypeError>,
+) {
+    let version = scope.db().evm_version();
+    if !Intrinsic::__tload.is_available(version) {
+        scope.fancy_error(
+            &format!("transient storage is not available in the `{version}` EVM version"),
+            vec![Label::primary(
+                node.span,
+                format!(
+                    "`#transient` requires the `{}` EVM version or later",
+                    Intrinsic::__tload.introduced_in()
+                ),
+            )],
+            vec![EVM_VERSION_HINT.into()],
+        );
+    }
+
+    if let Ok(typ) = typ {
+        if !typ.is_primitive(scope.db()) {
+            scope.error(
+                "`#transient` fields must have a primitive type",
+                node.kind.typ.span,
+                &format!("this has type `{}`", typ.display(scope.db())),
+            );
+        }
+    }
+}
+
 pub fn contract_dependency_graph(db: &dyn AnalyzerDb, contract: ContractId) -> DepGraphWrapper {
     // A contract depends on the types of its fields, and the things those types
     // depend on. Note that this *does not* include the contract's public
@@ -378,7 +507,10 @@ pub fn contract_runtime_dependency_graph(
     let root_fns = if let Some(call_id) = contract.call_function(db) {
         vec![call_id]
     } else {
-        contract.public_functions(db).values().copied().collect()
+        let mut fns: Vec<_> = contract.public_functions(db).values().copied().collect();
+        fns.extend(contract.receive_function(db));
+        fns.extend(contract.fallback_function(db));
+        fns
     }
     .into_iter()
     .map(|fun| (root, Item::Function(fun), DepLocality::Local))
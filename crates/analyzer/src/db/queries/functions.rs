@@ -3,7 +3,8 @@ use crate::db::{Analysis, AnalyzerDb};
 use crate::display::Displayable;
 use crate::errors::TypeError;
 use crate::namespace::items::{
-    DepGraph, DepGraphWrapper, DepLocality, FunctionId, FunctionSigId, Item, TypeDef,
+    is_special_function_name, DepGraph, DepGraphWrapper, DepLocality, FunctionId, FunctionSigId,
+    Item, TypeDef,
 };
 use crate::namespace::scopes::{BlockScope, BlockScopeType, FunctionScope, ItemScope};
 use crate::namespace::types::{self, CtxDecl, Generic, SelfDecl, Type, TypeId};
@@ -221,8 +222,9 @@ pub fn function_signature(
         .as_ref()
         .map(|type_node| {
             let fn_name = &function.name(db);
-            if fn_name == "__init__" || fn_name == "__call__" {
-                // `__init__` and `__call__` must not return any type other than `()`.
+            if is_special_function_name(fn_name) {
+                // `__init__`, `__call__`, `__receive__` and `__fallback__` must not
+                // return any type other than `()`.
                 if type_node.kind != ast::TypeDesc::Unit {
                     scope.fancy_error(
                         &format!("`{fn_name}` function has incorrect return type"),
@@ -325,6 +327,28 @@ pub fn function_body(db: &dyn AnalyzerDb, function: FunctionId) -> Analysis<Rc<F
         }
     }
 
+    if function.is_inline(db) && function.is_noinline(db) {
+        scope.fancy_error(
+            "conflicting inline attributes",
+            vec![Label::primary(
+                function.name_span(db),
+                "this function is marked with both `#inline` and `#noinline`",
+            )],
+            vec!["Hint: Remove one of the attributes".into()],
+        );
+    }
+
+    if !function.is_contract_func(db) && function.is_payable(db) {
+        scope.fancy_error(
+            "`#payable` can only be applied to public contract functions",
+            vec![Label::primary(
+                function.name_span(db),
+                "this function can't receive ether",
+            )],
+            vec!["Hint: Remove the `#payable` attribute".into()],
+        );
+    }
+
     let mut block_scope = BlockScope::new(
         &scope,
         if function.is_unsafe(db) {
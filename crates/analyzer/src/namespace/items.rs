@@ -1,4 +1,6 @@
-use crate::constants::{EMITTABLE_TRAIT_NAME, INDEXED};
+use crate::constants::{
+    EMITTABLE_TRAIT_NAME, INDEXED, INLINE, NAMESPACE, NOINLINE, PAYABLE, TRANSIENT, UNPACKED,
+};
 use crate::context::{self, Analysis, Constant, NamedThing};
 use crate::display::{DisplayWithDb, Displayable};
 use crate::errors::{self, IncompleteItem, TypeError};
@@ -10,11 +12,13 @@ use fe_common::diagnostics::Diagnostic;
 use fe_common::diagnostics::Label;
 use fe_common::files::{common_prefix, Utf8Path};
 use fe_common::utils::files::{BuildFiles, ProjectMode};
+use fe_common::utils::keccak;
 use fe_common::{impl_intern_key, FileKind, SourceFileId};
 use fe_parser::ast::GenericParameter;
 use fe_parser::node::{Node, Span};
 use fe_parser::{ast, node::NodeId};
 use indexmap::{indexmap, IndexMap};
+use num_bigint::BigInt;
 use smallvec::SmallVec;
 use smol_str::SmolStr;
 use std::rc::Rc;
@@ -253,10 +257,8 @@ impl Item {
             Item::Trait(id) => id.sink_diagnostics(db, sink),
             Item::Impl(id) => id.sink_diagnostics(db, sink),
             Item::Function(id) => id.sink_diagnostics(db, sink),
-            Item::GenericType(_)
-            | Item::BuiltinFunction(_)
-            | Item::Intrinsic(_)
-            | Item::Attribute(_) => {}
+            Item::GenericType(_) | Item::BuiltinFunction(_) | Item::Intrinsic(_) => {}
+            Item::Attribute(id) => id.sink_diagnostics(db, sink),
             Item::Constant(id) => id.sink_diagnostics(db, sink),
             Item::Ingot(id) => id.sink_diagnostics(db, sink),
             Item::Module(id) => id.sink_diagnostics(db, sink),
@@ -1059,10 +1061,47 @@ impl ContractId {
         self.data(db).module
     }
 
+    /// Returns `true` if the contract is marked with `#unpacked`, i.e., each
+    /// field is stored in its own storage slot.
+    pub fn is_unpacked(&self, db: &dyn AnalyzerDb) -> bool {
+        Item::Type(TypeDef::Contract(*self))
+            .attributes(db)
+            .iter()
+            .any(|attribute| attribute.name(db) == UNPACKED)
+    }
+
+    /// Returns the ERC-7201 namespace id if the contract is marked with
+    /// `#namespace("<id>")`, i.e., its fields are stored from the root slot
+    /// of the namespace instead of the slot `0`.
+    pub fn storage_namespace(&self, db: &dyn AnalyzerDb) -> Option<SmolStr> {
+        Item::Type(TypeDef::Contract(*self))
+            .attributes(db)
+            .iter()
+            .filter(|attribute| attribute.name(db) == NAMESPACE)
+            .find_map(|attribute| attribute.arg(db))
+    }
+
     pub fn fields(&self, db: &dyn AnalyzerDb) -> Rc<IndexMap<SmolStr, ContractFieldId>> {
         db.contract_field_map(*self).value
     }
 
+    /// Returns the transient storage slot of the `#transient` field `name`.
+    /// Transient fields take a slot each, in the order of their declaration,
+    /// from the root slot of the ERC-7201 namespace if the contract is marked
+    /// with `#namespace("<id>")`, and from the slot `0` otherwise.
+    pub fn transient_field_slot(&self, db: &dyn AnalyzerDb, name: &str) -> Option<BigInt> {
+        let index = self
+            .fields(db)
+            .values()
+            .filter(|field| field.is_transient(db))
+            .position(|field| field.name(db) == name)?;
+        let root = match self.storage_namespace(db) {
+            Some(id) => keccak::erc7201_slot(&id),
+            None => BigInt::default(),
+        };
+        Some(root + index)
+    }
+
     pub fn field_type(
         &self,
         db: &dyn AnalyzerDb,
@@ -1097,11 +1136,24 @@ impl ContractId {
         db.contract_call_function(*self).value
     }
 
+    /// The function the dispatcher calls when the contract receives ether
+    /// with empty call data.
+    pub fn receive_function(&self, db: &dyn AnalyzerDb) -> Option<FunctionId> {
+        db.contract_receive_function(*self).value
+    }
+
+    /// The function the dispatcher calls when no other function matches the
+    /// call data.
+    pub fn fallback_function(&self, db: &dyn AnalyzerDb) -> Option<FunctionId> {
+        db.contract_fallback_function(*self).value
+    }
+
     pub fn all_functions(&self, db: &dyn AnalyzerDb) -> Rc<[FunctionId]> {
         db.contract_all_functions(*self)
     }
 
-    /// User functions, public and not. Excludes `__init__` and `__call__`.
+    /// User functions, public and not. Excludes `__init__`, `__call__`,
+    /// `__receive__` and `__fallback__`.
     pub fn functions(&self, db: &dyn AnalyzerDb) -> Rc<IndexMap<SmolStr, FunctionId>> {
         db.contract_function_map(*self).value
     }
@@ -1145,6 +1197,8 @@ impl ContractId {
         // functions
         db.contract_init_function(*self).sink_diagnostics(sink);
         db.contract_call_function(*self).sink_diagnostics(sink);
+        db.contract_receive_function(*self).sink_diagnostics(sink);
+        db.contract_fallback_function(*self).sink_diagnostics(sink);
         db.contract_function_map(*self).sink_diagnostics(sink);
         db.contract_all_functions(*self)
             .iter()
@@ -1171,6 +1225,17 @@ impl ContractFieldId {
     pub fn typ(&self, db: &dyn AnalyzerDb) -> Result<types::TypeId, TypeError> {
         db.contract_field_type(*self).value
     }
+
+    /// Returns `true` if the field is marked with `#transient`, i.e., it's kept
+    /// in transient storage, which is cleared at the end of the transaction.
+    pub fn is_transient(&self, db: &dyn AnalyzerDb) -> bool {
+        self.data(db)
+            .ast
+            .kind
+            .attributes
+            .iter()
+            .any(|attribute| attribute.kind == TRANSIENT)
+    }
     pub fn sink_diagnostics(&self, db: &dyn AnalyzerDb, sink: &mut impl DiagnosticSink) {
         sink.push_all(db.contract_field_type(*self).diagnostics.iter())
     }
@@ -1297,6 +1362,15 @@ impl FunctionSigId {
     }
 }
 
+/// Returns `true` for the functions that are handled by the compiler instead
+/// of being dispatched to by their selector.
+pub(crate) fn is_special_function_name(name: &str) -> bool {
+    matches!(
+        name,
+        "__init__" | "__call__" | "__receive__" | "__fallback__"
+    )
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Function {
     pub ast: Node<ast::Function>,
@@ -1395,6 +1469,56 @@ impl FunctionId {
             .iter()
             .any(|attribute| attribute.name(db) == "test")
     }
+
+    /// The names of the attributes preceding the function definition.
+    pub fn attributes(&self, db: &dyn AnalyzerDb) -> Vec<SmolStr> {
+        if let Item::Type(TypeDef::Contract(contract)) = self.parent(db) {
+            let mut attributes = vec![];
+            for stmt in contract.data(db).ast.kind.body.iter() {
+                match stmt {
+                    ast::ContractStmt::Attribute(attribute) => {
+                        attributes.push(attribute.kind.clone())
+                    }
+                    ast::ContractStmt::Function(func) if func.id == self.data(db).ast.id => {
+                        return attributes;
+                    }
+                    ast::ContractStmt::Function(_) => attributes.clear(),
+                }
+            }
+            vec![]
+        } else {
+            Item::Function(*self)
+                .attributes(db)
+                .iter()
+                .map(|attribute| attribute.name(db))
+                .collect()
+        }
+    }
+
+    /// Returns `true` if the function is allowed to receive ether.
+    /// `__receive__` is always payable, other functions are payable if marked
+    /// with `#payable`.
+    pub fn is_payable(&self, db: &dyn AnalyzerDb) -> bool {
+        (self.is_contract_func(db) && self.name(db) == "__receive__")
+            || self
+                .attributes(db)
+                .iter()
+                .any(|attribute| attribute == PAYABLE)
+    }
+
+    /// Returns `true` if the function is marked with `#inline`.
+    pub fn is_inline(&self, db: &dyn AnalyzerDb) -> bool {
+        self.attributes(db)
+            .iter()
+            .any(|attribute| attribute == INLINE)
+    }
+
+    /// Returns `true` if the function is marked with `#noinline`.
+    pub fn is_noinline(&self, db: &dyn AnalyzerDb) -> bool {
+        self.attributes(db)
+            .iter()
+            .any(|attribute| attribute == NOINLINE)
+    }
 }
 
 trait FunctionsAsItems {
@@ -1465,6 +1589,15 @@ impl StructId {
         self.data(db).module
     }
 
+    /// Returns `true` if the struct is marked with `#unpacked`, i.e., each
+    /// field is stored in its own storage slot.
+    pub fn is_unpacked(&self, db: &dyn AnalyzerDb) -> bool {
+        Item::Type(TypeDef::Struct(*self))
+            .attributes(db)
+            .iter()
+            .any(|attribute| attribute.name(db) == UNPACKED)
+    }
+
     pub fn as_type(&self, db: &dyn AnalyzerDb) -> TypeId {
         db.intern_type(Type::Struct(*self))
     }
@@ -1559,7 +1692,7 @@ impl StructFieldId {
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Attribute {
-    pub ast: Node<SmolStr>,
+    pub ast: Node<ast::Attribute>,
     pub module: ModuleId,
 }
 #[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
@@ -1574,7 +1707,18 @@ impl AttributeId {
         self.data(db).ast.span
     }
     pub fn name(self, db: &dyn AnalyzerDb) -> SmolStr {
-        self.data(db).ast.kind.to_owned()
+        self.data(db).ast.kind.name.kind.to_owned()
+    }
+
+    /// The string argument of the attribute, e.g. `"example.main"` of
+    /// `#namespace("example.main")`.
+    pub fn arg(self, db: &dyn AnalyzerDb) -> Option<SmolStr> {
+        self.data(db)
+            .ast
+            .kind
+            .arg
+            .as_ref()
+            .map(|arg| arg.kind.clone())
     }
 
     pub fn module(self, db: &dyn AnalyzerDb) -> ModuleId {
@@ -1584,6 +1728,51 @@ impl AttributeId {
     pub fn parent(self, db: &dyn AnalyzerDb) -> Item {
         Item::Module(self.data(db).module)
     }
+
+    /// The item the attribute is attached to.
+    pub fn target(self, db: &dyn AnalyzerDb) -> Option<Item> {
+        let module = self.module(db);
+        module
+            .all_items(db)
+            .iter()
+            .skip_while(|item| **item != Item::Attribute(self))
+            .find(|item| !matches!(item, Item::Attribute(_)))
+            .copied()
+    }
+
+    pub fn sink_diagnostics(self, db: &dyn AnalyzerDb, sink: &mut impl DiagnosticSink) {
+        let data = self.data(db);
+        let name = &data.ast.kind.name;
+        match (name.kind.as_str(), &data.ast.kind.arg) {
+            (NAMESPACE, None) => sink.push(&errors::error(
+                "missing storage namespace",
+                name.span,
+                "`#namespace` requires a namespace id, e.g. `#namespace(\"example.main\")`",
+            )),
+            (NAMESPACE, Some(arg)) => {
+                if arg.kind.is_empty() {
+                    sink.push(&errors::error(
+                        "empty storage namespace",
+                        arg.span,
+                        "the namespace id must not be empty",
+                    ))
+                }
+                if !matches!(self.target(db), Some(Item::Type(TypeDef::Contract(_)))) {
+                    sink.push(&errors::error(
+                        "`#namespace` can only be applied to a contract",
+                        data.ast.span,
+                        "not followed by a contract definition",
+                    ))
+                }
+            }
+            (_, Some(arg)) => sink.push(&errors::error(
+                format!("attribute `{}` doesn't take an argument", name.kind),
+                arg.span,
+                "unexpected argument",
+            )),
+            (_, None) => {}
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -1,10 +1,12 @@
 use super::borrowck;
 use crate::builtins::{ContractTypeMethod, GlobalFunction, Intrinsic, ValueMethod};
+use crate::constants::EVM_VERSION_HINT;
 use crate::context::{AnalyzerContext, CallType, Constant, ExpressionAttributes, NamedThing};
 use crate::display::Displayable;
 use crate::errors::{self, FatalError, IndexingError, TypeCoercionError};
 use crate::namespace::items::{
-    EnumVariantId, EnumVariantKind, FunctionId, FunctionSigId, ImplId, Item, StructId, TypeDef,
+    is_special_function_name, EnumVariantId, EnumVariantKind, FunctionId, FunctionSigId, ImplId,
+    Item, StructId, TypeDef,
 };
 use crate::namespace::scopes::{check_visibility, BlockScopeType};
 use crate::namespace::types::{
@@ -17,15 +19,17 @@ use crate::traversal::types::{
     apply_generic_type_args, deref_type, try_cast_type, try_coerce_type,
 };
 use crate::traversal::utils::add_bin_operations_errors;
+use crate::AnalyzerDb;
 
 use fe_common::diagnostics::Label;
-use fe_common::{numeric, Span};
+use fe_common::{numeric, EvmVersion, Span};
 use fe_parser::ast as fe;
 use fe_parser::ast::GenericArg;
 use fe_parser::node::Node;
 use num_bigint::BigInt;
 use num_traits::{ToPrimitive, Zero};
 use smol_str::SmolStr;
+use std::collections::HashSet;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
@@ -921,6 +925,8 @@ fn expr_call(
         );
     }
 
+    validate_evm_version(context, func.span, &call_type);
+
     if context.is_in_function() {
         context.add_call(func, call_type);
     } else {
@@ -1330,6 +1336,100 @@ fn expr_call_intrinsic(
     ))
 }
 
+/// Reports calls of intrinsics which aren't available in the target EVM
+/// version, either directly or through functions of the standard library.
+fn validate_evm_version(context: &mut dyn AnalyzerContext, span: Span, call_type: &CallType) {
+    let db = context.db();
+    // The standard library wraps every intrinsic, so it's only checked where
+    // it's used.
+    if context.module().is_in_std(db) {
+        return;
+    }
+
+    let version = db.evm_version();
+    match call_type {
+        CallType::Intrinsic(intrinsic) if !intrinsic.is_available(version) => {
+            context.fancy_error(
+                &format!(
+                    "`{}` is not available in the `{version}` EVM version",
+                    intrinsic.as_ref()
+                ),
+                vec![Label::primary(
+                    span,
+                    format!(
+                        "`{}` {}",
+                        intrinsic.as_ref(),
+                        evm_version_requirement(*intrinsic)
+                    ),
+                )],
+                vec![EVM_VERSION_HINT.into()],
+            );
+        }
+        CallType::Pure(function)
+        | CallType::AssociatedFunction { function, .. }
+        | CallType::ValueMethod {
+            method: function, ..
+        } if function.module(db).is_in_std(db) => {
+            if let Some(intrinsic) = unavailable_std_intrinsic(db, *function, version) {
+                let name = function.name(db);
+                context.fancy_error(
+                    &format!("`{name}` is not available in the `{version}` EVM version"),
+                    vec![Label::primary(
+                        span,
+                        format!(
+                            "`{name}` uses `{}`, which {}",
+                            intrinsic.as_ref(),
+                            evm_version_requirement(intrinsic)
+                        ),
+                    )],
+                    vec![EVM_VERSION_HINT.into()],
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn evm_version_requirement(intrinsic: Intrinsic) -> String {
+    match intrinsic.removed_in() {
+        Some(removed) => format!("was removed in the `{removed}` EVM version"),
+        None => format!(
+            "requires the `{}` EVM version or later",
+            intrinsic.introduced_in()
+        ),
+    }
+}
+
+/// Returns an intrinsic which isn't available in `version` and is called by
+/// `function` of the standard library, or by the functions it calls.
+fn unavailable_std_intrinsic(
+    db: &dyn AnalyzerDb,
+    function: FunctionId,
+    version: EvmVersion,
+) -> Option<Intrinsic> {
+    let mut visited = HashSet::new();
+    let mut stack = vec![function];
+    while let Some(function) = stack.pop() {
+        if !visited.insert(function) {
+            continue;
+        }
+        for call_type in function.body(db).calls.values() {
+            match call_type {
+                CallType::Intrinsic(intrinsic) if !intrinsic.is_available(version) => {
+                    return Some(*intrinsic)
+                }
+                CallType::Pure(function)
+                | CallType::AssociatedFunction { function, .. }
+                | CallType::ValueMethod {
+                    method: function, ..
+                } if function.module(db).is_in_std(db) => stack.push(*function),
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
 fn validate_visibility_of_called_fn(
     context: &mut dyn AnalyzerContext,
     call_span: Span,
@@ -2079,13 +2179,17 @@ fn check_for_call_to_special_fns(
     name: &str,
     span: Span,
 ) -> Result<(), FatalError> {
-    if name == "__init__" || name == "__call__" {
-        let label = if name == "__init__" {
-            "Note: `__init__` is the constructor function, and can't be called at runtime."
-        } else {
+    if is_special_function_name(name) {
+        let label = match name {
+            "__init__" => {
+                "Note: `__init__` is the constructor function, and can't be called at runtime."
+            }
             // TODO: add a hint label explaining how to call contracts directly
             // with `Context` (not yet supported).
-            "Note: `__call__` is not part of the contract's interface, and can't be called."
+            "__call__" => {
+                "Note: `__call__` is not part of the contract's interface, and can't be called."
+            }
+            _ => "Note: this function is only called by the contract's dispatcher.",
         };
         Err(FatalError::new(context.fancy_error(
             &format!("`{name}()` is not directly callable"),
@@ -1,14 +1,20 @@
 //! Tests for contracts that should cause compile errors
 
 use fe_analyzer::namespace::items::{IngotId, ModuleId};
-use fe_analyzer::TestDb;
+use fe_analyzer::{AnalyzerDb, TestDb};
 use fe_common::diagnostics::diagnostics_string;
 use fe_common::utils::files::BuildFiles;
+use fe_common::EvmVersion;
 use insta::assert_snapshot;
 use wasm_bindgen_test::wasm_bindgen_test;
 
 fn error_string(path: &str, src: &str) -> String {
+    error_string_for_evm_version(path, src, EvmVersion::default())
+}
+
+fn error_string_for_evm_version(path: &str, src: &str, evm_version: EvmVersion) -> String {
     let mut db = TestDb::default();
+    db.set_evm_version(evm_version);
     let module = ModuleId::new_standalone(&mut db, path, src);
 
     let diags = module.diagnostics(&db);
@@ -70,6 +76,22 @@ macro_rules! test_file {
             }
         }
     };
+    ($name:ident, $evm_version:expr) => {
+        #[test]
+        #[wasm_bindgen_test]
+        fn $name() {
+            let path = concat!("compile_errors/", stringify!($name), ".fe");
+            let src = test_files::fixture(path);
+            if cfg!(target_arch = "wasm32") {
+                fe_common::assert_snapshot_wasm!(
+                    concat!("snapshots/errors__", stringify!($name), ".snap"),
+                    error_string_for_evm_version(&path, src, $evm_version)
+                );
+            } else {
+                assert_snapshot!(error_string_for_evm_version(&path, src, $evm_version));
+            }
+        }
+    };
 }
 
 macro_rules! test_stmt {
@@ -118,6 +140,7 @@ test_stmt! { call_address_with_label, "address(val: 0)" }
 test_stmt! { call_keccak_without_parameter, "keccak256()" }
 test_stmt! { call_keccak_with_wrong_type, "keccak256(true)" }
 test_stmt! { call_keccak_with_2_args, "keccak256(1, 2)" }
+test_stmt! { call_difficulty_after_paris, "unsafe { __difficulty() }" }
 test_stmt! { call_keccak_with_generic_args, "keccak256<10>(1)" }
 test_stmt! { cast_address_to_u64, "u64(address(0))" }
 
@@ -287,6 +310,10 @@ test_file! { not_callable }
 test_file! { not_emittable }
 test_file! { not_in_scope }
 test_file! { not_in_scope_2 }
+test_file! { payable_private_fn }
+test_file! { payable_free_fn }
+test_file! { conflicting_inline_attributes }
+test_file! { bad_namespace_attribute }
 test_file! { private_struct_field }
 test_file! { return_addition_with_mixed_types }
 test_file! { return_call_to_fn_with_param_type_mismatch }
@@ -320,6 +347,8 @@ test_file! { call_call_on_self }
 test_file! { call_method_in_storage }
 test_file! { call_call_on_external_contract }
 test_file! { call_with_pub_fns }
+test_file! { receive_with_params }
+test_file! { fallback_with_call }
 test_file! { abi_encode_u256 }
 test_file! { abi_encode_from_storage }
 test_file! { assert_sto_msg_no_copy }
@@ -363,6 +392,9 @@ test_file! { invalid_repeat_length }
 test_file! { invalid_struct_pub_qualifier }
 test_file! { mut_mistakes }
 test_file! { invalid_comparisons }
+test_file! { unavailable_intrinsics, EvmVersion::Berlin }
+test_file! { bad_transient_field, EvmVersion::Cancun }
+test_file! { transient_field_before_cancun }
 
 test_file! { _test_fn_call }
 test_file! { _test_fn_params }
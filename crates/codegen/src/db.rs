@@ -1,22 +1,42 @@
 #![allow(clippy::arc_with_non_send_sync)]
 use std::rc::Rc;
 
-use fe_abi::{contract::AbiContract, event::AbiEvent, function::AbiFunction, types::AbiType};
+use fe_abi::{
+    contract::AbiContract, error::AbiError, event::AbiEvent, function::AbiFunction,
+    storage_layout::StorageLayout, types::AbiType,
+};
 use fe_analyzer::{
     db::AnalyzerDbStorage,
     namespace::items::{ContractId, ModuleId},
     AnalyzerDb,
 };
-use fe_common::db::{SourceDb, SourceDbStorage, Upcast, UpcastMut};
+use fe_common::{
+    db::{SourceDb, SourceDbStorage, Upcast, UpcastMut},
+    EvmVersion,
+};
 use fe_mir::{
     db::{MirDb, MirDbStorage},
     ir::{FunctionBody, FunctionId, FunctionSignature, TypeId},
 };
 
+use crate::yul::isel::DispatcherStrategy;
+
 mod queries;
 
 #[salsa::query_group(CodegenDbStorage)]
 pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
+    /// How contracts dispatch calls to their public functions.
+    #[salsa::input]
+    fn codegen_dispatcher_strategy(&self) -> DispatcherStrategy;
+    /// Whether the IPFS hash of the metadata of a contract is appended to its
+    /// runtime bytecode.
+    #[salsa::input]
+    fn codegen_metadata_hash(&self) -> bool;
+    /// Whether Yul is compiled with the native backend even if solc is built
+    /// in. Without the built-in solc, the native backend is always used.
+    #[salsa::input]
+    fn codegen_native_backend(&self) -> bool;
+
     #[salsa::invoke(queries::function::legalized_signature)]
     fn codegen_legalized_signature(&self, function_id: FunctionId) -> Rc<FunctionSignature>;
     #[salsa::invoke(queries::function::legalized_body)]
@@ -33,6 +53,8 @@ pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
     fn codegen_abi_function(&self, function_id: FunctionId) -> AbiFunction;
     #[salsa::invoke(queries::abi::abi_event)]
     fn codegen_abi_event(&self, ty: TypeId) -> AbiEvent;
+    #[salsa::invoke(queries::abi::abi_error)]
+    fn codegen_abi_error(&self, ty: TypeId) -> AbiError;
     #[salsa::invoke(queries::abi::abi_contract)]
     fn codegen_abi_contract(&self, contract: ContractId) -> AbiContract;
     #[salsa::invoke(queries::abi::abi_module_events)]
@@ -46,6 +68,9 @@ pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
     #[salsa::invoke(queries::abi::abi_function_return_maximum_size)]
     fn codegen_abi_function_return_maximum_size(&self, function: FunctionId) -> usize;
 
+    #[salsa::invoke(queries::storage_layout::storage_layout)]
+    fn codegen_storage_layout(&self, contract: ContractId) -> StorageLayout;
+
     #[salsa::invoke(queries::contract::symbol_name)]
     fn codegen_contract_symbol_name(&self, contract: ContractId) -> Rc<String>;
     #[salsa::invoke(queries::contract::deployer_symbol_name)]
@@ -57,12 +82,26 @@ pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
 
 // TODO: Move this to driver.
 #[salsa::database(SourceDbStorage, AnalyzerDbStorage, MirDbStorage, CodegenDbStorage)]
-#[derive(Default)]
 pub struct Db {
     storage: salsa::Storage<Db>,
 }
 impl salsa::Database for Db {}
 
+impl Default for Db {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: salsa::Storage::default(),
+        };
+        db.set_mir_optimization_enabled(false);
+        db.set_mir_check_elimination_enabled(true);
+        db.set_evm_version(EvmVersion::default());
+        db.set_codegen_dispatcher_strategy(DispatcherStrategy::default());
+        db.set_codegen_metadata_hash(true);
+        db.set_codegen_native_backend(false);
+        db
+    }
+}
+
 impl Upcast<dyn MirDb> for Db {
     fn upcast(&self) -> &(dyn MirDb + 'static) {
         self
@@ -2,4 +2,5 @@ pub mod abi;
 pub mod constant;
 pub mod contract;
 pub mod function;
+pub mod storage_layout;
 pub mod types;
@@ -1,5 +1,6 @@
 use fe_abi::{
     contract::AbiContract,
+    error::AbiError,
     event::{AbiEvent, AbiEventField},
     function::{AbiFunction, AbiFunctionType, CtxParam, SelfParam, StateMutability},
     types::{AbiTupleField, AbiType},
@@ -11,17 +12,24 @@ use fe_analyzer::{
         types::{CtxDecl, SelfDecl},
     },
 };
-use fe_mir::ir::{self, FunctionId, TypeId};
+use fe_mir::ir::{
+    self,
+    inst::{CallType, InstKind},
+    FunctionId, TypeId,
+};
+use fxhash::FxHashSet;
 
 use crate::db::CodegenDb;
 
 pub fn abi_contract(db: &dyn CodegenDb, contract: ContractId) -> AbiContract {
     let mut funcs = vec![];
+    let mut entry_points = vec![];
 
     if let Some(init) = contract.init_function(db.upcast()) {
         let init_func = db.mir_lowered_func_signature(init);
         let init_abi = db.codegen_abi_function(init_func);
         funcs.push(init_abi);
+        entry_points.push(init_func);
     }
 
     for &func in contract.all_functions(db.upcast()).as_ref() {
@@ -29,12 +37,53 @@ pub fn abi_contract(db: &dyn CodegenDb, contract: ContractId) -> AbiContract {
         if mir_func.linkage(db.upcast()).is_exported() {
             let func_abi = db.codegen_abi_function(mir_func);
             funcs.push(func_abi);
+            entry_points.push(mir_func);
         }
     }
 
     let events = abi_module_events(db, contract.module(db.upcast()));
+    let errors = abi_contract_errors(db, entry_points);
 
-    AbiContract::new(funcs, events)
+    AbiContract::new(funcs, events, errors)
+}
+
+/// Collects the structs that are used as `revert` data in the functions
+/// reachable from `entry_points`.
+fn abi_contract_errors(db: &dyn CodegenDb, entry_points: Vec<FunctionId>) -> Vec<AbiError> {
+    let mut errors = vec![];
+    let mut error_types = FxHashSet::default();
+    let mut visited: FxHashSet<_> = entry_points.iter().copied().collect();
+    let mut worklist = entry_points;
+
+    while let Some(func) = worklist.pop() {
+        let body = db.mir_lowered_func_body(func);
+        for block in body.order.iter_block() {
+            for inst in body.order.iter_inst(block) {
+                match &body.store.inst_data(inst).kind {
+                    InstKind::Call {
+                        func: callee,
+                        call_type: CallType::Internal,
+                        ..
+                    } => {
+                        if visited.insert(*callee) {
+                            worklist.push(*callee);
+                        }
+                    }
+
+                    InstKind::Revert { arg: Some(arg) } => {
+                        let ty = body.store.value_ty(*arg).deref(db.upcast());
+                        if ty.is_struct(db.upcast()) && error_types.insert(ty) {
+                            errors.push(db.codegen_abi_error(ty));
+                        }
+                    }
+
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    errors
 }
 
 pub fn abi_module_events(db: &dyn CodegenDb, module: ModuleId) -> Vec<AbiEvent> {
@@ -66,6 +115,10 @@ pub fn abi_function(db: &dyn CodegenDb, function: FunctionId) -> AbiFunction {
 
     let func_type = if function.is_contract_init(db.upcast()) {
         AbiFunctionType::Constructor
+    } else if function.is_contract_receive(db.upcast()) {
+        AbiFunctionType::Receive
+    } else if function.is_contract_fallback(db.upcast()) {
+        AbiFunctionType::Fallback
     } else {
         AbiFunctionType::Function
     };
@@ -84,8 +137,10 @@ pub fn abi_function(db: &dyn CodegenDb, function: FunctionId) -> AbiFunction {
         Some(CtxDecl { mut_: Some(_), .. }) => CtxParam::Mut,
     };
 
-    let state_mutability = if name == "__init__" {
+    let state_mutability = if sig.analyzer_func_id.is_payable(db.upcast()) {
         StateMutability::Payable
+    } else if func_type != AbiFunctionType::Function {
+        StateMutability::Nonpayable
     } else {
         StateMutability::from_self_and_ctx_params(self_param, ctx_param)
     };
@@ -274,6 +329,24 @@ pub fn abi_event(db: &dyn CodegenDb, ty: TypeId) -> AbiEvent {
     AbiEvent::new(event_def.name.to_string(), fields, false)
 }
 
+pub fn abi_error(db: &dyn CodegenDb, ty: TypeId) -> AbiError {
+    debug_assert!(ty.is_struct(db.upcast()));
+
+    let legalized_ty = db.codegen_legalized_type(ty);
+    let error_def = match &legalized_ty.data(db.upcast()).kind {
+        ir::TypeKind::Struct(def) => def,
+        _ => unreachable!(),
+    };
+
+    let fields = error_def
+        .fields
+        .iter()
+        .map(|(name, ty)| (name.to_string(), db.codegen_abi_type(*ty)))
+        .collect();
+
+    AbiError::new(error_def.name.to_string(), fields)
+}
+
 fn ceil_32(value: usize) -> usize {
     ((value + 31) / 32) * 32
 }
@@ -20,7 +20,7 @@ pub fn legalized_signature(db: &dyn CodegenDb, function: FunctionId) -> Rc<Funct
 }
 
 pub fn legalized_body(db: &dyn CodegenDb, function: FunctionId) -> Rc<FunctionBody> {
-    let mut body = function.body(db.upcast()).as_ref().clone();
+    let mut body = function.optimized_body(db.upcast()).as_ref().clone();
     legalize::legalize_func_body(db, &mut body);
     body.into()
 }
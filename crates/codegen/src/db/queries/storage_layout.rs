@@ -0,0 +1,104 @@
+use fe_abi::storage_layout::{StorageEncoding, StorageField, StorageLayout};
+use fe_analyzer::{
+    display::Displayable,
+    namespace::{items::ContractId, types::Type},
+};
+use fe_mir::ir::{TypeId, TypeKind};
+use num_bigint::BigInt;
+
+use crate::{
+    db::CodegenDb,
+    yul::{slot_size::SLOT_SIZE, storage_root::StorageRoot},
+};
+
+pub fn storage_layout(db: &dyn CodegenDb, contract: ContractId) -> StorageLayout {
+    let contract_ty = db.mir_lowered_type(Type::SelfContract(contract).id(db.upcast()));
+    let contract_ty = db.codegen_legalized_type(contract_ty);
+
+    // Contract fields are placed from the storage root of the contract.
+    let root = StorageRoot::of_contract(db, contract);
+    let storage = member_layouts(db, root.slot(), contract_ty, 0);
+    StorageLayout::new(
+        contract.name(db.upcast()).to_string(),
+        contract
+            .storage_namespace(db.upcast())
+            .map(|namespace| namespace.to_string()),
+        storage,
+    )
+}
+
+/// Returns the layouts of the members of the aggregate type `ty` which is
+/// placed at the byte address `ptr` counted from the slot `base_slot`.
+fn member_layouts(
+    db: &dyn CodegenDb,
+    base_slot: &BigInt,
+    ty: TypeId,
+    ptr: usize,
+) -> Vec<StorageField> {
+    (0..ty.aggregate_field_num(db.upcast()))
+        .map(|idx| {
+            let name = match &ty.data(db.upcast()).kind {
+                TypeKind::Struct(def) | TypeKind::Contract(def) => def.fields[idx].0.to_string(),
+                _ => format!("item{idx}"),
+            };
+            let member_ty = ty.projection_ty_imm(db.upcast(), idx);
+            let member_ptr = ptr + ty.aggregate_elem_offset(db.upcast(), idx, SLOT_SIZE);
+            field_layout(db, base_slot, name, member_ty, member_ptr)
+        })
+        .collect()
+}
+
+fn field_layout(
+    db: &dyn CodegenDb,
+    base_slot: &BigInt,
+    name: String,
+    ty: TypeId,
+    ptr: usize,
+) -> StorageField {
+    let size = ty.size_of(db.upcast(), SLOT_SIZE);
+    // `ptr` is counted from the most significant byte of the slot, while the
+    // offset is counted from the least significant byte.
+    let offset = if ty.is_primitive(db.upcast()) {
+        SLOT_SIZE - ptr % SLOT_SIZE - size
+    } else {
+        0
+    };
+
+    let encoding = match &ty.data(db.upcast()).kind {
+        TypeKind::Struct(_) | TypeKind::Contract(_) | TypeKind::Tuple(_) => {
+            StorageEncoding::Struct {
+                members: member_layouts(db, base_slot, ty, ptr),
+            }
+        }
+
+        TypeKind::Array(def) => StorageEncoding::Array {
+            length: def.len,
+            stride: ty.array_elem_size(db.upcast(), SLOT_SIZE),
+            element: field_layout(db, base_slot, String::new(), def.elem_ty, ptr).into(),
+        },
+
+        // Slots of a map value are relative to the hashed slot.
+        TypeKind::Map(def) => StorageEncoding::Mapping {
+            key: type_name(db, def.key_ty),
+            value: field_layout(db, &BigInt::from(0), String::new(), def.value_ty, 0).into(),
+        },
+
+        _ => StorageEncoding::Inplace,
+    };
+
+    StorageField::new(
+        name,
+        type_name(db, ty),
+        (base_slot + ptr / SLOT_SIZE).to_string(),
+        offset,
+        size,
+        encoding,
+    )
+}
+
+fn type_name(db: &dyn CodegenDb, ty: TypeId) -> String {
+    match ty.analyzer_ty(db.upcast()) {
+        Some(analyzer_ty) => analyzer_ty.display(db.upcast()).to_string(),
+        None => ty.as_string(db.upcast()),
+    }
+}
@@ -50,6 +50,8 @@ pub fn legalized_type(db: &dyn CodegenDb, ty: TypeId) -> TypeId {
                 fields,
                 span: def.span,
                 module_id: def.module_id,
+                packed: def.packed,
+                storage_namespace: def.storage_namespace.clone(),
             };
             TypeKind::Struct(new_def)
         }
@@ -72,6 +74,8 @@ pub fn legalized_type(db: &dyn CodegenDb, ty: TypeId) -> TypeId {
                 fields,
                 span: def.span,
                 module_id: def.module_id,
+                packed: def.packed,
+                storage_namespace: def.storage_namespace.clone(),
             };
             TypeKind::Contract(new_def)
         }
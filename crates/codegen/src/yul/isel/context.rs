@@ -7,7 +7,10 @@ use yultsur::yul;
 
 use crate::{
     db::CodegenDb,
-    yul::runtime::{DefaultRuntimeProvider, RuntimeProvider},
+    yul::{
+        runtime::{DefaultRuntimeProvider, RuntimeProvider},
+        storage_root::StorageRoot,
+    },
 };
 
 use super::{lower_contract_deployable, lower_function};
@@ -36,6 +39,16 @@ impl Default for Context {
 }
 
 impl Context {
+    /// Returns the context to lower the code of `contract`, whose storage
+    /// accesses are relative to the storage root of the contract.
+    pub(super) fn for_contract(db: &dyn CodegenDb, contract: ContractId) -> Self {
+        let root = StorageRoot::of_contract(db, contract);
+        Self {
+            runtime: Box::new(DefaultRuntimeProvider::with_storage_root(&root)),
+            ..Default::default()
+        }
+    }
+
     pub(super) fn resolve_function_dependency(
         &mut self,
         db: &dyn CodegenDb,
@@ -1,22 +1,33 @@
+use fe_abi::function::StateMutability;
 use fe_analyzer::namespace::items::ContractId;
 use fe_mir::ir::{function::Linkage, FunctionId};
 use yultsur::{yul, *};
 
 use crate::{
     db::CodegenDb,
-    yul::{runtime::AbiSrcLocation, YulVariable},
+    yul::{
+        runtime::{init_free_memory_pointer, AbiSrcLocation},
+        source_map, YulVariable,
+    },
 };
 
-use super::context::Context;
+use super::{
+    context::Context,
+    dispatcher::{self, DispatchArm},
+};
 
 pub fn lower_contract_deployable(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
-    let mut context = Context::default();
+    let mut context = Context::for_contract(db, contract);
 
     let constructor = if let Some(init) = contract.init_function(db.upcast()) {
         let init = db.mir_lowered_func_signature(init);
         make_init(db, &mut context, contract, init)
     } else {
-        statements! {}
+        // Without an `__init__` function, the contract can't receive ether on
+        // deployment.
+        statements! {
+            (if (callvalue()) { (revert(0, 0)) })
+        }
     };
 
     let deploy_code = make_deploy(db, contract);
@@ -34,6 +45,7 @@ pub fn lower_contract_deployable(db: &dyn CodegenDb, contract: ContractId) -> yu
         .collect();
 
     let deploy_block = block_statement! {
+        ([init_free_memory_pointer()])
         [constructor...]
         [deploy_code...]
     };
@@ -51,14 +63,12 @@ pub fn lower_contract_deployable(db: &dyn CodegenDb, contract: ContractId) -> yu
     let name = identifier! {(
         db.codegen_contract_deployer_symbol_name(contract).as_ref()
     )};
-    let object = yul::Object {
+    yul::Object {
         name,
         code,
         objects: dep_contracts,
         data: dep_constants,
-    };
-
-    normalize_object(object)
+    }
 }
 
 pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
@@ -66,7 +76,10 @@ pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
         .mir_lower_contract_all_functions(contract)
         .iter()
         .filter_map(|fid| {
-            if fid.signature(db.upcast()).linkage == Linkage::Export {
+            if fid.signature(db.upcast()).linkage == Linkage::Export
+                && !fid.is_contract_receive(db.upcast())
+                && !fid.is_contract_fallback(db.upcast())
+            {
                 Some(*fid)
             } else {
                 None
@@ -74,7 +87,7 @@ pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
         })
         .collect();
 
-    let mut context = Context::default();
+    let mut context = Context::for_contract(db, contract);
     let dispatcher = if let Some(call_fn) = contract.call_function(db.upcast()) {
         let call_fn = db.mir_lowered_func_signature(call_fn);
         context.function_dependency.insert(call_fn);
@@ -83,7 +96,7 @@ pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
             ([call_symbol]())
         }
     } else {
-        make_dispatcher(db, &mut context, &exported_funcs)
+        make_dispatcher(db, &mut context, contract, &exported_funcs)
     };
 
     let dep_functions: Vec<_> = context
@@ -98,8 +111,13 @@ pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
         .map(yul::Statement::FunctionDefinition)
         .collect();
 
+    // The dispatcher is attributed to the contract, which tells it apart from
+    // the functions in source maps.
     let code = code! {
+        ([init_free_memory_pointer()])
+        ([source_map::src_comment(Some(contract.span(db.upcast())))])
         ([dispatcher])
+        ([source_map::src_comment(None)])
         [dep_functions...]
         [runtime_funcs...]
     };
@@ -122,6 +140,7 @@ pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
 fn make_dispatcher(
     db: &dyn CodegenDb,
     context: &mut Context,
+    contract: ContractId,
     funcs: &[FunctionId],
 ) -> yul::Statement {
     let arms = funcs
@@ -129,21 +148,61 @@ fn make_dispatcher(
         .map(|func| dispatch_arm(db, context, *func))
         .collect::<Vec<_>>();
 
-    if arms.is_empty() {
-        statement! { return(0, 0) }
+    // Calls that don't match any selector are passed to `__fallback__`.
+    let fallback = match contract.fallback_function(db.upcast()) {
+        Some(fallback) => {
+            let fallback = db.mir_lowered_func_signature(fallback);
+            make_hook_call(db, context, fallback)
+        }
+        None => statements! {
+            (if (callvalue()) { (revert(0, 0)) })
+            (return(0, 0))
+        },
+    };
+
+    let dispatcher = if arms.is_empty() {
+        block_statement! { [fallback...] }
     } else {
-        let selector = expression! {
-            and((shr((sub(256, 32)), (calldataload(0)))), 0xffffffff)
-        };
-        switch! {
-            switch ([selector])
-            [arms...]
-            (default { (return(0, 0)) })
+        dispatcher::make_dispatcher(
+            db.codegen_dispatcher_strategy(),
+            db.codegen_native_backend(),
+            arms,
+            fallback,
+        )
+    };
+
+    // Plain ether transfers without call data are passed to `__receive__`.
+    match contract.receive_function(db.upcast()) {
+        Some(receive) => {
+            let receive = db.mir_lowered_func_signature(receive);
+            let call_receive = make_hook_call(db, context, receive);
+            block_statement! {
+                (if (iszero((calldatasize()))) { [call_receive...] })
+                [dispatcher]
+            }
         }
+        None => dispatcher,
+    }
+}
+
+/// Calls a `__receive__` or `__fallback__` function, which take no parameters
+/// and return nothing.
+fn make_hook_call(
+    db: &dyn CodegenDb,
+    context: &mut Context,
+    func: FunctionId,
+) -> Vec<yul::Statement> {
+    context.function_dependency.insert(func);
+    let name = identifier! { (db.codegen_function_symbol_name(func)) };
+    let callvalue_check = make_callvalue_check(db, func);
+    statements! {
+        [callvalue_check...]
+        ([name]())
+        (return(0, 0))
     }
 }
 
-fn dispatch_arm(db: &dyn CodegenDb, context: &mut Context, func: FunctionId) -> yul::Case {
+fn dispatch_arm(db: &dyn CodegenDb, context: &mut Context, func: FunctionId) -> DispatchArm {
     context.function_dependency.insert(func);
     let func_sig = db.codegen_legalized_signature(func);
     let mut param_vars = Vec::with_capacity(func_sig.params.len());
@@ -198,11 +257,25 @@ fn dispatch_arm(db: &dyn CodegenDb, context: &mut Context, func: FunctionId) ->
     };
 
     let abi_sig = db.codegen_abi_function(func);
-    let selector = literal! { (format!("0x{}", abi_sig.selector().hex())) };
-    case! {
-        case [selector] {
+    let callvalue_check = make_callvalue_check(db, func);
+    DispatchArm {
+        selector: u32::from_be_bytes(abi_sig.selector().selector_raw()),
+        body: statements! {
+            [callvalue_check...]
             [decode_params...]
             [call_and_encode_return...]
+        },
+    }
+}
+
+/// Returns a guard that reverts if ether is sent to a function that isn't
+/// payable.
+fn make_callvalue_check(db: &dyn CodegenDb, func: FunctionId) -> Vec<yul::Statement> {
+    if db.codegen_abi_function(func).state_mutability() == &StateMutability::Payable {
+        statements! {}
+    } else {
+        statements! {
+            (if (callvalue()) { (revert(0, 0)) })
         }
     }
 }
@@ -239,7 +312,9 @@ fn make_init(
     };
 
     let call = expression! {[init_func_name]([(param_vars.iter().map(YulVariable::expr).collect::<Vec<_>>())...])};
+    let callvalue_check = make_callvalue_check(db, init);
     statements! {
+        [callvalue_check...]
         (let [program_size.ident()] := datasize([contract_name]))
         (let [code_size.ident()] := codesize())
         (let [arg_size.ident()] := sub([code_size.expr()], [program_size.expr()]))
@@ -260,30 +335,3 @@ fn make_deploy(db: &dyn CodegenDb, contract: ContractId) -> Vec<yul::Statement>
        (return (0, [size.expr()]))
     }
 }
-
-fn normalize_object(obj: yul::Object) -> yul::Object {
-    let data = obj
-        .data
-        .into_iter()
-        .map(|data| yul::Data {
-            name: data.name,
-            value: data
-                .value
-                .replace('\\', "\\\\\\\\")
-                .replace('\n', "\\\\n")
-                .replace('"', "\\\\\"")
-                .replace('\r', "\\\\r")
-                .replace('\t', "\\\\t"),
-        })
-        .collect::<Vec<_>>();
-    yul::Object {
-        name: obj.name,
-        code: obj.code,
-        objects: obj
-            .objects
-            .into_iter()
-            .map(normalize_object)
-            .collect::<Vec<_>>(),
-        data,
-    }
-}
@@ -0,0 +1,200 @@
+use std::{fmt, str::FromStr};
+
+use yultsur::{yul, *};
+
+use crate::yul::YulVariable;
+
+/// How the runtime code of a contract finds the public function to call from
+/// the selector in the call data.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DispatcherStrategy {
+    /// Linear dispatch for contracts with at most [`AUTO_LINEAR_MAX`]
+    /// public functions, and binary search otherwise.
+    #[default]
+    Auto,
+    /// Compares the selector with each function selector in turn.
+    Linear,
+    /// Binary search over the sorted function selectors.
+    BinarySearch,
+    /// Bucketed dispatch: switches on the selector modulo the number of
+    /// buckets, and compares the selector with the few function selectors of
+    /// its bucket.
+    ///
+    /// The native backend compiles the switch on the bucket into a jump
+    /// table, which reaches every function with about the same amount of gas.
+    /// solc has no jump tables and would compare the selector with each bucket
+    /// in turn, so with solc this falls back to binary search.
+    JumpTable,
+}
+
+/// The number of public functions up to which [`DispatcherStrategy::Auto`]
+/// dispatches linearly.
+pub const AUTO_LINEAR_MAX: usize = 8;
+
+/// The maximum number of function selectors that binary search compares
+/// linearly.
+const BINARY_SEARCH_LEAF_SIZE: usize = 4;
+
+impl FromStr for DispatcherStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "linear" => Ok(Self::Linear),
+            "binary-search" => Ok(Self::BinarySearch),
+            "jump-table" => Ok(Self::JumpTable),
+            _ => Err(format!(
+                "unknown dispatcher strategy `{s}`, expected one of `auto`, `linear`, `binary-search` or `jump-table`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DispatcherStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Linear => "linear",
+            Self::BinarySearch => "binary-search",
+            Self::JumpTable => "jump-table",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The code which runs the public function with the given selector.
+pub(super) struct DispatchArm {
+    pub(super) selector: u32,
+    pub(super) body: Vec<yul::Statement>,
+}
+
+/// Returns the code which runs the arm whose selector matches the selector in
+/// the call data, or `fallback` if there's none. Every arm must end the call.
+///
+/// `native_backend` tells whether the code is compiled with the native
+/// backend, which is the only one that compiles a switch into a jump table.
+pub(super) fn make_dispatcher(
+    strategy: DispatcherStrategy,
+    native_backend: bool,
+    mut arms: Vec<DispatchArm>,
+    fallback: Vec<yul::Statement>,
+) -> yul::Statement {
+    let selector = expression! {
+        and((shr((sub(256, 32)), (calldataload(0)))), 0xffffffff)
+    };
+    let strategy = match strategy {
+        DispatcherStrategy::Auto if arms.len() <= AUTO_LINEAR_MAX => DispatcherStrategy::Linear,
+        DispatcherStrategy::Auto => DispatcherStrategy::BinarySearch,
+        DispatcherStrategy::JumpTable if !native_backend => DispatcherStrategy::BinarySearch,
+        strategy => strategy,
+    };
+
+    if strategy == DispatcherStrategy::Linear {
+        let cases: Vec<_> = arms.into_iter().map(make_case).collect();
+        return switch! {
+            switch ([selector])
+            [cases...]
+            (default { [fallback...] })
+        };
+    }
+
+    // The other dispatchers fall through to the fallback if no arm matches.
+    arms.sort_by_key(|arm| arm.selector);
+    let selector_var = YulVariable::new("$selector");
+    let dispatch = if strategy == DispatcherStrategy::JumpTable {
+        make_jump_table(selector_var.expr(), arms)
+    } else {
+        make_binary_search(&selector_var.expr(), arms)
+    };
+    block_statement! {
+        (let [selector_var.ident()] := [selector])
+        [dispatch...]
+        [fallback...]
+    }
+}
+
+fn make_binary_search(
+    selector: &yul::Expression,
+    mut arms: Vec<DispatchArm>,
+) -> Vec<yul::Statement> {
+    if arms.len() <= BINARY_SEARCH_LEAF_SIZE {
+        return vec![make_switch(selector.clone(), arms)];
+    }
+
+    let upper = arms.split_off(arms.len() / 2);
+    let pivot = yul::Expression::Literal(selector_literal(upper[0].selector));
+    let lower = make_binary_search(selector, arms);
+    let upper = make_binary_search(selector, upper);
+    vec![switch! {
+        switch (lt([selector.clone()], [pivot]))
+        (case 0 { [upper...] })
+        (default { [lower...] })
+    }]
+}
+
+/// Returns the bucketed dispatch of [`DispatcherStrategy::JumpTable`], whose
+/// switch on the bucket is compiled into a jump table by the native backend.
+fn make_jump_table(selector: yul::Expression, arms: Vec<DispatchArm>) -> Vec<yul::Statement> {
+    let bucket_count = bucket_count(&arms);
+    let mut buckets: Vec<Vec<DispatchArm>> = (0..bucket_count).map(|_| vec![]).collect();
+    for arm in arms {
+        buckets[arm.selector as usize % bucket_count].push(arm);
+    }
+
+    let cases: Vec<_> = buckets
+        .into_iter()
+        .enumerate()
+        .filter(|(_, bucket)| !bucket.is_empty())
+        .map(|(index, bucket)| {
+            let dispatch = make_switch(selector.clone(), bucket);
+            yul::Case {
+                literal: Some(literal! { (index) }),
+                block: block! { [dispatch] },
+            }
+        })
+        .collect();
+    let bucket_count = literal_expression! { (bucket_count) };
+    vec![switch! {
+        switch (mod([selector], [bucket_count]))
+        [cases...]
+    }]
+}
+
+/// Returns the number of buckets between the number of arms and twice that
+/// which puts the fewest selectors in the fullest bucket, preferring fewer
+/// buckets.
+fn bucket_count(arms: &[DispatchArm]) -> usize {
+    let mut best = (usize::MAX, arms.len());
+    for bucket_count in arms.len()..=arms.len() * 2 {
+        let mut sizes = vec![0; bucket_count];
+        for arm in arms {
+            sizes[arm.selector as usize % bucket_count] += 1;
+        }
+        let max_size = sizes.into_iter().max().unwrap_or(0);
+        if max_size < best.0 {
+            best = (max_size, bucket_count);
+        }
+    }
+    best.1
+}
+
+fn make_switch(selector: yul::Expression, arms: Vec<DispatchArm>) -> yul::Statement {
+    let cases: Vec<_> = arms.into_iter().map(make_case).collect();
+    switch! {
+        switch ([selector])
+        [cases...]
+    }
+}
+
+fn make_case(arm: DispatchArm) -> yul::Case {
+    let body = arm.body;
+    yul::Case {
+        literal: Some(selector_literal(arm.selector)),
+        block: block! { [body...] },
+    }
+}
+
+fn selector_literal(selector: u32) -> yul::Literal {
+    literal! { (format!("0x{selector:08x}")) }
+}
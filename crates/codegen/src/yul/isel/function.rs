@@ -5,7 +5,7 @@ use super::{context::Context, inst_order::InstSerializer};
 use fe_common::numeric::to_hex_str;
 
 use fe_abi::function::{AbiFunction, AbiFunctionType};
-use fe_common::db::Upcast;
+use fe_common::{db::Upcast, Span};
 use fe_mir::{
     ir::{
         self,
@@ -30,7 +30,7 @@ use crate::{
     yul::slot_size::{function_hash_type, yul_primitive_type, SLOT_SIZE},
     yul::{
         runtime::{self, RuntimeProvider},
-        YulVariable,
+        source_map, YulVariable,
     },
 };
 
@@ -55,6 +55,8 @@ struct FuncLowerHelper<'db, 'a> {
     body: &'a FunctionBody,
     ret_value: Option<yul::Identifier>,
     sink: Vec<yul::Statement>,
+    /// The span of the last `@src` comment in the body.
+    src: Option<Span>,
 }
 
 impl<'db, 'a> FuncLowerHelper<'db, 'a> {
@@ -92,6 +94,7 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
             body,
             ret_value,
             sink: Vec::new(),
+            src: None,
         }
     }
 
@@ -127,6 +130,10 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
         for inst in inst_order {
             self.lower_structural_inst(inst)
         }
+        // The code after the body, e.g. the next function, is generated.
+        if self.src.is_some() {
+            self.sink.push(source_map::src_comment(None));
+        }
 
         yul::Block {
             statements: self.sink,
@@ -158,6 +165,7 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
     }
 
     fn lower_inst(&mut self, inst: InstId) {
+        self.lower_source(self.body.store.inst_data(inst).source.span);
         if let Some(lhs) = self.body.store.inst_result(inst) {
             self.declare_assignable_value(lhs)
         }
@@ -281,12 +289,21 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 self.assign_inst_result(inst, src, src_ty)
             }
 
-            InstKind::AggregateAccess { value, indices } => {
+            InstKind::AggregateAccess {
+                value,
+                indices,
+                unchecked,
+            } => {
                 let base = self.value_expr(*value);
                 let mut ptr = base;
                 let mut inner_ty = self.body.store.value_ty(*value);
                 for &idx in indices {
-                    ptr = self.aggregate_elem_ptr(ptr, idx, inner_ty.deref(self.db.upcast()));
+                    ptr = self.aggregate_elem_ptr(
+                        ptr,
+                        idx,
+                        inner_ty.deref(self.db.upcast()),
+                        *unchecked,
+                    );
                     inner_ty =
                         inner_ty.projection_ty(self.db.upcast(), self.body.store.value_data(idx));
                 }
@@ -448,6 +465,17 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
         }
     }
 
+    /// Attributes the code that follows to `span` with an `@src` comment,
+    /// unless it's already attributed to it. Code without a span is
+    /// attributed to the span of the code before it.
+    fn lower_source(&mut self, span: Span) {
+        if span.is_dummy() || self.src == Some(span) {
+            return;
+        }
+        self.src = Some(span);
+        self.sink.push(source_map::src_comment(Some(span)));
+    }
+
     fn lower_if(
         &mut self,
         cond: ValueId,
@@ -643,6 +671,9 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 .ctx
                 .runtime
                 .safe_pow(self.db, lhs_expr, rhs_expr, inst_result_ty),
+            BinOp::UncheckedAdd => expression! {add([lhs_expr], [rhs_expr])},
+            BinOp::UncheckedSub => expression! {sub([lhs_expr], [rhs_expr])},
+            BinOp::UncheckedMul => expression! {mul([lhs_expr], [rhs_expr])},
             BinOp::Shl => expression! {shl([rhs_expr], [lhs_expr])},
             BinOp::Shr if is_result_signed => expression! {sar([rhs_expr], [lhs_expr])},
             BinOp::Shr => expression! {shr([rhs_expr], [lhs_expr])},
@@ -849,7 +880,7 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 let ty = lhs
                     .ty(self.db.upcast(), &self.body.store)
                     .deref(self.db.upcast());
-                self.aggregate_elem_ptr(base_ptr, *idx, ty)
+                self.aggregate_elem_ptr(base_ptr, *idx, ty, false)
             }
             AssignableValue::Map { lhs, key } => {
                 let map_ptr = self.lower_assignable_value(lhs);
@@ -867,6 +898,7 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
         base_ptr: yul::Expression,
         idx: ValueId,
         base_ty: TypeId,
+        unchecked: bool,
     ) -> yul::Expression {
         debug_assert!(base_ty.is_aggregate(self.db.upcast()));
 
@@ -874,7 +906,9 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
             TypeKind::Array(def) => {
                 let elem_size =
                     literal_expression! {(base_ty.array_elem_size(self.db.upcast(), SLOT_SIZE))};
-                self.validate_array_indexing(def.len, idx);
+                if !unchecked {
+                    self.validate_array_indexing(def.len, idx);
+                }
                 let idx = self.value_expr(idx);
                 let offset = expression! {mul([elem_size], [idx])};
                 expression! { add([base_ptr], [offset]) }
@@ -1,9 +1,42 @@
 pub mod context;
 mod contract;
+mod dispatcher;
 mod function;
 mod inst_order;
 mod test;
 
 pub use contract::{lower_contract, lower_contract_deployable};
+pub use dispatcher::DispatcherStrategy;
 pub use function::lower_function;
 pub use test::lower_test;
+
+use yultsur::yul;
+
+/// Escapes the data of `obj` and of its sub-objects, so that they are printed
+/// as Yul string literals.
+pub fn normalize_object(obj: yul::Object) -> yul::Object {
+    let data = obj
+        .data
+        .into_iter()
+        .map(|data| yul::Data {
+            name: data.name,
+            value: data
+                .value
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('"', "\\\"")
+                .replace('\r', "\\r")
+                .replace('\t', "\\t"),
+        })
+        .collect::<Vec<_>>();
+    yul::Object {
+        name: obj.name,
+        code: obj.code,
+        objects: obj
+            .objects
+            .into_iter()
+            .map(normalize_object)
+            .collect::<Vec<_>>(),
+        data,
+    }
+}
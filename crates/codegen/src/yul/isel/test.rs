@@ -1,5 +1,5 @@
 use super::context::Context;
-use crate::db::CodegenDb;
+use crate::{db::CodegenDb, yul::runtime::init_free_memory_pointer};
 use fe_analyzer::namespace::items::FunctionId;
 use yultsur::{yul, *};
 
@@ -27,44 +27,16 @@ pub fn lower_test(db: &dyn CodegenDb, test: FunctionId) -> yul::Object {
     let code = code! {
         [dep_functions...]
         [runtime_funcs...]
+        ([init_free_memory_pointer()])
         [call]
         (stop())
     };
 
     let name = identifier! { test };
-    let object = yul::Object {
+    yul::Object {
         name,
         code,
         objects: dep_contracts,
         data: dep_constants,
-    };
-
-    normalize_object(object)
-}
-
-fn normalize_object(obj: yul::Object) -> yul::Object {
-    let data = obj
-        .data
-        .into_iter()
-        .map(|data| yul::Data {
-            name: data.name,
-            value: data
-                .value
-                .replace('\\', "\\\\\\\\")
-                .replace('\n', "\\\\n")
-                .replace('"', "\\\\\"")
-                .replace('\r', "\\\\r")
-                .replace('\t', "\\\\t"),
-        })
-        .collect::<Vec<_>>();
-    yul::Object {
-        name: obj.name,
-        code: obj.code,
-        objects: obj
-            .objects
-            .into_iter()
-            .map(normalize_object)
-            .collect::<Vec<_>>(),
-        data,
     }
 }
@@ -5,7 +5,7 @@ use fe_mir::ir::{
     FunctionBody, Inst, InstId, TypeId, TypeKind, Value, ValueId,
 };
 
-use crate::db::CodegenDb;
+use crate::{db::CodegenDb, yul::storage_root::StorageRoot};
 
 use super::critical_edge::CriticalEdgeSplitter;
 
@@ -186,9 +186,10 @@ fn change_ty(value: &mut Value, new_ty: TypeId) {
 
 fn make_storage_ptr(db: &dyn CodegenDb, ty: TypeId) -> Value {
     debug_assert!(ty.is_contract(db.upcast()));
+    let imm = StorageRoot::of_contract_type(db, ty).ptr();
     let ty = ty.make_sptr(db.upcast());
 
-    Value::Immediate { imm: 0.into(), ty }
+    Value::Immediate { imm, ty }
 }
 
 fn make_zst_ptr(db: &dyn CodegenDb, ty: TypeId) -> Value {
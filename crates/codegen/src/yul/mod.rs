@@ -3,8 +3,10 @@ use std::borrow::Cow;
 pub mod isel;
 pub mod legalize;
 pub mod runtime;
+pub mod source_map;
 
-mod slot_size;
+pub(crate) mod slot_size;
+pub(crate) mod storage_root;
 
 use yultsur::*;
 
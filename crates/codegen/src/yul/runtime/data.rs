@@ -10,14 +10,26 @@ use crate::{
 use super::{DefaultRuntimeProvider, RuntimeFunction, RuntimeProvider};
 
 use fe_mir::ir::{types::TupleDef, Type, TypeId, TypeKind};
+use num_bigint::{BigInt, Sign};
 
 use yultsur::*;
 
 const HASH_SCRATCH_SPACE_START: usize = 0x00;
 const HASH_SCRATCH_SPACE_SIZE: usize = 64;
 const FREE_MEMORY_ADDRESS_STORE: usize = HASH_SCRATCH_SPACE_START + HASH_SCRATCH_SPACE_SIZE;
+/// The start of the heap, which is given to `memoryguard` so that the Yul
+/// compiler can move the variables it can't keep on the stack below it.
 const FREE_MEMORY_START: usize = FREE_MEMORY_ADDRESS_STORE + 32;
 
+/// Sets the free memory pointer to the start of the heap. Objects start with
+/// this, so that every allocation, including the ones of the std library,
+/// starts above the variables moved to memory.
+pub(crate) fn init_free_memory_pointer() -> yul::Statement {
+    let free_address_ptr = literal_expression! {(FREE_MEMORY_ADDRESS_STORE)};
+    let free_memory_start = literal_expression! {(FREE_MEMORY_START)};
+    statement! { mstore([free_address_ptr], (memoryguard([free_memory_start]))) }
+}
+
 pub(super) fn make_alloc(func_name: &str) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let free_address_ptr = literal_expression! {(FREE_MEMORY_ADDRESS_STORE)};
@@ -25,7 +37,7 @@ pub(super) fn make_alloc(func_name: &str) -> RuntimeFunction {
     let func = function_definition! {
         function [func_name.ident()](size) -> ptr {
             (ptr := mload([free_address_ptr.clone()]))
-            (if (eq(ptr, 0x00)) { (ptr := [free_memory_start]) })
+            (if (eq(ptr, 0x00)) { (ptr := memoryguard([free_memory_start])) })
             (mstore([free_address_ptr], (add(ptr, size))))
         }
     };
@@ -40,7 +52,7 @@ pub(super) fn make_avail(func_name: &str) -> RuntimeFunction {
     let func = function_definition! {
         function [func_name.ident()]() -> ptr {
             (ptr := mload([free_address_ptr]))
-            (if (eq(ptr, 0x00)) { (ptr := [free_memory_start]) })
+            (if (eq(ptr, 0x00)) { (ptr := memoryguard([free_memory_start])) })
         }
     };
 
@@ -79,7 +91,7 @@ pub(super) fn make_mcopym(func_name: &str) -> RuntimeFunction {
     RuntimeFunction::from_statement(func)
 }
 
-pub(super) fn make_mcopys(func_name: &str) -> RuntimeFunction {
+pub(super) fn make_mcopys(func_name: &str, slot_offset: &BigInt) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let src = YulVariable::new("src");
     let dst = YulVariable::new("dst");
@@ -87,7 +99,7 @@ pub(super) fn make_mcopys(func_name: &str) -> RuntimeFunction {
 
     let func = function_definition! {
         function [func_name.ident()]([src.ident()], [dst.ident()], [size.ident()]) {
-            ([dst.ident()] := div([dst.expr()], 32))
+            ([dst.ident()] := [ptr_slot(dst.expr(), slot_offset)])
             (let iter_count := div([size.expr()], 32))
             (let original_src := [src.expr()])
             (for {(let i := 0)} (lt(i, iter_count)) {(i := (add(i, 1)))}
@@ -112,7 +124,7 @@ pub(super) fn make_mcopys(func_name: &str) -> RuntimeFunction {
     RuntimeFunction::from_statement(func)
 }
 
-pub(super) fn make_scopym(func_name: &str) -> RuntimeFunction {
+pub(super) fn make_scopym(func_name: &str, slot_offset: &BigInt) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let src = YulVariable::new("src");
     let dst = YulVariable::new("dst");
@@ -120,7 +132,7 @@ pub(super) fn make_scopym(func_name: &str) -> RuntimeFunction {
 
     let func = function_definition! {
         function [func_name.ident()]([src.ident()], [dst.ident()], [size.ident()]) {
-            ([src.ident()] := div([src.expr()], 32))
+            ([src.ident()] := [ptr_slot(src.expr(), slot_offset)])
             (let iter_count := div([size.expr()], 32))
             (let original_dst := [dst.expr()])
             (for {(let i := 0)} (lt(i, iter_count)) {(i := (add(i, 1)))}
@@ -145,15 +157,15 @@ pub(super) fn make_scopym(func_name: &str) -> RuntimeFunction {
     RuntimeFunction::from_statement(func)
 }
 
-pub(super) fn make_scopys(func_name: &str) -> RuntimeFunction {
+pub(super) fn make_scopys(func_name: &str, slot_offset: &BigInt) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let src = YulVariable::new("src");
     let dst = YulVariable::new("dst");
     let size = YulVariable::new("size");
     let func = function_definition! {
         function [func_name.ident()]([src.ident()], [dst.ident()], [size.ident()]) {
-            ([src.ident()] := div([src.expr()], 32))
-            ([dst.ident()] := div([dst.expr()], 32))
+            ([src.ident()] := [ptr_slot(src.expr(), slot_offset)])
+            ([dst.ident()] := [ptr_slot(dst.expr(), slot_offset)])
             (let iter_count := div((add([size.expr()], 31)), 32))
             (for {(let i := 0)} (lt(i, iter_count)) {(i := (add(i, 1)))}
             {
@@ -167,7 +179,7 @@ pub(super) fn make_scopys(func_name: &str) -> RuntimeFunction {
     RuntimeFunction::from_statement(func)
 }
 
-pub(super) fn make_sptr_store(func_name: &str) -> RuntimeFunction {
+pub(super) fn make_sptr_store(func_name: &str, slot_offset: &BigInt) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let func = function_definition! {
         function [func_name.ident()](ptr, value, size_bits) {
@@ -175,7 +187,7 @@ pub(super) fn make_sptr_store(func_name: &str) -> RuntimeFunction {
             (let shift_bits := sub(256, (add(rem_bits, size_bits))))
             (let mask := (shl(shift_bits, (sub((shl(size_bits, 1)), 1)))))
             (let inv_mask := not(mask))
-            (let slot := div(ptr, 32))
+            (let slot := [ptr_slot(yul::Expression::Identifier(identifier! {ptr}), slot_offset)])
             (let new_value := or((and((sload(slot)), inv_mask)), (and((shl(shift_bits, value)), mask))))
             (sstore(slot, new_value))
         }
@@ -198,14 +210,14 @@ pub(super) fn make_mptr_store(func_name: &str) -> RuntimeFunction {
     RuntimeFunction::from_statement(func)
 }
 
-pub(super) fn make_sptr_load(func_name: &str) -> RuntimeFunction {
+pub(super) fn make_sptr_load(func_name: &str, slot_offset: &BigInt) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let func = function_definition! {
         function [func_name.ident()](ptr, size_bits) -> ret {
             (let rem_bits := mul((mod(ptr, 32)), 8))
             (let shift_num := sub(256, (add(rem_bits, size_bits))))
-            (let slot := div(ptr, 32))
-            (ret := shr(shift_num, (sload(slot))))
+            (let slot := [ptr_slot(yul::Expression::Identifier(identifier! {ptr}), slot_offset)])
+            (ret := and((shr(shift_num, (sload(slot)))), (sub((shl(size_bits, 1)), 1))))
         }
     };
 
@@ -225,6 +237,16 @@ pub(super) fn make_mptr_load(func_name: &str) -> RuntimeFunction {
 
 // TODO: We can optimize aggregate initialization by combining multiple
 // `ptr_store` operations into single `ptr_store` operation.
+/// Returns the slot of the storage pointer `ptr`.
+fn ptr_slot(ptr: yul::Expression, slot_offset: &BigInt) -> yul::Expression {
+    if slot_offset.sign() == Sign::NoSign {
+        expression! { div([ptr], 32) }
+    } else {
+        let slot_offset = literal_expression! { (slot_offset) };
+        expression! { add((div([ptr], 32)), [slot_offset]) }
+    }
+}
+
 pub(super) fn make_aggregate_init(
     provider: &mut DefaultRuntimeProvider,
     db: &dyn CodegenDb,
@@ -357,7 +379,7 @@ pub(super) fn make_string_copy(
                 (let [tmp_ptr.ident()] := [provider.avail(db)])
                 (let data_offset := dataoffset([symbol_name.clone()]))
                 (let [data_size.ident()] := datasize([symbol_name]))
-                (let len_slot := div([dst_ptr.expr()], 32))
+                (let len_slot := [ptr_slot(dst_ptr.expr(), &provider.slot_offset)])
                 (sstore(len_slot, [data_size.expr()]))
                 (datacopy([tmp_ptr.expr()], data_offset, [data_size.expr()]))
                 ([dst_ptr.ident()] := add([dst_ptr.expr()], 32))
@@ -17,7 +17,9 @@ use num_bigint::BigInt;
 
 use crate::{db::CodegenDb, yul::slot_size::SLOT_SIZE};
 
-use super::slot_size::yul_primitive_type;
+use super::{slot_size::yul_primitive_type, storage_root::StorageRoot};
+
+pub(crate) use data::init_free_memory_pointer;
 
 pub trait RuntimeProvider {
     fn collect_definitions(&self) -> Vec<yul::FunctionDefinition>;
@@ -226,9 +228,19 @@ pub enum AbiSrcLocation {
 #[derive(Debug, Default)]
 pub struct DefaultRuntimeProvider {
     functions: IndexMap<String, RuntimeFunction>,
+    /// The high bits of the storage root slot of the contract, which are added
+    /// to every slot computed from a storage pointer.
+    slot_offset: BigInt,
 }
 
 impl DefaultRuntimeProvider {
+    pub(crate) fn with_storage_root(root: &StorageRoot) -> Self {
+        Self {
+            functions: IndexMap::default(),
+            slot_offset: root.slot_offset(),
+        }
+    }
+
     fn create_then_call<F>(
         &mut self,
         name: &str,
@@ -452,15 +464,21 @@ impl RuntimeProvider for DefaultRuntimeProvider {
         match (is_src_storage, is_dst_storage) {
             (true, true) => {
                 let name = "scopys";
-                self.create_then_call(name, args, |_| data::make_scopys(name))
+                self.create_then_call(name, args, |provider| {
+                    data::make_scopys(name, &provider.slot_offset)
+                })
             }
             (true, false) => {
                 let name = "scopym";
-                self.create_then_call(name, args, |_| data::make_scopym(name))
+                self.create_then_call(name, args, |provider| {
+                    data::make_scopym(name, &provider.slot_offset)
+                })
             }
             (false, true) => {
                 let name = "mcopys";
-                self.create_then_call(name, args, |_| data::make_mcopys(name))
+                self.create_then_call(name, args, |provider| {
+                    data::make_mcopys(name, &provider.slot_offset)
+                })
             }
             (false, false) => {
                 let name = "mcopym";
@@ -484,7 +502,9 @@ impl RuntimeProvider for DefaultRuntimeProvider {
         if ptr_ty.is_sptr(db.upcast()) {
             let name = "$sptr_store";
             let args = vec![ptr, imm, literal_expression! {(size_bits)}];
-            self.create_then_call(name, args, |_| data::make_sptr_store(name))
+            self.create_then_call(name, args, |provider| {
+                data::make_sptr_store(name, &provider.slot_offset)
+            })
         } else if ptr_ty.is_mptr(db.upcast()) {
             let name = "$mptr_store";
             let shift_num = literal_expression! {(256 - size_bits)};
@@ -510,7 +530,9 @@ impl RuntimeProvider for DefaultRuntimeProvider {
         if ptr_ty.is_sptr(db.upcast()) {
             let name = "$sptr_load";
             let args = vec![ptr, literal_expression! {(size_bits)}];
-            self.create_then_call(name, args, |_| data::make_sptr_load(name))
+            self.create_then_call(name, args, |provider| {
+                data::make_sptr_load(name, &provider.slot_offset)
+            })
         } else if ptr_ty.is_mptr(db.upcast()) {
             let name = "$mptr_load";
             let shift_num = literal_expression! {(256 - size_bits)};
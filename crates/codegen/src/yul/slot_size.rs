@@ -4,7 +4,10 @@ use crate::db::CodegenDb;
 
 // We use the same slot size between memory and storage to simplify the
 // implementation and minimize gas consumption in memory <-> storage copy
-// instructions.
+// instructions. As aggregates are copied between them slot by slot, memory
+// follows the storage layout, i.e., primitive fields are packed into a slot
+// from its least significant bytes as in Solidity, and `#unpacked` structs and
+// contracts store each field in its own slot.
 pub(crate) const SLOT_SIZE: usize = 32;
 
 pub(crate) fn yul_primitive_type(db: &dyn CodegenDb) -> TypeId {
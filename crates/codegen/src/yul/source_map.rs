@@ -0,0 +1,117 @@
+//! `@src` and `@use-src` comments, which attribute the Yul code to the spans
+//! of the Fe source files it's generated from.
+//!
+//! A source file is referred to by the index of its interned
+//! [`SourceFileId`], so that the indices of all contracts compiled with one
+//! database agree.
+
+use fe_common::{db::SourceDb, SourceFileId, Span};
+use salsa::InternKey;
+use yultsur::yul;
+
+/// Returns a statement which attributes the code that follows it to `span`,
+/// or marks it as generated if there's no span.
+///
+/// Yul has no comment statements, so the comment is an identifier expression
+/// which is printed as is.
+pub(super) fn src_comment(span: Option<Span>) -> yul::Statement {
+    let src = match span {
+        Some(span) => format!("{}:{}:{}", source_index(span.file_id), span.start, span.end),
+        None => "-1:-1:-1".to_string(),
+    };
+    yul::Statement::Expression(yul::Expression::Identifier(yul::Identifier {
+        identifier: format!("/** @src {src} */"),
+        yultype: None,
+    }))
+}
+
+/// Returns the index of `file` in `@src` comments and source maps.
+pub fn source_index(file: SourceFileId) -> u32 {
+    file.as_intern_id().as_u32()
+}
+
+/// Returns the source files referred to by the `@src` comments of a Yul
+/// object and of the objects nested in it, ordered by their index.
+pub fn source_files(object: &yul::Object) -> Vec<SourceFileId> {
+    let mut files = vec![];
+    collect_object_files(object, &mut files);
+    files.sort_by_key(|file| source_index(*file));
+    files.dedup();
+    files
+}
+
+/// Prints a Yul object, declaring the source files referred to by its `@src`
+/// comments with a `@use-src` comment before it and each of its nested
+/// objects, as solc requires.
+pub fn print_with_use_src(db: &dyn SourceDb, object: &yul::Object) -> String {
+    let files = source_files(object);
+    let use_src = if files.is_empty() {
+        String::new()
+    } else {
+        let sources: Vec<_> = files
+            .into_iter()
+            .map(|file| format!("{}:{:?}", source_index(file), file.path(db).as_str()))
+            .collect();
+        format!("/** @use-src {} */ ", sources.join(", "))
+    };
+
+    let mut yul = String::new();
+    print_object(object, &use_src, &mut yul);
+    yul
+}
+
+fn print_object(object: &yul::Object, use_src: &str, yul: &mut String) {
+    yul.push_str(&format!(
+        "{use_src}object \"{}\" {{ {} ",
+        object.name, object.code
+    ));
+    for nested in &object.objects {
+        print_object(nested, use_src, yul);
+        yul.push(' ');
+    }
+    for data in &object.data {
+        yul.push_str(&format!("{data} "));
+    }
+    yul.push('}');
+}
+
+fn collect_object_files(object: &yul::Object, files: &mut Vec<SourceFileId>) {
+    collect_block_files(&object.code.block, files);
+    for nested in &object.objects {
+        collect_object_files(nested, files);
+    }
+}
+
+fn collect_block_files(block: &yul::Block, files: &mut Vec<SourceFileId>) {
+    for stmt in &block.statements {
+        match stmt {
+            yul::Statement::Expression(yul::Expression::Identifier(ident)) => {
+                if let Some(file) = src_comment_file(&ident.identifier) {
+                    files.push(file);
+                }
+            }
+            yul::Statement::Block(block) => collect_block_files(block, files),
+            yul::Statement::FunctionDefinition(def) => collect_block_files(&def.block, files),
+            yul::Statement::If(if_) => collect_block_files(&if_.block, files),
+            yul::Statement::Switch(switch) => {
+                for case in &switch.cases {
+                    collect_block_files(&case.block, files);
+                }
+            }
+            yul::Statement::ForLoop(for_) => {
+                collect_block_files(&for_.pre, files);
+                collect_block_files(&for_.post, files);
+                collect_block_files(&for_.body, files);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the source file of a comment made by [`src_comment`], or `None` if
+/// `comment` isn't one or the code is marked as generated.
+fn src_comment_file(comment: &str) -> Option<SourceFileId> {
+    let src = comment.strip_prefix("/** @src ")?;
+    let idx: u32 = src.split(':').next()?.parse().ok()?;
+    Some(SourceFileId::from_intern_id(idx.into()))
+}
@@ -0,0 +1,66 @@
+use fe_analyzer::namespace::items::ContractId;
+use fe_common::utils::keccak::erc7201_slot;
+use fe_mir::ir::{TypeId, TypeKind};
+use num_bigint::BigInt;
+
+use crate::db::CodegenDb;
+
+/// The number of bits of a slot which a storage pointer can represent. A
+/// storage pointer is a byte address, so its slot is `ptr / 32`.
+const PTR_SLOT_BITS: usize = 251;
+
+/// The slot from which the fields of a contract are placed.
+///
+/// The fields of a contract marked with `#namespace("<id>")` are placed from
+/// the root slot of the ERC-7201 namespace `<id>`, and from the slot `0`
+/// otherwise.
+///
+/// The root slot doesn't always fit in a storage pointer, so it's split into
+/// the base pointer of the contract and the high bits of the slot, which are
+/// added to every slot computed from a storage pointer.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct StorageRoot {
+    slot: BigInt,
+}
+
+impl StorageRoot {
+    pub(crate) fn new(namespace: Option<&str>) -> Self {
+        match namespace {
+            Some(id) => Self {
+                slot: erc7201_slot(id),
+            },
+            None => Self::default(),
+        }
+    }
+
+    pub(crate) fn of_contract(db: &dyn CodegenDb, contract: ContractId) -> Self {
+        Self::new(contract.storage_namespace(db.upcast()).as_deref())
+    }
+
+    /// Returns the storage root of the contract type `ty`.
+    pub(crate) fn of_contract_type(db: &dyn CodegenDb, ty: TypeId) -> Self {
+        match &ty.data(db.upcast()).kind {
+            TypeKind::Contract(def) => Self::new(def.storage_namespace.as_deref()),
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn slot(&self) -> &BigInt {
+        &self.slot
+    }
+
+    /// Returns the byte address of the first field of the contract.
+    pub(crate) fn ptr(&self) -> BigInt {
+        (&self.slot & ptr_slot_mask()) << 5
+    }
+
+    /// Returns the high bits of the root slot which a storage pointer can't
+    /// represent.
+    pub(crate) fn slot_offset(&self) -> BigInt {
+        &self.slot - (&self.slot & ptr_slot_mask())
+    }
+}
+
+fn ptr_slot_mask() -> BigInt {
+    (BigInt::from(1) << PTR_SLOT_BITS) - 1
+}
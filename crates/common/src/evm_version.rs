@@ -0,0 +1,85 @@
+use std::{fmt, str::FromStr};
+
+/// A hard fork of the EVM which the compiled code targets.
+///
+/// The versions are ordered by activation, so later hard forks compare
+/// greater.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EvmVersion {
+    Constantinople,
+    Petersburg,
+    Istanbul,
+    Berlin,
+    London,
+    /// The merge, which replaces `difficulty` with `prevrandao`.
+    #[default]
+    Paris,
+    /// Introduces `push0`.
+    Shanghai,
+    /// Introduces transient storage, `mcopy` and blob hashes.
+    Cancun,
+}
+
+impl EvmVersion {
+    pub const ALL: [EvmVersion; 8] = [
+        Self::Constantinople,
+        Self::Petersburg,
+        Self::Istanbul,
+        Self::Berlin,
+        Self::London,
+        Self::Paris,
+        Self::Shanghai,
+        Self::Cancun,
+    ];
+
+    /// Returns the name of the version, as used by solc.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Constantinople => "constantinople",
+            Self::Petersburg => "petersburg",
+            Self::Istanbul => "istanbul",
+            Self::Berlin => "berlin",
+            Self::London => "london",
+            Self::Paris => "paris",
+            Self::Shanghai => "shanghai",
+            Self::Cancun => "cancun",
+        }
+    }
+
+    /// Returns `true` if the `push0` instruction is available.
+    pub fn has_push0(self) -> bool {
+        self >= Self::Shanghai
+    }
+
+    /// Returns `true` if the size of the code which creates a contract is
+    /// limited, as introduced by EIP-3860.
+    pub fn has_initcode_size_limit(self) -> bool {
+        self >= Self::Shanghai
+    }
+}
+
+impl FromStr for EvmVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|version| version.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL
+                    .iter()
+                    .map(|version| format!("`{version}`"))
+                    .collect();
+                format!(
+                    "unknown EVM version `{s}`, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for EvmVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -140,6 +140,14 @@ impl BuildFiles {
 pub struct ProjectFiles {
     pub name: SmolStr,
     pub version: SmolStr,
+    /// The `dispatcher` setting of the manifest.
+    pub dispatcher: Option<SmolStr>,
+    /// The `evm-version` setting of the manifest.
+    pub evm_version: Option<SmolStr>,
+    /// The `[optimizer]` table of the manifest.
+    pub optimizer: ManifestOptimizer,
+    /// The `solc` setting of the manifest.
+    pub solc: Option<SmolStr>,
     pub mode: ProjectMode,
     pub dependencies: Vec<Dependency>,
     pub src: Vec<(String, String)>,
@@ -155,6 +163,10 @@ impl ProjectFiles {
         let manifest = Manifest::load(loader, &manifest_path)?;
         let name = manifest.name;
         let version = manifest.version;
+        let dispatcher = manifest.dispatcher;
+        let evm_version = manifest.evm_version;
+        let optimizer = manifest.optimizer.unwrap_or_default();
+        let solc = manifest.solc;
 
         let mut dependencies = vec![];
         let mut errors = vec![];
@@ -198,6 +210,10 @@ impl ProjectFiles {
         Ok(Self {
             name,
             version,
+            dispatcher,
+            evm_version,
+            optimizer,
+            solc,
             mode,
             dependencies,
             src,
@@ -379,9 +395,24 @@ impl Dependency {
 struct Manifest {
     pub name: SmolStr,
     pub version: SmolStr,
+    dispatcher: Option<SmolStr>,
+    #[serde(rename = "evm-version")]
+    evm_version: Option<SmolStr>,
+    optimizer: Option<ManifestOptimizer>,
+    solc: Option<SmolStr>,
     dependencies: Option<Table>,
 }
 
+/// The optimizer settings of a manifest, which are all optional.
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestOptimizer {
+    pub enabled: Option<bool>,
+    pub runs: Option<u32>,
+    #[serde(rename = "yul-steps")]
+    pub yul_steps: Option<SmolStr>,
+}
+
 impl Manifest {
     pub fn load(loader: &FileLoader, path: &str) -> Result<Self, String> {
         let content = loader
@@ -1,3 +1,4 @@
+use num_bigint::{BigInt, Sign};
 use tiny_keccak::{Hasher, Keccak};
 
 /// Get the full 32 byte hash of the content.
@@ -34,3 +35,18 @@ pub fn full_as_bytes(content: &[u8]) -> [u8; 32] {
 
     selector
 }
+
+/// Get the root slot of the ERC-7201 namespace `id`, i.e.,
+/// `keccak256(abi.encode(uint256(keccak256(id)) - 1)) & ~bytes32(uint256(0xff))`.
+pub fn erc7201_slot(id: &str) -> BigInt {
+    let modulus = BigInt::from(1) << 256;
+    let hash = BigInt::from_bytes_be(Sign::Plus, &full_as_bytes(id.as_bytes()));
+    let word: BigInt = (hash + &modulus - 1) % &modulus;
+
+    let (_, bytes) = word.to_bytes_be();
+    let mut word = [0; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+
+    let slot = BigInt::from_bytes_be(Sign::Plus, &full_as_bytes(&word));
+    &slot - (&slot & BigInt::from(0xff))
+}
@@ -0,0 +1,43 @@
+//! Static estimates of the gas that calls to the public functions of a
+//! contract use. They are laid out like the `external` section of the
+//! `gasEstimates` output of solc, but each signature maps to the estimates of
+//! the cheapest and the most expensive path instead of a single estimate.
+
+use fe_analyzer::namespace::items::ContractId;
+use fe_mir::analysis::GasEstimator;
+use fe_mir::db::MirDb;
+use fe_mir::ir::function::Linkage;
+use serde_json::json;
+
+use crate::{CodegenDb, Db};
+
+/// Returns the estimated gas of the cheapest and the most expensive path of a
+/// call to each public function of a contract, by their signatures, as JSON.
+///
+/// The runtime helpers are charged approximate costs, and the intrinsic gas of
+/// transactions, the dispatcher and the gas used by other contracts are left
+/// out, so the estimates aren't bounds. The most expensive path is
+/// `"infinite"` for functions with loops whose trip count isn't a constant, or
+/// whose memory use can't be bounded.
+pub(crate) fn gas_estimates(db: &Db, contract: ContractId) -> String {
+    let mut estimator = GasEstimator::new(db);
+    let external: serde_json::Map<_, _> = db
+        .mir_lower_contract_all_functions(contract)
+        .iter()
+        .filter(|func| {
+            func.signature(db).linkage == Linkage::Export
+                && !func.is_contract_receive(db)
+                && !func.is_contract_fallback(db)
+        })
+        .map(|&func| {
+            let abi = db.codegen_abi_function(func);
+            let estimate = estimator.estimate_external(func);
+            let estimate = json!({
+                "cheapestPath": estimate.best.to_string(),
+                "costliestPath": estimate.worst.to_string(),
+            });
+            (abi.selector().selector_signature().to_string(), estimate)
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({ "external": external })).unwrap()
+}
@@ -1,12 +1,20 @@
 #![allow(unused_imports, dead_code)]
 
+use fe_abi::contract::AbiContract;
 use fe_abi::event::AbiEvent;
 use fe_abi::types::{AbiTupleField, AbiType};
+pub use fe_analyzer::AnalyzerDb;
 pub use fe_codegen::db::{CodegenDb, Db};
+pub use fe_codegen::yul::isel::DispatcherStrategy;
+pub use fe_common::EvmVersion;
+pub use fe_mir::db::MirDb;
+pub use fe_yulc::{OptimizerSettings, SolcBinary};
+pub use size_report::{MAX_CODE_SIZE, MAX_INITCODE_SIZE};
+pub use standard_json::{compile_standard_json, contract_artifact};
 
 use fe_analyzer::namespace::items::{ContractId, FunctionId, IngotId, IngotMode, ModuleId};
 use fe_common::diagnostics::Diagnostic;
-use fe_common::files::FileKind;
+use fe_common::files::{FileKind, SourceFileId};
 use fe_common::{db::Upcast, utils::files::BuildFiles};
 use fe_parser::ast::SmolStr;
 use fe_test_runner::ethabi::{Event, EventParam, ParamType};
@@ -14,23 +22,47 @@ use fe_test_runner::TestSink;
 use indexmap::{indexmap, IndexMap};
 use serde_json::Value;
 use std::fmt::Display;
+use std::path::Path;
+use yultsur::yul;
+
+mod gas_estimates;
+mod size_report;
+mod standard_json;
 
 /// The artifacts of a compiled module.
 pub struct CompiledModule {
     pub src_ast: String,
     pub lowered_ast: String,
     pub contracts: IndexMap<String, CompiledContract>,
+    /// Warnings about the compiled contracts, such as code which exceeds the
+    /// size limits of the EVM.
+    pub warnings: Vec<Diagnostic>,
 }
 
 /// The artifacts of a compiled contract.
 pub struct CompiledContract {
     pub json_abi: String,
+    pub storage_layout: String,
+    pub solidity_interface: String,
+    pub rust_bindings: String,
+    /// The compiler version, the settings and the hashes of the sources the
+    /// contract was built with, and its ABI.
+    pub metadata: String,
     pub yul: String,
     pub origin: ContractId,
-    #[cfg(feature = "solc-backend")]
     pub bytecode: String,
-    #[cfg(feature = "solc-backend")]
     pub runtime_bytecode: String,
+    /// The source maps of `bytecode` and `runtime_bytecode`, which map their
+    /// instructions to spans of the Fe source files, as JSON.
+    pub source_map: String,
+    /// A listing of the instructions of `runtime_bytecode`.
+    pub asm: String,
+    /// The sizes of the deploy and runtime code, and the share of the runtime
+    /// code of the dispatcher and of each function.
+    pub size_report: String,
+    /// Estimates of the gas of the cheapest and the most expensive paths of
+    /// calls to the public functions, as JSON.
+    pub gas_estimates: String,
 }
 
 #[cfg(feature = "solc-backend")]
@@ -115,13 +147,23 @@ pub fn compile_single_file(
     src: &str,
     with_bytecode: bool,
     with_runtime_bytecode: bool,
-    optimize: bool,
+    with_gas_estimates: bool,
+    optimizer: &OptimizerSettings,
+    solc: Option<&SolcBinary>,
 ) -> Result<CompiledModule, CompileError> {
     let module = ModuleId::new_standalone(db, path, src);
     let diags = module.diagnostics(db);
 
     if diags.is_empty() {
-        compile_module(db, module, with_bytecode, with_runtime_bytecode, optimize)
+        compile_module(
+            db,
+            module,
+            with_bytecode,
+            with_runtime_bytecode,
+            with_gas_estimates,
+            optimizer,
+            solc,
+        )
     } else {
         Err(CompileError(diags))
     }
@@ -133,12 +175,16 @@ pub fn compile_single_file_tests(
     path: &str,
     src: &str,
     optimize: bool,
+    solc: Option<&SolcBinary>,
 ) -> Result<(SmolStr, Vec<CompiledTest>), CompileError> {
     let module = ModuleId::new_standalone(db, path, src);
     let diags = module.diagnostics(db);
 
     if diags.is_empty() {
-        Ok((module.name(db), compile_module_tests(db, module, optimize)))
+        Ok((
+            module.name(db),
+            compile_module_tests(db, module, optimize, solc)?,
+        ))
     } else {
         Err(CompileError(diags))
     }
@@ -154,16 +200,80 @@ pub fn check_ingot(db: &mut Db, build_files: &BuildFiles) -> Vec<Diagnostic> {
     diags
 }
 
+/// Returns the dispatcher strategy set by the manifest of the root project, or
+/// the default one.
+pub fn manifest_dispatcher_strategy(
+    build_files: &BuildFiles,
+) -> Result<DispatcherStrategy, String> {
+    match &build_files.project_files[&build_files.root_project_path].dispatcher {
+        Some(strategy) => strategy
+            .parse()
+            .map_err(|err| format!("invalid `dispatcher` in fe.toml: {err}")),
+        None => Ok(DispatcherStrategy::default()),
+    }
+}
+
+/// Returns the EVM version set by the manifest of the root project, or the
+/// default one.
+pub fn manifest_evm_version(build_files: &BuildFiles) -> Result<EvmVersion, String> {
+    match &build_files.project_files[&build_files.root_project_path].evm_version {
+        Some(version) => version
+            .parse()
+            .map_err(|err| format!("invalid `evm-version` in fe.toml: {err}")),
+        None => Ok(EvmVersion::default()),
+    }
+}
+
+/// Returns the optimizer settings of the manifest of the root project, with
+/// the default settings in place of the unset ones.
+pub fn manifest_optimizer_settings(build_files: &BuildFiles) -> OptimizerSettings {
+    let manifest = &build_files.project_files[&build_files.root_project_path].optimizer;
+    let default = OptimizerSettings::default();
+    OptimizerSettings {
+        enabled: manifest.enabled.unwrap_or(default.enabled),
+        runs: manifest.runs.unwrap_or(default.runs),
+        yul_steps: manifest
+            .yul_steps
+            .as_ref()
+            .map(ToString::to_string)
+            .or(default.yul_steps),
+    }
+}
+
+/// Returns the path of the solc executable set by the manifest of the root
+/// project. A relative path with a directory is relative to the project.
+pub fn manifest_solc_path(build_files: &BuildFiles) -> Option<String> {
+    let solc = build_files.project_files[&build_files.root_project_path]
+        .solc
+        .as_ref()?;
+    let path = Path::new(solc.as_str());
+    if path.is_relative() && path.components().count() > 1 {
+        Some(
+            Path::new(build_files.root_project_path.as_str())
+                .join(path)
+                .to_string_lossy()
+                .to_string(),
+        )
+    } else {
+        Some(solc.to_string())
+    }
+}
+
 /// Compiles the main module of a project.
 ///
 /// If `with_bytecode` is set to false, the compiler will skip the final Yul ->
-/// Bytecode pass. This is useful when debugging invalid Yul code.
+/// Bytecode pass. This is useful when debugging invalid Yul code. The Yul code
+/// is compiled with `solc` if it's given, and with the built-in backend
+/// otherwise. The gas estimates are only computed if `with_gas_estimates` is
+/// set, since they need the MIR of all functions of the contracts.
 pub fn compile_ingot(
     db: &mut Db,
     build_files: &BuildFiles,
     with_bytecode: bool,
     with_runtime_bytecode: bool,
-    optimize: bool,
+    with_gas_estimates: bool,
+    optimizer: &OptimizerSettings,
+    solc: Option<&SolcBinary>,
 ) -> Result<CompiledModule, CompileError> {
     let ingot = IngotId::from_build_files(db, build_files);
 
@@ -180,7 +290,9 @@ pub fn compile_ingot(
         main_module,
         with_bytecode,
         with_runtime_bytecode,
-        optimize,
+        with_gas_estimates,
+        optimizer,
+        solc,
     )
 }
 
@@ -189,6 +301,7 @@ pub fn compile_ingot_tests(
     db: &mut Db,
     build_files: &BuildFiles,
     optimize: bool,
+    solc: Option<&SolcBinary>,
 ) -> Result<Vec<(SmolStr, Vec<CompiledTest>)>, CompileError> {
     let ingot = IngotId::from_build_files(db, build_files);
 
@@ -199,13 +312,16 @@ pub fn compile_ingot_tests(
     }
 
     if diags.is_empty() {
-        Ok(ingot
+        ingot
             .all_modules(db)
             .iter()
-            .fold(vec![], |mut accum, module| {
-                accum.push((module.name(db), compile_module_tests(db, *module, optimize)));
-                accum
-            }))
+            .map(|module| {
+                Ok((
+                    module.name(db),
+                    compile_module_tests(db, *module, optimize, solc)?,
+                ))
+            })
+            .collect()
     } else {
         Err(CompileError(diags))
     }
@@ -227,50 +343,120 @@ pub fn dump_mir_single_file(db: &mut Db, path: &str, src: &str) -> Result<String
 }
 
 #[cfg(feature = "solc-backend")]
-fn compile_test(db: &mut Db, test: FunctionId, optimize: bool) -> CompiledTest {
-    let yul_test = fe_codegen::yul::isel::lower_test(db, test)
-        .to_string()
-        .replace('"', "\\\"");
-    let bytecode = compile_to_evm("test", &yul_test, optimize, false).bytecode;
+fn compile_test(
+    db: &mut Db,
+    test: FunctionId,
+    optimize: bool,
+    solc: Option<&SolcBinary>,
+) -> Result<CompiledTest, CompileError> {
+    let yul_test = fe_codegen::yul::isel::lower_test(db, test);
+    let optimizer = OptimizerSettings {
+        enabled: optimize,
+        ..OptimizerSettings::default()
+    };
+    let bytecode =
+        compile_to_evm(db, &yul_test, &optimizer, db.evm_version(), solc, false)?.bytecode;
     let events = db.codegen_abi_module_events(test.module(db));
-    CompiledTest::new(test.name(db), events, bytecode)
+    Ok(CompiledTest::new(test.name(db), events, bytecode))
 }
 
 #[cfg(feature = "solc-backend")]
-fn compile_module_tests(db: &mut Db, module_id: ModuleId, optimize: bool) -> Vec<CompiledTest> {
+fn compile_module_tests(
+    db: &mut Db,
+    module_id: ModuleId,
+    optimize: bool,
+    solc: Option<&SolcBinary>,
+) -> Result<Vec<CompiledTest>, CompileError> {
+    db.set_mir_optimization_enabled(optimize);
     module_id
         .tests(db)
         .iter()
-        .map(|test| compile_test(db, *test, optimize))
+        .map(|test| compile_test(db, *test, optimize, solc))
         .collect()
 }
 
-#[cfg(feature = "solc-backend")]
 fn compile_module(
     db: &mut Db,
     module_id: ModuleId,
     with_bytecode: bool,
     with_runtime_bytecode: bool,
-    optimize: bool,
+    with_gas_estimates: bool,
+    optimizer: &OptimizerSettings,
+    solc: Option<&SolcBinary>,
 ) -> Result<CompiledModule, CompileError> {
+    db.set_mir_optimization_enabled(optimizer.enabled);
     let mut contracts = IndexMap::default();
+    let mut warnings = vec![];
 
     for contract in module_id.all_contracts(db.upcast()) {
         let name = &contract.data(db.upcast()).name;
         let abi = db.codegen_abi_contract(contract);
-        let yul_contract = compile_to_yul(db, contract);
-
-        let (bytecode, runtime_bytecode) = if with_bytecode || with_runtime_bytecode {
-            let deployable_name = db.codegen_contract_deployer_symbol_name(contract);
-            let bytecode = compile_to_evm(
-                deployable_name.as_str(),
-                &yul_contract,
-                optimize,
-                with_runtime_bytecode,
-            );
-            (bytecode.bytecode, bytecode.runtime_bytecode)
-        } else {
+        let storage_layout = db.codegen_storage_layout(contract);
+        let metadata = build_metadata(db, module_id, &abi, optimizer, solc);
+        let mut yul_object = fe_codegen::yul::isel::lower_contract_deployable(db, contract);
+        if db.codegen_metadata_hash() {
+            let trailer = fe_yulc::metadata_trailer(&metadata, env!("CARGO_PKG_VERSION"));
+            yul_object
+                .objects
+                .last_mut()
+                .expect("deployable contract without a runtime object")
+                .data
+                .push(fe_yulc::metadata_data(&trailer));
+        }
+        let yul_contract = compile_to_yul(db, &yul_object);
+
+        let (bytecode, runtime_bytecode, source_map, runtime_locations) =
+            if with_bytecode || with_runtime_bytecode {
+                let bytecode = compile_to_evm(
+                    db,
+                    &yul_object,
+                    optimizer,
+                    db.evm_version(),
+                    solc,
+                    with_runtime_bytecode,
+                )?;
+                let source_map = build_source_map(db, &yul_object, &bytecode);
+                // Without the locations, the runtime code is only left
+                // unattributed in the size report.
+                let runtime_locations =
+                    fe_yulc::parse_source_map(&bytecode.runtime_source_map).unwrap_or_default();
+                (
+                    bytecode.bytecode,
+                    bytecode.runtime_bytecode,
+                    source_map,
+                    runtime_locations,
+                )
+            } else {
+                ("".to_string(), "".to_string(), "".to_string(), vec![])
+            };
+
+        let deploy_size = hex::decode(&bytecode).unwrap_or_default().len();
+        let runtime_code = hex::decode(&runtime_bytecode).unwrap_or_default();
+        let (asm, size_report) = if runtime_code.is_empty() {
             ("".to_string(), "".to_string())
+        } else {
+            warnings.extend(size_report::size_limit_warnings(
+                db,
+                contract,
+                deploy_size,
+                runtime_code.len(),
+            ));
+            (
+                size_report::asm_listing(&runtime_code, &runtime_locations),
+                size_report::size_report(
+                    db,
+                    contract,
+                    deploy_size,
+                    &runtime_code,
+                    &runtime_locations,
+                ),
+            )
+        };
+
+        let gas_estimates = if with_gas_estimates {
+            gas_estimates::gas_estimates(db, contract)
+        } else {
+            "".to_string()
         };
 
         contracts.insert(
@@ -278,10 +464,18 @@ fn compile_module(
             // Maybe put the ContractID here so we can trace it back to the source file
             CompiledContract {
                 json_abi: serde_json::to_string_pretty(&abi).unwrap(),
+                storage_layout: serde_json::to_string_pretty(&storage_layout).unwrap(),
+                solidity_interface: fe_abi::solidity::interface(&format!("I{name}"), &abi),
+                rust_bindings: fe_abi::rust_bindings::bindings(name, &abi),
+                metadata,
                 yul: yul_contract,
                 origin: contract,
                 bytecode,
                 runtime_bytecode,
+                source_map,
+                asm,
+                size_report,
+                gas_estimates,
             },
         );
     }
@@ -290,70 +484,204 @@ fn compile_module(
         src_ast: format!("{:#?}", module_id.ast(db)),
         lowered_ast: format!("{:#?}", module_id.ast(db)),
         contracts,
+        warnings,
     })
 }
 
-#[cfg(not(feature = "solc-backend"))]
-fn compile_module(
-    db: &mut Db,
+/// Returns the build metadata of a contract of a module as JSON, whose hash is
+/// appended to the runtime bytecode.
+fn build_metadata(
+    db: &Db,
     module_id: ModuleId,
-    _with_bytecode: bool,
-    _with_runtime_bytecode: bool,
-    _optimize: bool,
-) -> Result<CompiledModule, CompileError> {
-    let mut contracts = IndexMap::default();
-    for contract in module_id.all_contracts(db.upcast()) {
-        let name = &contract.data(db.upcast()).name;
-        let abi = db.codegen_abi_contract(contract);
-        let yul_contract = compile_to_yul(db, contract);
+    abi: &AbiContract,
+    optimizer: &OptimizerSettings,
+    solc: Option<&SolcBinary>,
+) -> String {
+    let root = module_id.ingot(db);
+    let sources: serde_json::Map<_, _> = source_files(db, root)
+        .into_iter()
+        .map(|(ingot, file)| {
+            let hash = fe_common::utils::keccak::full(file.content(db).as_bytes());
+            let source = serde_json::json!({ "keccak256": format!("0x{hash}") });
+            (metadata_source_path(db, root, ingot, file), source)
+        })
+        .collect();
+    let bytecode_hash = if db.codegen_metadata_hash() {
+        "ipfs"
+    } else {
+        "none"
+    };
+
+    let mut metadata = serde_json::json!({
+        "compiler": { "version": env!("CARGO_PKG_VERSION") },
+        "language": "Fe",
+        "output": { "abi": abi },
+        "settings": {
+            "evmVersion": db.evm_version().name(),
+            "dispatcher": db.codegen_dispatcher_strategy().to_string(),
+            "metadata": { "bytecodeHash": bytecode_hash },
+            "optimizer": optimizer.to_json(),
+        },
+        "sources": sources,
+        "version": 1,
+    });
+    if let Some(solc) = solc {
+        metadata["compiler"]["solc"] = solc.version().to_string().into();
+    }
+    serde_json::to_string_pretty(&metadata).unwrap()
+}
 
-        contracts.insert(
-            name.to_string(),
-            CompiledContract {
-                json_abi: serde_json::to_string_pretty(&abi).unwrap(),
-                yul: yul_contract,
-                origin: contract,
-            },
-        );
+/// Returns the source files of an ingot and of the ingots it depends on,
+/// along with their ingot.
+fn source_files(db: &Db, ingot: IngotId) -> Vec<(IngotId, SourceFileId)> {
+    let mut ingots = vec![ingot];
+    let mut idx = 0;
+    while let Some(ingot) = ingots.get(idx).copied() {
+        for dep in ingot.external_ingots(db).values() {
+            if !ingots.contains(dep) {
+                ingots.push(*dep);
+            }
+        }
+        idx += 1;
     }
 
-    Ok(CompiledModule {
-        src_ast: format!("{:#?}", module_id.ast(db)),
-        lowered_ast: format!("{:#?}", module_id.ast(db)),
-        contracts,
-    })
+    ingots
+        .into_iter()
+        .flat_map(|ingot| {
+            db.ingot_files(ingot)
+                .iter()
+                .map(move |file| (ingot, *file))
+                .collect::<Vec<_>>()
+        })
+        .collect()
 }
 
-fn compile_to_yul(db: &mut Db, contract: ContractId) -> String {
-    let yul_contract = fe_codegen::yul::isel::lower_contract_deployable(db, contract);
-    yul_contract.to_string().replace('"', "\\\"")
+/// Returns the path of a source file in the metadata, which is relative to the
+/// source directory of its ingot, so that the metadata and its hash don't
+/// depend on where the ingot is built. The paths of the files of the ingots
+/// `root` depends on start with the name of the ingot.
+fn metadata_source_path(db: &Db, root: IngotId, ingot: IngotId, file: SourceFileId) -> String {
+    let path = file.path(db);
+    let src_dir = ingot.data(db).src_dir.clone();
+    let relative = path
+        .strip_prefix(src_dir.as_str())
+        .unwrap_or_else(|_| path.as_path());
+    if ingot == root {
+        relative.to_string()
+    } else {
+        format!("{}/{}", ingot.name(db), relative)
+    }
+}
+
+/// Returns the source maps of a contract as JSON, along with the paths of the
+/// source files their indices refer to.
+fn build_source_map(
+    db: &Db,
+    yul_object: &yul::Object,
+    bytecode: &fe_yulc::ContractBytecode,
+) -> String {
+    let sources: serde_json::Map<_, _> = fe_codegen::yul::source_map::source_files(yul_object)
+        .into_iter()
+        .map(|file| {
+            let idx = fe_codegen::yul::source_map::source_index(file);
+            (idx.to_string(), file.path(db).as_str().into())
+        })
+        .collect();
+    let source_map = serde_json::json!({
+        "sources": sources,
+        "bytecode": bytecode.source_map,
+        "runtimeBytecode": bytecode.runtime_source_map,
+    });
+    serde_json::to_string_pretty(&source_map).unwrap()
+}
+
+fn compile_to_yul(db: &Db, yul_object: &yul::Object) -> String {
+    let yul = fe_codegen::yul::isel::normalize_object(yul_object.clone());
+    let yul = fe_codegen::yul::source_map::print_with_use_src(db, &yul);
+    fe_yulc::print_metadata_as_hex(&yul)
 }
 
-#[cfg(feature = "solc-backend")]
 fn compile_to_evm(
-    name: &str,
-    yul_object: &str,
-    optimize: bool,
+    db: &Db,
+    yul_object: &yul::Object,
+    optimizer: &OptimizerSettings,
+    evm_version: EvmVersion,
+    solc: Option<&SolcBinary>,
     verify_runtime_bytecode: bool,
-) -> fe_yulc::ContractBytecode {
-    match fe_yulc::compile_single_contract(name, yul_object, optimize, verify_runtime_bytecode) {
-        Ok(bytecode) => bytecode,
-
-        Err(error) => {
-            for error in serde_json::from_str::<Value>(&error.0)
-                .expect("unable to deserialize json output")["errors"]
-                .as_array()
-                .expect("errors not an array")
-            {
-                eprintln!(
-                    "Error: {}",
+) -> Result<fe_yulc::ContractBytecode, CompileError> {
+    let compiled = match solc {
+        Some(solc) => fe_yulc::compile_single_contract_with_solc(
+            solc,
+            &yul_object.name.identifier,
+            &compile_to_yul(db, yul_object),
+            optimizer,
+            evm_version,
+            verify_runtime_bytecode,
+        ),
+        None => compile_with_builtin_backend(
+            db,
+            yul_object,
+            optimizer,
+            evm_version,
+            verify_runtime_bytecode,
+        ),
+    };
+
+    compiled.map_err(|error| CompileError(yulc_error_diagnostics(&error)))
+}
+
+/// Returns the errors of the standard JSON output of solc as diagnostics, or
+/// the error itself if it isn't solc output.
+fn yulc_error_diagnostics(error: &fe_yulc::YulcError) -> Vec<Diagnostic> {
+    match serde_json::from_str::<Value>(&error.0) {
+        Ok(output) if output["errors"].is_array() => output["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| {
+                Diagnostic::error(format!(
+                    "Yul compilation failed: {}",
                     error["formattedMessage"]
                         .as_str()
                         .expect("error value not a string")
                         .replace("\\\n", "\n")
-                )
-            }
-            panic!("Yul compilation failed with the above errors")
-        }
+                ))
+            })
+            .collect(),
+        _ => vec![Diagnostic::error(format!(
+            "Yul compilation failed: {}",
+            error.0
+        ))],
+    }
+}
+
+#[cfg(feature = "solc-backend")]
+fn compile_with_builtin_backend(
+    db: &Db,
+    yul_object: &yul::Object,
+    optimizer: &OptimizerSettings,
+    evm_version: EvmVersion,
+    verify_runtime_bytecode: bool,
+) -> Result<fe_yulc::ContractBytecode, fe_yulc::YulcError> {
+    if db.codegen_native_backend() {
+        return fe_yulc::compile_single_object(yul_object, evm_version, verify_runtime_bytecode);
     }
+    fe_yulc::compile_single_contract(
+        &yul_object.name.identifier,
+        &compile_to_yul(db, yul_object),
+        optimizer,
+        evm_version,
+        verify_runtime_bytecode,
+    )
+}
+
+#[cfg(not(feature = "solc-backend"))]
+fn compile_with_builtin_backend(
+    _db: &Db,
+    yul_object: &yul::Object,
+    _optimizer: &OptimizerSettings,
+    evm_version: EvmVersion,
+    verify_runtime_bytecode: bool,
+) -> Result<fe_yulc::ContractBytecode, fe_yulc::YulcError> {
+    fe_yulc::compile_single_object(yul_object, evm_version, verify_runtime_bytecode)
 }
@@ -0,0 +1,184 @@
+//! The size of the bytecode of a contract: a listing of the instructions of
+//! its runtime code, which parts of the contract the runtime code comes from,
+//! and whether the code fits the limits of the EVM.
+
+use fe_analyzer::namespace::items::ContractId;
+use fe_analyzer::AnalyzerDb;
+use fe_codegen::yul::source_map::source_index;
+use fe_common::diagnostics::{Diagnostic, Label, Severity};
+use fe_common::Span;
+use fe_yulc::{Instruction, SourceLocation};
+
+use crate::Db;
+
+/// The maximum size of the runtime code of a contract, set by EIP-170.
+pub const MAX_CODE_SIZE: usize = 0x6000;
+
+/// The maximum size of the code which creates a contract, set by EIP-3860.
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+/// Returns a listing of the instructions of the runtime code of a contract,
+/// followed by its data.
+///
+/// `locations` are the source locations of the instructions, whose number
+/// tells the code apart from the data that follows it.
+pub(crate) fn asm_listing(runtime_bytecode: &[u8], locations: &[Option<SourceLocation>]) -> String {
+    let (code, data) = split_code(runtime_bytecode, locations);
+    let mut listing: String = code.iter().map(|inst| format!("{inst}\n")).collect();
+    if !data.is_empty() {
+        listing.push_str(&format!("\ndata: 0x{}\n", hex::encode(data)));
+    }
+    listing
+}
+
+/// Returns the sizes of the deploy and runtime code of a contract, and how
+/// many bytes of the runtime code the dispatcher, each function of the
+/// contract, other functions and data take.
+pub(crate) fn size_report(
+    db: &Db,
+    contract: ContractId,
+    deploy_size: usize,
+    runtime_bytecode: &[u8],
+    locations: &[Option<SourceLocation>],
+) -> String {
+    let runtime_size = runtime_bytecode.len();
+    let initcode_limit = if db.evm_version().has_initcode_size_limit() {
+        format!(" (EIP-3860 limit: {MAX_INITCODE_SIZE} bytes)")
+    } else {
+        String::new()
+    };
+    let mut report = format!(
+        "deploy size: {deploy_size} bytes{initcode_limit}\n\
+         runtime size: {runtime_size} bytes (EIP-170 limit: {MAX_CODE_SIZE} bytes)\n"
+    );
+    if runtime_size == 0 {
+        return report;
+    }
+
+    let functions: Vec<_> = contract
+        .all_functions(db)
+        .iter()
+        .filter(|func| !func.is_constructor(db))
+        .map(|func| (format!("fn {}", func.name(db)), func.span(db)))
+        .collect();
+    let mut function_sizes = vec![0; functions.len()];
+    let mut dispatcher_size = 0;
+    let mut other_size = 0;
+
+    let contract_span = contract.span(db);
+    let (code, data) = split_code(runtime_bytecode, locations);
+    for (inst, location) in code.iter().zip(locations) {
+        let location = match location {
+            Some(location) => *location,
+            None => {
+                other_size += inst.size();
+                continue;
+            }
+        };
+        match functions
+            .iter()
+            .position(|(_, span)| contains(*span, location))
+        {
+            Some(idx) => function_sizes[idx] += inst.size(),
+            None if contains(contract_span, location) => dispatcher_size += inst.size(),
+            None => other_size += inst.size(),
+        }
+    }
+    // Without a source map, none of the code can be attributed.
+    other_size += code
+        .iter()
+        .skip(locations.len())
+        .map(Instruction::size)
+        .sum::<usize>();
+
+    let mut rows = vec![("dispatcher".to_string(), dispatcher_size)];
+    rows.extend(
+        functions
+            .into_iter()
+            .map(|(name, _)| name)
+            .zip(function_sizes),
+    );
+    rows.push(("other functions".to_string(), other_size));
+    rows.push(("data".to_string(), data.len()));
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    let size_width = runtime_size.to_string().len();
+    report.push_str("\nruntime code by origin:\n");
+    for (name, size) in rows {
+        let share = size as f64 * 100. / runtime_size as f64;
+        report.push_str(&format!(
+            "  {name:name_width$}  {size:>size_width$} bytes  {share:5.1}%\n"
+        ));
+    }
+    report
+}
+
+/// Returns warnings about code of a contract which exceeds the size limits of
+/// the EVM, and so can't be deployed.
+pub(crate) fn size_limit_warnings(
+    db: &Db,
+    contract: ContractId,
+    deploy_size: usize,
+    runtime_size: usize,
+) -> Vec<Diagnostic> {
+    let name = contract.name(db);
+    let mut warnings = vec![];
+    if runtime_size > MAX_CODE_SIZE {
+        warnings.push(size_limit_warning(
+            format!("contract `{name}` exceeds the EIP-170 code size limit"),
+            contract.name_span(db),
+            format!(
+                "the runtime code is {runtime_size} bytes, but at most {MAX_CODE_SIZE} bytes can be deployed"
+            ),
+        ));
+    }
+    if db.evm_version().has_initcode_size_limit() && deploy_size > MAX_INITCODE_SIZE {
+        warnings.push(size_limit_warning(
+            format!("contract `{name}` exceeds the EIP-3860 initcode size limit"),
+            contract.name_span(db),
+            format!(
+                "the deploy code is {deploy_size} bytes, but at most {MAX_INITCODE_SIZE} bytes can create a contract"
+            ),
+        ));
+    }
+    warnings
+}
+
+fn size_limit_warning(message: String, span: Span, label: String) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Warning,
+        message,
+        labels: vec![Label::primary(span, label)],
+        notes: vec![
+            "Hint: enable the optimizer, or move some of the functions to another contract"
+                .to_string(),
+        ],
+    }
+}
+
+/// Splits bytecode into the instructions of its code and the data that follows
+/// it. Without source locations, all of the bytecode is taken as code.
+fn split_code<'a>(
+    bytecode: &'a [u8],
+    locations: &[Option<SourceLocation>],
+) -> (Vec<Instruction>, &'a [u8]) {
+    let mut code = fe_yulc::disassemble(bytecode);
+    if locations.is_empty() || locations.len() >= code.len() {
+        return (code, &[]);
+    }
+
+    code.truncate(locations.len());
+    let code_size = code.iter().map(Instruction::size).sum();
+    (code, &bytecode[code_size..])
+}
+
+/// Returns `true` if `location` lies within `span`.
+fn contains(span: Span, location: SourceLocation) -> bool {
+    location.file == source_index(span.file_id)
+        && span.start <= location.start
+        && location.end <= span.end
+}
@@ -0,0 +1,575 @@
+//! The standard JSON interface, which compiles the sources of a JSON request
+//! with the settings of the request, and returns the outputs selected by the
+//! request and the diagnostics as one JSON response, like solc's
+//! `--standard-json` does.
+//!
+//! The request looks like
+//!
+//! ```json
+//! {
+//!   "language": "Fe",
+//!   "sources": { "src/main.fe": { "content": "contract Foo { ... }" } },
+//!   "settings": {
+//!     "evmVersion": "paris",
+//!     "dispatcher": "auto",
+//!     "optimizer": { "enabled": true, "runs": 200, "yulSteps": "dhfoDgvulfnTUtnIf" },
+//!     "metadata": { "bytecodeHash": "ipfs" },
+//!     "outputSelection": { "*": { "*": ["abi", "evm.bytecode.object"] } }
+//!   }
+//! }
+//! ```
+//!
+//! A single source is compiled as a standalone module, and multiple sources
+//! as an ingot with a `main.fe` module.
+
+use std::rc::Rc;
+
+use fe_analyzer::namespace::items::ModuleSource;
+use fe_common::db::SourceDb;
+use fe_common::diagnostics::{diagnostics_string, Diagnostic, LabelStyle, Severity};
+use fe_common::files::{File, FileKind};
+use fe_common::utils::files::{BuildFiles, ManifestOptimizer, ProjectFiles, ProjectMode};
+use fe_common::EvmVersion;
+use indexmap::indexmap;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    AnalyzerDb, CodegenDb, CompiledContract, Db, DispatcherStrategy, OptimizerSettings, SolcBinary,
+};
+
+/// The outputs of a contract which can be selected.
+const CONTRACT_OUTPUTS: [&str; 9] = [
+    "abi",
+    "metadata",
+    "ir",
+    "storageLayout",
+    "evm.bytecode.object",
+    "evm.bytecode.sourceMap",
+    "evm.deployedBytecode.object",
+    "evm.deployedBytecode.sourceMap",
+    "evm.gasEstimates",
+];
+
+/// Compiles a standard JSON request, and returns the JSON response. Yul is
+/// compiled with the solc executable at `solc_path` if it's given.
+pub fn compile_standard_json(input: &str, solc_path: Option<&str>) -> String {
+    let output = match Request::parse(input) {
+        Ok(request) => request.compile(solc_path),
+        Err(err) => json!({ "errors": [json_error(&err)] }),
+    };
+    output.to_string()
+}
+
+/// Returns all outputs of a compiled contract as one JSON artifact.
+pub fn contract_artifact(contract: &CompiledContract) -> String {
+    serde_json::to_string_pretty(&contract_output(contract, |_| true)).unwrap()
+}
+
+struct Request {
+    sources: Vec<(String, String)>,
+    evm_version: EvmVersion,
+    dispatcher: DispatcherStrategy,
+    optimizer: OptimizerSettings,
+    metadata_hash: bool,
+    output_selection: Value,
+}
+
+impl Request {
+    fn parse(input: &str) -> Result<Self, String> {
+        let input: Value =
+            serde_json::from_str(input).map_err(|err| format!("invalid JSON request: {err}"))?;
+        if input["language"] != "Fe" {
+            return Err("`language` must be \"Fe\"".into());
+        }
+
+        let sources = match input["sources"].as_object() {
+            Some(sources) if !sources.is_empty() => sources
+                .iter()
+                .map(|(path, source)| match source["content"].as_str() {
+                    Some(content) => Ok((path.clone(), content.to_string())),
+                    None => Err(format!("source `{path}` has no `content` string")),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err("`sources` must be a non-empty object".into()),
+        };
+
+        let settings = &input["settings"];
+        let evm_version = match optional(settings, "evmVersion", Value::as_str, "a string")? {
+            Some(version) => version.parse()?,
+            None => EvmVersion::default(),
+        };
+        let dispatcher = match optional(settings, "dispatcher", Value::as_str, "a string")? {
+            Some(strategy) => strategy.parse()?,
+            None => DispatcherStrategy::default(),
+        };
+
+        let optimizer = &settings["optimizer"];
+        let default = OptimizerSettings::default();
+        let optimizer = OptimizerSettings {
+            enabled: optional(optimizer, "enabled", Value::as_bool, "a boolean")?
+                .unwrap_or(default.enabled),
+            runs: optional(optimizer, "runs", as_u32, "an integer")?.unwrap_or(default.runs),
+            yul_steps: optional(optimizer, "yulSteps", Value::as_str, "a string")?
+                .map(ToString::to_string)
+                .or(default.yul_steps),
+        };
+
+        let metadata_hash = match optional(
+            &settings["metadata"],
+            "bytecodeHash",
+            Value::as_str,
+            "a string",
+        )? {
+            Some("ipfs") | None => true,
+            Some("none") => false,
+            Some(hash) => {
+                return Err(format!(
+                    "unknown `bytecodeHash` `{hash}`, expected `ipfs` or `none`"
+                ))
+            }
+        };
+
+        Ok(Self {
+            sources,
+            evm_version,
+            dispatcher,
+            optimizer,
+            metadata_hash,
+            output_selection: settings["outputSelection"].clone(),
+        })
+    }
+
+    fn compile(self, solc_path: Option<&str>) -> Value {
+        let solc = match solc_path {
+            Some(path) => match SolcBinary::new(path)
+                .and_then(|solc| solc.check_evm_version(self.evm_version).map(|_| solc))
+            {
+                Ok(solc) => Some(solc),
+                Err(err) => return json!({ "errors": [json_error(&err.0)] }),
+            },
+            None => None,
+        };
+
+        let mut db = Db::default();
+        db.set_evm_version(self.evm_version);
+        db.set_codegen_dispatcher_strategy(self.dispatcher);
+        db.set_codegen_metadata_hash(self.metadata_hash);
+
+        let with_bytecode = selects_bytecode(&self.output_selection);
+        let with_gas_estimates = selects_gas_estimates(&self.output_selection);
+        let compiled = match &self.sources[..] {
+            [(path, content)] => crate::compile_single_file(
+                &mut db,
+                path,
+                content,
+                with_bytecode,
+                with_bytecode,
+                with_gas_estimates,
+                &self.optimizer,
+                solc.as_ref(),
+            ),
+            _ => crate::compile_ingot(
+                &mut db,
+                &build_files(self.sources.clone()),
+                with_bytecode,
+                with_bytecode,
+                with_gas_estimates,
+                &self.optimizer,
+                solc.as_ref(),
+            ),
+        };
+
+        let mut sources = Map::new();
+        for (path, _) in &self.sources {
+            let file = db.intern_file(File {
+                kind: FileKind::Local,
+                path: Rc::new(path.as_str().into()),
+            });
+            let id = fe_codegen::yul::source_map::source_index(file);
+            sources.insert(path.clone(), json!({ "id": id }));
+        }
+
+        let module = match compiled {
+            Ok(module) => module,
+            Err(error) => {
+                let errors: Vec<_> = error
+                    .0
+                    .iter()
+                    .map(|diag| diagnostic_json(&db, diag))
+                    .collect();
+                return json!({ "errors": errors, "sources": sources });
+            }
+        };
+
+        let mut contracts = Map::new();
+        for (name, contract) in &module.contracts {
+            let path = contract_path(&db, contract);
+            let output = contract_output(contract, |output| {
+                is_selected(&self.output_selection, &path, name, output)
+            });
+
+            // The source maps refer to the files of the std library too.
+            if let Ok(source_map) = serde_json::from_str::<Value>(&contract.source_map) {
+                for (id, source) in source_map["sources"].as_object().into_iter().flatten() {
+                    if let (Some(source), Ok(id)) = (source.as_str(), id.parse::<u32>()) {
+                        sources.insert(source.to_string(), json!({ "id": id }));
+                    }
+                }
+            }
+
+            contracts.entry(path).or_insert_with(|| json!({}))[name.as_str()] = output;
+        }
+
+        let warnings: Vec<_> = module
+            .warnings
+            .iter()
+            .map(|diag| diagnostic_json(&db, diag))
+            .collect();
+        json!({
+            "errors": warnings,
+            "sources": sources,
+            "contracts": contracts,
+        })
+    }
+}
+
+/// Returns the build files of an ingot of the given sources.
+fn build_files(sources: Vec<(String, String)>) -> BuildFiles {
+    let project = ProjectFiles {
+        name: "main".into(),
+        version: "".into(),
+        dispatcher: None,
+        evm_version: None,
+        optimizer: ManifestOptimizer::default(),
+        solc: None,
+        mode: ProjectMode::Main,
+        dependencies: vec![],
+        src: sources,
+    };
+    BuildFiles {
+        root_project_path: "".into(),
+        project_files: indexmap! { "".into() => project },
+    }
+}
+
+/// Returns the outputs of a contract for which `selected` returns `true`.
+fn contract_output(contract: &CompiledContract, selected: impl Fn(&str) -> bool) -> Value {
+    let source_map: Value = serde_json::from_str(&contract.source_map).unwrap_or_default();
+    let mut output = json!({});
+    for name in CONTRACT_OUTPUTS {
+        if !selected(name) {
+            continue;
+        }
+
+        let value: Value = match name {
+            "abi" => serde_json::from_str(&contract.json_abi).unwrap(),
+            "metadata" => contract.metadata.clone().into(),
+            "ir" => contract.yul.clone().into(),
+            "storageLayout" => serde_json::from_str(&contract.storage_layout).unwrap(),
+            "evm.bytecode.object" => contract.bytecode.clone().into(),
+            "evm.bytecode.sourceMap" => source_map["bytecode"].clone(),
+            "evm.deployedBytecode.object" => contract.runtime_bytecode.clone().into(),
+            "evm.deployedBytecode.sourceMap" => source_map["runtimeBytecode"].clone(),
+            "evm.gasEstimates" => serde_json::from_str(&contract.gas_estimates).unwrap(),
+            _ => unreachable!(),
+        };
+        insert(&mut output, name, value);
+    }
+    output
+}
+
+/// Inserts `value` at a dotted path like `evm.bytecode.object`.
+fn insert(output: &mut Value, path: &str, value: Value) {
+    let mut target = output;
+    for key in path.split('.') {
+        target = &mut target[key];
+    }
+    *target = value;
+}
+
+/// Returns `true` if `output` of the contract `name` of the source `path` is
+/// selected. `*` selects all sources, contracts or outputs, and an output
+/// like `evm.bytecode` selects all outputs it contains.
+fn is_selected(selection: &Value, path: &str, name: &str, output: &str) -> bool {
+    [path, "*"]
+        .iter()
+        .flat_map(|path| [name, "*"].map(|name| &selection[*path][name]))
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(Value::as_str)
+        .any(|selected| {
+            selected == "*"
+                || selected == output
+                || (output.starts_with(selected) && output[selected.len()..].starts_with('.'))
+        })
+}
+
+/// Returns `true` if an output which requires compiling the Yul code is
+/// selected for any contract.
+fn selects_bytecode(selection: &Value) -> bool {
+    selected_outputs(selection).any(|selected| selected == "*" || selected.starts_with("evm"))
+}
+
+/// Returns `true` if the gas estimates are selected for any contract.
+fn selects_gas_estimates(selection: &Value) -> bool {
+    selected_outputs(selection).any(|selected| matches!(selected, "*" | "evm" | "evm.gasEstimates"))
+}
+
+/// Returns the outputs selected for all contracts of all sources.
+fn selected_outputs(selection: &Value) -> impl Iterator<Item = &str> {
+    selection
+        .as_object()
+        .into_iter()
+        .flat_map(Map::values)
+        .filter_map(Value::as_object)
+        .flat_map(Map::values)
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(Value::as_str)
+}
+
+/// Returns the path of the source file of a contract.
+fn contract_path(db: &Db, contract: &CompiledContract) -> String {
+    match &contract.origin.module(db).data(db).source {
+        ModuleSource::File(file) => file.path(db).to_string(),
+        ModuleSource::Dir(dir) => dir.to_string(),
+    }
+}
+
+fn optional<'a, T>(
+    object: &'a Value,
+    key: &str,
+    get: impl Fn(&'a Value) -> Option<T>,
+    expected: &str,
+) -> Result<Option<T>, String> {
+    match &object[key] {
+        Value::Null => Ok(None),
+        value => get(value)
+            .map(Some)
+            .ok_or_else(|| format!("`{key}` must be {expected}")),
+    }
+}
+
+fn as_u32(value: &Value) -> Option<u32> {
+    value.as_u64()?.try_into().ok()
+}
+
+/// Returns an error about the request, which isn't a diagnostic of the
+/// sources.
+fn json_error(message: &str) -> Value {
+    json!({
+        "severity": "error",
+        "message": message,
+        "formattedMessage": format!("error: {message}"),
+    })
+}
+
+fn diagnostic_json(db: &Db, diagnostic: &Diagnostic) -> Value {
+    let severity = match diagnostic.severity {
+        Severity::Bug => "bug",
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+        Severity::Help => "help",
+    };
+    let labels: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            let style = match label.style {
+                LabelStyle::Primary => "primary",
+                LabelStyle::Secondary => "secondary",
+            };
+            json!({
+                "style": style,
+                "file": label.span.file_id.path(db).as_str(),
+                "start": label.span.start,
+                "end": label.span.end,
+                "message": label.message,
+            })
+        })
+        .collect();
+
+    json!({
+        "severity": severity,
+        "message": diagnostic.message,
+        "formattedMessage": diagnostics_string(db, &[diagnostic.clone()]),
+        "labels": labels,
+        "notes": diagnostic.notes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile_standard_json, is_selected, selects_gas_estimates};
+    use serde_json::{json, Value};
+
+    fn compile(request: &Value) -> Value {
+        serde_json::from_str(&compile_standard_json(&request.to_string(), None)).unwrap()
+    }
+
+    fn request(sources: &[(&str, &str)], selection: &[&str]) -> Value {
+        let sources: serde_json::Map<_, _> = sources
+            .iter()
+            .map(|(path, content)| (path.to_string(), json!({ "content": content })))
+            .collect();
+        json!({
+            "language": "Fe",
+            "sources": sources,
+            "settings": { "outputSelection": { "*": { "*": selection } } },
+        })
+    }
+
+    #[test]
+    fn request_errors() {
+        let source = json!({ "a.fe": { "content": "" } });
+        for (request, message) in [
+            (
+                json!({ "language": "Solidity" }),
+                "`language` must be \"Fe\"",
+            ),
+            (
+                json!({ "language": "Fe", "sources": {} }),
+                "`sources` must be a non-empty object",
+            ),
+            (
+                json!({ "language": "Fe", "sources": { "a.fe": {} } }),
+                "source `a.fe` has no `content` string",
+            ),
+            (
+                json!({ "language": "Fe", "sources": source, "settings": { "evmVersion": 1 } }),
+                "`evmVersion` must be a string",
+            ),
+            (
+                json!({
+                    "language": "Fe",
+                    "sources": source,
+                    "settings": { "optimizer": { "runs": -1 } },
+                }),
+                "`runs` must be an integer",
+            ),
+            (
+                json!({
+                    "language": "Fe",
+                    "sources": source,
+                    "settings": { "metadata": { "bytecodeHash": "bzzr1" } },
+                }),
+                "unknown `bytecodeHash` `bzzr1`, expected `ipfs` or `none`",
+            ),
+        ] {
+            let output = compile(&request);
+            assert_eq!(output["errors"][0]["severity"], "error");
+            assert_eq!(output["errors"][0]["message"], message);
+            assert!(output.get("contracts").is_none());
+        }
+
+        let output: Value = serde_json::from_str(&compile_standard_json("{", None)).unwrap();
+        assert!(output["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid JSON request"));
+    }
+
+    #[test]
+    fn output_selection() {
+        let selection = json!({
+            "*": { "*": ["abi"] },
+            "a.fe": { "Foo": ["evm.bytecode"] },
+            "b.fe": { "*": ["*"] },
+        });
+        let selected = |path, name, output| is_selected(&selection, path, name, output);
+
+        assert!(selected("a.fe", "Foo", "abi"));
+        assert!(selected("c.fe", "Bar", "abi"));
+        assert!(!selected("a.fe", "Foo", "metadata"));
+
+        assert!(selected("a.fe", "Foo", "evm.bytecode.object"));
+        assert!(selected("a.fe", "Foo", "evm.bytecode.sourceMap"));
+        assert!(!selected("a.fe", "Foo", "evm.deployedBytecode.object"));
+        assert!(!selected("a.fe", "Bar", "evm.bytecode.object"));
+
+        assert!(selected("b.fe", "Bar", "metadata"));
+        assert!(selected("b.fe", "Bar", "evm.gasEstimates"));
+
+        let selection = json!({ "*": { "*": ["evm.byte"] } });
+        assert!(!is_selected(
+            &selection,
+            "a.fe",
+            "Foo",
+            "evm.bytecode.object"
+        ));
+    }
+
+    #[test]
+    fn gas_estimates_selection() {
+        let selects = |outputs: &[&str]| {
+            selects_gas_estimates(&json!({ "a.fe": { "Foo": outputs }, "*": { "*": ["abi"] } }))
+        };
+
+        assert!(selects(&["evm.gasEstimates"]));
+        assert!(selects(&["evm"]));
+        assert!(selects(&["*"]));
+        assert!(!selects(&["evm.bytecode"]));
+        assert!(!selects(&["abi", "metadata"]));
+    }
+
+    #[test]
+    fn single_file() {
+        let src = "contract Foo {\n    pub fn get() -> u256 {\n        return 1\n    }\n}\n";
+        let output = compile(&request(&[("a.fe", src)], &["abi", "ir"]));
+
+        assert_eq!(output["errors"], json!([]));
+        assert!(output["sources"]["a.fe"]["id"].is_u64());
+        let foo = &output["contracts"]["a.fe"]["Foo"];
+        assert_eq!(foo["abi"][0]["name"], "get");
+        let ir = foo["ir"].as_str().unwrap();
+        assert!(ir.contains("object \"Foo\""));
+        assert!(!ir.contains("\\\""));
+        assert!(foo.get("metadata").is_none());
+        assert!(foo.get("evm").is_none());
+    }
+
+    #[test]
+    fn multiple_sources() {
+        let output = compile(&request(
+            &[
+                ("src/main.fe", "use foo::Bar\n\ncontract Foo {}\n"),
+                ("src/foo.fe", "pub contract Bar {}\n"),
+            ],
+            &["abi"],
+        ));
+
+        assert_eq!(output["errors"], json!([]));
+        assert!(output["sources"]["src/main.fe"]["id"].is_u64());
+        assert!(output["sources"]["src/foo.fe"]["id"].is_u64());
+        assert_eq!(output["contracts"]["src/main.fe"]["Foo"]["abi"], json!([]));
+        assert_eq!(output["contracts"]["src/foo.fe"]["Bar"]["abi"], json!([]));
+    }
+
+    #[test]
+    fn diagnostics() {
+        let src = "contract Foo {\n    pub fn f() -> u8 {\n        return true\n    }\n}\n";
+        let output = compile(&request(&[("a.fe", src)], &["abi"]));
+
+        assert!(output.get("contracts").is_none());
+        let error = &output["errors"][0];
+        assert_eq!(error["severity"], "error");
+        assert_eq!(
+            error["message"],
+            "expected function to return `u8` but was `bool`"
+        );
+        assert!(error["formattedMessage"]
+            .as_str()
+            .unwrap()
+            .starts_with("error: expected function to return `u8` but was `bool`\n"));
+
+        let label = &error["labels"][0];
+        assert_eq!(label["style"], "primary");
+        assert_eq!(label["file"], "a.fe");
+        let (start, end) = (
+            label["start"].as_u64().unwrap() as usize,
+            label["end"].as_u64().unwrap() as usize,
+        );
+        assert_eq!(&src[start..end], "return true");
+    }
+}
@@ -1,14 +1,19 @@
 mod task;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use fe_common::panic::install_panic_hook;
 use task::Commands;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct FelangCli {
+    #[clap(
+        long,
+        help("Compile a standard JSON request read from stdin, and print the JSON response")
+    )]
+    standard_json: bool,
     #[clap(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 fn main() {
@@ -16,13 +21,31 @@ fn main() {
 
     let cli = FelangCli::parse();
 
-    match cli.command {
+    if cli.standard_json {
+        return task::standard_json();
+    }
+
+    let command = match cli.command {
+        Some(command) => command,
+        None => {
+            FelangCli::command().print_help().unwrap();
+            std::process::exit(2)
+        }
+    };
+
+    match command {
+        Commands::AbiStub(arg) => {
+            task::abi_stub(arg);
+        }
         Commands::Build(arg) => {
             task::build(arg);
         }
         Commands::Check(arg) => {
             task::check(arg);
         }
+        Commands::CheckLayout(arg) => {
+            task::check_layout(arg);
+        }
         Commands::New(arg) => {
             task::create_new_project(arg);
         }
@@ -0,0 +1,519 @@
+use std::fs;
+use std::path::Path;
+
+use clap::Args;
+use fe_abi::{
+    event::AbiEventField,
+    function::StateMutability,
+    types::{AbiTupleField, AbiType},
+};
+use fe_common::files::SourceFileId;
+use fe_parser::lexer::{Lexer, TokenKind};
+use serde::Deserialize;
+
+/// Integer sizes that have a Fe counterpart.
+const FE_INT_BITS: [usize; 6] = [8, 16, 32, 64, 128, 256];
+
+#[derive(Args)]
+#[clap(about = "Generate a Fe contract interface from a Solidity JSON ABI file")]
+pub struct AbiStubArgs {
+    #[clap(help("Path to a JSON ABI file, or a build artifact with an `abi` entry"))]
+    abi_path: String,
+    #[clap(long, help("Name of the generated contract [default: ABI file stem]"))]
+    name: Option<String>,
+    #[clap(
+        short,
+        long,
+        help("Write the generated module to this file instead of stdout")
+    )]
+    output: Option<String>,
+    #[clap(long)]
+    overwrite: bool,
+    #[clap(long, default_value_t = 100, help("Maximum length of `string` values"))]
+    string_len: usize,
+    #[clap(long, default_value_t = 100, help("Maximum length of `bytes` values"))]
+    bytes_len: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum AbiItem {
+    Function {
+        name: String,
+        inputs: Vec<AbiTupleField>,
+        #[serde(default)]
+        outputs: Vec<AbiTupleField>,
+        #[serde(rename = "stateMutability")]
+        state_mutability: Option<StateMutability>,
+    },
+    Event {
+        name: String,
+        inputs: Vec<AbiEventField>,
+        #[serde(default)]
+        anonymous: bool,
+    },
+    Error {
+        name: String,
+        inputs: Vec<AbiTupleField>,
+    },
+    // Constructors, `receive` and `fallback` are not callable from Fe.
+    #[serde(other)]
+    Other,
+}
+
+pub fn abi_stub(args: AbiStubArgs) {
+    if let Err(err) = do_work(&args) {
+        eprintln!(
+            "Failed to generate a Fe interface from `{}`.",
+            args.abi_path
+        );
+        eprintln!("{err}");
+        std::process::exit(1)
+    }
+}
+
+fn do_work(args: &AbiStubArgs) -> Result<(), String> {
+    let content = fs::read_to_string(&args.abi_path)
+        .map_err(|err| format!("Failed to load file: `{}`. Error: {err}", args.abi_path))?;
+    let mut abi: serde_json::Value =
+        serde_json::from_str(&content).map_err(|err| err.to_string())?;
+    // Build artifacts of other toolchains nest the ABI in an `abi` entry.
+    if let Some(inner) = abi.get_mut("abi") {
+        abi = inner.take();
+    }
+    let items: Vec<serde_json::Value> =
+        serde_json::from_value(abi).map_err(|err| err.to_string())?;
+
+    let contract_name = match &args.name {
+        Some(name) => name.clone(),
+        None => Path::new(&args.abi_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| stem.trim_end_matches("_abi").to_string())
+            .ok_or_else(|| "unable to derive a contract name, use `--name`".to_string())?,
+    };
+    if !is_fe_name(&contract_name) {
+        return Err(format!("`{contract_name}` is not a valid Fe contract name"));
+    }
+
+    let mut generator = StubGenerator::new(args.string_len, args.bytes_len);
+    let module = generator.generate(&contract_name, &items);
+    for warning in &generator.warnings {
+        eprintln!("Warning: {warning}");
+    }
+
+    match &args.output {
+        Some(output) => {
+            if Path::new(output).exists() && !args.overwrite {
+                return Err(format!(
+                    "File `{output}` already exists. Use --overwrite to overwrite."
+                ));
+            }
+            fs::write(output, module).map_err(|err| err.to_string())?;
+            eprintln!("Generated `{contract_name}` in `{output}`");
+        }
+        None => print!("{module}"),
+    }
+    Ok(())
+}
+
+struct StubGenerator {
+    string_len: usize,
+    bytes_len: usize,
+    /// Struct definitions for events, errors and tuples, in definition order.
+    structs: Vec<StructStub>,
+    /// The ABI items which are skipped, and why.
+    warnings: Vec<String>,
+}
+
+struct StructStub {
+    name: String,
+    fields: StructFields,
+}
+
+/// `(attribute, name, type)` of each field of a struct.
+type StructFields = Vec<(Option<&'static str>, String, String)>;
+
+impl StubGenerator {
+    fn new(string_len: usize, bytes_len: usize) -> Self {
+        Self {
+            string_len,
+            bytes_len,
+            structs: vec![],
+            warnings: vec![],
+        }
+    }
+
+    /// Generates a module with a contract named `contract_name` from the items
+    /// of a JSON ABI. Items which have no Fe counterpart are skipped with a
+    /// warning.
+    fn generate(&mut self, contract_name: &str, items: &[serde_json::Value]) -> String {
+        let mut parsed = vec![];
+        for item in items {
+            match AbiItem::deserialize(item) {
+                Ok(parsed_item) => parsed.push(parsed_item),
+                Err(err) => {
+                    let name = item["name"].as_str().unwrap_or_default();
+                    self.warnings
+                        .push(format!("skipping ABI item `{name}`: {err}"));
+                }
+            }
+        }
+
+        // Events and errors are defined first, as their struct names are part of the
+        // ABI and must not be taken by structs generated for tuples.
+        let (structs, others): (Vec<_>, Vec<_>) = parsed
+            .iter()
+            .partition(|item| matches!(item, AbiItem::Event { .. } | AbiItem::Error { .. }));
+        let mut funcs: Vec<(&str, String)> = vec![];
+        for item in structs.into_iter().chain(others) {
+            // Structs of tuples defined for an item which is skipped are removed.
+            let struct_num = self.structs.len();
+            if let Err(err) = self.item(item, &mut funcs) {
+                self.structs.truncate(struct_num);
+                self.warnings.push(err);
+            }
+        }
+
+        let mut module = String::new();
+        for stub in &self.structs {
+            module.push_str(&format!("pub struct {} {{\n", stub.name));
+            for (attr, name, ty) in &stub.fields {
+                if let Some(attr) = attr {
+                    module.push_str(&format!("    #{attr}\n"));
+                }
+                module.push_str(&format!("    pub {name}: {ty}\n"));
+            }
+            module.push_str("}\n\n");
+        }
+
+        module.push_str(&format!("contract {contract_name} {{"));
+        for (_, func) in &funcs {
+            module.push_str(&format!("\n{func}"));
+        }
+        module.push_str("}\n");
+        module
+    }
+
+    /// Adds a function to `funcs`, or defines the struct of an event or error.
+    /// Returns why the item is skipped otherwise.
+    fn item<'a>(
+        &mut self,
+        item: &'a AbiItem,
+        funcs: &mut Vec<(&'a str, String)>,
+    ) -> Result<(), String> {
+        match item {
+            AbiItem::Function {
+                name,
+                inputs,
+                outputs,
+                state_mutability,
+            } => {
+                if funcs.iter().any(|(func_name, _)| func_name == name) {
+                    return Err(format!(
+                        "skipping overloaded function `{name}`, Fe doesn't support overloading"
+                    ));
+                }
+                let func = self
+                    .function(name, inputs, outputs, state_mutability.as_ref())
+                    .map_err(|err| format!("skipping function `{name}`: {err}"))?;
+                funcs.push((name, func));
+            }
+
+            AbiItem::Event {
+                name,
+                inputs,
+                anonymous,
+            } => {
+                if *anonymous {
+                    return Err(format!(
+                        "skipping anonymous event `{name}`, Fe doesn't support anonymous events"
+                    ));
+                }
+                inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, input)| {
+                        let attr = input.indexed.then_some("indexed");
+                        let name = field_name(&input.name, idx);
+                        Ok((attr, name.clone(), self.fe_type(&input.ty, &name)?))
+                    })
+                    .collect::<Result<_, String>>()
+                    .and_then(|fields| self.define_abi_struct(name, fields))
+                    .map_err(|err| format!("skipping event `{name}`: {err}"))?;
+            }
+
+            AbiItem::Error { name, inputs } => self
+                .tuple_fields(inputs)
+                .and_then(|fields| self.define_abi_struct(name, fields))
+                .map_err(|err| format!("skipping error `{name}`: {err}"))?,
+
+            AbiItem::Other => {}
+        }
+        Ok(())
+    }
+
+    fn function(
+        &mut self,
+        name: &str,
+        inputs: &[AbiTupleField],
+        outputs: &[AbiTupleField],
+        state_mutability: Option<&StateMutability>,
+    ) -> Result<String, String> {
+        if !is_fe_name(name) {
+            return Err(format!("`{name}` is not a valid Fe function name"));
+        }
+
+        // Functions are always given a `self` parameter, otherwise they couldn't
+        // be called on a contract instance.
+        let state_mutability = state_mutability.unwrap_or(&StateMutability::Nonpayable);
+        let mut params = match state_mutability {
+            StateMutability::Pure | StateMutability::View => vec!["self".to_string()],
+            StateMutability::Nonpayable | StateMutability::Payable => vec!["mut self".to_string()],
+        };
+        for (_, param_name, param_ty) in self.tuple_fields(inputs)? {
+            params.push(format!("{param_name}: {param_ty}"));
+        }
+
+        let mut ret_types = vec![];
+        for output in outputs {
+            let hint = if output.name.is_empty() {
+                format!("{name}_result")
+            } else {
+                output.name.clone()
+            };
+            ret_types.push(self.fe_type(&output.ty, &hint)?);
+        }
+        let ret_ty = match ret_types.as_slice() {
+            [] => "".to_string(),
+            [ret_ty] => format!(" -> {ret_ty}"),
+            _ => format!(" -> ({})", ret_types.join(", ")),
+        };
+
+        let attribute = if state_mutability == &StateMutability::Payable {
+            "    #payable\n"
+        } else {
+            ""
+        };
+        Ok(format!(
+            "{attribute}    pub fn {name}({}){ret_ty} {{\n        revert\n    }}\n",
+            params.join(", ")
+        ))
+    }
+
+    fn tuple_fields(&mut self, fields: &[AbiTupleField]) -> Result<StructFields, String> {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let name = field_name(&field.name, idx);
+                Ok((None, name.clone(), self.fe_type(&field.ty, &name)?))
+            })
+            .collect()
+    }
+
+    /// Maps an ABI type to the Fe type that `codegen_abi_type` lowers to it.
+    /// `hint` is used to name the struct generated for a tuple type.
+    fn fe_type(&mut self, ty: &AbiType, hint: &str) -> Result<String, String> {
+        match ty {
+            AbiType::UInt(bits) if FE_INT_BITS.contains(bits) => Ok(format!("u{bits}")),
+            AbiType::Int(bits) if FE_INT_BITS.contains(bits) => Ok(format!("i{bits}")),
+            AbiType::UInt(_) | AbiType::Int(_) => Err(format!(
+                "`{}` has no Fe counterpart",
+                ty.selector_type_name()
+            )),
+            AbiType::Address => Ok("address".to_string()),
+            AbiType::Bool => Ok("bool".to_string()),
+            AbiType::String => Ok(format!("String<{}>", self.string_len)),
+            AbiType::Bytes => Ok(format!("Array<u8, {}>", self.bytes_len)),
+            AbiType::Array { elem_ty, len } => {
+                // `Array<u8, N>` is always encoded as `bytes` in Fe.
+                if elem_ty.as_ref() == &AbiType::UInt(8) {
+                    return Err(format!("`uint8[{len}]` has no Fe counterpart"));
+                }
+                Ok(format!("Array<{}, {len}>", self.fe_type(elem_ty, hint)?))
+            }
+            AbiType::Tuple(fields) => {
+                let fields = self.tuple_fields(fields)?;
+                self.define_struct(&struct_name(hint), fields)
+            }
+            AbiType::Function => Err("`function` has no Fe counterpart".to_string()),
+        }
+    }
+
+    /// Defines the struct of an event or error, whose name must be kept as is.
+    fn define_abi_struct(&mut self, name: &str, fields: StructFields) -> Result<(), String> {
+        if self.structs.iter().any(|stub| stub.name == name) {
+            return Err(format!("struct `{name}` is defined more than once"));
+        }
+        self.define_struct(name, fields).map(|_| ())
+    }
+
+    /// Defines a struct and returns its name. Structurally equal tuples share a
+    /// single definition.
+    fn define_struct(&mut self, name: &str, fields: StructFields) -> Result<String, String> {
+        if !is_fe_name(name) {
+            return Err(format!("`{name}` is not a valid Fe struct name"));
+        }
+
+        let mut unique_name = name.to_string();
+        let mut suffix = 1;
+        while let Some(stub) = self.structs.iter().find(|stub| stub.name == unique_name) {
+            if stub.fields == fields {
+                return Ok(unique_name);
+            }
+            suffix += 1;
+            unique_name = format!("{name}{suffix}");
+        }
+
+        self.structs.push(StructStub {
+            name: unique_name.clone(),
+            fields,
+        });
+        Ok(unique_name)
+    }
+}
+
+/// Returns `true` if `name` lexes to a single non-keyword name token.
+fn is_fe_name(name: &str) -> bool {
+    let mut lexer = Lexer::new(SourceFileId::dummy_file(), name);
+    matches!(
+        (lexer.next(), lexer.next()),
+        (Some(tok), None) if tok.kind == TokenKind::Name
+    )
+}
+
+fn field_name(name: &str, idx: usize) -> String {
+    if name.is_empty() {
+        format!("arg{idx}")
+    } else if !is_fe_name(name) || name == "ctx" {
+        // Keywords (e.g. `type`, `idx`) and the context parameter name are suffixed.
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+fn struct_name(hint: &str) -> String {
+    hint.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StubGenerator;
+
+    fn generate(abi: &str) -> (String, Vec<String>) {
+        let items: Vec<serde_json::Value> = serde_json::from_str(abi).unwrap();
+        let mut generator = StubGenerator::new(100, 100);
+        let module = generator.generate("Foo", &items);
+        (module, generator.warnings)
+    }
+
+    #[test]
+    fn functions_events_and_errors() {
+        let (module, warnings) = generate(
+            r#"[
+                {"type": "function", "name": "transfer", "stateMutability": "nonpayable",
+                 "inputs": [{"name": "to", "type": "address"}, {"name": "", "type": "uint256"}],
+                 "outputs": [{"name": "", "type": "bool"}]},
+                {"type": "function", "name": "deposit", "stateMutability": "payable",
+                 "inputs": [], "outputs": []},
+                {"type": "function", "name": "position", "stateMutability": "view",
+                 "inputs": [],
+                 "outputs": [{"name": "", "type": "tuple", "components": [
+                    {"name": "x", "type": "int64"}, {"name": "y", "type": "int64"}]}]},
+                {"type": "event", "name": "Transfer", "anonymous": false, "inputs": [
+                    {"name": "from", "type": "address", "indexed": true},
+                    {"name": "value", "type": "uint256", "indexed": false}]},
+                {"type": "error", "name": "Unauthorized", "inputs": [
+                    {"name": "type", "type": "string"}]},
+                {"type": "constructor", "inputs": []}
+            ]"#,
+        );
+        assert!(warnings.is_empty());
+        assert_eq!(
+            module,
+            "\
+pub struct Transfer {
+    #indexed
+    pub from: address
+    pub value: u256
+}
+
+pub struct Unauthorized {
+    pub type_: String<100>
+}
+
+pub struct PositionResult {
+    pub x: i64
+    pub y: i64
+}
+
+contract Foo {
+    pub fn transfer(mut self, to: address, arg1: u256) -> bool {
+        revert
+    }
+
+    #payable
+    pub fn deposit(mut self) {
+        revert
+    }
+
+    pub fn position(self) -> PositionResult {
+        revert
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn skip_unsupported_items() {
+        let (module, warnings) = generate(
+            r#"[
+                {"type": "function", "name": "root", "stateMutability": "view",
+                 "inputs": [], "outputs": [{"name": "", "type": "bytes32"}]},
+                {"type": "function", "name": "holders", "stateMutability": "view",
+                 "inputs": [], "outputs": [{"name": "", "type": "address[]"}]},
+                {"type": "function", "name": "pair", "stateMutability": "view",
+                 "inputs": [{"name": "p", "type": "tuple", "components": [
+                    {"name": "f", "type": "function"}]}], "outputs": []},
+                {"type": "function", "name": "owner", "stateMutability": "view",
+                 "inputs": [], "outputs": [{"name": "", "type": "address"}]},
+                {"type": "function", "name": "owner", "stateMutability": "view",
+                 "inputs": [{"name": "idx", "type": "uint256"}],
+                 "outputs": [{"name": "", "type": "address"}]},
+                {"type": "event", "name": "Ping", "anonymous": true, "inputs": []}
+            ]"#,
+        );
+        assert_eq!(
+            warnings,
+            [
+                "skipping ABI item `root`: unsupported ABI type `bytes32`",
+                "skipping ABI item `holders`: dynamically sized array `address[]` is not supported",
+                "skipping anonymous event `Ping`, Fe doesn't support anonymous events",
+                "skipping function `pair`: `function` has no Fe counterpart",
+                "skipping overloaded function `owner`, Fe doesn't support overloading",
+            ]
+        );
+        assert_eq!(
+            module,
+            "\
+contract Foo {
+    pub fn owner(self) -> address {
+        revert
+    }
+}
+"
+        );
+    }
+}
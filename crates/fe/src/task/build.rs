@@ -6,17 +6,31 @@ use clap::{ArgEnum, Args};
 use fe_common::diagnostics::print_diagnostics;
 use fe_common::files::SourceFileId;
 use fe_common::utils::files::{get_project_root, BuildFiles, ProjectMode};
-use fe_driver::CompiledModule;
+use fe_driver::{
+    AnalyzerDb, CodegenDb, CompiledModule, DispatcherStrategy, EvmVersion, OptimizerSettings,
+    SolcBinary,
+};
 
 const DEFAULT_OUTPUT_DIR_NAME: &str = "output";
 
+/// The environment variable with the path of the solc executable.
+pub(crate) const SOLC_ENV_VAR: &str = "FE_SOLC";
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum, Debug)]
 enum Emit {
     Abi,
+    Artifact,
+    Asm,
     Ast,
     LoweredAst,
     Bytecode,
+    GasEstimates,
+    Metadata,
     RuntimeBytecode,
+    RustBindings,
+    SolidityInterface,
+    SourceMap,
+    StorageLayout,
     Tokens,
     Yul,
 }
@@ -42,16 +56,62 @@ pub struct BuildArgs {
     overwrite: bool,
     #[clap(long, takes_value(true))]
     optimize: Option<bool>,
+    #[clap(
+        long,
+        help("How often the deployed code is expected to run, which trades code size for cheaper calls [default: `runs` of `[optimizer]` in fe.toml, or 200]")
+    )]
+    optimizer_runs: Option<u32>,
+    #[clap(
+        long,
+        help("The sequence of Yul optimizer steps run by solc [default: `yul-steps` of `[optimizer]` in fe.toml, or the sequence of solc]")
+    )]
+    yul_optimizer_steps: Option<String>,
+    #[clap(
+        long,
+        help("How contracts dispatch calls: auto, linear, binary-search or jump-table [default: `dispatcher` in fe.toml, or auto]")
+    )]
+    dispatcher: Option<DispatcherStrategy>,
+    #[clap(
+        long,
+        help("The EVM version to target [default: `evm-version` in fe.toml, or paris]")
+    )]
+    evm_version: Option<EvmVersion>,
+    #[clap(
+        long,
+        help("A solc executable to compile Yul with, instead of the built-in backend [default: $FE_SOLC, or `solc` in fe.toml]")
+    )]
+    solc: Option<String>,
+    #[clap(
+        long,
+        help("Don't append the IPFS hash of the contract metadata to the runtime bytecode")
+    )]
+    no_metadata_hash: bool,
 }
 
 fn build_single_file(compile_arg: &BuildArgs) -> (String, CompiledModule) {
     let emit = &compile_arg.emit;
-    let with_bytecode = emit.contains(&Emit::Bytecode);
-    let with_runtime_bytecode = emit.contains(&Emit::RuntimeBytecode);
+    let with_artifact = emit.contains(&Emit::Artifact);
+    let with_bytecode =
+        with_artifact || emit.contains(&Emit::Bytecode) || emit.contains(&Emit::SourceMap);
+    let with_runtime_bytecode =
+        with_artifact || emit.contains(&Emit::RuntimeBytecode) || emit.contains(&Emit::Asm);
+    let with_gas_estimates = with_artifact || emit.contains(&Emit::GasEstimates);
     let input_path = &compile_arg.input_path;
-    let optimize = compile_arg.optimize.unwrap_or(true);
+    let optimizer = optimizer_settings(compile_arg, OptimizerSettings::default());
+
+    let evm_version = compile_arg.evm_version.unwrap_or_default();
+    let solc = match solc_binary(compile_arg.solc.as_deref(), None, evm_version) {
+        Ok(solc) => solc,
+        Err(err) => {
+            eprintln!("Unable to compile {input_path}. Error: {err}");
+            std::process::exit(1)
+        }
+    };
 
     let mut db = fe_driver::Db::default();
+    db.set_codegen_dispatcher_strategy(compile_arg.dispatcher.unwrap_or_default());
+    db.set_evm_version(evm_version);
+    db.set_codegen_metadata_hash(!compile_arg.no_metadata_hash);
     let content = match std::fs::read_to_string(input_path) {
         Err(err) => {
             eprintln!("Failed to load file: `{input_path}`. Error: {err}");
@@ -66,7 +126,9 @@ fn build_single_file(compile_arg: &BuildArgs) -> (String, CompiledModule) {
         &content,
         with_bytecode,
         with_runtime_bytecode,
-        optimize,
+        with_gas_estimates,
+        &optimizer,
+        solc.as_ref(),
     ) {
         Ok(module) => module,
         Err(error) => {
@@ -75,15 +137,21 @@ fn build_single_file(compile_arg: &BuildArgs) -> (String, CompiledModule) {
             std::process::exit(1)
         }
     };
+    if !compiled_module.warnings.is_empty() {
+        print_diagnostics(&db, &compiled_module.warnings);
+    }
     (content, compiled_module)
 }
 
 fn build_ingot(compile_arg: &BuildArgs) -> (String, CompiledModule) {
     let emit = &compile_arg.emit;
-    let with_bytecode = emit.contains(&Emit::Bytecode);
-    let with_runtime_bytecode = emit.contains(&Emit::RuntimeBytecode);
+    let with_artifact = emit.contains(&Emit::Artifact);
+    let with_bytecode =
+        with_artifact || emit.contains(&Emit::Bytecode) || emit.contains(&Emit::SourceMap);
+    let with_runtime_bytecode =
+        with_artifact || emit.contains(&Emit::RuntimeBytecode) || emit.contains(&Emit::Asm);
+    let with_gas_estimates = with_artifact || emit.contains(&Emit::GasEstimates);
     let input_path = &compile_arg.input_path;
-    let optimize = compile_arg.optimize.unwrap_or(true);
 
     if !Path::new(input_path).exists() {
         eprintln!("Input directory does not exist: `{input_path}`.");
@@ -104,13 +172,53 @@ fn build_ingot(compile_arg: &BuildArgs) -> (String, CompiledModule) {
         std::process::exit(1)
     }
 
+    let dispatcher = match compile_arg.dispatcher {
+        Some(strategy) => strategy,
+        None => match fe_driver::manifest_dispatcher_strategy(&build_files) {
+            Ok(strategy) => strategy,
+            Err(err) => {
+                eprintln!("Unable to compile {input_path}. Error: {err}");
+                std::process::exit(1)
+            }
+        },
+    };
+
+    let evm_version = match compile_arg.evm_version {
+        Some(version) => version,
+        None => match fe_driver::manifest_evm_version(&build_files) {
+            Ok(version) => version,
+            Err(err) => {
+                eprintln!("Unable to compile {input_path}. Error: {err}");
+                std::process::exit(1)
+            }
+        },
+    };
+
+    let optimizer = optimizer_settings(
+        compile_arg,
+        fe_driver::manifest_optimizer_settings(&build_files),
+    );
+
+    let solc = match solc_binary(compile_arg.solc.as_deref(), Some(&build_files), evm_version) {
+        Ok(solc) => solc,
+        Err(err) => {
+            eprintln!("Unable to compile {input_path}. Error: {err}");
+            std::process::exit(1)
+        }
+    };
+
     let mut db = fe_driver::Db::default();
+    db.set_codegen_dispatcher_strategy(dispatcher);
+    db.set_evm_version(evm_version);
+    db.set_codegen_metadata_hash(!compile_arg.no_metadata_hash);
     let compiled_module = match fe_driver::compile_ingot(
         &mut db,
         &build_files,
         with_bytecode,
         with_runtime_bytecode,
-        optimize,
+        with_gas_estimates,
+        &optimizer,
+        solc.as_ref(),
     ) {
         Ok(module) => module,
         Err(error) => {
@@ -119,11 +227,47 @@ fn build_ingot(compile_arg: &BuildArgs) -> (String, CompiledModule) {
             std::process::exit(1)
         }
     };
+    if !compiled_module.warnings.is_empty() {
+        print_diagnostics(&db, &compiled_module.warnings);
+    }
 
     // no file content for ingots
     ("".to_string(), compiled_module)
 }
 
+/// Overrides the given optimizer settings with the ones set by flags.
+fn optimizer_settings(compile_arg: &BuildArgs, settings: OptimizerSettings) -> OptimizerSettings {
+    OptimizerSettings {
+        enabled: compile_arg.optimize.unwrap_or(settings.enabled),
+        runs: compile_arg.optimizer_runs.unwrap_or(settings.runs),
+        yul_steps: compile_arg
+            .yul_optimizer_steps
+            .clone()
+            .or(settings.yul_steps),
+    }
+}
+
+/// Returns the solc executable set by the flag, the environment variable or the
+/// manifest, in this order, or `None` to use the built-in backend.
+pub(crate) fn solc_binary(
+    flag: Option<&str>,
+    build_files: Option<&BuildFiles>,
+    evm_version: EvmVersion,
+) -> Result<Option<SolcBinary>, String> {
+    let path = flag
+        .map(ToString::to_string)
+        .or_else(|| std::env::var(SOLC_ENV_VAR).ok())
+        .or_else(|| build_files.and_then(fe_driver::manifest_solc_path));
+    match path {
+        Some(path) => {
+            let solc = SolcBinary::new(path).map_err(|err| err.0)?;
+            solc.check_evm_version(evm_version).map_err(|err| err.0)?;
+            Ok(Some(solc))
+        }
+        None => Ok(None),
+    }
+}
+
 pub fn build(compile_arg: BuildArgs) {
     let emit = &compile_arg.emit;
 
@@ -133,12 +277,6 @@ pub fn build(compile_arg: BuildArgs) {
         return mir_dump(input_path);
     }
 
-    let _with_bytecode = emit.contains(&Emit::Bytecode);
-    #[cfg(not(feature = "solc-backend"))]
-    if _with_bytecode {
-        eprintln!("Warning: bytecode output requires 'solc-backend' feature. Try `cargo build --release --features solc-backend`. Skipping.");
-    }
-
     let (content, compiled_module) = if Path::new(input_path).is_file() {
         build_single_file(&compile_arg)
     } else {
@@ -202,17 +340,44 @@ fn write_compiled_module(
             write_output(&contract_output_dir.join(file_name), &contract.json_abi)?;
         }
 
+        if targets.contains(&Emit::StorageLayout) {
+            let file_name = format!("{}_storage_layout.json", &name);
+            write_output(
+                &contract_output_dir.join(file_name),
+                &contract.storage_layout,
+            )?;
+        }
+
+        if targets.contains(&Emit::RustBindings) {
+            let file_name = format!("{}.rs", to_snake_case(&name));
+            write_output(
+                &contract_output_dir.join(file_name),
+                &contract.rust_bindings,
+            )?;
+        }
+
+        if targets.contains(&Emit::SolidityInterface) {
+            let file_name = format!("I{}.sol", &name);
+            write_output(
+                &contract_output_dir.join(file_name),
+                &contract.solidity_interface,
+            )?;
+        }
+
+        if targets.contains(&Emit::Metadata) {
+            let file_name = format!("{}_metadata.json", &name);
+            write_output(&contract_output_dir.join(file_name), &contract.metadata)?;
+        }
+
         if targets.contains(&Emit::Yul) {
             let file_name = format!("{}_ir.yul", &name);
             write_output(&contract_output_dir.join(file_name), &contract.yul)?;
         }
 
-        #[cfg(feature = "solc-backend")]
         if targets.contains(&Emit::Bytecode) {
             let file_name = format!("{}.bin", &name);
             write_output(&contract_output_dir.join(file_name), &contract.bytecode)?;
         }
-        #[cfg(feature = "solc-backend")]
         if targets.contains(&Emit::RuntimeBytecode) {
             let file_name = format!("{}.runtime.bin", &name);
             write_output(
@@ -220,11 +385,46 @@ fn write_compiled_module(
                 &contract.runtime_bytecode,
             )?;
         }
+        if targets.contains(&Emit::SourceMap) {
+            let file_name = format!("{}_source_map.json", &name);
+            write_output(&contract_output_dir.join(file_name), &contract.source_map)?;
+        }
+        if targets.contains(&Emit::Asm) {
+            let file_name = format!("{}.asm", &name);
+            write_output(&contract_output_dir.join(file_name), &contract.asm)?;
+            let file_name = format!("{}_size_report.txt", &name);
+            write_output(&contract_output_dir.join(file_name), &contract.size_report)?;
+        }
+        if targets.contains(&Emit::GasEstimates) {
+            let file_name = format!("{}_gas_estimates.json", &name);
+            write_output(
+                &contract_output_dir.join(file_name),
+                &contract.gas_estimates,
+            )?;
+        }
+        if targets.contains(&Emit::Artifact) {
+            let file_name = format!("{}.json", &name);
+            write_output(
+                &contract_output_dir.join(file_name),
+                &fe_driver::contract_artifact(&contract),
+            )?;
+        }
     }
 
     Ok(())
 }
 
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (idx, c) in name.char_indices() {
+        if c.is_uppercase() && idx != 0 && !name[..idx].ends_with('_') {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
 fn write_output(path: &Path, content: &str) -> Result<(), String> {
     let mut file = fs::OpenOptions::new()
         .write(true)
@@ -6,7 +6,7 @@ use fe_common::{
     utils::files::get_project_root,
     utils::files::BuildFiles,
 };
-use fe_driver::Db;
+use fe_driver::{AnalyzerDb, Db};
 
 #[derive(Args)]
 #[clap(about = "Analyze the current project and report errors, but don't build artifacts")]
@@ -35,6 +35,13 @@ fn check_ingot(db: &mut Db, input_path: &str) -> Vec<Diagnostic> {
             std::process::exit(1)
         }
     };
+    match fe_driver::manifest_evm_version(&build_files) {
+        Ok(version) => db.set_evm_version(version),
+        Err(err) => {
+            eprintln!("Unable to check {input_path}. Error: {err}");
+            std::process::exit(1)
+        }
+    }
 
     fe_driver::check_ingot(db, &build_files)
 }
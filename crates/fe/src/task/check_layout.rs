@@ -0,0 +1,371 @@
+use std::fs;
+use std::path::Path;
+
+use clap::Args;
+use colored::Colorize;
+use fe_abi::storage_layout::StorageLayout;
+use fe_common::diagnostics::print_diagnostics;
+use fe_common::utils::files::BuildFiles;
+use fe_driver::{AnalyzerDb, CodegenDb, CompiledModule, EvmVersion, OptimizerSettings};
+use walkdir::WalkDir;
+
+const STORAGE_LAYOUT_SUFFIX: &str = "_storage_layout.json";
+
+#[derive(Args)]
+#[clap(about = "Check that a new version of contracts keeps the storage layout of the old one")]
+pub struct CheckLayoutArgs {
+    #[clap(help(
+        "The old version: a Fe file or project, a build output directory, or a storage layout JSON file"
+    ))]
+    old: String,
+    #[clap(help("The new version, in any of the forms of the old one"))]
+    new: String,
+    #[clap(long, help("Only check the contract with this name"))]
+    contract: Option<String>,
+    #[clap(
+        long,
+        help("The EVM version to compile Fe files and projects for [default: `evm-version` in fe.toml, or paris]")
+    )]
+    evm_version: Option<EvmVersion>,
+}
+
+pub fn check_layout(args: CheckLayoutArgs) {
+    match do_work(&args) {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{}", "Failed to check the storage layouts.\n".bold());
+            eprintln!("{err}");
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Returns `true` if all the checked layouts are compatible.
+fn do_work(args: &CheckLayoutArgs) -> Result<bool, String> {
+    let old_layouts = load_layouts(&args.old, args.evm_version)?;
+    let new_layouts = load_layouts(&args.new, args.evm_version)?;
+
+    let mut compatible = true;
+    let mut checked = 0;
+    for old in old_layouts.iter().filter(|layout| match &args.contract {
+        Some(name) => &layout.contract == name,
+        None => true,
+    }) {
+        // Two single layouts are compared even if the contract was renamed.
+        let new = if old_layouts.len() == 1 && new_layouts.len() == 1 {
+            new_layouts.first()
+        } else {
+            new_layouts
+                .iter()
+                .find(|layout| layout.contract == old.contract)
+        };
+        let new = match new {
+            Some(new) => new,
+            None => {
+                println!(
+                    "{} `{}` is missing in `{}`",
+                    "error:".red().bold(),
+                    old.contract,
+                    args.new
+                );
+                compatible = false;
+                continue;
+            }
+        };
+
+        checked += 1;
+        let incompatibilities = old.incompatibilities(new);
+        if incompatibilities.is_empty() {
+            println!("{} `{}`", "compatible:".green().bold(), old.contract);
+        } else {
+            compatible = false;
+            println!(
+                "{} `{}` has an incompatible storage layout",
+                "error:".red().bold(),
+                old.contract
+            );
+            for incompatibility in incompatibilities {
+                println!("  - {incompatibility}");
+            }
+        }
+    }
+
+    if checked == 0 && compatible {
+        return Err(match &args.contract {
+            Some(name) => format!("No contract named `{name}` in `{}`.", args.old),
+            None => format!("No contract in `{}`.", args.old),
+        });
+    }
+
+    Ok(compatible)
+}
+
+/// Loads the storage layouts from a storage layout JSON file, a directory
+/// written by `fe build --emit storage-layout`, or by compiling a Fe file or
+/// project.
+fn load_layouts(path: &str, evm_version: Option<EvmVersion>) -> Result<Vec<StorageLayout>, String> {
+    let input_path = Path::new(path);
+    if !input_path.exists() {
+        return Err(format!("Input path does not exist: `{path}`."));
+    }
+
+    if input_path.is_file() && path.ends_with(".json") {
+        return Ok(vec![load_layout_file(input_path)?]);
+    }
+
+    if input_path.is_dir() && !input_path.join("fe.toml").is_file() {
+        let mut layouts = vec![];
+        for entry in WalkDir::new(input_path).sort_by_file_name() {
+            let entry = entry.map_err(|err| err.to_string())?;
+            if entry.file_type().is_file()
+                && entry
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with(STORAGE_LAYOUT_SUFFIX)
+            {
+                layouts.push(load_layout_file(entry.path())?);
+            }
+        }
+        return Ok(layouts);
+    }
+
+    compile(path, evm_version)?
+        .contracts
+        .values()
+        .map(|contract| {
+            serde_json::from_str(&contract.storage_layout).map_err(|err| err.to_string())
+        })
+        .collect()
+}
+
+fn load_layout_file(path: &Path) -> Result<StorageLayout, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to load file: `{}`. Error: {err}", path.display()))?;
+    serde_json::from_str(&content)
+        .map_err(|err| format!("Invalid storage layout `{}`. Error: {err}", path.display()))
+}
+
+fn compile(path: &str, evm_version: Option<EvmVersion>) -> Result<CompiledModule, String> {
+    let mut db = fe_driver::Db::default();
+    let compiled_module = if Path::new(path).is_file() {
+        db.set_evm_version(evm_version.unwrap_or_default());
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to load file: `{path}`. Error: {err}"))?;
+        fe_driver::compile_single_file(
+            &mut db,
+            path,
+            &content,
+            false,
+            false,
+            false,
+            &OptimizerSettings::default(),
+            None,
+        )
+    } else {
+        let build_files = BuildFiles::load_fs(path)
+            .map_err(|err| format!("Failed to load project files.\nError: {err}"))?;
+        // The layout is the same for any dispatcher, but an invalid setting
+        // is reported like `fe build` does.
+        db.set_codegen_dispatcher_strategy(fe_driver::manifest_dispatcher_strategy(&build_files)?);
+        db.set_evm_version(match evm_version {
+            Some(version) => version,
+            None => fe_driver::manifest_evm_version(&build_files)?,
+        });
+        fe_driver::compile_ingot(
+            &mut db,
+            &build_files,
+            false,
+            false,
+            false,
+            &OptimizerSettings::default(),
+            None,
+        )
+    };
+
+    compiled_module.map_err(|error| {
+        print_diagnostics(&db, &error.0);
+        format!("Unable to compile {path}.")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fe_abi::storage_layout::{StorageEncoding, StorageField};
+    use std::path::PathBuf;
+
+    const VAULT: &str = "contract Vault {
+    owner: address
+    balance: u256
+    locked: bool
+}
+";
+
+    /// Creates a temporary directory for the test `test_name`, which the test
+    /// removes when it's done.
+    fn temp_dir(test_name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "fe-check-layout-{}-{test_name}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes `content` to the file `name` of `dir` and returns its path.
+    fn write_file(dir: &Path, name: &str, content: &str) -> String {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    /// Compares the storage layout of the `Vault` contract in `old` and
+    /// `new`. Returns whether they are compatible and the incompatibilities.
+    fn check(test_name: &str, old: &str, new: &str) -> (bool, Vec<String>) {
+        let dir = temp_dir(test_name);
+        let args = CheckLayoutArgs {
+            old: write_file(&dir, "old.fe", old),
+            new: write_file(&dir, "new.fe", new),
+            contract: None,
+            evm_version: None,
+        };
+        let compatible = do_work(&args).unwrap();
+
+        let old = load_layouts(&args.old, None).unwrap();
+        let new = load_layouts(&args.new, None).unwrap();
+        let incompatibilities = old[0]
+            .incompatibilities(&new[0])
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        fs::remove_dir_all(dir).unwrap();
+        (compatible, incompatibilities)
+    }
+
+    #[test]
+    fn compatible_layouts() {
+        let new = VAULT.replace("    locked: bool\n", "    locked: bool\n    fee: u8\n");
+        assert_eq!(check("compatible", VAULT, &new), (true, vec![]));
+    }
+
+    #[test]
+    fn reordered_field() {
+        let new = "contract Vault {
+    balance: u256
+    owner: address
+    locked: bool
+}
+";
+        assert_eq!(
+            check("reordered", VAULT, new),
+            (
+                false,
+                vec![
+                    "`owner` moved from slot 0 (offset 0) to slot 1 (offset 0)".to_string(),
+                    "`balance` moved from slot 1 (offset 0) to slot 0 (offset 0)".to_string(),
+                    "`locked` moved from slot 2 (offset 0) to slot 1 (offset 20)".to_string(),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn changed_type() {
+        let new = VAULT.replace("locked: bool", "locked: u8");
+        assert_eq!(
+            check("changed_type", VAULT, &new),
+            (
+                false,
+                vec!["the type of `locked` changed from `bool` to `u8`".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn removed_field() {
+        let new = VAULT.replace("    locked: bool\n", "");
+        assert_eq!(
+            check("removed", VAULT, &new),
+            (false, vec!["`locked` was removed".to_string()])
+        );
+    }
+
+    #[test]
+    fn layout_files_and_directories() {
+        let field = |name: &str, slot: &str| {
+            StorageField::new(
+                name.into(),
+                "u256".into(),
+                slot.into(),
+                0,
+                32,
+                StorageEncoding::Inplace,
+            )
+        };
+        let layout = |contract: &str, fields| {
+            serde_json::to_string(&StorageLayout::new(contract.into(), None, fields)).unwrap()
+        };
+
+        let dir = temp_dir("files");
+        let old = write_file(
+            &dir,
+            "Foo_storage_layout.json",
+            &layout("Foo", vec![field("a", "0"), field("b", "1")]),
+        );
+        write_file(
+            &dir,
+            "new/Foo_storage_layout.json",
+            &layout("Foo", vec![field("b", "0"), field("a", "1")]),
+        );
+        write_file(
+            &dir,
+            "new/Bar_storage_layout.json",
+            &layout("Bar", vec![field("a", "0")]),
+        );
+
+        let args = |contract: &str| CheckLayoutArgs {
+            old: old.clone(),
+            new: dir.join("new").to_string_lossy().into_owned(),
+            contract: Some(contract.into()),
+            evm_version: None,
+        };
+        assert_eq!(do_work(&args("Foo")), Ok(false));
+        assert_eq!(
+            do_work(&args("Baz")),
+            Err(format!("No contract named `Baz` in `{old}`."))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn manifest_evm_version() {
+        let dir = temp_dir("manifest_evm_version");
+        let project = |name: &str, fields: &str| {
+            write_file(
+                &dir,
+                &format!("{name}/fe.toml"),
+                "name = \"vault\"\nversion = \"1.0\"\nevm-version = \"cancun\"\n",
+            );
+            write_file(
+                &dir,
+                &format!("{name}/src/main.fe"),
+                &format!("contract Vault {{\n    #transient\n    locked: bool\n{fields}}}\n"),
+            );
+            dir.join(name).to_string_lossy().into_owned()
+        };
+
+        // Transient fields require Cancun, which only the manifest selects.
+        let args = CheckLayoutArgs {
+            old: project("old", "    balance: u256\n"),
+            new: project("new", "    balance: u256\n    fee: u8\n"),
+            contract: None,
+            evm_version: None,
+        };
+        assert_eq!(do_work(&args), Ok(true));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,14 +1,20 @@
+mod abi_stub;
 mod build;
 mod check;
+mod check_layout;
 mod new;
+mod standard_json;
 #[cfg(feature = "solc-backend")]
 mod test;
 mod verify;
 
+pub use abi_stub::{abi_stub, AbiStubArgs};
 pub use build::{build, BuildArgs};
 pub use check::{check, CheckArgs};
+pub use check_layout::{check_layout, CheckLayoutArgs};
 use clap::Subcommand;
 pub use new::{create_new_project, NewProjectArgs};
+pub use standard_json::standard_json;
 #[cfg(feature = "solc-backend")]
 pub use test::{test, TestArgs};
 #[cfg(feature = "solc-backend")]
@@ -16,8 +22,10 @@ pub use verify::{verify, VerifyArgs};
 
 #[derive(Subcommand)]
 pub enum Commands {
+    AbiStub(AbiStubArgs),
     Build(BuildArgs),
     Check(CheckArgs),
+    CheckLayout(CheckLayoutArgs),
     New(NewProjectArgs),
     #[cfg(feature = "solc-backend")]
     Verify(VerifyArgs),
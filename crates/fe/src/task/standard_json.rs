@@ -0,0 +1,19 @@
+use std::io::Read;
+
+use super::build::SOLC_ENV_VAR;
+
+/// Compiles the standard JSON request on stdin, and prints the JSON response.
+/// Yul is compiled with the solc executable of `$FE_SOLC` if it's set.
+pub fn standard_json() {
+    let mut input = String::new();
+    if let Err(err) = std::io::stdin().read_to_string(&mut input) {
+        eprintln!("Failed to read the standard JSON request from stdin: {err}");
+        std::process::exit(1)
+    }
+
+    let solc = std::env::var(SOLC_ENV_VAR).ok();
+    println!(
+        "{}",
+        fe_driver::compile_standard_json(&input, solc.as_deref())
+    );
+}
@@ -5,9 +5,11 @@ use clap::Args;
 use colored::Colorize;
 use fe_common::diagnostics::print_diagnostics;
 use fe_common::utils::files::{get_project_root, BuildFiles};
-use fe_driver::CompiledTest;
+use fe_driver::{AnalyzerDb, CompiledTest, EvmVersion};
 use fe_test_runner::TestSink;
 
+use super::build::solc_binary;
+
 #[derive(Args)]
 #[clap(about = "Execute tests in the current project")]
 pub struct TestArgs {
@@ -19,6 +21,16 @@ pub struct TestArgs {
     optimize: Option<bool>,
     #[clap(long)]
     logs: bool,
+    #[clap(
+        long,
+        help("The EVM version to target [default: `evm-version` in fe.toml, or paris]")
+    )]
+    evm_version: Option<EvmVersion>,
+    #[clap(
+        long,
+        help("A solc executable to compile Yul with, instead of the built-in backend [default: $FE_SOLC, or `solc` in fe.toml]")
+    )]
+    solc: Option<String>,
 }
 
 pub fn test(args: TestArgs) {
@@ -62,7 +74,17 @@ fn test_single_file(args: &TestArgs) -> TestSink {
     let optimize = args.optimize.unwrap_or(true);
     let logs = args.logs;
 
+    let evm_version = args.evm_version.unwrap_or_default();
+    let solc = match solc_binary(args.solc.as_deref(), None, evm_version) {
+        Ok(solc) => solc,
+        Err(err) => {
+            eprintln!("Unable to compile {input_path}. Error: {err}");
+            std::process::exit(1)
+        }
+    };
+
     let mut db = fe_driver::Db::default();
+    db.set_evm_version(evm_version);
     let content = match std::fs::read_to_string(input_path) {
         Err(err) => {
             eprintln!("Failed to load file: `{input_path}`. Error: {err}");
@@ -71,7 +93,13 @@ fn test_single_file(args: &TestArgs) -> TestSink {
         Ok(content) => content,
     };
 
-    match fe_driver::compile_single_file_tests(&mut db, input_path, &content, optimize) {
+    match fe_driver::compile_single_file_tests(
+        &mut db,
+        input_path,
+        &content,
+        optimize,
+        solc.as_ref(),
+    ) {
         Ok((name, tests)) => {
             let mut sink = TestSink::new(logs);
             execute_tests(&name, &tests, &mut sink);
@@ -103,9 +131,29 @@ fn test_ingot(args: &TestArgs) -> TestSink {
         }
     };
 
+    let evm_version = match args.evm_version {
+        Some(version) => version,
+        None => match fe_driver::manifest_evm_version(&build_files) {
+            Ok(version) => version,
+            Err(err) => {
+                eprintln!("Unable to compile {input_path}. Error: {err}");
+                std::process::exit(1)
+            }
+        },
+    };
+
+    let solc = match solc_binary(args.solc.as_deref(), Some(&build_files), evm_version) {
+        Ok(solc) => solc,
+        Err(err) => {
+            eprintln!("Unable to compile {input_path}. Error: {err}");
+            std::process::exit(1)
+        }
+    };
+
     let mut db = fe_driver::Db::default();
+    db.set_evm_version(evm_version);
 
-    match fe_driver::compile_ingot_tests(&mut db, &build_files, optimize) {
+    match fe_driver::compile_ingot_tests(&mut db, &build_files, optimize, solc.as_ref()) {
         Ok(test_batches) => {
             let mut sink = TestSink::new(logs);
             for (module_name, tests) in test_batches {
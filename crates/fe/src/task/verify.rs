@@ -4,10 +4,12 @@ use std::path::Path;
 use clap::Args;
 use colored::Colorize;
 use fe_common::utils::files::BuildFiles;
-use fe_driver::{CompiledContract, CompiledModule, Db};
+use fe_driver::{AnalyzerDb, CodegenDb, CompiledContract, CompiledModule, Db, OptimizerSettings};
 use serde::Deserialize;
 use url::Url;
 
+use super::build::solc_binary;
+
 #[derive(Args)]
 #[clap(about = "Verify any onchain contract against local available source code.")]
 pub struct VerifyArgs {
@@ -19,6 +21,16 @@ pub struct VerifyArgs {
     verbose: bool,
     #[clap(long, help("Verify against unoptimized bytecode"))]
     unoptimized: bool,
+    #[clap(
+        long,
+        help("A solc executable to compile Yul with, instead of the built-in solc [default: $FE_SOLC, or `solc` in fe.toml]")
+    )]
+    solc: Option<String>,
+    #[clap(
+        long,
+        help("Verify against bytecode without the IPFS hash of the contract metadata")
+    )]
+    no_metadata_hash: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -26,7 +38,7 @@ struct Response {
     result: String,
 }
 
-fn build_ingot(db: &mut Db, optimize: bool) -> Result<CompiledModule, String> {
+fn build_ingot(db: &mut Db, args: &VerifyArgs) -> Result<CompiledModule, String> {
     let input_path = ".";
 
     if !Path::new(input_path).exists() {
@@ -37,8 +49,25 @@ fn build_ingot(db: &mut Db, optimize: bool) -> Result<CompiledModule, String> {
         Ok(files) => files,
         Err(err) => return Err(format!("Failed to load project files.\nError: {err}")),
     };
+    db.set_codegen_dispatcher_strategy(fe_driver::manifest_dispatcher_strategy(&build_files)?);
+    db.set_evm_version(fe_driver::manifest_evm_version(&build_files)?);
+    db.set_codegen_metadata_hash(!args.no_metadata_hash);
 
-    let compiled_module = match fe_driver::compile_ingot(db, &build_files, true, true, optimize) {
+    let optimizer = OptimizerSettings {
+        enabled: !args.unoptimized,
+        ..fe_driver::manifest_optimizer_settings(&build_files)
+    };
+    let solc = solc_binary(args.solc.as_deref(), Some(&build_files), db.evm_version())?;
+
+    let compiled_module = match fe_driver::compile_ingot(
+        db,
+        &build_files,
+        true,
+        true,
+        false,
+        &optimizer,
+        solc.as_ref(),
+    ) {
         Ok(module) => module,
         Err(_) => return Err(format!("Unable to compile {input_path}.")),
     };
@@ -103,7 +132,7 @@ fn do_work(args: VerifyArgs) -> Result<(), String> {
     let bytecode = byte_code.strip_prefix("0x").unwrap_or(&byte_code);
 
     let mut db = fe_driver::Db::default();
-    let compiled_module = build_ingot(&mut db, !args.unoptimized)?;
+    let compiled_module = build_ingot(&mut db, &args)?;
     for (_, contract) in compiled_module.contracts {
         if contract.runtime_bytecode == bytecode {
             print_success(contract, &args, bytecode, &db);
@@ -112,6 +141,7 @@ fn do_work(args: VerifyArgs) -> Result<(), String> {
     }
     eprintln!("{}", "No contract found with matching bytecode".bold());
     eprintln!("Note: If the contract was deployed with optimization disabled, try verifying with the `--unoptimized` flag.");
+    eprintln!("Note: If the contract was deployed without the metadata hash, try verifying with the `--no-metadata-hash` flag.");
     eprintln!(
         "Note: Make sure to use the same compiler version as the one used to deploy the contract."
     );
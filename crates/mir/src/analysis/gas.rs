@@ -0,0 +1,1081 @@
+//! This module contains a static estimate of the gas that a call to a
+//! function uses on its cheapest and its most expensive path.
+//!
+//! Each instruction is charged the gas of the EVM instructions and runtime
+//! helpers it is lowered to. The gas of the runtime helpers is approximated by
+//! constants rather than derived from the code generated for them, so the
+//! estimates aren't bounds of the gas. Storage is charged as warm on the
+//! cheapest path and as cold on the most expensive one, which is also charged
+//! the expansion of the memory that it may allocate and use. The gas used by
+//! code of other contracts isn't taken into account.
+//!
+//! The cheapest path is the cheapest one to a `return`, which may skip loops
+//! entirely. On the most expensive path, a loop is run as many times as its
+//! trip count allows. The trip count is only known for loops which count an
+//! index up by one to a constant, like `for` loops over arrays do, so the
+//! estimate of the most expensive path is infinite for other loops. It's
+//! infinite too if the memory that a function uses can't be bounded, like
+//! that of contract creation or of intrinsics with variable offsets.
+
+use std::{cmp::Reverse, collections::BinaryHeap, fmt, ops::Add};
+
+use fxhash::{FxHashMap, FxHashSet};
+use num_bigint::BigInt;
+use num_traits::{One, ToPrimitive};
+
+use crate::{
+    db::MirDb,
+    ir::{
+        constant::ConstantValue,
+        inst::{BinOp, CallType, InstKind, UnOp, YulIntrinsicOp},
+        value::AssignableValue,
+        BasicBlockId, FunctionBody, FunctionId, InstId, TypeId, TypeKind, Value, ValueId,
+    },
+};
+
+use super::{
+    loop_tree::LoopId,
+    range::{Interval, RangeState},
+    ControlFlowGraph, DomTree, LoopTree,
+};
+
+/// The size of a memory and storage slot in bytes, as used by the code
+/// generator.
+const SLOT_SIZE: usize = 32;
+
+/// The gas of pushing the operands of an instruction onto the stack and
+/// popping its result.
+const STACK: u64 = 6;
+/// The gas of `ADD`, `LT`, `MLOAD` and other cheap EVM instructions.
+const VERY_LOW: u64 = 3;
+/// The gas of `MUL`, `DIV` and `MOD`.
+const LOW: u64 = 5;
+/// The gas of a `JUMPI` and the `JUMPDEST` it jumps to.
+const BRANCH: u64 = 15;
+/// The gas of calling a function, including the jumps in and out of it.
+const CALL_OVERHEAD: u64 = 40;
+/// The gas of a runtime helper which checks arithmetic for overflow.
+const CHECKED_ARITHMETIC: u64 = 70;
+/// The gas of a step of the loop of the runtime helper of `**`.
+const POW_STEP: u64 = 60;
+/// The gas of checking an array index against the length of the array.
+const BOUNDS_CHECK: u64 = 20;
+/// The gas of encoding or decoding a word of ABI data.
+const ABI_WORD: u64 = 30;
+
+const WARM_ACCESS: u64 = 100;
+const COLD_SLOAD: u64 = 2100;
+const COLD_ACCOUNT_ACCESS: u64 = 2600;
+/// The gas of setting a cold storage slot from zero.
+const COLD_SSTORE_SET: u64 = 22100;
+const CALL_VALUE: u64 = 9000;
+const NEW_ACCOUNT: u64 = 25000;
+const CREATE: u64 = 32000;
+const SELFDESTRUCT: u64 = 5000;
+const EXP: u64 = 10;
+const EXP_BYTE: u64 = 50;
+const KECCAK256: u64 = 30;
+const KECCAK256_WORD: u64 = 6;
+const LOG: u64 = 375;
+const LOG_TOPIC: u64 = 375;
+const LOG_DATA_BYTE: u64 = 8;
+const BLOCKHASH: u64 = 20;
+const MEMORY_WORD: u64 = 3;
+/// The divisor of the square of the number of words in the memory cost.
+const MEMORY_QUAD_DIVISOR: u64 = 512;
+/// The number of words below the memory that the code generator allocates
+/// from, which hold the hash scratch space and the free memory pointer. The
+/// Yul compiler may move the start of the allocated memory up to keep
+/// variables there, which isn't taken into account.
+const HEAP_START: u64 = 3;
+
+/// An amount of gas, which is infinite for code that may not terminate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Gas {
+    Finite(u64),
+    Infinite,
+}
+
+impl Gas {
+    pub const ZERO: Gas = Gas::Finite(0);
+
+    /// Returns the gas of running code `n` times.
+    fn times(self, n: u64) -> Gas {
+        match self {
+            Gas::Finite(gas) => gas.checked_mul(n).map_or(Gas::Infinite, Gas::Finite),
+            Gas::Infinite if n == 0 => Gas::ZERO,
+            Gas::Infinite => Gas::Infinite,
+        }
+    }
+}
+
+impl Add for Gas {
+    type Output = Gas;
+
+    fn add(self, rhs: Gas) -> Gas {
+        match (self, rhs) {
+            (Gas::Finite(lhs), Gas::Finite(rhs)) => {
+                lhs.checked_add(rhs).map_or(Gas::Infinite, Gas::Finite)
+            }
+            _ => Gas::Infinite,
+        }
+    }
+}
+
+impl fmt::Display for Gas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Gas::Finite(gas) => write!(f, "{gas}"),
+            Gas::Infinite => write!(f, "infinite"),
+        }
+    }
+}
+
+/// A number of words of memory, which is infinite if it can't be bounded.
+/// It's summed over paths like gas is.
+type Words = Gas;
+
+/// The estimated gas that running some code uses on its cheapest and its most
+/// expensive path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GasEstimate {
+    /// The gas of the cheapest path.
+    pub best: Gas,
+    /// The gas of the most expensive path.
+    pub worst: Gas,
+}
+
+impl GasEstimate {
+    pub const ZERO: GasEstimate = GasEstimate::exact(0);
+
+    pub const fn new(best: u64, worst: u64) -> Self {
+        Self {
+            best: Gas::Finite(best),
+            worst: Gas::Finite(worst),
+        }
+    }
+
+    pub const fn exact(gas: u64) -> Self {
+        Self::new(gas, gas)
+    }
+}
+
+impl Add for GasEstimate {
+    type Output = GasEstimate;
+
+    fn add(self, rhs: GasEstimate) -> GasEstimate {
+        GasEstimate {
+            best: self.best + rhs.best,
+            worst: self.worst + rhs.worst,
+        }
+    }
+}
+
+/// The estimate of the body of a function, and the memory it uses.
+#[derive(Debug, Clone, Copy)]
+struct FunctionCost {
+    /// The gas of the body, without memory expansion.
+    gas: GasEstimate,
+    /// The most memory that the body allocates.
+    allocated: Words,
+    /// The most memory that the body uses above the memory allocated when
+    /// it's called, which includes its allocations.
+    used: Words,
+}
+
+/// Estimates the gas of functions, memoizing the estimates of the functions
+/// they call.
+pub struct GasEstimator<'db> {
+    db: &'db dyn MirDb,
+    costs: FxHashMap<FunctionId, FunctionCost>,
+    /// Functions whose estimates are being computed, whose calls are
+    /// recursive.
+    in_progress: FxHashSet<FunctionId>,
+}
+
+impl<'db> GasEstimator<'db> {
+    pub fn new(db: &'db dyn MirDb) -> Self {
+        Self {
+            db,
+            costs: FxHashMap::default(),
+            in_progress: FxHashSet::default(),
+        }
+    }
+
+    /// Returns the estimate of a call to an exported function from another
+    /// account, which includes decoding its arguments and encoding its return
+    /// value, but not the intrinsic gas of the transaction or the dispatcher.
+    pub fn estimate_external(&mut self, func: FunctionId) -> GasEstimate {
+        let cost = self.function_cost(func);
+        let sig = func.signature(self.db);
+        let (mut arg_words, mut decoded_words) = (0, 0);
+        // The contract isn't passed in the call data.
+        for param in sig
+            .params
+            .iter()
+            .filter(|param| !param.ty.is_contract(self.db))
+        {
+            arg_words += abi_words(self.db, param.ty);
+            // Arguments of other than primitive types are decoded into memory.
+            if !param.ty.deref(self.db).is_primitive(self.db) {
+                decoded_words += words(self.db, param.ty);
+            }
+        }
+        let ret_words = sig.return_type.map_or(0, |ty| abi_words(self.db, ty));
+
+        let gas = cost.gas + GasEstimate::exact((arg_words + ret_words) * ABI_WORD);
+        // The return value is encoded above the memory allocated by the body.
+        let used = Gas::Finite(decoded_words) + cost.used + Gas::Finite(ret_words);
+        with_memory_expansion(gas, used)
+    }
+
+    /// Returns the estimate of running the body of `func`, in which the most
+    /// expensive path expands memory from the start of the allocated memory.
+    pub fn estimate(&mut self, func: FunctionId) -> GasEstimate {
+        let cost = self.function_cost(func);
+        with_memory_expansion(cost.gas, cost.used)
+    }
+
+    fn function_cost(&mut self, func: FunctionId) -> FunctionCost {
+        if let Some(cost) = self.costs.get(&func) {
+            return *cost;
+        }
+        // The depth of a recursion isn't known.
+        if !self.in_progress.insert(func) {
+            return FunctionCost {
+                gas: GasEstimate {
+                    best: Gas::ZERO,
+                    worst: Gas::Infinite,
+                },
+                allocated: Gas::Infinite,
+                used: Gas::Infinite,
+            };
+        }
+
+        let body = self.db.mir_optimized_func_body(func);
+        let cost = self.body_cost(&body);
+        self.in_progress.remove(&func);
+        self.costs.insert(func, cost);
+        cost
+    }
+
+    fn body_cost(&mut self, body: &FunctionBody) -> FunctionCost {
+        let cfg = ControlFlowGraph::compute(body);
+        let domtree = DomTree::compute(&cfg);
+        let loop_tree = LoopTree::compute(&cfg, &domtree);
+
+        let mut best_costs = FxHashMap::default();
+        let mut worst_costs = FxHashMap::default();
+        let mut allocations = FxHashMap::default();
+        // The most memory used above the allocated memory at once, by any
+        // instruction.
+        let mut scratch = Gas::ZERO;
+        for &block in domtree.rpo() {
+            let (mut cost, mut allocated) = (GasEstimate::ZERO, Gas::ZERO);
+            for inst in body.order.iter_inst(block) {
+                cost = cost + self.inst_cost(body, inst);
+                let (inst_allocated, inst_scratch) = self.inst_memory(body, inst);
+                allocated = allocated + inst_allocated;
+                scratch = scratch.max(inst_scratch);
+            }
+            best_costs.insert(block, cost.best);
+            worst_costs.insert(block, cost.worst);
+            allocations.insert(block, allocated);
+        }
+
+        let estimator = PathEstimator {
+            db: self.db,
+            body,
+            cfg: &cfg,
+            domtree: &domtree,
+            loop_tree: &loop_tree,
+        };
+        let allocated = estimator.worst_case(&allocations);
+        FunctionCost {
+            gas: GasEstimate {
+                best: estimator.best_case(&best_costs),
+                worst: estimator.worst_case(&worst_costs),
+            },
+            allocated,
+            used: allocated + scratch,
+        }
+    }
+
+    fn inst_cost(&mut self, body: &FunctionBody, inst: InstId) -> GasEstimate {
+        let db = self.db;
+        let store = &body.store;
+        match &store.inst_data(inst).kind {
+            InstKind::Declare { .. } | InstKind::Nop => GasEstimate::ZERO,
+
+            InstKind::Unary { op, .. } => match op {
+                UnOp::Neg => GasEstimate::exact(CHECKED_ARITHMETIC),
+                UnOp::Not | UnOp::Inv => GasEstimate::exact(STACK + VERY_LOW),
+            },
+
+            InstKind::Binary { op, .. } => match op {
+                BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => {
+                    GasEstimate::exact(CHECKED_ARITHMETIC)
+                }
+                BinOp::Pow => GasEstimate::new(
+                    CHECKED_ARITHMETIC + EXP + EXP_BYTE,
+                    CHECKED_ARITHMETIC + 256 * POW_STEP,
+                ),
+                BinOp::UncheckedMul => GasEstimate::exact(STACK + LOW),
+                _ => GasEstimate::exact(STACK + VERY_LOW),
+            },
+
+            InstKind::Cast { .. } => GasEstimate::exact(STACK + 2 * VERY_LOW),
+
+            InstKind::AggregateConstruct { args, .. } => {
+                let result_ty = self.result_ty(body, inst);
+                let store_cost = if matches!(result_ty, Some(ty) if ty.is_sptr(db)) {
+                    sstore_cost()
+                } else {
+                    GasEstimate::exact(STACK + VERY_LOW)
+                };
+                (0..args.len()).fold(GasEstimate::ZERO, |cost, _| cost + store_cost)
+            }
+
+            InstKind::Bind { src } | InstKind::MemCopy { src } | InstKind::Load { src } => {
+                match self.result_ty(body, inst) {
+                    Some(result_ty) => transfer_cost(db, result_ty, store.value_ty(*src)),
+                    None => GasEstimate::exact(STACK),
+                }
+            }
+
+            InstKind::AggregateAccess {
+                indices, unchecked, ..
+            } => {
+                let index_cost = if *unchecked {
+                    STACK + VERY_LOW
+                } else {
+                    STACK + VERY_LOW + BOUNDS_CHECK
+                };
+                GasEstimate::exact(indices.len() as u64 * index_cost)
+            }
+
+            InstKind::MapAccess { .. } => {
+                GasEstimate::exact(CALL_OVERHEAD + KECCAK256 + 2 * KECCAK256_WORD + 2 * VERY_LOW)
+            }
+
+            InstKind::Call {
+                func,
+                args,
+                call_type,
+            } => {
+                let arg_words: u64 = args
+                    .iter()
+                    .map(|arg| abi_words(db, store.value_ty(*arg)))
+                    .sum();
+                match call_type {
+                    CallType::Internal => {
+                        GasEstimate::exact(CALL_OVERHEAD + args.len() as u64 * STACK)
+                            + self.function_cost(*func).gas
+                    }
+                    // The gas used by the callee is left out, since it runs
+                    // code of another contract.
+                    CallType::External => {
+                        let ret_words = func.return_type(db).map_or(0, |ty| abi_words(db, ty));
+                        let abi_cost = CALL_OVERHEAD + (arg_words + ret_words) * ABI_WORD;
+                        GasEstimate::new(abi_cost + WARM_ACCESS, abi_cost + COLD_ACCOUNT_ACCESS)
+                    }
+                }
+            }
+
+            InstKind::Jump { .. } | InstKind::Return { .. } => GasEstimate::exact(STACK),
+
+            InstKind::Branch { .. } => GasEstimate::exact(BRANCH),
+
+            InstKind::Switch { table, .. } => {
+                GasEstimate::new(BRANCH, BRANCH * (table.len() as u64).max(1))
+            }
+
+            InstKind::Revert { arg } => {
+                let arg_words = arg.map_or(0, |arg| abi_words(db, store.value_ty(arg)));
+                GasEstimate::exact(STACK + arg_words * ABI_WORD)
+            }
+
+            InstKind::Emit { arg } => {
+                let arg_ty = store.value_ty(*arg);
+                let data_size = arg_ty.deref(db).size_of(db, SLOT_SIZE) as u64;
+                let data_cost = abi_words(db, arg_ty) * ABI_WORD + data_size * LOG_DATA_BYTE;
+                GasEstimate::new(
+                    CALL_OVERHEAD + LOG + LOG_TOPIC,
+                    CALL_OVERHEAD + LOG + 4 * LOG_TOPIC + data_cost,
+                )
+            }
+
+            InstKind::Keccak256 { arg } => GasEstimate::exact(
+                CALL_OVERHEAD + KECCAK256 + words(db, store.value_ty(*arg)) * KECCAK256_WORD,
+            ),
+
+            InstKind::AbiEncode { arg } => {
+                GasEstimate::exact(CALL_OVERHEAD + abi_words(db, store.value_ty(*arg)) * ABI_WORD)
+            }
+
+            InstKind::Create { .. } | InstKind::Create2 { .. } => {
+                GasEstimate::exact(CALL_OVERHEAD + CREATE)
+            }
+
+            InstKind::YulIntrinsic { op, .. } => GasEstimate::exact(STACK) + intrinsic_cost(*op),
+        }
+    }
+
+    /// Returns the most memory that an instruction allocates, and the most
+    /// memory it uses above the allocated memory at once, like the runtime
+    /// helpers which write to the free memory do.
+    fn inst_memory(&mut self, body: &FunctionBody, inst: InstId) -> (Words, Words) {
+        let db = self.db;
+        let store = &body.store;
+        let inst_data = store.inst_data(inst);
+
+        // Memory is allocated for each value which is kept in memory when
+        // it's declared, and for each use of a string constant.
+        let mut allocated = 0;
+        let declared = match (&inst_data.kind, store.inst_result(inst)) {
+            (InstKind::Declare { local }, _) => Some(*local),
+            (_, Some(AssignableValue::Value(value)))
+                if matches!(store.value_data(*value), Value::Temporary { .. }) =>
+            {
+                Some(*value)
+            }
+            _ => None,
+        };
+        if let Some(value) = declared {
+            if is_in_memory(db, store.value_ty(value)) {
+                allocated += words(db, store.value_ty(value));
+            }
+        }
+        for arg in inst_data.args() {
+            if let Value::Constant { constant, .. } = store.value_data(arg) {
+                if let ConstantValue::Str(data) = &constant.data(db).value {
+                    allocated += 1 + data.len().div_ceil(SLOT_SIZE) as u64;
+                }
+            }
+        }
+        let allocated = Gas::Finite(allocated);
+
+        match &inst_data.kind {
+            InstKind::Call {
+                func,
+                args,
+                call_type,
+            } => match call_type {
+                CallType::Internal => {
+                    let cost = self.function_cost(*func);
+                    (allocated + cost.allocated, cost.used)
+                }
+                // The arguments are encoded into free memory, the return data
+                // is copied above them, and the return value is decoded into
+                // allocated memory.
+                CallType::External => {
+                    let arg_words: u64 = args
+                        .iter()
+                        .map(|arg| abi_words(db, store.value_ty(*arg)))
+                        .sum();
+                    let (ret_words, decoded_words) = match func.return_type(db) {
+                        Some(ty) => (abi_words(db, ty), words(db, ty)),
+                        None => (0, 0),
+                    };
+                    (
+                        allocated + Gas::Finite(decoded_words),
+                        Gas::Finite(1 + arg_words + ret_words),
+                    )
+                }
+            },
+            InstKind::AbiEncode { arg } | InstKind::Emit { arg } => {
+                (allocated, Gas::Finite(abi_words(db, store.value_ty(*arg))))
+            }
+            InstKind::Revert { arg } => {
+                let arg_words = arg.map_or(0, |arg| abi_words(db, store.value_ty(arg)));
+                (allocated, Gas::Finite(1 + arg_words))
+            }
+            InstKind::Keccak256 { arg } => {
+                (allocated, Gas::Finite(words(db, store.value_ty(*arg))))
+            }
+            // The init code of the created contract is copied to memory, and
+            // its size isn't known yet.
+            InstKind::Create { .. } | InstKind::Create2 { .. } => (allocated, Gas::Infinite),
+            InstKind::YulIntrinsic { op, args } => {
+                (allocated, intrinsic_memory(db, body, *op, args))
+            }
+            _ => (allocated, Gas::ZERO),
+        }
+    }
+
+    fn result_ty(&self, body: &FunctionBody, inst: InstId) -> Option<TypeId> {
+        body.store
+            .inst_result(inst)
+            .map(|result| result.ty(self.db, &body.store))
+    }
+}
+
+/// Finds the cheapest and the most expensive paths through a function body,
+/// given the costs of its blocks.
+struct PathEstimator<'a> {
+    db: &'a dyn MirDb,
+    body: &'a FunctionBody,
+    cfg: &'a ControlFlowGraph,
+    domtree: &'a DomTree,
+    loop_tree: &'a LoopTree,
+}
+
+impl<'a> PathEstimator<'a> {
+    /// Returns the gas of the cheapest path from the entry to a `return`, or to
+    /// any exit of the function if it never returns.
+    fn best_case(&self, block_costs: &FxHashMap<BasicBlockId, Gas>) -> Gas {
+        let entry = self.cfg.entry();
+        let mut dists = FxHashMap::default();
+        dists.insert(entry, block_costs[&entry]);
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((block_costs[&entry], entry)));
+
+        let (mut best_return, mut best_exit) = (Gas::Infinite, Gas::Infinite);
+        while let Some(Reverse((dist, block))) = queue.pop() {
+            if dists[&block] < dist {
+                continue;
+            }
+            let succs = self.cfg.succs(block);
+            if succs.is_empty() {
+                best_exit = best_exit.min(dist);
+                if self.returns(block) {
+                    best_return = best_return.min(dist);
+                }
+            }
+            for &succ in succs {
+                let succ_dist = dist + block_costs[&succ];
+                match dists.get(&succ) {
+                    Some(old) if *old <= succ_dist => {}
+                    _ => {
+                        dists.insert(succ, succ_dist);
+                        queue.push(Reverse((succ_dist, succ)));
+                    }
+                }
+            }
+        }
+
+        if best_return == Gas::Infinite {
+            best_exit
+        } else {
+            best_return
+        }
+    }
+
+    /// Returns the gas of the most expensive path from the entry to an exit of
+    /// the function.
+    fn worst_case(&self, block_costs: &FxHashMap<BasicBlockId, Gas>) -> Gas {
+        // Inner loops come after outer loops, so loops are collapsed from
+        // the innermost one outwards.
+        let loops: Vec<_> = self.loop_tree.loops().collect();
+        let mut loop_costs = FxHashMap::default();
+        for &lp in loops.iter().rev() {
+            let (iteration, exit) = self.longest_paths(Some(lp), block_costs, &loop_costs);
+            let cost = match (self.trip_count(lp), exit) {
+                (Some(trip_count), Some(exit)) => {
+                    iteration.unwrap_or(Gas::ZERO).times(trip_count) + exit
+                }
+                _ => Gas::Infinite,
+            };
+            loop_costs.insert(lp, cost);
+        }
+
+        let (_, exit) = self.longest_paths(None, block_costs, &loop_costs);
+        exit.unwrap_or(Gas::Infinite)
+    }
+
+    /// Returns the most gas used from the header of a loop, or the entry of the
+    /// function, to a back edge of the loop and to an exit of it, in which
+    /// inner loops are collapsed into their costs.
+    fn longest_paths(
+        &self,
+        region: Option<LoopId>,
+        block_costs: &FxHashMap<BasicBlockId, Gas>,
+        loop_costs: &FxHashMap<LoopId, Gas>,
+    ) -> (Option<Gas>, Option<Gas>) {
+        let header = match region {
+            Some(lp) => self.loop_tree.loop_header(lp),
+            None => self.cfg.entry(),
+        };
+        let in_region = |block| match region {
+            Some(lp) => self.loop_tree.is_block_in_loop(block, lp),
+            None => true,
+        };
+
+        let mut paths: FxHashMap<BasicBlockId, (Option<Gas>, Option<Gas>)> = FxHashMap::default();
+        // Back edges aside, blocks only jump forward in RPO, so successors are
+        // visited before their predecessors.
+        for &block in self.domtree.rpo().iter().rev() {
+            if !in_region(block) || self.node_of(block, region) != block {
+                continue;
+            }
+
+            let (cost, succs) = match self.child_loop(block, region) {
+                Some(child) => (loop_costs[&child], self.loop_exits(child)),
+                None => (block_costs[&block], self.cfg.succs(block).to_vec()),
+            };
+            let (mut to_back_edge, mut to_exit) = (None, None);
+            if succs.is_empty() {
+                to_exit = Some(Gas::ZERO);
+            }
+            for succ in succs {
+                if region.is_some() && succ == header {
+                    to_back_edge = to_back_edge.max(Some(Gas::ZERO));
+                } else if !in_region(succ) {
+                    to_exit = to_exit.max(Some(Gas::ZERO));
+                } else if let Some(&(succ_back_edge, succ_exit)) =
+                    paths.get(&self.node_of(succ, region))
+                {
+                    to_back_edge = to_back_edge.max(succ_back_edge);
+                    to_exit = to_exit.max(succ_exit);
+                }
+            }
+            paths.insert(
+                block,
+                (
+                    to_back_edge.map(|gas| gas + cost),
+                    to_exit.map(|gas| gas + cost),
+                ),
+            );
+        }
+
+        paths.get(&header).copied().unwrap_or_default()
+    }
+
+    /// Returns the block which stands for `block` in `region`: the header of
+    /// the outermost loop in `region` which contains `block`, or `block`
+    /// itself.
+    fn node_of(&self, block: BasicBlockId, region: Option<LoopId>) -> BasicBlockId {
+        let mut node = block;
+        let mut lp = self.loop_tree.loop_of_block(block);
+        while let Some(inner) = lp {
+            if Some(inner) == region {
+                break;
+            }
+            node = self.loop_tree.loop_header(inner);
+            lp = self.loop_tree.parent_loop(inner);
+        }
+        node
+    }
+
+    /// Returns the loop directly inside `region` whose header is `block`.
+    fn child_loop(&self, block: BasicBlockId, region: Option<LoopId>) -> Option<LoopId> {
+        let lp = self.loop_tree.loop_of_block(block)?;
+        (Some(lp) != region && self.loop_tree.loop_header(lp) == block).then_some(lp)
+    }
+
+    /// Returns the blocks outside of `lp` that blocks in `lp` jump to.
+    fn loop_exits(&self, lp: LoopId) -> Vec<BasicBlockId> {
+        let mut exits = vec![];
+        for block in self.loop_tree.iter_blocks_post_order(self.cfg, lp) {
+            for &succ in self.cfg.succs(block) {
+                if !self.loop_tree.is_block_in_loop(succ, lp) && !exits.contains(&succ) {
+                    exits.push(succ);
+                }
+            }
+        }
+        exits
+    }
+
+    /// Returns `true` if `block` returns from the function.
+    fn returns(&self, block: BasicBlockId) -> bool {
+        let terminator = match self.body.order.terminator(&self.body.store, block) {
+            Some(terminator) => terminator,
+            None => return false,
+        };
+        matches!(
+            self.body.store.inst_data(terminator).kind,
+            InstKind::Return { .. }
+                | InstKind::YulIntrinsic {
+                    op: YulIntrinsicOp::Return | YulIntrinsicOp::Stop,
+                    ..
+                }
+        )
+    }
+
+    /// Returns the most times the back edges of `lp` can be taken, if each of
+    /// them increments an index by one and then tests it against a constant
+    /// limit, like those of `for` loops.
+    fn trip_count(&self, lp: LoopId) -> Option<u64> {
+        let header = self.loop_tree.loop_header(lp);
+        let latches: Vec<_> = self
+            .cfg
+            .preds(header)
+            .iter()
+            .copied()
+            .filter(|pred| self.loop_tree.is_block_in_loop(*pred, lp))
+            .collect();
+
+        let mut index_and_limit = None;
+        for &latch in &latches {
+            let latch_test = self.latch_test(lp, latch)?;
+            match &index_and_limit {
+                Some(index_and_limit) if *index_and_limit != latch_test => return None,
+                Some(_) => {}
+                None => index_and_limit = Some(latch_test),
+            }
+        }
+        let (index, limit) = index_and_limit?;
+
+        // The index must only be changed by the increments of the latches in
+        // the loop, so that it goes up by at least one in each iteration, and
+        // it must start at a constant.
+        let mut initial_values = vec![];
+        let mut increments = 0;
+        for block in self.body.order.iter_block() {
+            for inst in self.body.order.iter_inst(block) {
+                if self.body.store.inst_result(inst) != Some(&AssignableValue::Value(index)) {
+                    continue;
+                }
+                if self.loop_tree.is_block_in_loop(block, lp) {
+                    increments += 1;
+                    continue;
+                }
+                match &self.body.store.inst_data(inst).kind {
+                    InstKind::Bind { src } => initial_values.push(self.constant(*src)?),
+                    _ => return None,
+                }
+            }
+        }
+        if increments != latches.len() {
+            return None;
+        }
+        let initial_value = match initial_values.into_iter().min() {
+            Some(initial_value) => initial_value,
+            // The index is an argument.
+            None if !self.body.store.value_ty(index).is_signed(self.db) => BigInt::default(),
+            None => return None,
+        };
+
+        // After `n` back edges, the index is at least `initial_value + n + 1`
+        // when it's tested.
+        let trip_count = match limit {
+            Limit::Below(bound) => bound - initial_value - BigInt::one(),
+            Limit::NotEqual(bound) if initial_value < bound => {
+                bound - initial_value - BigInt::one()
+            }
+            Limit::NotEqual(_) => return None,
+        };
+        if trip_count < BigInt::default() {
+            Some(0)
+        } else {
+            Some(trip_count.to_u64().unwrap_or(u64::MAX))
+        }
+    }
+
+    /// Returns the index that `latch` increments by one right before testing
+    /// it against a limit, and the limit, if it continues `lp` only while the
+    /// limit isn't reached and leaves `lp` otherwise.
+    fn latch_test(&self, lp: LoopId, latch: BasicBlockId) -> Option<(ValueId, Limit)> {
+        let store = &self.body.store;
+        let header = self.loop_tree.loop_header(lp);
+        let terminator = self.body.order.terminator(store, latch)?;
+        let (cond, then, else_) = match store.inst_data(terminator).kind {
+            InstKind::Branch { cond, then, else_ } => (cond, then, else_),
+            _ => return None,
+        };
+        let (continues_if, exit) = if then == header {
+            (true, else_)
+        } else {
+            (false, then)
+        };
+        if exit == header || self.loop_tree.is_block_in_loop(exit, lp) {
+            return None;
+        }
+
+        let test = match store.value_data(cond) {
+            Value::Temporary { inst, .. } if self.body.order.inst_block(*inst) == latch => *inst,
+            _ => return None,
+        };
+        let (op, lhs, rhs) = match store.inst_data(test).kind {
+            InstKind::Binary { op, lhs, rhs } => (op, lhs, rhs),
+            _ => return None,
+        };
+        let (op, index, bound) = match (self.constant(lhs), self.constant(rhs)) {
+            (None, Some(bound)) => (op, lhs, bound),
+            (Some(bound), None) => (swap_comparison(op)?, rhs, bound),
+            _ => return None,
+        };
+        // The condition which continues the loop.
+        let op = if continues_if {
+            op
+        } else {
+            negate_comparison(op)?
+        };
+        let limit = match op {
+            BinOp::Ne => Limit::NotEqual(bound),
+            BinOp::Lt => Limit::Below(bound),
+            BinOp::Le => Limit::Below(bound + BigInt::one()),
+            _ => return None,
+        };
+
+        // The index must be incremented in the latch before it's tested.
+        let increment = self
+            .body
+            .order
+            .iter_inst(latch)
+            .take_while(|inst| *inst != test)
+            .find(|inst| store.inst_result(*inst) == Some(&AssignableValue::Value(index)))?;
+        match store.inst_data(increment).kind {
+            InstKind::Binary {
+                op: BinOp::Add | BinOp::UncheckedAdd,
+                lhs,
+                rhs,
+            } if (lhs == index && self.constant(rhs) == Some(BigInt::one()))
+                || (rhs == index && self.constant(lhs) == Some(BigInt::one())) =>
+            {
+                Some((index, limit))
+            }
+            _ => None,
+        }
+    }
+
+    fn constant(&self, value: ValueId) -> Option<BigInt> {
+        constant(self.db, self.body, value)
+    }
+}
+
+/// Returns the value of an immediate or a constant.
+fn constant(db: &dyn MirDb, body: &FunctionBody, value: ValueId) -> Option<BigInt> {
+    match body.store.value_data(value) {
+        Value::Immediate { .. } | Value::Constant { .. } => {
+            let Interval { lo, hi } = RangeState::default().range(db, body, value)?;
+            (lo == hi).then_some(lo)
+        }
+        _ => None,
+    }
+}
+
+/// A limit which a loop index must stay within for the loop to continue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Limit {
+    Below(BigInt),
+    NotEqual(BigInt),
+}
+
+/// Returns the comparison with its operands swapped.
+fn swap_comparison(op: BinOp) -> Option<BinOp> {
+    match op {
+        BinOp::Eq | BinOp::Ne => Some(op),
+        BinOp::Lt => Some(BinOp::Gt),
+        BinOp::Le => Some(BinOp::Ge),
+        BinOp::Gt => Some(BinOp::Lt),
+        BinOp::Ge => Some(BinOp::Le),
+        _ => None,
+    }
+}
+
+/// Returns the comparison which holds iff `op` doesn't.
+fn negate_comparison(op: BinOp) -> Option<BinOp> {
+    match op {
+        BinOp::Eq => Some(BinOp::Ne),
+        BinOp::Ne => Some(BinOp::Eq),
+        BinOp::Lt => Some(BinOp::Ge),
+        BinOp::Le => Some(BinOp::Gt),
+        BinOp::Gt => Some(BinOp::Le),
+        BinOp::Ge => Some(BinOp::Lt),
+        _ => None,
+    }
+}
+
+/// Returns the number of slots that a value of `ty`, or that `ty` points to,
+/// takes.
+fn words(db: &dyn MirDb, ty: TypeId) -> u64 {
+    ty.deref(db).size_of(db, SLOT_SIZE).div_ceil(SLOT_SIZE) as u64
+}
+
+/// Returns `true` if the code generator allocates memory for a value of `ty`
+/// when it's declared, which it does for memory pointers, and for aggregates
+/// and strings, which it turns into memory pointers.
+fn is_in_memory(db: &dyn MirDb, ty: TypeId) -> bool {
+    ty.is_mptr(db)
+        || ((ty.is_aggregate(db) || ty.is_string(db))
+            && !ty.is_contract(db)
+            && !ty.is_zero_sized(db))
+}
+
+/// Returns the most words that the ABI encoding of a value of `ty`, or of the
+/// value `ty` points to, takes.
+fn abi_words(db: &dyn MirDb, ty: TypeId) -> u64 {
+    let ty = ty.deref(db);
+    match &ty.data(db).kind {
+        // The offset, the length and the data of `string` and `bytes`.
+        TypeKind::String(len) => 2 + len.div_ceil(SLOT_SIZE) as u64 + 1,
+        TypeKind::Array(def) if def.elem_ty.data(db).kind == TypeKind::U8 => {
+            2 + def.len.div_ceil(SLOT_SIZE) as u64 + 1
+        }
+        TypeKind::Array(def) => {
+            def.len as u64 * abi_words(db, def.elem_ty) + u64::from(is_abi_dynamic(db, ty))
+        }
+        TypeKind::Tuple(_) | TypeKind::Struct(_) => {
+            let fields: u64 = (0..ty.aggregate_field_num(db))
+                .map(|idx| abi_words(db, ty.projection_ty_imm(db, idx)))
+                .sum();
+            fields + u64::from(is_abi_dynamic(db, ty))
+        }
+        _ => words(db, ty),
+    }
+}
+
+/// Returns `true` if the ABI encoding of `ty` is dynamic, in which case it's
+/// preceded by an offset.
+fn is_abi_dynamic(db: &dyn MirDb, ty: TypeId) -> bool {
+    match &ty.data(db).kind {
+        TypeKind::String(_) => true,
+        TypeKind::Array(def) => {
+            def.elem_ty.data(db).kind == TypeKind::U8 || is_abi_dynamic(db, def.elem_ty)
+        }
+        TypeKind::Tuple(_) | TypeKind::Struct(_) => (0..ty.aggregate_field_num(db))
+            .any(|idx| is_abi_dynamic(db, ty.projection_ty_imm(db, idx))),
+        _ => false,
+    }
+}
+
+/// Returns the gas of expanding memory to `words` above the start of the
+/// allocated memory.
+fn memory_expansion(words: Words) -> Gas {
+    let words = match words + Gas::Finite(HEAP_START) {
+        Gas::Finite(words) => words,
+        Gas::Infinite => return Gas::Infinite,
+    };
+    match words.checked_mul(words) {
+        Some(square) => {
+            Gas::Finite(words * MEMORY_WORD) + Gas::Finite(square / MEMORY_QUAD_DIVISOR)
+        }
+        None => Gas::Infinite,
+    }
+}
+
+/// Adds the expansion of the memory that the most expensive path uses to an
+/// estimate.
+fn with_memory_expansion(estimate: GasEstimate, used: Words) -> GasEstimate {
+    GasEstimate {
+        best: estimate.best,
+        worst: estimate.worst + memory_expansion(used),
+    }
+}
+
+/// Returns the words of memory that an intrinsic accesses, or infinity if the
+/// offsets or the sizes of the accessed memory aren't constants.
+fn intrinsic_memory(
+    db: &dyn MirDb,
+    body: &FunctionBody,
+    op: YulIntrinsicOp,
+    args: &[ValueId],
+) -> Words {
+    let arg = |idx: usize| {
+        args.get(idx)
+            .and_then(|arg| constant(db, body, *arg))
+            .and_then(|value| value.to_u64())
+    };
+    let extent = |offset: Option<u64>, size: Option<u64>| match (offset, size) {
+        (_, Some(0)) => Gas::ZERO,
+        (Some(offset), Some(size)) => offset.checked_add(size).map_or(Gas::Infinite, |end| {
+            Gas::Finite(end.div_ceil(SLOT_SIZE as u64))
+        }),
+        _ => Gas::Infinite,
+    };
+
+    match op {
+        YulIntrinsicOp::Mload | YulIntrinsicOp::Mstore => extent(arg(0), Some(32)),
+        YulIntrinsicOp::Mstore8 => extent(arg(0), Some(1)),
+        YulIntrinsicOp::Mcopy => extent(arg(0), arg(2)).max(extent(arg(1), arg(2))),
+        YulIntrinsicOp::Keccak256
+        | YulIntrinsicOp::Return
+        | YulIntrinsicOp::Revert
+        | YulIntrinsicOp::Log0
+        | YulIntrinsicOp::Log1
+        | YulIntrinsicOp::Log2
+        | YulIntrinsicOp::Log3
+        | YulIntrinsicOp::Log4 => extent(arg(0), arg(1)),
+        YulIntrinsicOp::Calldatacopy
+        | YulIntrinsicOp::Codecopy
+        | YulIntrinsicOp::Returndatacopy => extent(arg(0), arg(2)),
+        YulIntrinsicOp::Extcodecopy => extent(arg(1), arg(3)),
+        YulIntrinsicOp::Create | YulIntrinsicOp::Create2 => extent(arg(1), arg(2)),
+        YulIntrinsicOp::Call | YulIntrinsicOp::Callcode => {
+            extent(arg(3), arg(4)).max(extent(arg(5), arg(6)))
+        }
+        YulIntrinsicOp::Delegatecall | YulIntrinsicOp::Staticcall => {
+            extent(arg(2), arg(3)).max(extent(arg(4), arg(5)))
+        }
+        _ => Gas::ZERO,
+    }
+}
+
+fn sload_cost() -> GasEstimate {
+    GasEstimate::new(STACK + WARM_ACCESS, STACK + COLD_SLOAD)
+}
+
+fn sstore_cost() -> GasEstimate {
+    GasEstimate::new(STACK + WARM_ACCESS, STACK + COLD_SSTORE_SET)
+}
+
+/// Returns the gas of assigning a value of `src_ty` to a value of `dest_ty`,
+/// which loads, stores or copies the data that pointers point to.
+fn transfer_cost(db: &dyn MirDb, dest_ty: TypeId, src_ty: TypeId) -> GasEstimate {
+    let memory_cost = GasEstimate::exact(STACK + VERY_LOW);
+    let load_cost = |ty: TypeId| {
+        if ty.is_sptr(db) {
+            sload_cost()
+        } else {
+            memory_cost
+        }
+    };
+    let store_cost = |ty: TypeId| {
+        if ty.is_sptr(db) {
+            sstore_cost()
+        } else {
+            memory_cost
+        }
+    };
+    let times = |cost: GasEstimate, n: u64| GasEstimate {
+        best: cost.best.times(n),
+        worst: cost.worst.times(n),
+    };
+
+    match (dest_ty.is_ptr(db), src_ty.is_ptr(db)) {
+        (true, true) if dest_ty.is_sptr(db) == src_ty.is_sptr(db) => GasEstimate::exact(STACK),
+        (true, true) => times(load_cost(src_ty) + store_cost(dest_ty), words(db, src_ty)),
+        (true, false) => times(store_cost(dest_ty), words(db, src_ty).max(1)),
+        (false, true) => times(load_cost(src_ty), words(db, dest_ty).max(1)),
+        (false, false) => GasEstimate::exact(STACK),
+    }
+}
+
+fn intrinsic_cost(op: YulIntrinsicOp) -> GasEstimate {
+    match op {
+        YulIntrinsicOp::Sload => sload_cost(),
+        YulIntrinsicOp::Sstore => sstore_cost(),
+        YulIntrinsicOp::Tload | YulIntrinsicOp::Tstore => GasEstimate::exact(WARM_ACCESS),
+        YulIntrinsicOp::Balance
+        | YulIntrinsicOp::Extcodesize
+        | YulIntrinsicOp::Extcodecopy
+        | YulIntrinsicOp::Extcodehash
+        | YulIntrinsicOp::Delegatecall
+        | YulIntrinsicOp::Staticcall => GasEstimate::new(WARM_ACCESS, COLD_ACCOUNT_ACCESS),
+        YulIntrinsicOp::Call | YulIntrinsicOp::Callcode => {
+            GasEstimate::new(WARM_ACCESS, COLD_ACCOUNT_ACCESS + CALL_VALUE + NEW_ACCOUNT)
+        }
+        YulIntrinsicOp::Create | YulIntrinsicOp::Create2 => GasEstimate::exact(CREATE),
+        YulIntrinsicOp::Selfdestruct => GasEstimate::new(
+            SELFDESTRUCT,
+            SELFDESTRUCT + COLD_ACCOUNT_ACCESS + NEW_ACCOUNT,
+        ),
+        YulIntrinsicOp::Exp => GasEstimate::new(EXP, EXP + 32 * EXP_BYTE),
+        YulIntrinsicOp::Keccak256 => GasEstimate::exact(KECCAK256),
+        YulIntrinsicOp::Log0 => GasEstimate::exact(LOG),
+        YulIntrinsicOp::Log1 => GasEstimate::exact(LOG + LOG_TOPIC),
+        YulIntrinsicOp::Log2 => GasEstimate::exact(LOG + 2 * LOG_TOPIC),
+        YulIntrinsicOp::Log3 => GasEstimate::exact(LOG + 3 * LOG_TOPIC),
+        YulIntrinsicOp::Log4 => GasEstimate::exact(LOG + 4 * LOG_TOPIC),
+        YulIntrinsicOp::Blockhash => GasEstimate::exact(BLOCKHASH),
+        YulIntrinsicOp::Mul
+        | YulIntrinsicOp::Div
+        | YulIntrinsicOp::Sdiv
+        | YulIntrinsicOp::Mod
+        | YulIntrinsicOp::Smod
+        | YulIntrinsicOp::Signextend => GasEstimate::exact(LOW),
+        YulIntrinsicOp::Addmod | YulIntrinsicOp::Mulmod => GasEstimate::exact(8),
+        _ => GasEstimate::exact(VERY_LOW),
+    }
+}
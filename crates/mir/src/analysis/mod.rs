@@ -1,9 +1,13 @@
 pub mod cfg;
 pub mod domtree;
+pub mod gas;
 pub mod loop_tree;
 pub mod post_domtree;
+pub mod range;
 
 pub use cfg::ControlFlowGraph;
 pub use domtree::DomTree;
+pub use gas::{Gas, GasEstimate, GasEstimator};
 pub use loop_tree::LoopTree;
 pub use post_domtree::PostDomTree;
+pub use range::RangeAnalysis;
@@ -0,0 +1,645 @@
+//! This module contains a value range analysis, which computes an interval of
+//! integers that each value can take at each program point.
+//!
+//! The analysis is a forward abstract interpretation over the CFG. Ranges are
+//! refined on conditional branches, e.g., `x` is known to be less than `y` in
+//! the `then` block of `if x < y`, and widened to constants which appear in the
+//! function body when a loop doesn't converge quickly.
+
+use std::collections::BTreeSet;
+
+use fxhash::FxHashMap;
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    db::MirDb,
+    ir::{
+        inst::{BinOp, BranchInfo, CastKind, InstKind, UnOp},
+        value::AssignableValue,
+        BasicBlockId, FunctionBody, InstId, TypeId, TypeKind, Value, ValueId,
+    },
+    transform::const_fold::{word_of, IntTy},
+};
+
+use super::{ControlFlowGraph, DomTree};
+
+/// The number of times the entry state of a block is updated before its
+/// ranges are widened.
+const WIDENING_DELAY: usize = 2;
+
+/// The number of times the states are narrowed after they get stable.
+const NARROWING_ITERATIONS: usize = 2;
+
+/// The maximum depth of conditions followed to refine ranges on a branch.
+const MAX_REFINEMENT_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct RangeAnalysis {
+    /// Maps reachable blocks to the state at their entry.
+    entry_states: FxHashMap<BasicBlockId, RangeState>,
+}
+
+impl RangeAnalysis {
+    pub fn compute(db: &dyn MirDb, body: &FunctionBody) -> Self {
+        let cfg = ControlFlowGraph::compute(body);
+        let domtree = DomTree::compute(&cfg);
+        let thresholds = widening_thresholds(db, body);
+
+        let rpo_nums: FxHashMap<_, _> = domtree
+            .rpo()
+            .iter()
+            .enumerate()
+            .map(|(i, block)| (*block, i))
+            .collect();
+        let mut entry_states = FxHashMap::default();
+        entry_states.insert(cfg.entry(), RangeState::default());
+        let mut visits: FxHashMap<BasicBlockId, usize> = FxHashMap::default();
+
+        // Blocks are processed in reverse post order so that a block is
+        // processed after its forward predecessors.
+        let mut worklist = BTreeSet::new();
+        worklist.insert(0);
+        while let Some(rpo_num) = worklist.pop_first() {
+            let block = domtree.rpo()[rpo_num];
+            let state = entry_states[&block].exit_state(db, body, block);
+            let terminator = match body.order.terminator(&body.store, block) {
+                Some(terminator) => terminator,
+                None => continue,
+            };
+
+            for (dest, dest_state) in state.edge_states(db, body, terminator) {
+                let new_state = match entry_states.get(&dest) {
+                    Some(old) => {
+                        let joined = old.join(&dest_state);
+                        if &joined == old {
+                            continue;
+                        }
+
+                        let visit = visits.entry(dest).or_default();
+                        *visit += 1;
+                        if *visit > WIDENING_DELAY {
+                            old.widen(db, body, &joined, &thresholds)
+                        } else {
+                            joined
+                        }
+                    }
+                    None => dest_state,
+                };
+
+                entry_states.insert(dest, new_state);
+                worklist.insert(rpo_nums[&dest]);
+            }
+        }
+
+        // Widening may overshoot bounds which are restored by conditions on
+        // back edges, e.g., `i < n` in a `while` loop, so the states are
+        // narrowed by recomputing them from the stable states.
+        for _ in 0..NARROWING_ITERATIONS {
+            let mut narrowed: FxHashMap<BasicBlockId, RangeState> = FxHashMap::default();
+            narrowed.insert(cfg.entry(), RangeState::default());
+            for &block in domtree.rpo() {
+                let state = match entry_states.get(&block) {
+                    Some(state) => state.exit_state(db, body, block),
+                    None => continue,
+                };
+                let terminator = match body.order.terminator(&body.store, block) {
+                    Some(terminator) => terminator,
+                    None => continue,
+                };
+                for (dest, dest_state) in state.edge_states(db, body, terminator) {
+                    let dest_state = match narrowed.get(&dest) {
+                        Some(other) => other.join(&dest_state),
+                        None => dest_state,
+                    };
+                    narrowed.insert(dest, dest_state);
+                }
+            }
+            entry_states = narrowed;
+        }
+
+        Self { entry_states }
+    }
+
+    /// Calls `f` with each instruction in reachable blocks and the state right
+    /// before the instruction is executed.
+    pub fn for_each_inst(
+        &self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        mut f: impl FnMut(InstId, &RangeState),
+    ) {
+        for block in body.order.iter_block() {
+            let mut state = match self.entry_states.get(&block) {
+                Some(state) => state.clone(),
+                None => continue,
+            };
+            for inst in body.order.iter_inst(block) {
+                f(inst, &state);
+                state.transfer(db, body, inst);
+            }
+        }
+    }
+
+    /// Returns `true` if `block` may be executed.
+    pub fn is_reachable(&self, block: BasicBlockId) -> bool {
+        self.entry_states.contains_key(&block)
+    }
+}
+
+/// A closed interval of integers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    pub lo: BigInt,
+    pub hi: BigInt,
+}
+
+impl Interval {
+    pub fn new(lo: BigInt, hi: BigInt) -> Self {
+        Self { lo, hi }
+    }
+
+    pub fn singleton(num: BigInt) -> Self {
+        Self::new(num.clone(), num)
+    }
+
+    /// Returns the interval of all values of `ty` if `ty` is an integral type
+    /// or `bool`.
+    pub fn of_type(db: &dyn MirDb, ty: TypeId) -> Option<Self> {
+        if ty.data(db).kind == TypeKind::Bool {
+            return Some(Self::new(BigInt::zero(), BigInt::one()));
+        }
+        let int_ty = IntTy::of(db, ty)?;
+        Some(Self::new(int_ty.min(), int_ty.max()))
+    }
+
+    pub fn is_within(&self, other: &Self) -> bool {
+        other.lo <= self.lo && self.hi <= other.hi
+    }
+
+    /// Returns the intersection of two intervals, or `None` if they're
+    /// disjoint.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let lo = (&self.lo).max(&other.lo).clone();
+        let hi = (&self.hi).min(&other.hi).clone();
+        (lo <= hi).then(|| Self::new(lo, hi))
+    }
+
+    /// Returns the smallest interval containing both intervals.
+    pub fn hull(&self, other: &Self) -> Self {
+        let lo = (&self.lo).min(&other.lo).clone();
+        let hi = (&self.hi).max(&other.hi).clone();
+        Self::new(lo, hi)
+    }
+
+    fn as_singleton(&self) -> Option<&BigInt> {
+        (self.lo == self.hi).then_some(&self.lo)
+    }
+}
+
+/// Ranges of values at a program point. A value which isn't recorded in the
+/// state can take any value of its type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeState {
+    ranges: FxHashMap<ValueId, Interval>,
+}
+
+impl RangeState {
+    /// Returns the range of `value`, or `None` if `value` isn't an integer.
+    pub fn range(&self, db: &dyn MirDb, body: &FunctionBody, value: ValueId) -> Option<Interval> {
+        if let Some(range) = self.ranges.get(&value) {
+            return Some(range.clone());
+        }
+
+        let ty_range = Interval::of_type(db, body.store.value_ty(value))?;
+        let imm_range = match body.store.value_data(value) {
+            Value::Immediate { ty, .. } | Value::Constant { ty, .. } => word_of(db, body, value)
+                .map(|word| Interval::singleton(IntTy::of(db, *ty).unwrap().decode(&word))),
+            _ => None,
+        };
+        match imm_range {
+            Some(range) if range.is_within(&ty_range) => Some(range),
+            _ => Some(ty_range),
+        }
+    }
+
+    /// Returns the state right before the terminator of `block` is executed.
+    fn exit_state(&self, db: &dyn MirDb, body: &FunctionBody, block: BasicBlockId) -> Self {
+        let mut state = self.clone();
+        for inst in body.order.iter_inst(block) {
+            state.transfer(db, body, inst);
+        }
+        state
+    }
+
+    /// Updates the state with the result of `inst`.
+    fn transfer(&mut self, db: &dyn MirDb, body: &FunctionBody, inst: InstId) {
+        let result = match body.store.inst_result(inst) {
+            Some(AssignableValue::Value(result)) => *result,
+            _ => return,
+        };
+        let ty_range = match Interval::of_type(db, body.store.value_ty(result)) {
+            Some(ty_range) => ty_range,
+            None => return,
+        };
+
+        // Checked arithmetic reverts if its result doesn't fit in the type, so
+        // the result is always in the type range.
+        match self
+            .eval(db, body, inst)
+            .and_then(|range| range.intersect(&ty_range))
+        {
+            Some(range) if range != ty_range => {
+                self.ranges.insert(result, range);
+            }
+            _ => {
+                self.ranges.remove(&result);
+            }
+        }
+    }
+
+    /// Returns the range of the mathematical result of `inst`.
+    fn eval(&self, db: &dyn MirDb, body: &FunctionBody, inst: InstId) -> Option<Interval> {
+        match &body.store.inst_data(inst).kind {
+            InstKind::Bind { src } => self.range(db, body, *src),
+
+            InstKind::Cast {
+                kind: CastKind::Primitive,
+                value,
+                to,
+            } => {
+                // A cast to a narrower type truncates the value.
+                let range = self.range(db, body, *value)?;
+                range
+                    .is_within(&Interval::of_type(db, *to)?)
+                    .then_some(range)
+            }
+
+            InstKind::Unary { op, value } => match op {
+                UnOp::Not => Some(Interval::new(BigInt::zero(), BigInt::one())),
+                UnOp::Neg => {
+                    let range = self.range(db, body, *value)?;
+                    Some(Interval::new(-range.hi, -range.lo))
+                }
+                UnOp::Inv => None,
+            },
+
+            InstKind::Binary { op, lhs, rhs } => {
+                let lhs = self.range(db, body, *lhs)?;
+                let rhs = self.range(db, body, *rhs)?;
+                eval_binary(*op, &lhs, &rhs)
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Returns states on each outgoing edge of `terminator`. Edges which can't
+    /// be taken are omitted.
+    fn edge_states(
+        &self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        terminator: InstId,
+    ) -> Vec<(BasicBlockId, RangeState)> {
+        match body.store.branch_info(terminator) {
+            BranchInfo::Branch(cond, then, else_) => [(then, true), (else_, false)]
+                .into_iter()
+                .filter_map(|(dest, truth)| {
+                    let mut state = self.clone();
+                    state
+                        .refine(db, body, terminator, cond, truth, MAX_REFINEMENT_DEPTH)
+                        .then_some((dest, state))
+                })
+                .collect(),
+            info => info.block_iter().map(|dest| (dest, self.clone())).collect(),
+        }
+    }
+
+    /// Refines the state under the assumption that `cond` is `truth` when
+    /// `branch` is executed. Returns `false` if the assumption never holds.
+    fn refine(
+        &mut self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        branch: InstId,
+        cond: ValueId,
+        truth: bool,
+        depth: usize,
+    ) -> bool {
+        let truth_range = Interval::singleton(BigInt::from(truth as u8));
+        if !self.restrict(db, body, cond, &truth_range) {
+            return false;
+        }
+
+        let def = match body.store.value_data(cond) {
+            Value::Temporary { inst, .. } if depth > 0 => *inst,
+            _ => return true,
+        };
+        match &body.store.inst_data(def).kind {
+            InstKind::Unary {
+                op: UnOp::Not,
+                value,
+            } if is_unchanged_until(body, *value, def, branch) => {
+                self.refine(db, body, branch, *value, !truth, depth - 1)
+            }
+
+            InstKind::Bind { src } if is_unchanged_until(body, *src, def, branch) => {
+                self.refine(db, body, branch, *src, truth, depth - 1)
+            }
+
+            InstKind::Binary { op, lhs, rhs }
+                if is_unchanged_until(body, *lhs, def, branch)
+                    && is_unchanged_until(body, *rhs, def, branch) =>
+            {
+                let (lhs, rhs) = (*lhs, *rhs);
+                match (op, truth) {
+                    (BinOp::Lt, true) | (BinOp::Ge, false) => {
+                        self.refine_le(db, body, lhs, rhs, true)
+                    }
+                    (BinOp::Le, true) | (BinOp::Gt, false) => {
+                        self.refine_le(db, body, lhs, rhs, false)
+                    }
+                    (BinOp::Gt, true) | (BinOp::Le, false) => {
+                        self.refine_le(db, body, rhs, lhs, true)
+                    }
+                    (BinOp::Ge, true) | (BinOp::Lt, false) => {
+                        self.refine_le(db, body, rhs, lhs, false)
+                    }
+                    (BinOp::Eq, true) | (BinOp::Ne, false) => self.refine_eq(db, body, lhs, rhs),
+                    (BinOp::Eq, false) | (BinOp::Ne, true) => self.refine_ne(db, body, lhs, rhs),
+                    _ => true,
+                }
+            }
+
+            _ => true,
+        }
+    }
+
+    /// Refines the state under the assumption `lhs <= rhs`, or `lhs < rhs` if
+    /// `strict` is `true`.
+    fn refine_le(
+        &mut self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        lhs: ValueId,
+        rhs: ValueId,
+        strict: bool,
+    ) -> bool {
+        let (lhs_range, rhs_range) = match (self.range(db, body, lhs), self.range(db, body, rhs)) {
+            (Some(lhs_range), Some(rhs_range)) => (lhs_range, rhs_range),
+            _ => return true,
+        };
+        let gap = if strict {
+            BigInt::one()
+        } else {
+            BigInt::zero()
+        };
+
+        let lhs_bound = Interval::new(lhs_range.lo.clone(), &rhs_range.hi - &gap);
+        let rhs_bound = Interval::new(&lhs_range.lo + &gap, rhs_range.hi.clone());
+        self.restrict(db, body, lhs, &lhs_bound) && self.restrict(db, body, rhs, &rhs_bound)
+    }
+
+    fn refine_eq(
+        &mut self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        lhs: ValueId,
+        rhs: ValueId,
+    ) -> bool {
+        let range = match (self.range(db, body, lhs), self.range(db, body, rhs)) {
+            (Some(lhs_range), Some(rhs_range)) => match lhs_range.intersect(&rhs_range) {
+                Some(range) => range,
+                None => return false,
+            },
+            _ => return true,
+        };
+        self.restrict(db, body, lhs, &range) && self.restrict(db, body, rhs, &range)
+    }
+
+    fn refine_ne(
+        &mut self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        lhs: ValueId,
+        rhs: ValueId,
+    ) -> bool {
+        let (lhs_range, rhs_range) = match (self.range(db, body, lhs), self.range(db, body, rhs)) {
+            (Some(lhs_range), Some(rhs_range)) => (lhs_range, rhs_range),
+            _ => return true,
+        };
+
+        // Only a bound which equals to a known value can be excluded.
+        let exclude = |range: &Interval, num: &BigInt| -> Option<Interval> {
+            if &range.lo == num && &range.hi == num {
+                None
+            } else if &range.lo == num {
+                Some(Interval::new(num + 1, range.hi.clone()))
+            } else if &range.hi == num {
+                Some(Interval::new(range.lo.clone(), num - 1))
+            } else {
+                Some(range.clone())
+            }
+        };
+        if let Some(num) = rhs_range.as_singleton() {
+            match exclude(&lhs_range, num) {
+                Some(range) => {
+                    self.restrict(db, body, lhs, &range);
+                }
+                None => return false,
+            }
+        }
+        if let Some(num) = lhs_range.as_singleton() {
+            match exclude(&rhs_range, num) {
+                Some(range) => {
+                    self.restrict(db, body, rhs, &range);
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Restricts the range of `value` to `bound`. Returns `false` if `value`
+    /// can't be in `bound`.
+    fn restrict(
+        &mut self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        value: ValueId,
+        bound: &Interval,
+    ) -> bool {
+        let range = match self.range(db, body, value) {
+            Some(range) => range,
+            None => return true,
+        };
+        let range = match range.intersect(bound) {
+            Some(range) => range,
+            None => return false,
+        };
+
+        if matches!(
+            body.store.value_data(value),
+            Value::Temporary { .. } | Value::Local(_)
+        ) {
+            self.ranges.insert(value, range);
+        }
+        true
+    }
+
+    /// Returns a state containing ranges of both states.
+    fn join(&self, other: &Self) -> Self {
+        let ranges = self
+            .ranges
+            .iter()
+            .filter_map(|(value, range)| {
+                other
+                    .ranges
+                    .get(value)
+                    .map(|other| (*value, range.hull(other)))
+            })
+            .collect();
+        Self { ranges }
+    }
+
+    /// Returns `new` whose growing bounds are extended to the nearest
+    /// threshold, or to the type bounds if no threshold is found. `new` must
+    /// contain `self`.
+    fn widen(
+        &self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        new: &Self,
+        thresholds: &BTreeSet<BigInt>,
+    ) -> Self {
+        let mut widened = new.clone();
+        for (value, range) in widened.ranges.iter_mut() {
+            let old = &self.ranges[value];
+            let ty_range = Interval::of_type(db, body.store.value_ty(*value)).unwrap();
+            if range.lo < old.lo {
+                range.lo = thresholds
+                    .range(..=&range.lo)
+                    .next_back()
+                    .filter(|lo| **lo >= ty_range.lo)
+                    .unwrap_or(&ty_range.lo)
+                    .clone();
+            }
+            if range.hi > old.hi {
+                range.hi = thresholds
+                    .range(&range.hi..)
+                    .next()
+                    .filter(|hi| **hi <= ty_range.hi)
+                    .unwrap_or(&ty_range.hi)
+                    .clone();
+            }
+        }
+        widened
+    }
+}
+
+/// Returns the range of the mathematical result of `op` applied to values in
+/// `lhs` and `rhs`, or `None` if it's unknown.
+pub fn eval_binary(op: BinOp, lhs: &Interval, rhs: &Interval) -> Option<Interval> {
+    let is_non_negative = !lhs.lo.is_negative() && !rhs.lo.is_negative();
+    match op {
+        BinOp::Add | BinOp::UncheckedAdd => {
+            Some(Interval::new(&lhs.lo + &rhs.lo, &lhs.hi + &rhs.hi))
+        }
+
+        BinOp::Sub | BinOp::UncheckedSub => {
+            Some(Interval::new(&lhs.lo - &rhs.hi, &lhs.hi - &rhs.lo))
+        }
+
+        BinOp::Mul | BinOp::UncheckedMul => {
+            let products = [
+                &lhs.lo * &rhs.lo,
+                &lhs.lo * &rhs.hi,
+                &lhs.hi * &rhs.lo,
+                &lhs.hi * &rhs.hi,
+            ];
+            let lo = products.iter().min().unwrap().clone();
+            let hi = products.iter().max().unwrap().clone();
+            Some(Interval::new(lo, hi))
+        }
+
+        // Division by zero reverts.
+        BinOp::Div if is_non_negative && !rhs.hi.is_zero() => {
+            let min_divisor = (&rhs.lo).max(&BigInt::one()).clone();
+            Some(Interval::new(&lhs.lo / &rhs.hi, &lhs.hi / min_divisor))
+        }
+
+        BinOp::Mod if is_non_negative && !rhs.hi.is_zero() => Some(Interval::new(
+            BigInt::zero(),
+            (&lhs.hi).min(&(&rhs.hi - 1)).clone(),
+        )),
+
+        BinOp::BitAnd if is_non_negative => Some(Interval::new(
+            BigInt::zero(),
+            (&lhs.hi).min(&rhs.hi).clone(),
+        )),
+
+        BinOp::Shr if is_non_negative => {
+            let shift = |num: &BigInt| num.to_usize().unwrap_or(256).min(256);
+            Some(Interval::new(
+                &lhs.lo >> shift(&rhs.hi),
+                &lhs.hi >> shift(&rhs.lo),
+            ))
+        }
+
+        BinOp::Eq
+        | BinOp::Ne
+        | BinOp::Ge
+        | BinOp::Gt
+        | BinOp::Le
+        | BinOp::Lt
+        | BinOp::LogicalAnd
+        | BinOp::LogicalOr => Some(Interval::new(BigInt::zero(), BigInt::one())),
+
+        _ => None,
+    }
+}
+
+/// Returns `true` if `value` used in `def` isn't reassigned before `user` is
+/// executed. `def` must dominate `user`.
+fn is_unchanged_until(body: &FunctionBody, value: ValueId, def: InstId, user: InstId) -> bool {
+    match body.store.value_data(value) {
+        Value::Local(_) => {}
+        _ => return true,
+    }
+
+    if body.order.inst_block(def) != body.order.inst_block(user) {
+        return false;
+    }
+    let mut next = body.order.next_inst(def);
+    while let Some(inst) = next {
+        if inst == user {
+            return true;
+        }
+        if body.store.inst_result(inst) == Some(&AssignableValue::Value(value)) {
+            return false;
+        }
+        next = body.order.next_inst(inst);
+    }
+    false
+}
+
+/// Returns bounds which ranges are widened to. Constants appearing in a body
+/// are likely to bound loops, e.g., the length of an iterated array.
+fn widening_thresholds(db: &dyn MirDb, body: &FunctionBody) -> BTreeSet<BigInt> {
+    let mut thresholds = BTreeSet::new();
+    for value in body.store.values() {
+        let (imm, ty) = match value {
+            Value::Immediate { imm, ty } => (imm, *ty),
+            _ => continue,
+        };
+        let num = match IntTy::of(db, ty) {
+            Some(int_ty) if !imm.is_negative() => int_ty.decode(imm),
+            _ => continue,
+        };
+        thresholds.insert(&num - 1);
+        thresholds.insert(&num + 1);
+        thresholds.insert(num);
+    }
+    thresholds
+}
@@ -6,7 +6,10 @@ use fe_analyzer::{
     namespace::{items as analyzer_items, types as analyzer_types},
     AnalyzerDb,
 };
-use fe_common::db::{SourceDb, SourceDbStorage, Upcast, UpcastMut};
+use fe_common::{
+    db::{SourceDb, SourceDbStorage, Upcast, UpcastMut},
+    EvmVersion,
+};
 use smol_str::SmolStr;
 
 use crate::ir::{self, ConstantId, TypeId};
@@ -15,6 +18,14 @@ mod queries;
 
 #[salsa::query_group(MirDbStorage)]
 pub trait MirDb: AnalyzerDb + Upcast<dyn AnalyzerDb> + UpcastMut<dyn AnalyzerDb> {
+    /// Whether MIR optimization passes run before codegen.
+    #[salsa::input]
+    fn mir_optimization_enabled(&self) -> bool;
+    /// Whether the optimization passes replace checked arithmetic and bounds
+    /// checks which the range analysis proves safe with unchecked ones.
+    #[salsa::input]
+    fn mir_check_elimination_enabled(&self) -> bool;
+
     #[salsa::interned]
     fn mir_intern_const(&self, data: Rc<ir::Constant>) -> ir::ConstantId;
     #[salsa::interned]
@@ -70,15 +81,28 @@ pub trait MirDb: AnalyzerDb + Upcast<dyn AnalyzerDb> + UpcastMut<dyn AnalyzerDb>
     ) -> ir::FunctionId;
     #[salsa::invoke(queries::function::mir_lowered_func_body)]
     fn mir_lowered_func_body(&self, func: ir::FunctionId) -> Rc<ir::FunctionBody>;
+    #[salsa::invoke(queries::function::mir_optimized_func_body)]
+    fn mir_optimized_func_body(&self, func: ir::FunctionId) -> Rc<ir::FunctionBody>;
 }
 
 #[salsa::database(SourceDbStorage, AnalyzerDbStorage, MirDbStorage)]
-#[derive(Default)]
 pub struct NewDb {
     storage: salsa::Storage<NewDb>,
 }
 impl salsa::Database for NewDb {}
 
+impl Default for NewDb {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: salsa::Storage::default(),
+        };
+        db.set_mir_optimization_enabled(false);
+        db.set_mir_check_elimination_enabled(true);
+        db.set_evm_version(EvmVersion::default());
+        db
+    }
+}
+
 impl Upcast<dyn SourceDb> for NewDb {
     fn upcast(&self) -> &(dyn SourceDb + 'static) {
         self
@@ -11,6 +11,7 @@ use crate::{
     db::MirDb,
     ir::{self, function::Linkage, FunctionSignature, TypeId},
     lower::function::{lower_func_body, lower_func_signature, lower_monomorphized_func_signature},
+    transform,
 };
 
 pub fn mir_lowered_func_signature(
@@ -47,6 +48,17 @@ pub fn mir_lowered_func_body(db: &dyn MirDb, func: ir::FunctionId) -> Rc<ir::Fun
     lower_func_body(db, func)
 }
 
+pub fn mir_optimized_func_body(db: &dyn MirDb, func: ir::FunctionId) -> Rc<ir::FunctionBody> {
+    let body = db.mir_lowered_func_body(func);
+    if !db.mir_optimization_enabled() {
+        return body;
+    }
+
+    let mut body = body.as_ref().clone();
+    transform::optimize_func_body(db, &mut body);
+    body.into()
+}
+
 impl ir::FunctionId {
     pub fn signature(self, db: &dyn MirDb) -> Rc<FunctionSignature> {
         db.lookup_mir_intern_function(self)
@@ -68,6 +80,12 @@ impl ir::FunctionId {
         db.mir_lowered_func_body(self)
     }
 
+    /// Returns the function body after MIR optimization passes if they are
+    /// enabled, otherwise the lowered body.
+    pub fn optimized_body(self, db: &dyn MirDb) -> Rc<ir::FunctionBody> {
+        db.mir_optimized_func_body(self)
+    }
+
     pub fn module(self, db: &dyn MirDb) -> analyzer_items::ModuleId {
         let analyzer_func = self.analyzer_func(db);
         analyzer_func.module(db.upcast())
@@ -80,6 +98,33 @@ impl ir::FunctionId {
             .is_constructor(db.upcast())
     }
 
+    /// Returns `true` if the function is the `__receive__` function of a
+    /// contract.
+    pub fn is_contract_receive(self, db: &dyn MirDb) -> bool {
+        self.is_contract_hook(db, "__receive__")
+    }
+
+    /// Returns `true` if the function is the `__fallback__` function of a
+    /// contract.
+    pub fn is_contract_fallback(self, db: &dyn MirDb) -> bool {
+        self.is_contract_hook(db, "__fallback__")
+    }
+
+    /// Returns `true` if the function is marked with `#inline`.
+    pub fn is_inline(self, db: &dyn MirDb) -> bool {
+        self.analyzer_func(db).is_inline(db.upcast())
+    }
+
+    /// Returns `true` if the function is marked with `#noinline`.
+    pub fn is_noinline(self, db: &dyn MirDb) -> bool {
+        self.analyzer_func(db).is_noinline(db.upcast())
+    }
+
+    fn is_contract_hook(self, db: &dyn MirDb, name: &str) -> bool {
+        let analyzer_func = self.analyzer_func(db);
+        analyzer_func.is_contract_func(db.upcast()) && analyzer_func.name(db.upcast()) == name
+    }
+
     /// Returns a type suffix if a generic function was monomorphized
     pub fn type_suffix(&self, db: &dyn MirDb) -> SmolStr {
         self.signature(db)
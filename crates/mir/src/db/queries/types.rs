@@ -241,22 +241,23 @@ impl TypeId {
 
             TypeKind::Array(def) => array_elem_size_imp(db, def, slot_size) * def.len,
 
-            TypeKind::Tuple(def) => {
-                if def.items.is_empty() {
+            TypeKind::Tuple(_) | TypeKind::Struct(_) | TypeKind::Contract(_) => {
+                let field_num = self.aggregate_field_num(db);
+                if field_num == 0 {
                     return 0;
                 }
-                let last_idx = def.items.len() - 1;
-                self.aggregate_elem_offset(db, last_idx, slot_size)
-                    + def.items[last_idx].size_of(db, slot_size)
-            }
-
-            TypeKind::Struct(def) | TypeKind::Contract(def) => {
-                if def.fields.is_empty() {
-                    return 0;
+                let last_idx = field_num - 1;
+                let last_ty = self.projection_ty_imm(db, last_idx);
+                let end = self.aggregate_elem_offset(db, last_idx, slot_size)
+                    + last_ty.size_of(db, slot_size);
+
+                // A primitive field is placed at the end of its slot, so the rest
+                // of the slot is in use.
+                if last_ty.is_primitive(db) {
+                    round_up(end, slot_size)
+                } else {
+                    end
                 }
-                let last_idx = def.fields.len() - 1;
-                self.aggregate_elem_offset(db, last_idx, slot_size)
-                    + def.fields[last_idx].1.size_of(db, slot_size)
             }
 
             TypeKind::Enum(def) => {
@@ -297,25 +298,43 @@ impl TypeId {
         debug_assert!(elem_idx.to_usize().unwrap() < self.aggregate_field_num(db));
         let elem_idx = elem_idx.to_usize().unwrap();
 
-        if elem_idx == 0 {
-            return 0;
-        }
-
         match &self.data(db).kind {
             TypeKind::Array(def) => array_elem_size_imp(db, def, slot_size) * elem_idx,
+            TypeKind::Enum(_) if elem_idx == 0 => 0,
             TypeKind::Enum(_) => self.enum_data_offset(db, slot_size),
             _ => {
-                let mut offset = self.aggregate_elem_offset(db, elem_idx - 1, slot_size)
-                    + self
-                        .projection_ty_imm(db, elem_idx - 1)
-                        .size_of(db, slot_size);
-
+                // Fields are packed into a slot in the same way as Solidity, i.e., the
+                // first field of a slot is placed in its least significant bytes, and
+                // a field starts a new slot if it doesn't fit in the rest of the slot
+                // or if it or the previous field isn't primitive. Fields of an
+                // unpacked struct always start a new slot.
+                //
+                // Offsets are counted from the most significant byte of the slot, so a
+                // primitive field is placed at the end of its slot, and the following
+                // fields of the same slot are placed before it.
                 let elem_ty = self.projection_ty_imm(db, elem_idx);
-                if (offset % slot_size + elem_ty.size_of(db, slot_size)) > slot_size {
-                    offset = round_up(offset, slot_size);
+                let elem_size = elem_ty.size_of(db, slot_size);
+                let slot_start = if elem_idx == 0 {
+                    0
+                } else {
+                    let prev_ty = self.projection_ty_imm(db, elem_idx - 1);
+                    let prev_offset = self.aggregate_elem_offset(db, elem_idx - 1, slot_size);
+                    if self.is_packed(db)
+                        && prev_ty.is_primitive(db)
+                        && elem_ty.is_primitive(db)
+                        && 0 < elem_size
+                        && elem_size <= prev_offset % slot_size
+                    {
+                        return prev_offset - elem_size;
+                    }
+                    round_up(prev_offset + prev_ty.size_of(db, slot_size), slot_size)
+                };
+
+                if elem_ty.is_primitive(db) && elem_size > 0 {
+                    slot_start + slot_size.saturating_sub(elem_size)
+                } else {
+                    slot_start
                 }
-
-                round_up(offset, elem_ty.align_of(db, slot_size))
             }
         }
     }
@@ -366,6 +385,15 @@ impl TypeId {
         matches!(self.data(db).kind, TypeKind::Contract(_))
     }
 
+    /// Returns `false` if the type is a struct or a contract marked with
+    /// `#unpacked`.
+    pub fn is_packed(self, db: &dyn MirDb) -> bool {
+        match &self.data(db).kind {
+            TypeKind::Struct(def) | TypeKind::Contract(def) => def.packed,
+            _ => true,
+        }
+    }
+
     pub fn array_elem_size(self, db: &dyn MirDb, slot_size: usize) -> usize {
         let data = self.data(db);
         if let TypeKind::Array(def) = &data.kind {
@@ -550,6 +578,8 @@ mod tests {
             fields,
             span: Span::dummy(),
             module_id: ModuleId::from_raw_internal(0),
+            packed: true,
+            storage_namespace: None,
         };
         let aggregate = db.mir_intern_type(Type::new(TypeKind::Struct(struct_def), None).into());
 
@@ -593,21 +623,25 @@ mod tests {
             fields,
             span: Span::dummy(),
             module_id: ModuleId::from_raw_internal(0),
+            packed: true,
+            storage_namespace: None,
         };
         let aggregate = db.mir_intern_type(Type::new(TypeKind::Struct(struct_def), None).into());
 
         debug_assert_eq!(aggregate.size_of(&db, 1), 34);
-        debug_assert_eq!(aggregate.size_of(&db, 32), 49);
+        debug_assert_eq!(aggregate.size_of(&db, 32), 64);
 
         debug_assert_eq!(aggregate.align_of(&db, 1), 1);
         debug_assert_eq!(aggregate.align_of(&db, 32), 32);
 
         debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 0, 1), 0);
-        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 0, 32), 0);
+        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 0, 32), 24);
+        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 1, 32), 16);
+        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 2, 32), 15);
         debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 3, 1), 17);
-        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 3, 32), 32);
+        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 3, 32), 48);
         debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 4, 1), 33);
-        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 4, 32), 48);
+        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 4, 32), 47);
     }
 
     #[test]
@@ -630,6 +664,8 @@ mod tests {
             fields: fields_inner,
             span: Span::dummy(),
             module_id: ModuleId::from_raw_internal(0),
+            packed: true,
+            storage_namespace: None,
         };
         let aggregate_inner =
             db.mir_intern_type(Type::new(TypeKind::Struct(struct_def_inner), None).into());
@@ -640,18 +676,71 @@ mod tests {
             fields,
             span: Span::dummy(),
             module_id: ModuleId::from_raw_internal(0),
+            packed: true,
+            storage_namespace: None,
         };
         let aggregate = db.mir_intern_type(Type::new(TypeKind::Struct(struct_def), None).into());
 
         debug_assert_eq!(aggregate.size_of(&db, 1), 35);
-        debug_assert_eq!(aggregate.size_of(&db, 32), 81);
+        debug_assert_eq!(aggregate.size_of(&db, 32), 96);
 
         debug_assert_eq!(aggregate.align_of(&db, 1), 1);
         debug_assert_eq!(aggregate.align_of(&db, 32), 32);
 
         debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 0, 1), 0);
-        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 0, 32), 0);
+        debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 0, 32), 31);
         debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 1, 1), 1);
         debug_assert_eq!(aggregate.aggregate_elem_offset(&db, 1, 32), 32);
     }
+
+    #[test]
+    fn test_packed_struct_type_info() {
+        let db = NewDb::default();
+        let u8 = db.mir_intern_type(Type::new(TypeKind::U8, None).into());
+        let bool = db.mir_intern_type(Type::new(TypeKind::Bool, None).into());
+        let address = db.mir_intern_type(Type::new(TypeKind::Address, None).into());
+        let u128 = db.mir_intern_type(Type::new(TypeKind::U128, None).into());
+
+        let make_struct = |fields, packed| {
+            let struct_def = StructDef {
+                name: "".into(),
+                fields,
+                span: Span::dummy(),
+                module_id: ModuleId::from_raw_internal(0),
+                packed,
+                storage_namespace: None,
+            };
+            db.mir_intern_type(Type::new(TypeKind::Struct(struct_def), None).into())
+        };
+
+        let inner = make_struct(vec![("".into(), u8), ("".into(), u8)], true);
+        let fields = vec![
+            ("".into(), u8),
+            ("".into(), bool),
+            ("".into(), address),
+            ("".into(), u128),
+            ("".into(), inner),
+            ("".into(), u8),
+        ];
+        let packed = make_struct(fields.clone(), true);
+        let unpacked = make_struct(fields, false);
+
+        // The first field of a slot is placed in its least significant bytes.
+        debug_assert_eq!(packed.aggregate_elem_offset(&db, 0, 32), 31);
+        debug_assert_eq!(packed.aggregate_elem_offset(&db, 1, 32), 30);
+        debug_assert_eq!(packed.aggregate_elem_offset(&db, 2, 32), 10);
+        debug_assert_eq!(packed.aggregate_elem_offset(&db, 3, 32), 48);
+        debug_assert_eq!(packed.aggregate_elem_offset(&db, 4, 32), 64);
+        // A field following a struct starts a new slot.
+        debug_assert_eq!(packed.aggregate_elem_offset(&db, 5, 32), 127);
+        debug_assert_eq!(packed.size_of(&db, 32), 128);
+
+        debug_assert_eq!(unpacked.aggregate_elem_offset(&db, 0, 32), 31);
+        debug_assert_eq!(unpacked.aggregate_elem_offset(&db, 1, 32), 63);
+        debug_assert_eq!(unpacked.aggregate_elem_offset(&db, 2, 32), 76);
+        debug_assert_eq!(unpacked.aggregate_elem_offset(&db, 3, 32), 112);
+        debug_assert_eq!(unpacked.aggregate_elem_offset(&db, 4, 32), 128);
+        debug_assert_eq!(unpacked.aggregate_elem_offset(&db, 5, 32), 191);
+        debug_assert_eq!(unpacked.size_of(&db, 32), 192);
+    }
 }
@@ -202,7 +202,11 @@ impl BodyBuilder {
         indices: Vec<ValueId>,
         source: SourceInfo,
     ) -> InstId {
-        let kind = InstKind::AggregateAccess { value, indices };
+        let kind = InstKind::AggregateAccess {
+            value,
+            indices,
+            unchecked: false,
+        };
         let inst = Inst::new(kind, source);
         self.insert_inst(inst)
     }
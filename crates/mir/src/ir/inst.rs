@@ -71,6 +71,9 @@ pub enum InstKind {
     AggregateAccess {
         value: ValueId,
         indices: Vec<ValueId>,
+        /// `true` if all array indices are proven to be in bounds, so they
+        /// are not checked at runtime.
+        unchecked: bool,
     },
 
     MapAccess {
@@ -254,7 +257,7 @@ impl Inst {
 
             Nop | Jump { .. } => ValueIter::Zero,
 
-            AggregateAccess { value, indices } => {
+            AggregateAccess { value, indices, .. } => {
                 ValueIter::one(*value).chain(ValueIter::Slice(indices.iter()))
             }
 
@@ -298,7 +301,7 @@ impl Inst {
 
             Nop | Jump { .. } => ValueIterMut::Zero,
 
-            AggregateAccess { value, indices } => {
+            AggregateAccess { value, indices, .. } => {
                 ValueIterMut::one(value).chain(ValueIterMut::Slice(indices.iter_mut()))
             }
 
@@ -337,6 +340,12 @@ pub enum BinOp {
     Div,
     Mod,
     Pow,
+    /// Addition which is proven not to overflow.
+    UncheckedAdd,
+    /// Subtraction which is proven not to overflow.
+    UncheckedSub,
+    /// Multiplication which is proven not to overflow.
+    UncheckedMul,
     Shl,
     Shr,
     BitOr,
@@ -352,6 +361,17 @@ pub enum BinOp {
     Lt,
 }
 
+impl BinOp {
+    /// Returns `true` if the operation is lowered without an overflow check
+    /// although it's an arithmetic operation.
+    pub fn is_unchecked(self) -> bool {
+        matches!(
+            self,
+            Self::UncheckedAdd | Self::UncheckedSub | Self::UncheckedMul
+        )
+    }
+}
+
 impl fmt::Display for BinOp {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -361,6 +381,9 @@ impl fmt::Display for BinOp {
             Self::Div => write!(w, "/"),
             Self::Mod => write!(w, "%"),
             Self::Pow => write!(w, "**"),
+            Self::UncheckedAdd => write!(w, "+"),
+            Self::UncheckedSub => write!(w, "-"),
+            Self::UncheckedMul => write!(w, "*"),
             Self::Shl => write!(w, "<<"),
             Self::Shr => write!(w, ">>"),
             Self::BitOr => write!(w, "|"),
@@ -437,8 +460,11 @@ pub enum YulIntrinsicOp {
     Mload,
     Mstore,
     Mstore8,
+    Mcopy,
     Sload,
     Sstore,
+    Tload,
+    Tstore,
     Msize,
     Gas,
     Address,
@@ -479,8 +505,11 @@ pub enum YulIntrinsicOp {
     Coinbase,
     Timestamp,
     Number,
+    Difficulty,
     Prevrandao,
     Gaslimit,
+    Blobhash,
+    Blobbasefee,
 }
 impl YulIntrinsicOp {
     pub fn is_terminator(self) -> bool {
@@ -526,8 +555,11 @@ impl fmt::Display for YulIntrinsicOp {
             Self::Mload => "__mload",
             Self::Mstore => "__mstore",
             Self::Mstore8 => "__mstore8",
+            Self::Mcopy => "__mcopy",
             Self::Sload => "__sload",
             Self::Sstore => "__sstore",
+            Self::Tload => "__tload",
+            Self::Tstore => "__tstore",
             Self::Msize => "__msize",
             Self::Gas => "__gas",
             Self::Address => "__address",
@@ -568,8 +600,11 @@ impl fmt::Display for YulIntrinsicOp {
             Self::Coinbase => "__coinbase",
             Self::Timestamp => "__timestamp",
             Self::Number => "__number",
+            Self::Difficulty => "__difficulty",
             Self::Prevrandao => "__prevrandao",
             Self::Gaslimit => "__gaslimit",
+            Self::Blobhash => "__blobhash",
+            Self::Blobbasefee => "__blobbasefee",
         };
 
         write!(w, "{op}")
@@ -612,8 +647,11 @@ impl From<fe_analyzer::builtins::Intrinsic> for YulIntrinsicOp {
             Intrinsic::__mload => Self::Mload,
             Intrinsic::__mstore => Self::Mstore,
             Intrinsic::__mstore8 => Self::Mstore8,
+            Intrinsic::__mcopy => Self::Mcopy,
             Intrinsic::__sload => Self::Sload,
             Intrinsic::__sstore => Self::Sstore,
+            Intrinsic::__tload => Self::Tload,
+            Intrinsic::__tstore => Self::Tstore,
             Intrinsic::__msize => Self::Msize,
             Intrinsic::__gas => Self::Gas,
             Intrinsic::__address => Self::Address,
@@ -654,8 +692,11 @@ impl From<fe_analyzer::builtins::Intrinsic> for YulIntrinsicOp {
             Intrinsic::__coinbase => Self::Coinbase,
             Intrinsic::__timestamp => Self::Timestamp,
             Intrinsic::__number => Self::Number,
+            Intrinsic::__difficulty => Self::Difficulty,
             Intrinsic::__prevrandao => Self::Prevrandao,
             Intrinsic::__gaslimit => Self::Gaslimit,
+            Intrinsic::__blobhash => Self::Blobhash,
+            Intrinsic::__blobbasefee => Self::Blobbasefee,
         }
     }
 }
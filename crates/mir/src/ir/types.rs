@@ -69,6 +69,12 @@ pub struct StructDef {
     pub fields: Vec<(SmolStr, TypeId)>,
     pub span: Span,
     pub module_id: analyzer_items::ModuleId,
+    /// `false` if the struct is marked with `#unpacked`, i.e., each field
+    /// starts a new slot.
+    pub packed: bool,
+    /// The ERC-7201 namespace id of a contract marked with `#namespace`.
+    /// Always `None` for a struct.
+    pub storage_namespace: Option<SmolStr>,
 }
 
 /// A user defined struct type definition.
@@ -3,5 +3,6 @@ pub mod db;
 pub mod graphviz;
 pub mod ir;
 pub mod pretty_print;
+pub mod transform;
 
 mod lower;
@@ -23,7 +23,7 @@ use crate::{
         body_builder::BodyBuilder,
         constant::ConstantValue,
         function::Linkage,
-        inst::{CallType, InstKind},
+        inst::{CallType, InstKind, YulIntrinsicOp},
         value::{AssignableValue, Local},
         BasicBlockId, Constant, FunctionBody, FunctionId, FunctionParam, FunctionSignature, InstId,
         SourceInfo, TypeId, Value, ValueId,
@@ -139,12 +139,28 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
             }
 
             ast::FuncStmt::Assign { target, value } => {
+                if let Some(slot) = self.transient_field_slot(target) {
+                    let value = self.lower_expr_to_value(value);
+                    self.lower_transient_store(slot, value, stmt.into());
+                    return;
+                }
+
                 let result = self.lower_assignable_value(target);
                 let (expr, _ty) = self.lower_expr(value);
                 self.builder.map_result(expr, result)
             }
 
             ast::FuncStmt::AugAssign { target, op, value } => {
+                if let Some(slot) = self.transient_field_slot(target) {
+                    let lhs = self.lower_expr_to_value(target);
+                    let rhs = self.lower_expr_to_value(value);
+                    let inst = self.lower_binop(op.kind, lhs, rhs, stmt.into());
+                    let ty = self.expr_ty(target).deref(self.db);
+                    let value = self.map_to_tmp(inst, ty);
+                    self.lower_transient_store(slot, value, stmt.into());
+                    return;
+                }
+
                 let result = self.lower_assignable_value(target);
                 let lhs = self.lower_expr_to_value(target);
                 let rhs = self.lower_expr_to_value(value);
@@ -338,6 +354,7 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
 
     pub(super) fn lower_expr(&mut self, expr: &Node<ast::Expr>) -> (InstId, TypeId) {
         let mut ty = self.expr_ty(expr);
+        let mut is_transient_load = false;
         let mut inst = match &expr.kind {
             ast::Expr::Ternary {
                 if_expr,
@@ -395,6 +412,17 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
                 self.lower_comp_op(op.kind, lhs, rhs, expr.into())
             }
 
+            ast::Expr::Attribute { .. } if self.transient_field_slot(expr).is_some() => {
+                // The field is loaded here, instead of through a storage
+                // pointer.
+                ty = ty.deref(self.db);
+                is_transient_load = true;
+                let slot = self.transient_field_slot(expr).unwrap();
+                let slot = self.make_u256_imm(slot);
+                self.builder
+                    .yul_intrinsic(YulIntrinsicOp::Tload, vec![slot], expr.into())
+            }
+
             ast::Expr::Attribute { .. } => {
                 let mut indices = vec![];
                 let value = self.lower_aggregate_access(expr, &mut indices);
@@ -497,6 +525,7 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
                     let val = self.inst_result_or_tmp(inst, ty);
                     inst = self.builder.mem_copy(val, expr.into());
                 }
+                AdjustmentKind::Load if is_transient_load => {}
                 AdjustmentKind::Load => {
                     let val = self.inst_result_or_tmp(inst, ty);
                     inst = self.builder.load(val, expr.into());
@@ -1097,6 +1126,24 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
         }
     }
 
+    /// Returns the transient storage slot of the field if `expr` accesses a
+    /// `#transient` field of the contract.
+    fn transient_field_slot(&self, expr: &Node<ast::Expr>) -> Option<BigInt> {
+        if let ast::Expr::Attribute { value, attr } = &expr.kind {
+            let value_ty = self.analyzer_body.expressions[&value.id].typ;
+            if let Type::SelfContract(contract) = value_ty.deref_typ(self.db.upcast()) {
+                return contract.transient_field_slot(self.db.upcast(), &attr.kind);
+            }
+        }
+        None
+    }
+
+    fn lower_transient_store(&mut self, slot: BigInt, value: ValueId, source: SourceInfo) {
+        let slot = self.make_u256_imm(slot);
+        self.builder
+            .yul_intrinsic(YulIntrinsicOp::Tstore, vec![slot, value], source);
+    }
+
     fn lower_aggregate_access(
         &mut self,
         expr: &Node<ast::Expr>,
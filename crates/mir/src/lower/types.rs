@@ -101,9 +101,11 @@ fn lower_contract(db: &dyn MirDb, contract: analyzer_items::ContractId) -> TypeK
     let name = contract.name(db.upcast());
 
     // Note: contract field types are wrapped in SPtr in TypeId::projection_ty
+    // Transient fields aren't part of the storage of the contract.
     let fields = contract
         .fields(db.upcast())
         .iter()
+        .filter(|(_, fid)| !fid.is_transient(db.upcast()))
         .map(|(fname, fid)| {
             let analyzer_type = fid.typ(db.upcast()).unwrap();
             let ty = db.mir_lowered_type(analyzer_type);
@@ -121,6 +123,8 @@ fn lower_contract(db: &dyn MirDb, contract: analyzer_items::ContractId) -> TypeK
         fields,
         span,
         module_id,
+        packed: !contract.is_unpacked(db.upcast()),
+        storage_namespace: contract.storage_namespace(db.upcast()),
     };
     TypeKind::Contract(def)
 }
@@ -149,6 +153,8 @@ fn lower_struct(db: &dyn MirDb, id: analyzer_items::StructId) -> TypeKind {
         fields,
         span,
         module_id,
+        packed: !id.is_unpacked(db.upcast()),
+        storage_namespace: None,
     };
     TypeKind::Struct(def)
 }
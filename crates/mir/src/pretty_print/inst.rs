@@ -37,6 +37,9 @@ impl PrettyPrint for InstId {
             }
 
             InstKind::Binary { op, lhs, rhs } => {
+                if op.is_unchecked() {
+                    write!(w, "unchecked ")?;
+                }
                 lhs.pretty_print(db, store, w)?;
                 write!(w, " {op} ")?;
                 rhs.pretty_print(db, store, w)
@@ -82,7 +85,14 @@ impl PrettyPrint for InstId {
                 src.pretty_print(db, store, w)
             }
 
-            InstKind::AggregateAccess { value, indices } => {
+            InstKind::AggregateAccess {
+                value,
+                indices,
+                unchecked,
+            } => {
+                if *unchecked {
+                    write!(w, "unchecked ")?;
+                }
                 value.pretty_print(db, store, w)?;
                 for index in indices {
                     write!(w, ".<")?;
@@ -1,6 +1,9 @@
 use std::fmt;
 
-use crate::{db::MirDb, ir::function::BodyDataStore};
+use crate::{
+    db::MirDb,
+    ir::{function::BodyDataStore, FunctionBody},
+};
 
 mod inst;
 mod types;
@@ -20,3 +23,26 @@ pub trait PrettyPrint {
         s
     }
 }
+
+/// Prints all blocks of `body` in the layout order.
+pub fn pretty_print_body<W: fmt::Write>(
+    db: &dyn MirDb,
+    body: &FunctionBody,
+    w: &mut W,
+) -> fmt::Result {
+    for block in body.order.iter_block() {
+        writeln!(w, "BB{}:", block.index())?;
+        for inst in body.order.iter_inst(block) {
+            write!(w, "    ")?;
+            inst.pretty_print(db, &body.store, w)?;
+            writeln!(w)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn pretty_body_string(db: &dyn MirDb, body: &FunctionBody) -> String {
+    let mut s = String::new();
+    pretty_print_body(db, body, &mut s).unwrap();
+    s
+}
@@ -0,0 +1,119 @@
+use crate::{
+    analysis::{
+        range::{eval_binary, Interval, RangeState},
+        RangeAnalysis,
+    },
+    db::MirDb,
+    ir::{
+        inst::{BinOp, InstKind},
+        FunctionBody, InstId, TypeKind,
+    },
+};
+
+use super::Pass;
+
+/// Replaces checked arithmetic and bounds checked array accesses which are
+/// proven never to revert by the range analysis with their unchecked
+/// counterparts.
+///
+/// e.g., the loop index of `for x in array` is always in bounds, and `i + 1`
+/// can't overflow after `assert i < n`.
+#[derive(Debug, Default)]
+pub struct CheckEliminator {}
+
+impl CheckEliminator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        if !db.mir_check_elimination_enabled() {
+            return false;
+        }
+
+        let ranges = RangeAnalysis::compute(db, body);
+
+        let mut safe_insts = vec![];
+        ranges.for_each_inst(db, body, |inst, state| {
+            if is_safe(db, body, inst, state) {
+                safe_insts.push(inst);
+            }
+        });
+
+        let changed = !safe_insts.is_empty();
+        for inst in safe_insts {
+            match &mut body.store.inst_data_mut(inst).kind {
+                InstKind::Binary { op, .. } => {
+                    *op = match op {
+                        BinOp::Add => BinOp::UncheckedAdd,
+                        BinOp::Sub => BinOp::UncheckedSub,
+                        BinOp::Mul => BinOp::UncheckedMul,
+                        _ => unreachable!(),
+                    }
+                }
+                InstKind::AggregateAccess { unchecked, .. } => *unchecked = true,
+                _ => unreachable!(),
+            }
+        }
+        changed
+    }
+}
+
+impl Pass for CheckEliminator {
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        CheckEliminator::run(self, db, body)
+    }
+}
+
+/// Returns `true` if `inst` is checked at runtime and the check never fails
+/// in `state`.
+fn is_safe(db: &dyn MirDb, body: &FunctionBody, inst: InstId, state: &RangeState) -> bool {
+    match &body.store.inst_data(inst).kind {
+        InstKind::Binary { op, lhs, rhs } if matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul) => {
+            let result_ty = match body.store.inst_result(inst) {
+                Some(result) => result.ty(db, &body.store).deref(db),
+                None => return false,
+            };
+            let (lhs, rhs) = match (state.range(db, body, *lhs), state.range(db, body, *rhs)) {
+                (Some(lhs), Some(rhs)) => (lhs, rhs),
+                _ => return false,
+            };
+            let range = match eval_binary(*op, &lhs, &rhs) {
+                Some(range) => range,
+                None => return false,
+            };
+            match Interval::of_type(db, result_ty) {
+                Some(ty_range) => range.is_within(&ty_range),
+                None => false,
+            }
+        }
+
+        InstKind::AggregateAccess {
+            value,
+            indices,
+            unchecked: false,
+        } => {
+            let mut has_dynamic_index = false;
+            let mut inner_ty = body.store.value_ty(*value);
+            for &idx in indices {
+                if let TypeKind::Array(def) = &inner_ty.deref(db).data(db).kind {
+                    if !body.store.value_data(idx).is_imm() {
+                        if def.len == 0 {
+                            return false;
+                        }
+                        let in_bounds = Interval::new(0.into(), (def.len - 1).into());
+                        match state.range(db, body, idx) {
+                            Some(range) if range.is_within(&in_bounds) => {}
+                            _ => return false,
+                        }
+                        has_dynamic_index = true;
+                    }
+                }
+                inner_ty = inner_ty.projection_ty(db, body.store.value_data(idx));
+            }
+            has_dynamic_index
+        }
+
+        _ => false,
+    }
+}
@@ -0,0 +1,366 @@
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    db::MirDb,
+    ir::{
+        constant::ConstantValue,
+        inst::{BinOp, CastKind, InstKind, UnOp},
+        value::AssignableValue,
+        BasicBlockId, FunctionBody, Inst, InstId, TypeId, TypeKind, Value, ValueId,
+    },
+};
+
+use super::Pass;
+
+/// Folds instructions whose operands are all known at compile time into
+/// `Bind` of an immediate, and turns branches on a known condition into jumps.
+///
+/// Checked arithmetic is folded only when it can't revert at runtime, i.e.,
+/// the folded result fits in the result type and no division by zero occurs.
+#[derive(Debug, Default)]
+pub struct ConstantFolder {}
+
+impl ConstantFolder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let insts: Vec<_> = body
+            .order
+            .iter_block()
+            .flat_map(|block| body.order.iter_inst(block))
+            .collect();
+
+        let mut changed = false;
+        for inst in insts {
+            changed |= self.fold_inst(db, body, inst);
+        }
+        changed
+    }
+
+    fn fold_inst(&mut self, db: &dyn MirDb, body: &mut FunctionBody, inst: InstId) -> bool {
+        let folded = match &body.store.inst_data(inst).kind {
+            InstKind::Unary { op, value } => fold_unary(db, body, inst, *op, *value),
+            InstKind::Binary { op, lhs, rhs } => fold_binary(db, body, inst, *op, *lhs, *rhs),
+            InstKind::Cast {
+                kind: CastKind::Primitive,
+                value,
+                to,
+            } => fold_cast(db, body, *value, *to),
+
+            InstKind::Branch { cond, then, else_ } => {
+                let dest = match word_of(db, body, *cond) {
+                    Some(cond) if cond.is_zero() => *else_,
+                    Some(_) => *then,
+                    None => return false,
+                };
+                replace_with_jump(body, inst, dest);
+                return true;
+            }
+
+            InstKind::Switch {
+                disc,
+                table,
+                default,
+            } => {
+                let disc = match word_of(db, body, *disc) {
+                    Some(disc) => disc,
+                    None => return false,
+                };
+                let mut dest = *default;
+                for (value, block) in table.iter() {
+                    match word_of(db, body, value) {
+                        Some(value) if value == disc => {
+                            dest = Some(block);
+                            break;
+                        }
+                        Some(_) => {}
+                        None => return false,
+                    }
+                }
+                return match dest {
+                    Some(dest) => {
+                        replace_with_jump(body, inst, dest);
+                        true
+                    }
+                    None => false,
+                };
+            }
+
+            _ => None,
+        };
+
+        let imm = match folded {
+            Some(imm) => imm,
+            None => return false,
+        };
+        let result_ty = match body.store.inst_result(inst) {
+            Some(AssignableValue::Value(result)) => body.store.value_ty(*result),
+            _ => unreachable!(),
+        };
+        let src = body
+            .store
+            .store_value(Value::Immediate { imm, ty: result_ty });
+        let source = body.store.inst_data(inst).source.clone();
+        body.store
+            .replace_inst(inst, Inst::new(InstKind::Bind { src }, source));
+        true
+    }
+}
+
+impl Pass for ConstantFolder {
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        ConstantFolder::run(self, db, body)
+    }
+}
+
+/// A bit width and signedness of a type whose values are represented as a
+/// single word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntTy {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl IntTy {
+    pub fn of(db: &dyn MirDb, ty: TypeId) -> Option<Self> {
+        let (bits, signed) = match ty.data(db).kind {
+            TypeKind::I8 => (8, true),
+            TypeKind::I16 => (16, true),
+            TypeKind::I32 => (32, true),
+            TypeKind::I64 => (64, true),
+            TypeKind::I128 => (128, true),
+            TypeKind::I256 => (256, true),
+            TypeKind::U8 | TypeKind::Bool => (8, false),
+            TypeKind::U16 => (16, false),
+            TypeKind::U32 => (32, false),
+            TypeKind::U64 => (64, false),
+            TypeKind::U128 => (128, false),
+            TypeKind::U256 => (256, false),
+            TypeKind::Address => (160, false),
+            _ => return None,
+        };
+        Some(Self { bits, signed })
+    }
+
+    pub fn min(self) -> BigInt {
+        if self.signed {
+            -(BigInt::one() << (self.bits - 1))
+        } else {
+            BigInt::zero()
+        }
+    }
+
+    pub fn max(self) -> BigInt {
+        if self.signed {
+            (BigInt::one() << (self.bits - 1)) - 1
+        } else {
+            (BigInt::one() << self.bits) - 1
+        }
+    }
+
+    pub fn contains(self, num: &BigInt) -> bool {
+        &self.min() <= num && num <= &self.max()
+    }
+
+    /// Returns a number which `word` represents in the type.
+    pub fn decode(self, word: &BigInt) -> BigInt {
+        if self.signed {
+            to_signed_word(word)
+        } else {
+            word.clone()
+        }
+    }
+
+    /// Truncates `word` to the type in the same way as the runtime does when a
+    /// value is assigned, i.e., sign extension for signed types and masking for
+    /// unsigned types.
+    pub fn extend(self, word: &BigInt) -> BigInt {
+        let low: BigInt = word & ((BigInt::one() << self.bits) - 1);
+        if self.signed && low.bit(u64::from(self.bits - 1)) {
+            to_word(&(low - (BigInt::one() << self.bits)))
+        } else {
+            low
+        }
+    }
+}
+
+/// Returns a word of `value` if it's known at compile time.
+pub fn word_of(db: &dyn MirDb, body: &FunctionBody, value: ValueId) -> Option<BigInt> {
+    let word = match body.store.value_data(value) {
+        Value::Immediate { imm, .. } => imm.clone(),
+        Value::Constant { constant, .. } => match &constant.data(db).value {
+            ConstantValue::Immediate(imm) => imm.clone(),
+            ConstantValue::Bool(flag) => BigInt::from(*flag as u8),
+            ConstantValue::Str(_) => return None,
+        },
+        _ => return None,
+    };
+
+    // Negative constants are emitted in their minimal byte representation, so
+    // we leave them to the runtime.
+    if word.is_negative() || word >= word_modulus() {
+        None
+    } else {
+        Some(word)
+    }
+}
+
+fn fold_unary(
+    db: &dyn MirDb,
+    body: &FunctionBody,
+    inst: InstId,
+    op: UnOp,
+    value: ValueId,
+) -> Option<BigInt> {
+    let result_ty = result_int_ty(db, body, inst)?;
+    let value_ty = IntTy::of(db, body.store.value_ty(value))?;
+    let word = word_of(db, body, value)?;
+
+    match op {
+        UnOp::Not => Some(bool_word(word.is_zero())),
+        UnOp::Inv => Some(result_ty.extend(&(word_modulus() - 1 - word))),
+        UnOp::Neg => {
+            let num = -value_ty.decode(&word);
+            result_ty.contains(&num).then(|| to_word(&num))
+        }
+    }
+}
+
+fn fold_binary(
+    db: &dyn MirDb,
+    body: &FunctionBody,
+    inst: InstId,
+    op: BinOp,
+    lhs: ValueId,
+    rhs: ValueId,
+) -> Option<BigInt> {
+    let result_ty = result_int_ty(db, body, inst)?;
+    let lhs_ty = IntTy::of(db, body.store.value_ty(lhs))?;
+    let rhs_ty = IntTy::of(db, body.store.value_ty(rhs))?;
+    let lhs = word_of(db, body, lhs)?;
+    let rhs = word_of(db, body, rhs)?;
+
+    match op {
+        BinOp::Add
+        | BinOp::Sub
+        | BinOp::Mul
+        | BinOp::Div
+        | BinOp::Mod
+        | BinOp::Pow
+        | BinOp::UncheckedAdd
+        | BinOp::UncheckedSub
+        | BinOp::UncheckedMul => {
+            let lhs = lhs_ty.decode(&lhs);
+            let rhs = rhs_ty.decode(&rhs);
+            if !lhs_ty.contains(&lhs) || !rhs_ty.contains(&rhs) {
+                return None;
+            }
+
+            let num = match op {
+                BinOp::Add | BinOp::UncheckedAdd => lhs + rhs,
+                BinOp::Sub | BinOp::UncheckedSub => lhs - rhs,
+                BinOp::Mul | BinOp::UncheckedMul => lhs * rhs,
+                BinOp::Div if rhs.is_zero() => return None,
+                BinOp::Div => lhs / rhs,
+                BinOp::Mod if rhs.is_zero() => return None,
+                BinOp::Mod => lhs % rhs,
+                BinOp::Pow => {
+                    let exp = rhs.to_u32().filter(|exp| *exp <= 256)?;
+                    num_traits::pow::Pow::pow(lhs, exp)
+                }
+                _ => unreachable!(),
+            };
+            result_ty.contains(&num).then(|| to_word(&num))
+        }
+
+        BinOp::Shl => {
+            let word = match rhs.to_usize().filter(|shift| *shift < 256) {
+                Some(shift) => (lhs << shift) % word_modulus(),
+                None => BigInt::zero(),
+            };
+            Some(result_ty.extend(&word))
+        }
+
+        BinOp::Shr => {
+            let shift = rhs.to_usize().unwrap_or(256).min(256);
+            let word = if result_ty.signed {
+                to_word(&(to_signed_word(&lhs) >> shift))
+            } else {
+                lhs >> shift
+            };
+            Some(result_ty.extend(&word))
+        }
+
+        BinOp::BitOr | BinOp::LogicalOr => Some(result_ty.extend(&(lhs | rhs))),
+        BinOp::BitXor => Some(result_ty.extend(&(lhs ^ rhs))),
+        BinOp::BitAnd | BinOp::LogicalAnd => Some(result_ty.extend(&(lhs & rhs))),
+
+        BinOp::Eq => Some(bool_word(lhs == rhs)),
+        BinOp::Ne => Some(bool_word(lhs != rhs)),
+        BinOp::Ge | BinOp::Gt | BinOp::Le | BinOp::Lt => {
+            let (lhs, rhs) = if lhs_ty.signed {
+                (to_signed_word(&lhs), to_signed_word(&rhs))
+            } else {
+                (lhs, rhs)
+            };
+            let res = match op {
+                BinOp::Ge => lhs >= rhs,
+                BinOp::Gt => lhs > rhs,
+                BinOp::Le => lhs <= rhs,
+                BinOp::Lt => lhs < rhs,
+                _ => unreachable!(),
+            };
+            Some(bool_word(res))
+        }
+    }
+}
+
+fn fold_cast(db: &dyn MirDb, body: &FunctionBody, value: ValueId, to: TypeId) -> Option<BigInt> {
+    let from_ty = IntTy::of(db, body.store.value_ty(value))?;
+    let to_ty = IntTy::of(db, to)?;
+    let word = word_of(db, body, value)?;
+    Some(to_ty.extend(&from_ty.extend(&word)))
+}
+
+fn replace_with_jump(body: &mut FunctionBody, inst: InstId, dest: BasicBlockId) {
+    let source = body.store.inst_data(inst).source.clone();
+    body.store
+        .replace_inst(inst, Inst::new(InstKind::Jump { dest }, source));
+}
+
+/// Returns the type of the instruction result if the result is a plain value
+/// which can hold an immediate.
+fn result_int_ty(db: &dyn MirDb, body: &FunctionBody, inst: InstId) -> Option<IntTy> {
+    match body.store.inst_result(inst)? {
+        AssignableValue::Value(value) => IntTy::of(db, body.store.value_ty(*value)),
+        _ => None,
+    }
+}
+
+/// Returns a two's complement word representation of `num`.
+pub fn to_word(num: &BigInt) -> BigInt {
+    if num.is_negative() {
+        num + word_modulus()
+    } else {
+        num.clone()
+    }
+}
+
+fn to_signed_word(word: &BigInt) -> BigInt {
+    if word.bit(255) {
+        word - word_modulus()
+    } else {
+        word.clone()
+    }
+}
+
+fn word_modulus() -> BigInt {
+    BigInt::one() << 256
+}
+
+fn bool_word(flag: bool) -> BigInt {
+    BigInt::from(flag as u8)
+}
@@ -0,0 +1,195 @@
+use fxhash::FxHashMap;
+
+use crate::{
+    analysis::{ControlFlowGraph, DomTree},
+    db::MirDb,
+    ir::{
+        inst::{InstKind, UnOp},
+        value::AssignableValue,
+        FunctionBody, InstId, Value, ValueId,
+    },
+};
+
+use super::{
+    const_fold::{word_of, IntTy},
+    Pass,
+};
+
+/// Replaces uses of a value which is a copy of an immediate, a constant or
+/// another temporary with its source.
+///
+/// A local is propagated only if it's assigned exactly once, and only to the
+/// uses dominated by the assignment. Such a local is also propagated as a
+/// source of a copy, since it never changes after the assignment.
+#[derive(Debug, Default)]
+pub struct CopyPropagator {
+    copies: FxHashMap<ValueId, CopySource>,
+}
+
+impl CopyPropagator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        self.collect_copies(db, body);
+        if self.copies.is_empty() {
+            return false;
+        }
+
+        let cfg = ControlFlowGraph::compute(body);
+        let domtree = DomTree::compute(&cfg);
+
+        let mut changed = false;
+        let blocks: Vec<_> = body.order.iter_block().collect();
+        for block in blocks {
+            let insts: Vec<_> = body.order.iter_inst(block).collect();
+            for inst in insts {
+                changed |= self.rewrite_inst(body, &domtree, inst);
+            }
+        }
+
+        self.copies.clear();
+        changed
+    }
+
+    fn collect_copies(&mut self, db: &dyn MirDb, body: &mut FunctionBody) {
+        let mut def_num: FxHashMap<ValueId, usize> = FxHashMap::default();
+        let mut candidates = vec![];
+        for block in body.order.iter_block() {
+            for inst in body.order.iter_inst(block) {
+                let result = match body.store.inst_result(inst) {
+                    Some(AssignableValue::Value(result)) => *result,
+                    _ => continue,
+                };
+                *def_num.entry(result).or_default() += 1;
+
+                if let InstKind::Bind { src } = body.store.inst_data(inst).kind {
+                    candidates.push((inst, result, src));
+                }
+            }
+        }
+
+        for (inst, dest, src) in candidates {
+            let ty = body.store.value_ty(dest);
+            let int_ty = match IntTy::of(db, ty) {
+                Some(int_ty) if body.store.value_ty(src) == ty => int_ty,
+                _ => continue,
+            };
+
+            let is_single_def = |value: ValueId| match body.store.value_data(value) {
+                Value::Local(local) => !local.is_arg && def_num.get(&value) == Some(&1),
+                _ => false,
+            };
+            let needs_dominance = match body.store.value_data(dest) {
+                Value::Temporary { .. } => false,
+                Value::Local(_) if is_single_def(dest) => true,
+                _ => continue,
+            };
+
+            let src = match body.store.value_data(src) {
+                Value::Immediate { .. } => src,
+                Value::Constant { .. } => match word_of(db, body, src) {
+                    Some(word) if int_ty.extend(&word) == word => {
+                        body.store.store_value(Value::Immediate { imm: word, ty })
+                    }
+                    _ => continue,
+                },
+                // A temporary is defined before the copy on every path reaching the copy,
+                // and so is a single def local which is read by the copy.
+                Value::Temporary { .. } => src,
+                Value::Local(_) if is_single_def(src) => src,
+                _ => continue,
+            };
+
+            self.copies.insert(
+                dest,
+                CopySource {
+                    src,
+                    def: needs_dominance.then_some(inst),
+                },
+            );
+        }
+    }
+
+    fn rewrite_inst(&self, body: &mut FunctionBody, domtree: &DomTree, inst: InstId) -> bool {
+        match &body.store.inst_data(inst).kind {
+            // `Declare` is not a use of the local. Array indices are bounds checked only
+            // if they are not immediates, and negation of an immediate is not
+            // checked against overflow, so we keep them as they are.
+            InstKind::Declare { .. }
+            | InstKind::AggregateAccess { .. }
+            | InstKind::Unary { op: UnOp::Neg, .. } => return false,
+            _ => {}
+        }
+
+        let args: Vec<_> = body.store.inst_data(inst).args().collect();
+        let mut new_args = Vec::with_capacity(args.len());
+        let mut changed = false;
+        for arg in args {
+            let new_arg = self.resolve(body, domtree, arg, inst);
+            changed |= new_arg != arg;
+            new_args.push(new_arg);
+        }
+
+        if changed {
+            for (arg, new_arg) in body.store.inst_data_mut(inst).args_mut().zip(new_args) {
+                *arg = new_arg;
+            }
+        }
+        changed
+    }
+
+    /// Returns the source of the copy chain starting from `value` which is
+    /// available at `user`.
+    fn resolve(
+        &self,
+        body: &FunctionBody,
+        domtree: &DomTree,
+        mut value: ValueId,
+        user: InstId,
+    ) -> ValueId {
+        while let Some(copy) = self.copies.get(&value) {
+            if let Some(def) = copy.def {
+                if !dominates(body, domtree, def, user) {
+                    break;
+                }
+            }
+            value = copy.src;
+        }
+        value
+    }
+}
+
+impl Pass for CopyPropagator {
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        CopyPropagator::run(self, db, body)
+    }
+}
+
+#[derive(Debug)]
+struct CopySource {
+    src: ValueId,
+    /// The assignment of a local, which must dominate the uses to be
+    /// rewritten. `None` for temporaries.
+    def: Option<InstId>,
+}
+
+/// Returns `true` if `def` is executed before `user` on every path reaching
+/// `user`.
+pub(super) fn dominates(body: &FunctionBody, domtree: &DomTree, def: InstId, user: InstId) -> bool {
+    let def_block = body.order.inst_block(def);
+    let user_block = body.order.inst_block(user);
+    if def_block != user_block {
+        return domtree.strictly_dominates(def_block, user_block);
+    }
+
+    let mut next = body.order.next_inst(def);
+    while let Some(inst) = next {
+        if inst == user {
+            return true;
+        }
+        next = body.order.next_inst(inst);
+    }
+    false
+}
@@ -0,0 +1,180 @@
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{
+    analysis::ControlFlowGraph,
+    db::MirDb,
+    ir::{
+        body_cursor::{BodyCursor, CursorLocation},
+        inst::{BinOp, InstKind, UnOp},
+        value::AssignableValue,
+        FunctionBody, InstId, ValueId,
+    },
+};
+
+use super::Pass;
+
+/// Removes blocks unreachable from the entry block and instructions whose
+/// results are never used and which have no side effects.
+///
+/// Checked arithmetic is never removed because it can revert at runtime.
+#[derive(Debug, Default)]
+pub struct DeadCodeEliminator {
+    uses: FxHashMap<ValueId, usize>,
+}
+
+impl DeadCodeEliminator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let mut changed = self.remove_dead_blocks(body);
+
+        self.count_uses(body);
+        loop {
+            let dead_insts = self.collect_dead_insts(db, body);
+            if dead_insts.is_empty() {
+                break;
+            }
+
+            for inst in dead_insts {
+                self.remove_inst(body, inst);
+            }
+            changed = true;
+        }
+
+        self.uses.clear();
+        changed
+    }
+
+    fn remove_dead_blocks(&mut self, body: &mut FunctionBody) -> bool {
+        let cfg = ControlFlowGraph::compute(body);
+        let reachable: FxHashSet<_> = cfg.post_order().collect();
+        let dead_blocks: Vec<_> = body
+            .order
+            .iter_block()
+            .filter(|block| !reachable.contains(block))
+            .collect();
+
+        for &block in &dead_blocks {
+            let mut cursor = BodyCursor::new(body, CursorLocation::BlockTop(block));
+            cursor.remove_block();
+        }
+        !dead_blocks.is_empty()
+    }
+
+    fn count_uses(&mut self, body: &FunctionBody) {
+        for block in body.order.iter_block() {
+            for inst in body.order.iter_inst(block) {
+                for value in inst_uses(body, inst) {
+                    *self.uses.entry(value).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    fn collect_dead_insts(&self, db: &dyn MirDb, body: &FunctionBody) -> Vec<InstId> {
+        // Locals which are still assigned somewhere must keep their declaration.
+        let assigned: FxHashSet<_> = body
+            .order
+            .iter_block()
+            .flat_map(|block| body.order.iter_inst(block))
+            .filter_map(|inst| match body.store.inst_result(inst) {
+                Some(AssignableValue::Value(value)) => Some(*value),
+                _ => None,
+            })
+            .collect();
+
+        body.order
+            .iter_block()
+            .flat_map(|block| body.order.iter_inst(block))
+            .filter(|&inst| match body.store.inst_data(inst).kind {
+                InstKind::Declare { local } => !self.is_used(local) && !assigned.contains(&local),
+                _ => self.is_removable(db, body, inst),
+            })
+            .collect()
+    }
+
+    fn is_removable(&self, db: &dyn MirDb, body: &FunctionBody, inst: InstId) -> bool {
+        let is_pure = match &body.store.inst_data(inst).kind {
+            InstKind::Nop => return true,
+
+            InstKind::Unary { op, value } => {
+                // Negation of a non-immediate value is checked against overflow.
+                *op != UnOp::Neg || body.store.value_data(*value).is_imm()
+            }
+            InstKind::Binary { op, .. } => !matches!(
+                op,
+                BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::Pow
+            ),
+            InstKind::Cast { .. } | InstKind::Bind { .. } | InstKind::MapAccess { .. } => true,
+            _ => false,
+        };
+        if !is_pure {
+            return false;
+        }
+
+        // Assignment to a pointer typed value is a store to the memory or the
+        // storage unless the assigned value is a pointer to the same location, so
+        // only primitive results and pointer copies can be removed.
+        match body.store.inst_result(inst) {
+            Some(AssignableValue::Value(value)) => {
+                let ty = body.store.value_ty(*value);
+                let rhs_ty = match &body.store.inst_data(inst).kind {
+                    InstKind::Bind { src } => Some(body.store.value_ty(*src)),
+                    InstKind::MapAccess { .. } => Some(ty),
+                    _ => None,
+                };
+                let is_ptr_copy = ty.is_ptr(db)
+                    && matches!(rhs_ty, Some(rhs_ty) if rhs_ty.is_ptr(db) && rhs_ty.is_mptr(db) == ty.is_mptr(db));
+                !self.is_used(*value) && (ty.is_primitive(db) || is_ptr_copy)
+            }
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    fn remove_inst(&mut self, body: &mut FunctionBody, inst: InstId) {
+        for value in inst_uses(body, inst) {
+            if let Some(num) = self.uses.get_mut(&value) {
+                *num -= 1;
+            }
+        }
+        body.store.remove_inst_result(inst);
+        body.order.remove_inst(inst);
+    }
+
+    fn is_used(&self, value: ValueId) -> bool {
+        matches!(self.uses.get(&value), Some(num) if *num > 0)
+    }
+}
+
+impl Pass for DeadCodeEliminator {
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        DeadCodeEliminator::run(self, db, body)
+    }
+}
+
+/// Returns values used by `inst`.
+fn inst_uses(body: &FunctionBody, inst: InstId) -> Vec<ValueId> {
+    let inst_data = body.store.inst_data(inst);
+    let mut uses: Vec<_> = match inst_data.kind {
+        InstKind::Declare { .. } => vec![],
+        _ => inst_data.args().collect(),
+    };
+
+    // A partial assignment to an aggregate or a map also uses its base value.
+    if let Some(mut result) = body.store.inst_result(inst) {
+        while let AssignableValue::Aggregate { lhs, idx: arg }
+        | AssignableValue::Map { lhs, key: arg } = result
+        {
+            uses.push(*arg);
+            if let AssignableValue::Value(base) = lhs.as_ref() {
+                uses.push(*base);
+            }
+            result = lhs;
+        }
+    }
+
+    uses
+}
@@ -0,0 +1,429 @@
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{
+    analysis::{ControlFlowGraph, DomTree, LoopTree},
+    db::MirDb,
+    ir::{
+        inst::{CallType, InstKind, SwitchTable},
+        value::{AssignableValue, Local},
+        BasicBlock, BasicBlockId, FunctionBody, FunctionId, Inst, InstId, TypeId, Value, ValueId,
+    },
+};
+
+use super::Pass;
+
+/// A callee with at most this many instructions is always inlined.
+const SMALL_FUNCTION_SIZE: usize = 8;
+
+/// A callee is inlined if its size multiplied by the number of its call sites
+/// in the caller doesn't exceed this budget.
+const INLINE_BUDGET: usize = 32;
+
+/// Inlining stops once the caller grows beyond this many instructions.
+const MAX_CALLER_SIZE: usize = 2048;
+
+/// Calls in inlined bodies are inlined up to this depth.
+const MAX_INLINE_DEPTH: usize = 4;
+
+/// Splices bodies of internal callees into the caller.
+///
+/// Whether a call is inlined is decided by the callee's `#inline`/`#noinline`
+/// attributes and a cost model based on the callee's instruction count and
+/// the number of call sites of the callee in the caller. Calls in inlined
+/// bodies are inlined up to a fixed depth, so recursive functions never cause
+/// unbounded growth.
+///
+/// Only callees whose parameters and return value are passed by value, i.e.,
+/// primitives, zero-sized types and contracts, are inlined. Callees that
+/// return from inside a loop aren't inlined either, as the inlined loop would
+/// have more than one exit, which the Yul code generator doesn't support.
+#[derive(Debug, Default)]
+pub struct Inliner {}
+
+impl Inliner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let mut call_sites: FxHashMap<FunctionId, usize> = FxHashMap::default();
+        let insts: Vec<_> = body
+            .order
+            .iter_block()
+            .flat_map(|block| body.order.iter_inst(block))
+            .collect();
+        let mut calls: Vec<_> = internal_calls(body, insts)
+            .map(|(call, callee)| {
+                *call_sites.entry(callee).or_default() += 1;
+                (call, callee, 0)
+            })
+            .collect();
+        calls.reverse();
+
+        let mut caller_size = inst_num(body);
+        let mut changed = false;
+        while let Some((call, callee, depth)) = calls.pop() {
+            if caller_size > MAX_CALLER_SIZE {
+                break;
+            }
+
+            if depth > MAX_INLINE_DEPTH
+                || !self.should_inline(db, body.fid, callee, call_sites[&callee])
+            {
+                continue;
+            }
+
+            let callee_body = db.mir_lowered_func_body(callee);
+            if let Some(spliced) = inline_call(db, body, call, &callee_body) {
+                caller_size += inst_num(&callee_body);
+                changed = true;
+
+                for (call, callee) in internal_calls(body, spliced) {
+                    *call_sites.entry(callee).or_default() += 1;
+                    calls.push((call, callee, depth + 1));
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn should_inline(
+        &self,
+        db: &dyn MirDb,
+        caller: FunctionId,
+        callee: FunctionId,
+        call_sites: usize,
+    ) -> bool {
+        if caller == callee || callee.is_noinline(db) || !is_passed_by_value(db, callee) {
+            return false;
+        }
+
+        if callee.is_inline(db) {
+            return true;
+        }
+
+        let size = inst_num(&db.mir_lowered_func_body(callee));
+        size <= SMALL_FUNCTION_SIZE || size * call_sites <= INLINE_BUDGET
+    }
+}
+
+impl Pass for Inliner {
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        Inliner::run(self, db, body)
+    }
+}
+
+/// Replaces `call` with the body of the callee. Returns instructions copied
+/// from the callee, or `None` if the call can't be inlined.
+fn inline_call(
+    db: &dyn MirDb,
+    body: &mut FunctionBody,
+    call: InstId,
+    callee_body: &FunctionBody,
+) -> Option<Vec<InstId>> {
+    let args = match &body.store.inst_data(call).kind {
+        InstKind::Call { args, .. } => args.clone(),
+        _ => unreachable!(),
+    };
+
+    let params: Vec<_> = callee_body.store.func_args().collect();
+    if params.len() != args.len()
+        || params.iter().zip(args.iter()).any(|(param, arg)| {
+            !is_compatible_arg(
+                db,
+                callee_body.store.value_ty(*param),
+                body.store.value_ty(*arg),
+            )
+        })
+    {
+        return None;
+    }
+
+    // Parameters are substituted with arguments, so they must not be reassigned
+    // in the callee.
+    if callee_body
+        .order
+        .iter_block()
+        .flat_map(|block| callee_body.order.iter_inst(block))
+        .any(|inst| {
+            matches!(callee_body.store.inst_result(inst),
+                Some(AssignableValue::Value(value)) if params.contains(value))
+        })
+    {
+        return None;
+    }
+
+    if has_multi_exit_loop(callee_body) {
+        return None;
+    }
+
+    let mut splicer = Splicer::new(body, callee_body);
+    for (param, arg) in params.into_iter().zip(args) {
+        splicer.value_map.insert(param, arg);
+    }
+    splicer.splice(db, call);
+    Some(splicer.inst_map.into_values().collect())
+}
+
+/// Returns `true` if a loop of `body` has more than one exit, which happens
+/// when the function returns from inside the loop.
+fn has_multi_exit_loop(body: &FunctionBody) -> bool {
+    let cfg = ControlFlowGraph::compute(body);
+    let domtree = DomTree::compute(&cfg);
+    let loop_tree = LoopTree::compute(&cfg, &domtree);
+    for lp in loop_tree.loops() {
+        let mut exits: FxHashSet<BasicBlockId> = FxHashSet::default();
+        for block in body.order.iter_block() {
+            if loop_tree.is_block_in_loop(block, lp) {
+                exits.extend(
+                    cfg.succs(block)
+                        .iter()
+                        .filter(|succ| !loop_tree.is_block_in_loop(**succ, lp)),
+                );
+            }
+        }
+        if exits.len() > 1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns internal calls and their callees among `insts`.
+fn internal_calls(
+    body: &FunctionBody,
+    insts: Vec<InstId>,
+) -> impl Iterator<Item = (InstId, FunctionId)> + '_ {
+    insts
+        .into_iter()
+        .filter_map(|inst| match body.store.inst_data(inst).kind {
+            InstKind::Call {
+                func,
+                call_type: CallType::Internal,
+                ..
+            } => Some((inst, func)),
+            _ => None,
+        })
+}
+
+struct Splicer<'a> {
+    body: &'a mut FunctionBody,
+    callee_body: &'a FunctionBody,
+    value_map: FxHashMap<ValueId, ValueId>,
+    inst_map: FxHashMap<InstId, InstId>,
+    block_map: FxHashMap<BasicBlockId, BasicBlockId>,
+}
+
+impl<'a> Splicer<'a> {
+    fn new(body: &'a mut FunctionBody, callee_body: &'a FunctionBody) -> Self {
+        Self {
+            body,
+            callee_body,
+            value_map: FxHashMap::default(),
+            inst_map: FxHashMap::default(),
+            block_map: FxHashMap::default(),
+        }
+    }
+
+    fn splice(&mut self, db: &dyn MirDb, call: InstId) {
+        let call_block = self.body.order.inst_block(call);
+        let call_source = self.body.store.inst_data(call).source.clone();
+
+        // Split the call block at the call site; the continuation starts with
+        // the call itself, which is replaced with a copy of the returned value.
+        let cont_block = self.body.store.store_block(BasicBlock {});
+        self.body
+            .order
+            .insert_block_after_block(cont_block, call_block);
+        let mut next = Some(call);
+        while let Some(inst) = next {
+            next = self.body.order.next_inst(inst);
+            self.body.order.remove_inst(inst);
+            self.body.order.append_inst(inst, cont_block);
+        }
+
+        // Returned values are passed through a local so that they are
+        // available in the continuation regardless of the returning block.
+        let ret_ty = self
+            .body
+            .store
+            .inst_result(call)
+            .map(|result| result.ty(db, &self.body.store));
+        let ret_local = match ret_ty {
+            Some(ty) if !ty.is_zero_sized(db) => {
+                let local = self.make_ret_local(ty);
+                let declare = self
+                    .body
+                    .store
+                    .store_inst(Inst::new(InstKind::Declare { local }, call_source.clone()));
+                self.body.order.append_inst(declare, call_block);
+                self.body.store.replace_inst(
+                    call,
+                    Inst::new(InstKind::Bind { src: local }, call_source.clone()),
+                );
+                Some(local)
+            }
+            _ => {
+                self.body.store.remove_inst_result(call);
+                self.body.store.replace_inst(call, Inst::nop());
+                None
+            }
+        };
+
+        // Copy callee blocks between the call block and the continuation.
+        let mut last_block = call_block;
+        for block in self.callee_body.order.iter_block() {
+            let new_block = self.body.store.store_block(BasicBlock {});
+            self.body
+                .order
+                .insert_block_after_block(new_block, last_block);
+            self.block_map.insert(block, new_block);
+            last_block = new_block;
+
+            for inst in self.callee_body.order.iter_inst(block) {
+                let new_inst = self.body.store.store_inst(Inst::nop());
+                self.body.order.append_inst(new_inst, new_block);
+                self.inst_map.insert(inst, new_inst);
+            }
+        }
+
+        let entry = self.block_map[&self.callee_body.order.entry()];
+        let jump = self
+            .body
+            .store
+            .store_inst(Inst::new(InstKind::Jump { dest: entry }, call_source));
+        self.body.order.append_inst(jump, call_block);
+
+        let insts: Vec<_> = self.inst_map.iter().map(|(k, v)| (*k, *v)).collect();
+        for (inst, new_inst) in insts {
+            self.copy_inst(inst, new_inst, ret_local, cont_block);
+        }
+    }
+
+    fn copy_inst(
+        &mut self,
+        inst: InstId,
+        new_inst: InstId,
+        ret_local: Option<ValueId>,
+        cont_block: BasicBlockId,
+    ) {
+        let mut data = self.callee_body.store.inst_data(inst).clone();
+        if let InstKind::Return { arg } = data.kind {
+            if let (Some(arg), Some(ret_local)) = (arg, ret_local) {
+                let src = self.map_value(arg);
+                let bind = self
+                    .body
+                    .store
+                    .store_inst(Inst::new(InstKind::Bind { src }, data.source.clone()));
+                self.body.order.insert_inst_before_inst(bind, new_inst);
+                self.body.store.map_result(bind, ret_local.into());
+            }
+            data.kind = InstKind::Jump { dest: cont_block };
+            self.body.store.replace_inst(new_inst, data);
+            return;
+        }
+
+        for arg in data.args_mut() {
+            *arg = self.map_value(*arg);
+        }
+        match &mut data.kind {
+            InstKind::Jump { dest } => *dest = self.block_map[dest],
+            InstKind::Branch { then, else_, .. } => {
+                *then = self.block_map[then];
+                *else_ = self.block_map[else_];
+            }
+            InstKind::Switch { table, default, .. } => {
+                let mut new_table = SwitchTable::default();
+                for (value, block) in table.iter() {
+                    new_table.add_arm(value, self.block_map[&block]);
+                }
+                *table = new_table;
+                *default = default.map(|block| self.block_map[&block]);
+            }
+            _ => {}
+        }
+        self.body.store.replace_inst(new_inst, data);
+
+        if let Some(result) = self.callee_body.store.inst_result(inst) {
+            let result = self.map_assignable_value(result);
+            self.body.store.map_result(new_inst, result);
+        }
+    }
+
+    fn map_value(&mut self, value: ValueId) -> ValueId {
+        if let Some(new_value) = self.value_map.get(&value) {
+            return *new_value;
+        }
+
+        let new_value = match self.callee_body.store.value_data(value).clone() {
+            Value::Temporary { inst, ty } => Value::Temporary {
+                inst: self.inst_map[&inst],
+                ty,
+            },
+            value => value,
+        };
+        let new_value = self.body.store.store_value(new_value);
+        self.value_map.insert(value, new_value);
+        new_value
+    }
+
+    fn map_assignable_value(&mut self, value: &AssignableValue) -> AssignableValue {
+        match value {
+            AssignableValue::Value(value) => self.map_value(*value).into(),
+            AssignableValue::Aggregate { lhs, idx } => AssignableValue::Aggregate {
+                lhs: self.map_assignable_value(lhs).into(),
+                idx: self.map_value(*idx),
+            },
+            AssignableValue::Map { lhs, key } => AssignableValue::Map {
+                lhs: self.map_assignable_value(lhs).into(),
+                key: self.map_value(*key),
+            },
+        }
+    }
+
+    fn make_ret_local(&mut self, ty: TypeId) -> ValueId {
+        let local = Local::tmp_local("$ret".into(), ty);
+        self.body.store.store_value(Value::Local(local))
+    }
+}
+
+/// Returns `true` if all parameters and the return value of `func` are passed
+/// by value.
+fn is_passed_by_value(db: &dyn MirDb, func: FunctionId) -> bool {
+    let sig = func.signature(db);
+    let is_by_value = |ty: TypeId| ty.is_primitive(db) || ty.is_zero_sized(db);
+
+    sig.params
+        .iter()
+        .all(|param| is_by_value(param.ty) || param.ty.is_contract(db))
+        && match sig.return_type {
+            Some(ty) => is_by_value(ty),
+            None => true,
+        }
+}
+
+/// Returns `true` if an argument of `arg_ty` can be substituted for a
+/// parameter of `param_ty`. A pointer to a zero-sized type, e.g., a mutable
+/// `Context`, is interchangeable with the type itself.
+fn is_compatible_arg(db: &dyn MirDb, param_ty: TypeId, arg_ty: TypeId) -> bool {
+    if param_ty == arg_ty {
+        return true;
+    }
+
+    let deref_ty = param_ty.deref(db);
+    deref_ty == arg_ty.deref(db) && deref_ty.is_zero_sized(db)
+}
+
+fn inst_num(body: &FunctionBody) -> usize {
+    body.order
+        .iter_block()
+        .flat_map(|block| body.order.iter_inst(block))
+        .filter(|inst| {
+            !matches!(
+                body.store.inst_data(*inst).kind,
+                InstKind::Declare { .. } | InstKind::Nop
+            )
+        })
+        .count()
+}
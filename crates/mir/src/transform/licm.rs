@@ -0,0 +1,374 @@
+use fxhash::FxHashSet;
+
+use crate::{
+    analysis::{loop_tree::LoopId, ControlFlowGraph, DomTree, LoopTree},
+    db::MirDb,
+    ir::{
+        inst::{BinOp, CastKind, InstKind, UnOp},
+        value::AssignableValue,
+        BasicBlock, BasicBlockId, FunctionBody, Inst, InstId, Value, ValueId,
+    },
+};
+
+use super::{load_elim::is_call_constant, Pass};
+
+/// Hoists loop invariant computations into the preheader of the loop.
+///
+/// Computations which never revert, e.g., unchecked arithmetic, casts and map
+/// slot hashes, are hoisted from anywhere in the loop. Computations which may
+/// revert or read the memory or the storage, e.g., checked arithmetic and
+/// bounds checked array accesses, are hoisted only if they are executed first
+/// in the loop header, because the header is executed whenever the preheader
+/// is. Reads are hoisted only from loops which never write to the memory or
+/// the storage.
+///
+/// Inner loops are processed first, so that a computation can be hoisted out of
+/// a loop nest step by step.
+#[derive(Debug, Default)]
+pub struct LoopInvariantCodeMotion {}
+
+impl LoopInvariantCodeMotion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let (_, lpt) = compute_loop_tree(body);
+        let headers: Vec<_> = lpt.loops().map(|lp| lpt.loop_header(lp)).collect();
+
+        let mut changed = false;
+        for header in headers.into_iter().rev() {
+            // Preheaders inserted for other loops change the CFG, so the analyses
+            // are recomputed for each loop.
+            let (cfg, lpt) = compute_loop_tree(body);
+            let lp = lpt.loop_of_block(header).unwrap();
+            changed |= LoopHoister::new(db, body, &cfg, &lpt, lp).hoist();
+        }
+
+        changed
+    }
+}
+
+impl Pass for LoopInvariantCodeMotion {
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        LoopInvariantCodeMotion::run(self, db, body)
+    }
+}
+
+struct LoopHoister<'a> {
+    db: &'a dyn MirDb,
+    body: &'a mut FunctionBody,
+    cfg: &'a ControlFlowGraph,
+    header: BasicBlockId,
+    /// Blocks in the loop in reverse post order.
+    blocks: Vec<BasicBlockId>,
+    /// Locals which are assigned in the loop.
+    assigned: FxHashSet<ValueId>,
+    /// `true` if the loop may write to the memory or the storage.
+    has_writes: bool,
+    preheader: Option<BasicBlockId>,
+}
+
+impl<'a> LoopHoister<'a> {
+    fn new(
+        db: &'a dyn MirDb,
+        body: &'a mut FunctionBody,
+        cfg: &'a ControlFlowGraph,
+        lpt: &LoopTree,
+        lp: LoopId,
+    ) -> Self {
+        let mut blocks: Vec<_> = lpt.iter_blocks_post_order(cfg, lp).collect();
+        blocks.reverse();
+
+        let mut assigned = FxHashSet::default();
+        let mut has_writes = false;
+        for &block in &blocks {
+            for inst in body.order.iter_inst(block) {
+                if let Some(AssignableValue::Value(value)) = body.store.inst_result(inst) {
+                    if matches!(body.store.value_data(*value), Value::Local(_)) {
+                        assigned.insert(*value);
+                    }
+                }
+                has_writes |= !body.store.is_terminator(inst) && !is_read_only(db, body, inst);
+            }
+        }
+
+        Self {
+            db,
+            body,
+            cfg,
+            header: lpt.loop_header(lp),
+            blocks,
+            assigned,
+            has_writes,
+            preheader: None,
+        }
+    }
+
+    fn hoist(&mut self) -> bool {
+        let mut changed = false;
+        loop {
+            let candidates = self.collect_candidates();
+            if candidates.is_empty() {
+                break;
+            }
+
+            let preheader = self.preheader();
+            let terminator = self
+                .body
+                .order
+                .terminator(&self.body.store, preheader)
+                .unwrap();
+            for inst in candidates {
+                self.body.order.remove_inst(inst);
+                self.body.order.insert_inst_before_inst(inst, terminator);
+            }
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Returns loop invariant instructions which can be hoisted, in the order of
+    /// their execution.
+    fn collect_candidates(&self) -> Vec<InstId> {
+        let mut candidates = vec![];
+        let mut hoisted = FxHashSet::default();
+
+        // Instructions at the top of the header are executed whenever the
+        // preheader is executed.
+        let mut is_header_top = true;
+        for inst in self.body.order.iter_inst(self.header) {
+            if self.is_invariant(inst, &hoisted)
+                && (self.is_speculatable(inst) || self.is_hoistable_from_top(inst))
+            {
+                candidates.push(inst);
+                hoisted.insert(inst);
+                continue;
+            }
+
+            is_header_top &= !may_revert(self.body, inst) && is_read_only(self.db, self.body, inst);
+            if !is_header_top {
+                break;
+            }
+        }
+
+        for &block in &self.blocks {
+            for inst in self.body.order.iter_inst(block) {
+                if !hoisted.contains(&inst)
+                    && self.is_speculatable(inst)
+                    && self.is_invariant(inst, &hoisted)
+                {
+                    candidates.push(inst);
+                    hoisted.insert(inst);
+                }
+            }
+        }
+
+        candidates
+    }
+
+    /// Returns `true` if `inst` neither reverts nor reads mutable state, so it
+    /// can be executed even if the loop isn't entered.
+    fn is_speculatable(&self, inst: InstId) -> bool {
+        let store = &self.body.store;
+        if !matches!(
+            store.inst_result(inst),
+            Some(AssignableValue::Value(value))
+                if matches!(store.value_data(*value), Value::Temporary { .. })
+        ) {
+            return false;
+        }
+
+        match &store.inst_data(inst).kind {
+            InstKind::Unary { op, value } => *op != UnOp::Neg || store.value_data(*value).is_imm(),
+            InstKind::Binary { op, .. } => !is_checked(*op),
+            InstKind::Cast {
+                kind: CastKind::Primitive,
+                ..
+            } => true,
+            InstKind::MapAccess { key, .. } => store.value_ty(*key).is_primitive(self.db),
+            InstKind::AggregateAccess { .. } => {
+                computes_ptr(self.db, self.body, inst) && !may_revert(self.body, inst)
+            }
+            InstKind::YulIntrinsic { op, args } => args.is_empty() && is_call_constant(*op),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if `inst` can be hoisted when it's executed first in the
+    /// loop.
+    fn is_hoistable_from_top(&self, inst: InstId) -> bool {
+        let store = &self.body.store;
+        if !matches!(
+            store.inst_result(inst),
+            Some(AssignableValue::Value(value))
+                if matches!(store.value_data(*value), Value::Temporary { .. })
+        ) {
+            return false;
+        }
+
+        match &store.inst_data(inst).kind {
+            InstKind::Unary { .. } | InstKind::Binary { .. } => true,
+            InstKind::AggregateAccess { .. } => {
+                computes_ptr(self.db, self.body, inst) || !self.has_writes
+            }
+            InstKind::Load { .. } => !self.has_writes,
+            _ => false,
+        }
+    }
+
+    fn is_invariant(&self, inst: InstId, hoisted: &FxHashSet<InstId>) -> bool {
+        self.body
+            .store
+            .inst_data(inst)
+            .args()
+            .all(|arg| match self.body.store.value_data(arg) {
+                Value::Temporary { inst: def, .. } => {
+                    hoisted.contains(def) || !self.is_in_loop(self.body.order.inst_block(*def))
+                }
+                Value::Local(_) => !self.assigned.contains(&arg),
+                _ => true,
+            })
+    }
+
+    fn is_in_loop(&self, block: BasicBlockId) -> bool {
+        self.blocks.contains(&block)
+    }
+
+    /// Returns the preheader of the loop, i.e., the block which is the only
+    /// predecessor of the header from outside the loop and which jumps only to
+    /// the header. The preheader is inserted if it doesn't exist.
+    fn preheader(&mut self) -> BasicBlockId {
+        if let Some(preheader) = self.preheader {
+            return preheader;
+        }
+
+        let outer_preds: Vec<_> = self
+            .cfg
+            .preds(self.header)
+            .iter()
+            .copied()
+            .filter(|block| !self.is_in_loop(*block))
+            .collect();
+        let preheader = match outer_preds.as_slice() {
+            [pred] if self.cfg.succs(*pred) == [self.header] => *pred,
+            _ => {
+                let preheader = self.body.store.store_block(BasicBlock {});
+                self.body
+                    .order
+                    .insert_block_before_block(preheader, self.header);
+
+                let source = self
+                    .body
+                    .store
+                    .inst_data(self.body.order.first_inst(self.header).unwrap())
+                    .source
+                    .clone();
+                let jump = self
+                    .body
+                    .store
+                    .store_inst(Inst::new(InstKind::Jump { dest: self.header }, source));
+                self.body.order.append_inst(jump, preheader);
+
+                for pred in outer_preds {
+                    let terminator = self.body.order.terminator(&self.body.store, pred).unwrap();
+                    self.body
+                        .store
+                        .rewrite_branch_dest(terminator, self.header, preheader);
+                }
+                preheader
+            }
+        };
+
+        self.preheader = Some(preheader);
+        preheader
+    }
+}
+
+fn compute_loop_tree(body: &FunctionBody) -> (ControlFlowGraph, LoopTree) {
+    let cfg = ControlFlowGraph::compute(body);
+    let domtree = DomTree::compute(&cfg);
+    let lpt = LoopTree::compute(&cfg, &domtree);
+    (cfg, lpt)
+}
+
+fn is_checked(op: BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::Pow
+    )
+}
+
+/// Returns `true` if the aggregate access `inst` only computes a pointer to
+/// the element, rather than loading the element.
+fn computes_ptr(db: &dyn MirDb, body: &FunctionBody, inst: InstId) -> bool {
+    match body.store.inst_result(inst) {
+        Some(result) => result.ty(db, &body.store).is_ptr(db),
+        None => false,
+    }
+}
+
+fn may_revert(body: &FunctionBody, inst: InstId) -> bool {
+    let store = &body.store;
+    match &store.inst_data(inst).kind {
+        InstKind::Declare { .. }
+        | InstKind::Nop
+        | InstKind::Bind { .. }
+        | InstKind::Cast { .. }
+        | InstKind::Load { .. }
+        | InstKind::MapAccess { .. } => false,
+        InstKind::Unary { op, value } => *op == UnOp::Neg && !store.value_data(*value).is_imm(),
+        InstKind::Binary { op, .. } => is_checked(*op),
+        InstKind::AggregateAccess {
+            indices, unchecked, ..
+        } => !unchecked && !indices.iter().all(|idx| store.value_data(*idx).is_imm()),
+        InstKind::YulIntrinsic { op, args } => !(args.is_empty() && is_call_constant(*op)),
+        InstKind::Jump { .. } | InstKind::Branch { .. } | InstKind::Switch { .. } => false,
+        _ => true,
+    }
+}
+
+/// Returns `true` if `inst` doesn't write to the memory or the storage.
+fn is_read_only(db: &dyn MirDb, body: &FunctionBody, inst: InstId) -> bool {
+    let store = &body.store;
+    let kind = &store.inst_data(inst).kind;
+    let is_pure_kind = match kind {
+        InstKind::Declare { .. }
+        | InstKind::Nop
+        | InstKind::Unary { .. }
+        | InstKind::Binary { .. }
+        | InstKind::Cast { .. }
+        | InstKind::Bind { .. }
+        | InstKind::Load { .. }
+        | InstKind::AggregateAccess { .. }
+        | InstKind::MapAccess { .. }
+        | InstKind::Jump { .. }
+        | InstKind::Branch { .. }
+        | InstKind::Switch { .. } => true,
+        InstKind::YulIntrinsic { op, args } => args.is_empty() && is_call_constant(*op),
+        _ => false,
+    };
+    if !is_pure_kind {
+        return false;
+    }
+
+    // Assignment to a pointer is a store unless a pointer of the same kind is
+    // assigned.
+    match store.inst_result(inst) {
+        Some(AssignableValue::Value(value)) => {
+            let ty = store.value_ty(*value);
+            if !ty.is_ptr(db) {
+                return true;
+            }
+            let rhs_ty = match kind {
+                InstKind::Bind { src } => store.value_ty(*src),
+                InstKind::MapAccess { .. } | InstKind::AggregateAccess { .. } => ty,
+                _ => return false,
+            };
+            rhs_ty.is_ptr(db) && rhs_ty.is_mptr(db) == ty.is_mptr(db)
+        }
+        Some(_) => false,
+        None => true,
+    }
+}
@@ -0,0 +1,376 @@
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{
+    analysis::{ControlFlowGraph, DomTree},
+    db::MirDb,
+    ir::{
+        inst::{CastKind, InstKind, YulIntrinsicOp},
+        value::AssignableValue,
+        BasicBlockId, FunctionBody, Inst, InstId, TypeId, Value, ValueId,
+    },
+};
+
+use super::{copy_prop::dominates, Pass};
+
+/// Reuses storage slot computations and storage loads which are already
+/// computed by a dominating instruction.
+///
+/// `MapAccess` and `AggregateAccess` to storage only compute a slot, so they
+/// are reused whenever their operands are the same. A storage `Load` is reused
+/// only if no instruction which may write to the storage, i.e., a store to a
+/// storage pointer, a call or a contract creation, can be executed between the
+/// two loads. Primitive casts and environment queries which are constant
+/// during a call, e.g., `__caller`, are reused as well so that slots computed
+/// from them can be matched.
+#[derive(Debug, Default)]
+pub struct RedundantLoadEliminator {
+    /// Maps a replaced result to the result of the instruction reused in its
+    /// place.
+    leaders: FxHashMap<ValueId, ValueId>,
+    available: FxHashMap<Expr, Vec<InstId>>,
+}
+
+impl RedundantLoadEliminator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let cfg = ControlFlowGraph::compute(body);
+        let domtree = DomTree::compute(&cfg);
+        let assigned = assigned_locals(body);
+
+        let mut changed = false;
+        for &block in domtree.rpo() {
+            let insts: Vec<_> = body.order.iter_inst(block).collect();
+            for inst in insts {
+                self.record_copy(body, inst);
+                let expr = match self.make_expr(db, body, &assigned, inst) {
+                    Some(expr) => expr,
+                    None => continue,
+                };
+
+                match self.find_available(db, body, &cfg, &domtree, &expr, inst) {
+                    Some(src) => {
+                        self.reuse(body, inst, src);
+                        changed = true;
+                    }
+                    None => self.available.entry(expr).or_default().push(inst),
+                }
+            }
+        }
+
+        self.leaders.clear();
+        self.available.clear();
+        changed
+    }
+
+    fn make_expr(
+        &self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        assigned: &FxHashSet<ValueId>,
+        inst: InstId,
+    ) -> Option<Expr> {
+        // Only a temporary is guaranteed to keep the computed value.
+        match body.store.inst_result(inst) {
+            Some(AssignableValue::Value(result))
+                if matches!(body.store.value_data(*result), Value::Temporary { .. }) => {}
+            _ => return None,
+        }
+
+        let is_stable = |value: ValueId| match body.store.value_data(value) {
+            Value::Local(_) => !assigned.contains(&value),
+            _ => true,
+        };
+
+        let expr = match &body.store.inst_data(inst).kind {
+            InstKind::MapAccess { value, key } => {
+                // A key passed by a pointer is hashed from the memory, which might be
+                // modified.
+                if !body.store.value_ty(*value).is_sptr(db)
+                    || !body.store.value_ty(*key).is_primitive(db)
+                {
+                    return None;
+                }
+                Expr::Map {
+                    value: self.leader(*value),
+                    key: self.leader(*key),
+                }
+            }
+
+            InstKind::AggregateAccess { value, indices, .. } => {
+                // A contract is lowered to a pointer to the storage.
+                let value_ty = body.store.value_ty(*value);
+                if !value_ty.is_sptr(db) && !value_ty.is_contract(db) {
+                    return None;
+                }
+                Expr::Aggregate {
+                    value: self.leader(*value),
+                    indices: indices.iter().map(|idx| self.leader(*idx)).collect(),
+                }
+            }
+
+            InstKind::Load { src } => {
+                if !body.store.value_ty(*src).is_sptr(db) {
+                    return None;
+                }
+                Expr::Load {
+                    src: self.leader(*src),
+                }
+            }
+
+            InstKind::Cast {
+                kind: CastKind::Primitive,
+                value,
+                to,
+            } => Expr::Cast {
+                value: self.leader(*value),
+                to: *to,
+            },
+
+            InstKind::YulIntrinsic { op, args } if args.is_empty() && is_call_constant(*op) => {
+                Expr::Intrinsic { op: *op }
+            }
+
+            _ => return None,
+        };
+
+        if expr.args().all(is_stable) {
+            Some(expr)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the result of an instruction which computes `expr` and is
+    /// available at `inst`.
+    fn find_available(
+        &self,
+        db: &dyn MirDb,
+        body: &FunctionBody,
+        cfg: &ControlFlowGraph,
+        domtree: &DomTree,
+        expr: &Expr,
+        inst: InstId,
+    ) -> Option<InstId> {
+        self.available.get(expr)?.iter().copied().find(|&def| {
+            dominates(body, domtree, def, inst)
+                && (!matches!(expr, Expr::Load { .. })
+                    || !may_write_storage_between(db, body, cfg, def, inst))
+        })
+    }
+
+    fn reuse(&mut self, body: &mut FunctionBody, inst: InstId, src: InstId) {
+        let src = match body.store.inst_result(src) {
+            Some(AssignableValue::Value(value)) => *value,
+            _ => unreachable!(),
+        };
+        let result = match body.store.inst_result(inst) {
+            Some(AssignableValue::Value(value)) => *value,
+            _ => unreachable!(),
+        };
+
+        let source = body.store.inst_data(inst).source.clone();
+        body.store
+            .replace_inst(inst, Inst::new(InstKind::Bind { src }, source));
+        self.leaders.insert(result, src);
+    }
+
+    /// Records a copy between temporaries so that the copy and its source are
+    /// regarded as the same operand.
+    fn record_copy(&mut self, body: &FunctionBody, inst: InstId) {
+        let src = match body.store.inst_data(inst).kind {
+            InstKind::Bind { src } => src,
+            _ => return,
+        };
+
+        if let Some(AssignableValue::Value(result)) = body.store.inst_result(inst) {
+            if matches!(body.store.value_data(*result), Value::Temporary { .. })
+                && matches!(body.store.value_data(src), Value::Temporary { .. })
+            {
+                let leader = self.leader(src);
+                self.leaders.insert(*result, leader);
+            }
+        }
+    }
+
+    fn leader(&self, value: ValueId) -> ValueId {
+        self.leaders.get(&value).copied().unwrap_or(value)
+    }
+}
+
+impl Pass for RedundantLoadEliminator {
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        RedundantLoadEliminator::run(self, db, body)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Expr {
+    Map {
+        value: ValueId,
+        key: ValueId,
+    },
+    Aggregate {
+        value: ValueId,
+        indices: Vec<ValueId>,
+    },
+    Load {
+        src: ValueId,
+    },
+    Cast {
+        value: ValueId,
+        to: TypeId,
+    },
+    Intrinsic {
+        op: YulIntrinsicOp,
+    },
+}
+
+impl Expr {
+    fn args(&self) -> impl Iterator<Item = ValueId> + '_ {
+        let (first, rest) = match self {
+            Self::Map { value, key } => (Some(*value), std::slice::from_ref(key)),
+            Self::Aggregate { value, indices } => (Some(*value), indices.as_slice()),
+            Self::Load { src } | Self::Cast { value: src, .. } => (Some(*src), &[][..]),
+            Self::Intrinsic { .. } => (None, &[][..]),
+        };
+        first.into_iter().chain(rest.iter().copied())
+    }
+}
+
+/// Returns `true` if the result of `op` doesn't change during a call.
+pub(super) fn is_call_constant(op: YulIntrinsicOp) -> bool {
+    use YulIntrinsicOp::*;
+    matches!(
+        op,
+        Address
+            | Caller
+            | Callvalue
+            | Calldatasize
+            | Origin
+            | Gasprice
+            | Coinbase
+            | Timestamp
+            | Number
+            | Difficulty
+            | Prevrandao
+            | Gaslimit
+            | Chainid
+            | Basefee
+            | Blobbasefee
+    )
+}
+
+/// Returns locals which are assigned in `body`.
+fn assigned_locals(body: &FunctionBody) -> FxHashSet<ValueId> {
+    body.order
+        .iter_block()
+        .flat_map(|block| body.order.iter_inst(block))
+        .filter_map(|inst| match body.store.inst_result(inst) {
+            Some(AssignableValue::Value(value))
+                if matches!(body.store.value_data(*value), Value::Local(_)) =>
+            {
+                Some(*value)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns `true` if an instruction which may write to the storage can be
+/// executed after `def` and before `user`. `def` must dominate `user`.
+fn may_write_storage_between(
+    db: &dyn MirDb,
+    body: &FunctionBody,
+    cfg: &ControlFlowGraph,
+    def: InstId,
+    user: InstId,
+) -> bool {
+    let def_block = body.order.inst_block(def);
+    let user_block = body.order.inst_block(user);
+
+    let writes_in = |mut next: Option<InstId>, end: Option<InstId>| {
+        while let Some(inst) = next {
+            if Some(inst) == end {
+                return false;
+            }
+            if may_write_storage(db, body, inst) {
+                return true;
+            }
+            next = body.order.next_inst(inst);
+        }
+        false
+    };
+
+    if def_block == user_block {
+        return writes_in(body.order.next_inst(def), Some(user));
+    }
+
+    if writes_in(body.order.next_inst(def), None)
+        || writes_in(body.order.first_inst(user_block), Some(user))
+    {
+        return true;
+    }
+
+    // Blocks which can be passed from `def` to `user` without executing `def`
+    // again.
+    let reachable = walk(def_block, def_block, |block| cfg.succs(block));
+    let reaching = walk(user_block, def_block, |block| cfg.preds(block));
+    reachable
+        .intersection(&reaching)
+        .any(|&block| writes_in(body.order.first_inst(block), None))
+}
+
+/// Collects blocks visited from the neighbors of `start` without passing
+/// `stop`.
+fn walk<'a>(
+    start: BasicBlockId,
+    stop: BasicBlockId,
+    neighbors: impl Fn(BasicBlockId) -> &'a [BasicBlockId],
+) -> FxHashSet<BasicBlockId> {
+    let mut visited = FxHashSet::default();
+    let mut worklist = neighbors(start).to_vec();
+    while let Some(block) = worklist.pop() {
+        if block == stop || !visited.insert(block) {
+            continue;
+        }
+        worklist.extend_from_slice(neighbors(block));
+    }
+    visited
+}
+
+fn may_write_storage(db: &dyn MirDb, body: &FunctionBody, inst: InstId) -> bool {
+    let kind = &body.store.inst_data(inst).kind;
+    match kind {
+        InstKind::Call { .. } | InstKind::Create { .. } | InstKind::Create2 { .. } => return true,
+        InstKind::YulIntrinsic { op, .. } => {
+            return matches!(
+                op,
+                YulIntrinsicOp::Sstore
+                    | YulIntrinsicOp::Call
+                    | YulIntrinsicOp::Callcode
+                    | YulIntrinsicOp::Delegatecall
+                    | YulIntrinsicOp::Staticcall
+                    | YulIntrinsicOp::Create
+                    | YulIntrinsicOp::Create2
+            )
+        }
+        _ => {}
+    }
+
+    // Assignment to a pointer is a store unless a pointer is assigned.
+    match body.store.inst_result(inst) {
+        Some(AssignableValue::Value(value)) => {
+            body.store.value_ty(*value).is_sptr(db)
+                && !match kind {
+                    InstKind::MapAccess { .. } | InstKind::AggregateAccess { .. } => true,
+                    InstKind::Bind { src } => body.store.value_ty(*src).is_sptr(db),
+                    _ => false,
+                }
+        }
+        Some(_) => true,
+        None => false,
+    }
+}
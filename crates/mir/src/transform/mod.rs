@@ -0,0 +1,82 @@
+//! Optimization passes over MIR function bodies.
+//!
+//! All passes preserve the runtime behavior of a function body, including
+//! reverts caused by checked arithmetic, so an optimized body can be handed to
+//! the codegen in place of the lowered one.
+
+use crate::{db::MirDb, ir::FunctionBody};
+
+pub mod check_elim;
+pub mod const_fold;
+pub mod copy_prop;
+pub mod dce;
+pub mod inline;
+pub mod licm;
+pub mod load_elim;
+
+pub use check_elim::CheckEliminator;
+pub use const_fold::ConstantFolder;
+pub use copy_prop::CopyPropagator;
+pub use dce::DeadCodeEliminator;
+pub use inline::Inliner;
+pub use licm::LoopInvariantCodeMotion;
+pub use load_elim::RedundantLoadEliminator;
+
+/// The maximum number of times the pass pipeline is repeated on a single
+/// function body.
+const MAX_ITERATIONS: usize = 8;
+
+/// Inlines callees into `body`, then runs the MIR optimization pipeline until
+/// no pass makes further progress.
+pub fn optimize_func_body(db: &dyn MirDb, body: &mut FunctionBody) {
+    Inliner::new().run(db, body);
+    PassManager::default().run(db, body)
+}
+
+/// A pass manager which runs registered passes in order, and repeats the whole
+/// pipeline until it reaches a fixed point.
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        Self { passes: Vec::new() }
+    }
+
+    pub fn add_pass(&mut self, pass: impl Pass + 'static) -> &mut Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) {
+        for _ in 0..MAX_ITERATIONS {
+            let mut changed = false;
+            for pass in &mut self.passes {
+                changed |= pass.run(db, body);
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        let mut pm = Self::new();
+        pm.add_pass(ConstantFolder::new())
+            .add_pass(CopyPropagator::new())
+            .add_pass(RedundantLoadEliminator::new())
+            .add_pass(LoopInvariantCodeMotion::new())
+            .add_pass(CheckEliminator::new())
+            .add_pass(DeadCodeEliminator::new());
+        pm
+    }
+}
+
+pub trait Pass {
+    /// Transforms `body` in place. Returns `true` if `body` is modified.
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool;
+}
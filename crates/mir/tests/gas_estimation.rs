@@ -0,0 +1,91 @@
+use fe_analyzer::namespace::items::ModuleId;
+use fe_common::files::Utf8Path;
+use fe_mir::{
+    analysis::{Gas, GasEstimate, GasEstimator},
+    db::{MirDb, NewDb},
+};
+
+/// Returns the estimates and the external estimates of the functions of a
+/// fixture, by their debug names.
+fn estimates(path: &str) -> Vec<(String, GasEstimate, GasEstimate)> {
+    let mut db = NewDb::default();
+    let file_name = Utf8Path::new(path).file_name().unwrap();
+    let module = ModuleId::new_standalone(&mut db, file_name, test_files::fixture(path));
+    assert!(module.diagnostics(&db).is_empty());
+
+    let mut estimator = GasEstimator::new(&db);
+    db.mir_lower_module_all_functions(module)
+        .iter()
+        .map(|func| {
+            (
+                func.debug_name(&db).to_string(),
+                estimator.estimate(*func),
+                estimator.estimate_external(*func),
+            )
+        })
+        .collect()
+}
+
+/// Returns an estimate whose most expensive path is infinite.
+fn infinite(best: u64) -> GasEstimate {
+    GasEstimate {
+        best: Gas::Finite(best),
+        worst: Gas::Infinite,
+    }
+}
+
+/// Returns the estimate and the external estimate of the function `name` of
+/// the `gas_estimates.fe` fixture.
+fn estimate(name: &str) -> (GasEstimate, GasEstimate) {
+    estimates("features/gas_estimates.fe")
+        .into_iter()
+        .find(|(func_name, _, _)| func_name == name)
+        .map(|(_, estimate, external)| (estimate, external))
+        .unwrap()
+}
+
+#[test]
+fn bounded_loops() {
+    // The body of the `for` loop runs at most 8 times, and may be skipped on
+    // the cheapest path. The external call decodes 8 words into memory and
+    // encodes one.
+    assert_eq!(
+        estimate("Foo::sum"),
+        (GasEstimate::new(48, 1601), GasEstimate::new(318, 1898))
+    );
+
+    // The trip count of `while` loops which count up to a constant is known
+    // too.
+    assert_eq!(
+        estimate("Foo::count_to_ten"),
+        (GasEstimate::new(36, 985), GasEstimate::new(66, 1018))
+    );
+}
+
+#[test]
+fn unbounded_loop() {
+    assert_eq!(estimate("Foo::count_to"), (infinite(36), infinite(96)));
+}
+
+#[test]
+fn storage_writes() {
+    // The slot is warm on the cheapest path, and set from zero on the most
+    // expensive one.
+    assert_eq!(
+        estimate("Foo::set_total"),
+        (GasEstimate::new(112, 22121), GasEstimate::new(142, 22151))
+    );
+}
+
+#[test]
+fn memory_expansion() {
+    // A tuple of 2 words is allocated in each of the 4 iterations of the
+    // loop, above the 3 words of memory which are always used.
+    assert_eq!(
+        estimate("Foo::sum_squares"),
+        (GasEstimate::new(42, 1199), GasEstimate::new(72, 1232))
+    );
+
+    // The memory written at a variable offset can't be bounded.
+    assert_eq!(estimate("Foo::store_at"), (infinite(73), infinite(133)));
+}
@@ -2,7 +2,7 @@ use fe_analyzer::{
     namespace::items::{IngotId, ModuleId},
     AnalyzerDb,
 };
-use fe_common::{db::Upcast, db::UpcastMut, files::Utf8Path};
+use fe_common::{db::Upcast, db::UpcastMut, files::Utf8Path, EvmVersion};
 use fe_mir::{
     analysis::{ControlFlowGraph, DomTree, LoopTree, PostDomTree},
     db::{MirDb, NewDb},
@@ -10,9 +10,13 @@ use fe_mir::{
 
 macro_rules! test_lowering {
     ($name:ident, $path:expr) => {
+        test_lowering! { $name, $path, EvmVersion::default() }
+    };
+    ($name:ident, $path:expr, $evm_version:expr) => {
         #[test]
         fn $name() {
             let mut db = NewDb::default();
+            db.set_evm_version($evm_version);
 
             let file_name = Utf8Path::new($path).file_name().unwrap();
             let module = ModuleId::new_standalone(&mut db, file_name, test_files::fixture($path));
@@ -96,6 +100,7 @@ test_lowering! { mir_strings, "features/strings.fe"}
 test_lowering! { mir_structs, "features/structs.fe"}
 test_lowering! { mir_struct_fns, "features/struct_fns.fe"}
 test_lowering! { mir_ternary_expression, "features/ternary_expression.fe"}
+test_lowering! { mir_transient_storage, "features/transient_storage.fe", EvmVersion::Cancun }
 test_lowering! { mir_two_contracts, "features/two_contracts.fe"}
 test_lowering! { mir_u8_u8_map, "features/u8_u8_map.fe"}
 test_lowering! { mir_u16_u16_map, "features/u16_u16_map.fe"}
@@ -0,0 +1,633 @@
+use std::rc::Rc;
+
+use fe_analyzer::namespace::items::ModuleId;
+use fe_common::files::Utf8Path;
+use fe_mir::{
+    analysis::ControlFlowGraph,
+    db::{MirDb, NewDb},
+    ir::{
+        body_builder::BodyBuilder,
+        inst::{BinOp, InstKind},
+        types::MapDef,
+        value::Local,
+        FunctionBody, FunctionId, SourceInfo, Type, TypeId, TypeKind, Value, ValueId,
+    },
+    pretty_print::pretty_body_string,
+    transform::{optimize_func_body, CheckEliminator, LoopInvariantCodeMotion, Pass},
+};
+
+macro_rules! test_optimization {
+    ($name:ident, $path:expr) => {
+        #[test]
+        fn $name() {
+            let mut db = NewDb::default();
+            db.set_mir_optimization_enabled(true);
+
+            let file_name = Utf8Path::new($path).file_name().unwrap();
+            let module = ModuleId::new_standalone(&mut db, file_name, test_files::fixture($path));
+
+            let diags = module.diagnostics(&db);
+            if !diags.is_empty() {
+                panic!("lowering failed")
+            }
+
+            for func in db.mir_lower_module_all_functions(module).iter() {
+                let body = func.optimized_body(&db);
+                ControlFlowGraph::compute(&body);
+                check_defs(&body);
+            }
+        }
+    };
+}
+
+/// Checks all used values are still defined or declared.
+fn check_defs(body: &FunctionBody) {
+    let insts: Vec<_> = body
+        .order
+        .iter_block()
+        .flat_map(|block| body.order.iter_inst(block))
+        .collect();
+
+    for &inst in &insts {
+        if let InstKind::Declare { .. } = body.store.inst_data(inst).kind {
+            continue;
+        }
+
+        for arg in body.store.inst_data(inst).args() {
+            match body.store.value_data(arg) {
+                Value::Temporary { inst: def, .. } => {
+                    assert!(
+                        body.order.is_inst_inserted(*def),
+                        "use of removed temporary"
+                    )
+                }
+                Value::Local(local) if !local.is_arg => assert!(
+                    insts.iter().any(|inst| matches!(
+                        body.store.inst_data(*inst).kind,
+                        InstKind::Declare { local } if local == arg
+                    )),
+                    "use of undeclared local"
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn make_ty(db: &NewDb, kind: TypeKind) -> TypeId {
+    db.mir_intern_type(Rc::new(Type::new(kind, None)))
+}
+
+fn body_builder() -> BodyBuilder {
+    BodyBuilder::new(FunctionId(0), SourceInfo::dummy())
+}
+
+fn insts(body: &FunctionBody) -> Vec<InstKind> {
+    body.order
+        .iter_block()
+        .flat_map(|block| body.order.iter_inst(block))
+        .map(|inst| body.store.inst_data(inst).kind.clone())
+        .collect()
+}
+
+#[test]
+fn fold_checked_arithmetic() {
+    let db = NewDb::default();
+    let u8_ty = make_ty(&db, TypeKind::U8);
+
+    let mut builder = body_builder();
+    let lhs = builder.make_imm(200.into(), u8_ty);
+    let rhs = builder.make_imm(55.into(), u8_ty);
+    let inst = builder.add(lhs, rhs, SourceInfo::dummy());
+    let res = builder.make_value(Value::Temporary { inst, ty: u8_ty });
+    builder.map_result(inst, res.into());
+    builder.ret(res, SourceInfo::dummy());
+
+    let mut body = builder.build();
+    optimize_func_body(&db, &mut body);
+
+    let insts = insts(&body);
+    assert_eq!(insts.len(), 1);
+    match &insts[0] {
+        InstKind::Return { arg: Some(arg) } => assert!(matches!(
+            body.store.value_data(*arg),
+            Value::Immediate { imm, .. } if *imm == 255.into()
+        )),
+        _ => panic!("expected return of a folded immediate"),
+    }
+}
+
+#[test]
+fn keep_overflowing_arithmetic() {
+    let db = NewDb::default();
+    let u8_ty = make_ty(&db, TypeKind::U8);
+
+    let mut builder = body_builder();
+    let lhs = builder.make_imm(200.into(), u8_ty);
+    let rhs = builder.make_imm(56.into(), u8_ty);
+    let inst = builder.add(lhs, rhs, SourceInfo::dummy());
+    let res = builder.make_value(Value::Temporary { inst, ty: u8_ty });
+    builder.map_result(inst, res.into());
+    builder.ret(res, SourceInfo::dummy());
+
+    let mut body = builder.build();
+    optimize_func_body(&db, &mut body);
+
+    assert!(insts(&body)
+        .iter()
+        .any(|inst| matches!(inst, InstKind::Binary { op: BinOp::Add, .. })));
+}
+
+#[test]
+fn fold_signed_negation() {
+    let db = NewDb::default();
+    let i8_ty = make_ty(&db, TypeKind::I8);
+
+    let mut builder = body_builder();
+    let value = builder.make_imm(128.into(), i8_ty);
+    let inst = builder.neg(value, SourceInfo::dummy());
+    let res = builder.make_value(Value::Temporary { inst, ty: i8_ty });
+    builder.map_result(inst, res.into());
+    builder.ret(res, SourceInfo::dummy());
+
+    let mut body = builder.build();
+    optimize_func_body(&db, &mut body);
+
+    // -128 is represented as a sign extended word.
+    let expected = (num_bigint::BigInt::from(1) << 256) - 128;
+    match &insts(&body)[..] {
+        [InstKind::Return { arg: Some(arg) }] => assert!(matches!(
+            body.store.value_data(*arg),
+            Value::Immediate { imm, .. } if *imm == expected
+        )),
+        _ => panic!("expected return of a folded immediate"),
+    }
+}
+
+#[test]
+fn remove_dead_branch() {
+    let db = NewDb::default();
+    let bool_ty = make_ty(&db, TypeKind::Bool);
+    let u256_ty = make_ty(&db, TypeKind::U256);
+
+    let mut builder = body_builder();
+    let then = builder.make_block();
+    let else_ = builder.make_block();
+
+    let local = builder.declare(fe_mir::ir::value::Local::user_local(
+        "x".into(),
+        u256_ty,
+        SourceInfo::dummy(),
+    ));
+    let one = builder.make_imm(1.into(), u256_ty);
+    let bind = builder.bind(one, SourceInfo::dummy());
+    builder.map_result(bind, local.into());
+
+    let two = builder.make_imm(2.into(), u256_ty);
+    let cond_inst = builder.lt(local, two, SourceInfo::dummy());
+    let cond = builder.make_value(Value::Temporary {
+        inst: cond_inst,
+        ty: bool_ty,
+    });
+    builder.map_result(cond_inst, cond.into());
+    builder.branch(cond, then, else_, SourceInfo::dummy());
+
+    builder.move_to_block(then);
+    builder.ret(local, SourceInfo::dummy());
+
+    builder.move_to_block(else_);
+    builder.ret(two, SourceInfo::dummy());
+
+    let mut body = builder.build();
+    optimize_func_body(&db, &mut body);
+
+    assert!(!body.order.is_block_inserted(else_));
+    let insts = insts(&body);
+    assert!(
+        matches!(insts[..], [InstKind::Jump { dest }, InstKind::Return { .. }] if dest == then)
+    );
+}
+
+/// Builds a body which loads `map[key]` from the storage twice, and optionally
+/// stores to `map[key]` between the loads.
+fn storage_load_twice(db: &NewDb, store_between: bool) -> FunctionBody {
+    let u256_ty = make_ty(db, TypeKind::U256);
+    let map_ty = make_ty(
+        db,
+        TypeKind::Map(MapDef {
+            key_ty: u256_ty,
+            value_ty: u256_ty,
+        }),
+    );
+    let map_ptr_ty = map_ty.make_sptr(db);
+    let value_ptr_ty = u256_ty.make_sptr(db);
+
+    let mut builder = body_builder();
+    let map = builder.store_func_arg(Local::arg_local(
+        "map".into(),
+        map_ptr_ty,
+        SourceInfo::dummy(),
+    ));
+    let key = builder.store_func_arg(Local::arg_local("key".into(), u256_ty, SourceInfo::dummy()));
+
+    let load = |builder: &mut BodyBuilder| -> (ValueId, ValueId) {
+        let access = builder.map_access(map, key, SourceInfo::dummy());
+        let ptr = builder.make_value(Value::Temporary {
+            inst: access,
+            ty: value_ptr_ty,
+        });
+        builder.map_result(access, ptr.into());
+
+        let load = builder.load(ptr, SourceInfo::dummy());
+        let value = builder.make_value(Value::Temporary {
+            inst: load,
+            ty: u256_ty,
+        });
+        builder.map_result(load, value.into());
+        (ptr, value)
+    };
+
+    let (ptr, lhs) = load(&mut builder);
+    if store_between {
+        let one = builder.make_imm(1.into(), u256_ty);
+        let store = builder.bind(one, SourceInfo::dummy());
+        builder.map_result(store, ptr.into());
+    }
+    let (_, rhs) = load(&mut builder);
+
+    let inst = builder.bit_xor(lhs, rhs, SourceInfo::dummy());
+    let res = builder.make_value(Value::Temporary { inst, ty: u256_ty });
+    builder.map_result(inst, res.into());
+    builder.ret(res, SourceInfo::dummy());
+
+    builder.build()
+}
+
+fn count_insts(body: &FunctionBody, f: impl Fn(&InstKind) -> bool) -> usize {
+    insts(body).iter().filter(|inst| f(inst)).count()
+}
+
+#[test]
+fn reuse_storage_load() {
+    let db = NewDb::default();
+    let mut body = storage_load_twice(&db, false);
+    optimize_func_body(&db, &mut body);
+
+    assert_eq!(
+        count_insts(&body, |inst| matches!(inst, InstKind::MapAccess { .. })),
+        1
+    );
+    assert_eq!(
+        count_insts(&body, |inst| matches!(inst, InstKind::Load { .. })),
+        1
+    );
+}
+
+#[test]
+fn keep_storage_load_after_store() {
+    let db = NewDb::default();
+    let mut body = storage_load_twice(&db, true);
+    optimize_func_body(&db, &mut body);
+
+    assert_eq!(
+        count_insts(&body, |inst| matches!(inst, InstKind::MapAccess { .. })),
+        1
+    );
+    assert_eq!(
+        count_insts(&body, |inst| matches!(inst, InstKind::Load { .. })),
+        2
+    );
+}
+
+#[test]
+fn inline_small_functions() {
+    let mut db = NewDb::default();
+    db.set_mir_optimization_enabled(true);
+
+    let path = "features/inline_hints.fe";
+    let module = ModuleId::new_standalone(&mut db, "inline_hints.fe", test_files::fixture(path));
+    assert!(module.diagnostics(&db).is_empty());
+
+    let bar = db
+        .mir_lower_module_all_functions(module)
+        .iter()
+        .copied()
+        .find(|func| func.debug_name(&db) == "Foo::bar")
+        .unwrap();
+    let body = bar.optimized_body(&db);
+    ControlFlowGraph::compute(&body);
+    check_defs(&body);
+
+    let callees: Vec<_> = insts(&body)
+        .into_iter()
+        .filter_map(|inst| match inst {
+            InstKind::Call { func, .. } => Some(func.name(&db)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees, ["double"]);
+}
+
+#[test]
+fn keep_calls_returning_from_loops() {
+    let mut db = NewDb::default();
+    db.set_mir_optimization_enabled(true);
+
+    let path = "features/inline_hints.fe";
+    let module = ModuleId::new_standalone(&mut db, "inline_hints.fe", test_files::fixture(path));
+    assert!(module.diagnostics(&db).is_empty());
+
+    let baz = db
+        .mir_lower_module_all_functions(module)
+        .iter()
+        .copied()
+        .find(|func| func.debug_name(&db) == "Foo::baz")
+        .unwrap();
+    let body = baz.optimized_body(&db);
+
+    // `first_multiple` is marked `#inline`, but returns from inside a loop.
+    let callees: Vec<_> = insts(&body)
+        .into_iter()
+        .filter_map(|inst| match inst {
+            InstKind::Call { func, .. } => Some(func.name(&db)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees, ["first_multiple"]);
+}
+
+/// Runs `pass` on the lowered body of `func_name` in the fixture at `path` and
+/// returns the pretty-printed result.
+fn run_pass(path: &str, func_name: &str, mut pass: impl Pass) -> String {
+    let mut db = NewDb::default();
+    let file_name = Utf8Path::new(path).file_name().unwrap();
+    let module = ModuleId::new_standalone(&mut db, file_name, test_files::fixture(path));
+    assert!(module.diagnostics(&db).is_empty());
+
+    let func = db
+        .mir_lower_module_all_functions(module)
+        .iter()
+        .copied()
+        .find(|func| func.debug_name(&db) == func_name)
+        .unwrap();
+    let mut body = (*func.body(&db)).clone();
+    pass.run(&db, &mut body);
+    check_defs(&body);
+
+    pretty_body_string(&db, &body)
+}
+
+#[test]
+fn hoist_map_slot_and_load() {
+    let body = run_pass(
+        "features/loop_invariant.fe",
+        "Foo::sum_balance",
+        LoopInvariantCodeMotion::new(),
+    );
+    assert_eq!(
+        body,
+        "\
+BB0:
+    let _3: u256
+    _3: u256 = bind 0
+    let _5: u256
+    _5: u256 = bind 0
+    _6: bool = _5 < _2
+    branch _6 then: BB4 else: BB2
+BB4:
+    _8: *@s Map<address,u256> = _0.<0>
+    _9: *@s u256 = _8{_1}
+    _10: u256 = load _9
+    _12: u256 = _2 >> 1
+    _13: u256 = _10 + _12
+    jump BB1
+BB1:
+    _3: u256 = _3 + _13
+    _5: u256 = _5 + 1
+    _14: bool = _5 < _2
+    branch _14 then: BB1 else: BB2
+BB2:
+    return _3
+BB3:
+    return ()
+"
+    );
+}
+
+#[test]
+fn hoist_bounds_checked_access() {
+    let body = run_pass(
+        "features/loop_invariant.fe",
+        "Foo::sum_elem",
+        LoopInvariantCodeMotion::new(),
+    );
+    assert_eq!(
+        body,
+        "\
+BB0:
+    let _3: u256
+    _3: u256 = bind 0
+    let _5: u256
+    _5: u256 = bind 0
+    branch 1 then: BB7 else: BB2
+BB7:
+    _7: u256 = _0.<_1>
+    jump BB1
+BB1:
+    _3: u256 = _3 + _7
+    _5: u256 = _5 + 1
+    _9: bool = _5 >= _2
+    branch _9 then: BB3 else: BB4
+BB2:
+    return _3
+BB3:
+    jump BB2
+BB4:
+    branch 1 then: BB1 else: BB2
+BB5:
+    jump BB4
+BB6:
+    return ()
+"
+    );
+}
+
+#[test]
+fn keep_load_in_loop_with_store() {
+    let body = run_pass(
+        "features/loop_invariant.fe",
+        "Foo::add_balance",
+        LoopInvariantCodeMotion::new(),
+    );
+    assert_eq!(
+        body,
+        "\
+BB0:
+    let _3: u256
+    _3: u256 = bind 0
+    _5: bool = _3 < _2
+    branch _5 then: BB3 else: BB2
+BB3:
+    _7: *@s Map<address,u256> = _0.<0>
+    _8: *@s u256 = _7{_1}
+    jump BB1
+BB1:
+    _9: u256 = load _8
+    _0.<0>{_1}: *@s u256 = _9 + _3
+    _3: u256 = _3 + 1
+    _11: bool = _3 < _2
+    branch _11 then: BB1 else: BB2
+BB2:
+    return ()
+"
+    );
+}
+
+#[test]
+fn elide_checks_in_for_loop() {
+    let body = run_pass(
+        "features/range_checks.fe",
+        "Foo::sum",
+        CheckEliminator::new(),
+    );
+    assert_eq!(
+        body,
+        "\
+BB0:
+    let _1: u256
+    _1: u256 = bind 0
+    jump BB1
+BB1:
+    let _3: u256
+    let _4: u256
+    _4: u256 = bind 0
+    _7: bool = _4 == 8
+    branch _7 then: BB3 else: BB2
+BB2:
+    _3: u256 = unchecked _0.<_4>
+    _1: u256 = _1 + _3
+    _4: u256 = unchecked _4 + 1
+    _9: bool = _4 == 8
+    branch _9 then: BB3 else: BB2
+BB3:
+    return _1
+BB4:
+    return ()
+"
+    );
+}
+
+#[test]
+fn elide_bounds_check_after_assert() {
+    let body = run_pass(
+        "features/range_checks.fe",
+        "Foo::get",
+        CheckEliminator::new(),
+    );
+    assert_eq!(
+        body,
+        "\
+BB0:
+    _3: bool = _1 < 4
+    branch _3 then: BB1 else: BB2
+BB1:
+    _5: u8 = unchecked _0.<_1>
+    return _5
+BB2:
+    revert 1
+BB3:
+    return ()
+"
+    );
+}
+
+#[test]
+fn elide_overflow_check_in_while_loop() {
+    let body = run_pass(
+        "features/range_checks.fe",
+        "Foo::count_to",
+        CheckEliminator::new(),
+    );
+    assert_eq!(
+        body,
+        "\
+BB0:
+    let _1: u8
+    _1: u8 = bind 0
+    _3: bool = _1 < _0
+    branch _3 then: BB1 else: BB2
+BB1:
+    _1: u8 = unchecked _1 + 1
+    _5: bool = _1 < _0
+    branch _5 then: BB1 else: BB2
+BB2:
+    return _1
+BB3:
+    return ()
+"
+    );
+}
+
+#[test]
+fn elide_signed_checks_in_nested_conditions() {
+    let body = run_pass(
+        "features/range_checks.fe",
+        "Foo::double_or_zero",
+        CheckEliminator::new(),
+    );
+    assert!(body.contains("_7: i16 = unchecked _0 * 2"));
+    assert!(body.contains("_9: i16 = unchecked _7 - 1"));
+}
+
+#[test]
+fn keep_checks_on_unknown_ranges() {
+    let body = run_pass(
+        "features/loop_invariant.fe",
+        "Foo::sum_elem",
+        CheckEliminator::new(),
+    );
+    // Neither `k` nor the sum is bounded, but `i` is less than `n` when it's
+    // incremented.
+    assert!(body.contains("_7: u256 = _0.<_1>"));
+    assert!(body.contains("_3: u256 = _3 + _7"));
+    assert!(body.contains("_5: u256 = unchecked _5 + 1"));
+}
+
+#[test]
+fn keep_checks_if_check_elimination_is_disabled() {
+    let mut db = NewDb::default();
+    db.set_mir_check_elimination_enabled(false);
+    let path = "features/range_checks.fe";
+    let module = ModuleId::new_standalone(&mut db, "range_checks.fe", test_files::fixture(path));
+    assert!(module.diagnostics(&db).is_empty());
+
+    let func = db
+        .mir_lower_module_all_functions(module)
+        .iter()
+        .copied()
+        .find(|func| func.debug_name(&db) == "Foo::sum")
+        .unwrap();
+    let mut body = (*func.body(&db)).clone();
+    assert!(!CheckEliminator::new().run(&db, &mut body));
+}
+
+test_optimization! { mir_opt_erc20_token, "demos/erc20_token.fe"}
+test_optimization! { mir_opt_uniswap, "demos/uniswap.fe"}
+test_optimization! { mir_opt_assert, "features/assert.fe"}
+test_optimization! { mir_opt_aug_assign, "features/aug_assign.fe"}
+test_optimization! { mir_opt_checked_arithmetic, "features/checked_arithmetic.fe"}
+test_optimization! { mir_opt_for_loop_with_break, "features/for_loop_with_break.fe"}
+test_optimization! { mir_opt_if_statement, "features/if_statement.fe"}
+test_optimization! { mir_opt_if_statement_2, "features/if_statement_2.fe"}
+test_optimization! { mir_opt_math, "features/math.fe"}
+test_optimization! { mir_opt_module_const, "features/module_const.fe"}
+test_optimization! { mir_opt_numeric_sizes, "features/numeric_sizes.fe"}
+test_optimization! { mir_opt_structs, "features/structs.fe"}
+test_optimization! { mir_opt_ternary_expression, "features/ternary_expression.fe"}
+test_optimization! { mir_opt_while_loop, "features/while_loop.fe"}
+test_optimization! { mir_opt_while_loop_with_break, "features/while_loop_with_break.fe"}
+test_optimization! { mir_opt_tuple_stress, "stress/tuple_stress.fe"}
+test_optimization! { mir_opt_const_local, "features/const_local.fe" }
+test_optimization! { mir_opt_inline_hints, "features/inline_hints.fe" }
+test_optimization! { mir_opt_loop_invariant, "features/loop_invariant.fe" }
+test_optimization! { mir_opt_range_checks, "features/range_checks.fe" }
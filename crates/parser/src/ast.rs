@@ -25,7 +25,7 @@ pub enum ModuleStmt {
     Trait(Node<Trait>),
     Impl(Node<Impl>),
     Function(Node<Function>),
-    Attribute(Node<SmolStr>),
+    Attribute(Node<Attribute>),
     ParseError(Span),
 }
 
@@ -34,6 +34,13 @@ pub struct Pragma {
     pub version_requirement: Node<SmolStr>,
 }
 
+/// A module level attribute, e.g. `#test` or `#namespace("example.main")`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Attribute {
+    pub name: Node<SmolStr>,
+    pub arg: Option<Node<SmolStr>>,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Path {
     pub segments: Vec<Node<SmolStr>>,
@@ -236,6 +243,7 @@ pub enum VariantKind {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub enum ContractStmt {
     Function(Node<Function>),
+    Attribute(Node<SmolStr>),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
@@ -585,6 +593,7 @@ impl Spanned for ContractStmt {
     fn span(&self) -> Span {
         match self {
             ContractStmt::Function(inner) => inner.span,
+            ContractStmt::Attribute(inner) => inner.span,
         }
     }
 }
@@ -623,7 +632,7 @@ impl fmt::Display for ModuleStmt {
             ModuleStmt::Struct(node) => write!(f, "{}", node.kind),
             ModuleStmt::Enum(node) => write!(f, "{}", node.kind),
             ModuleStmt::Function(node) => write!(f, "{}", node.kind),
-            ModuleStmt::Attribute(node) => writeln!(f, "#{}", node.kind),
+            ModuleStmt::Attribute(node) => writeln!(f, "{}", node.kind),
             ModuleStmt::ParseError(span) => {
                 write!(f, "# PARSE ERROR: {}..{}", span.start, span.end)
             }
@@ -637,6 +646,16 @@ impl fmt::Display for Pragma {
     }
 }
 
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.name.kind)?;
+        if let Some(arg) = &self.arg {
+            write!(f, "({:?})", arg.kind)?;
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for Use {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         // TODO pub use
@@ -873,6 +892,7 @@ impl fmt::Display for ContractStmt {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             ContractStmt::Function(node) => write!(f, "{}", node.kind),
+            ContractStmt::Attribute(node) => write!(f, "#{}", node.kind),
         }
     }
 }
@@ -30,9 +30,17 @@ pub fn parse_contract_def(
 
     let mut fields = vec![];
     let mut defs = vec![];
+    let mut attributes = vec![];
 
     loop {
         par.eat_newlines();
+        if let Some(attr) = par.optional(TokenKind::Hash) {
+            let attr_name = par.expect_with_notes(TokenKind::Name, "failed to parse attribute definition", |_|
+                vec!["Note: an attribute name must start with a letter or underscore, and contain letters, numbers, or underscores".into()])?;
+            attributes.push(Node::new(attr_name.text.into(), attr.span + attr_name.span));
+            continue;
+        }
+
         let mut pub_qual = parse_opt_qualifier(par, TokenKind::Pub);
         let const_qual = parse_opt_qualifier(par, TokenKind::Const);
         if pub_qual.is_none() && const_qual.is_some() && par.peek() == Some(TokenKind::Pub) {
@@ -45,7 +53,8 @@ pub fn parse_contract_def(
 
         match par.peek_or_err()? {
             TokenKind::Name => {
-                let field = parse_field(par, vec![], pub_qual, const_qual)?;
+                let field =
+                    parse_field(par, std::mem::take(&mut attributes), pub_qual, const_qual)?;
                 if !defs.is_empty() {
                     par.error(
                         field.span,
@@ -61,9 +70,16 @@ pub fn parse_contract_def(
                         "`const` qualifier can't be used with function definitions",
                     );
                 }
+                defs.extend(attributes.drain(..).map(ContractStmt::Attribute));
                 defs.push(ContractStmt::Function(parse_fn_def(par, pub_qual)?));
             }
             TokenKind::BraceClose => {
+                if let Some(attr) = attributes.last() {
+                    par.error(
+                        attr.span,
+                        "attributes must be followed by a field or function definition",
+                    );
+                }
                 span += par.next()?.span;
                 break;
             }
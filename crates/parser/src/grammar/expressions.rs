@@ -447,7 +447,7 @@ fn atom(par: &mut Parser, tok: &Token) -> Node<Expr> {
     Node::new(expr, tok.span)
 }
 
-fn unescape_string(quoted_string: &str) -> Option<String> {
+pub(super) fn unescape_string(quoted_string: &str) -> Option<String> {
     let inner = &quoted_string[1..quoted_string.len() - 1];
     unescape::unescape(inner)
 }
@@ -1,11 +1,11 @@
-use super::expressions::parse_expr;
+use super::expressions::{parse_expr, unescape_string};
 use super::functions::parse_fn_def;
 use super::types::{
     parse_impl_def, parse_path_tail, parse_struct_def, parse_trait_def, parse_type_alias,
     parse_type_desc,
 };
 use super::{contracts::parse_contract_def, types::parse_enum_def};
-use crate::ast::{ConstantDecl, Module, ModuleStmt, Pragma, Use, UseTree};
+use crate::ast::{Attribute, ConstantDecl, Module, ModuleStmt, Pragma, Use, UseTree};
 use crate::node::{Node, Span};
 use crate::{Label, ParseFailed, ParseResult, Parser, TokenKind};
 
@@ -74,12 +74,7 @@ pub fn parse_module_stmt(par: &mut Parser) -> ParseResult<ModuleStmt> {
             }
         }
         TokenKind::Fn | TokenKind::Unsafe => ModuleStmt::Function(parse_fn_def(par, None)?),
-        TokenKind::Hash => {
-            let attr = par.expect(TokenKind::Hash, "expected `#`")?;
-            let attr_name = par.expect_with_notes(TokenKind::Name, "failed to parse attribute definition", |_|
-                vec!["Note: an attribute name must start with a letter or underscore, and contain letters, numbers, or underscores".into()])?;
-            ModuleStmt::Attribute(Node::new(attr_name.text.into(), attr.span + attr_name.span))
-        }
+        TokenKind::Hash => ModuleStmt::Attribute(parse_attribute(par)?),
         _ => {
             let tok = par.next()?;
             par.unexpected_token_error(
@@ -93,6 +88,37 @@ pub fn parse_module_stmt(par: &mut Parser) -> ParseResult<ModuleStmt> {
     Ok(stmt)
 }
 
+/// Parse a module level attribute, e.g. `#test` or `#namespace("example.main")`.
+/// # Panics
+/// Panics if the next token isn't `#`.
+pub fn parse_attribute(par: &mut Parser) -> ParseResult<Node<Attribute>> {
+    let hash = par.assert(TokenKind::Hash);
+    let name = par.expect_with_notes(TokenKind::Name, "failed to parse attribute definition", |_|
+        vec!["Note: an attribute name must start with a letter or underscore, and contain letters, numbers, or underscores".into()])?;
+    let name = Node::new(name.text.into(), name.span);
+
+    let mut span = hash.span + name.span;
+    let arg = if par.peek() == Some(TokenKind::ParenOpen) {
+        par.next()?;
+        let tok = par.expect(TokenKind::Text, "failed to parse attribute argument")?;
+        let arg = match unescape_string(tok.text) {
+            Some(arg) => arg.into(),
+            None => {
+                par.error(tok.span, "String contains an invalid escape sequence");
+                tok.text.into()
+            }
+        };
+        span += par
+            .expect(TokenKind::ParenClose, "failed to parse attribute argument")?
+            .span;
+        Some(Node::new(arg, tok.span))
+    } else {
+        None
+    };
+
+    Ok(Node::new(Attribute { name, arg }, span))
+}
+
 /// Parse a constant, e.g. `const MAGIC_NUMBER: u256 = 4711`.
 /// # Panics
 /// Panics if the next token isn't `const`.
@@ -237,8 +237,22 @@ test_parse! { contract_def, try_parse_module, r#"contract Foo {
 }
 "# }
 
+test_parse! { contract_def_attributes, try_parse_module, r#"contract Foo {
+  #transient
+  x: u8
+
+  #payable
+  pub fn foo(mut ctx: Context) {}
+}
+"# }
+
 test_parse! { empty_contract_def, try_parse_module, "contract Foo {}" }
 
+test_parse! { module_attributes, try_parse_module, r#"#unpacked
+#namespace("example.main")
+contract Foo {}
+"# }
+
 test_parse! { pub_contract_def, try_parse_module, r#"
 pub contract Foo {
     pub fn foo() -> u8 {
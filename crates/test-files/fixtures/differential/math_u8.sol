@@ -76,4 +76,14 @@ contract Foo {
           z = 1;
       }
   }
-}
\ No newline at end of file
+
+  function count_up(uint8 val1, uint8 val2) public pure returns (uint8) {
+      uint8 count = 0;
+      uint8 i = val1;
+      while (i < val2) {
+          i += 1;
+          count += 1;
+      }
+      return count;
+  }
+}
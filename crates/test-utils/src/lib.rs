@@ -336,17 +336,84 @@ pub fn deploy_contract(
     init_params: &[ethabi::Token],
 ) -> ContractHarness {
     let mut db = driver::Db::default();
+    compile_and_deploy_contract(executor, &mut db, fixture, contract_name, init_params)
+}
+
+/// Deploys a contract compiled with all optimizations but the elision of
+/// checks which the range analysis proves safe.
+#[allow(dead_code)]
+#[cfg(feature = "solc-backend")]
+pub fn deploy_contract_without_check_elimination(
+    executor: &mut Executor,
+    fixture: &str,
+    contract_name: &str,
+    init_params: &[ethabi::Token],
+) -> ContractHarness {
+    use driver::MirDb;
+
+    let mut db = driver::Db::default();
+    db.set_mir_check_elimination_enabled(false);
+    compile_and_deploy_contract(executor, &mut db, fixture, contract_name, init_params)
+}
+
+/// Deploys a contract whose calls are dispatched with the given strategy,
+/// compiled with the native backend or with solc.
+#[allow(dead_code)]
+#[cfg(feature = "solc-backend")]
+pub fn deploy_contract_with_dispatcher(
+    executor: &mut Executor,
+    fixture: &str,
+    contract_name: &str,
+    init_params: &[ethabi::Token],
+    dispatcher: driver::DispatcherStrategy,
+    native_backend: bool,
+) -> ContractHarness {
+    use driver::CodegenDb;
+
+    let mut db = driver::Db::default();
+    db.set_codegen_dispatcher_strategy(dispatcher);
+    db.set_codegen_native_backend(native_backend);
+    compile_and_deploy_contract(executor, &mut db, fixture, contract_name, init_params)
+}
+
+/// Deploys a contract whose Yul code is compiled with the native backend
+/// instead of solc.
+#[allow(dead_code)]
+#[cfg(feature = "solc-backend")]
+pub fn deploy_contract_with_native_backend(
+    executor: &mut Executor,
+    fixture: &str,
+    contract_name: &str,
+    init_params: &[ethabi::Token],
+) -> ContractHarness {
+    use driver::CodegenDb;
+
+    let mut db = driver::Db::default();
+    db.set_codegen_native_backend(true);
+    compile_and_deploy_contract(executor, &mut db, fixture, contract_name, init_params)
+}
+
+#[cfg(feature = "solc-backend")]
+fn compile_and_deploy_contract(
+    executor: &mut Executor,
+    db: &mut driver::Db,
+    fixture: &str,
+    contract_name: &str,
+    init_params: &[ethabi::Token],
+) -> ContractHarness {
     let compiled_module = match driver::compile_single_file(
-        &mut db,
+        db,
         fixture,
         test_files::fixture(fixture),
         true,
         false,
-        true,
+        false,
+        &driver::OptimizerSettings::default(),
+        None,
     ) {
         Ok(module) => module,
         Err(error) => {
-            fe_common::diagnostics::print_diagnostics(&db, &error.0);
+            fe_common::diagnostics::print_diagnostics(db, &error.0);
             panic!("failed to compile module: {fixture}")
         }
     };
@@ -377,7 +444,15 @@ pub fn deploy_contract_from_ingot(
     let files = test_files::fixture_dir_files("ingots");
     let build_files = BuildFiles::load_static(files, path).expect("failed to load build files");
     let mut db = driver::Db::default();
-    let compiled_module = match driver::compile_ingot(&mut db, &build_files, true, false, true) {
+    let compiled_module = match driver::compile_ingot(
+        &mut db,
+        &build_files,
+        true,
+        false,
+        false,
+        &driver::OptimizerSettings::default(),
+        None,
+    ) {
         Ok(module) => module,
         Err(error) => {
             fe_common::diagnostics::print_diagnostics(&db, &error.0);
@@ -594,7 +669,9 @@ pub fn load_contract(address: H160, fixture: &str, contract_name: &str) -> Contr
         test_files::fixture(fixture),
         true,
         false,
-        true,
+        false,
+        &driver::OptimizerSettings::default(),
+        None,
     )
     .unwrap_or_else(|err| {
         print_diagnostics(&db, &err.0);
@@ -718,9 +795,18 @@ impl ExecutionOutput {
 
 #[cfg(feature = "solc-backend")]
 fn execute_runtime_functions(executor: &mut Executor, runtime: &Runtime) -> (ExitReason, Vec<u8>) {
-    let yul_code = runtime.to_yul().to_string().replace('"', "\\\"");
-    let contract_bytecode = fe_yulc::compile_single_contract("Contract", &yul_code, false, false)
-        .expect("failed to compile Yul");
+    let yul_code = runtime.to_yul().to_string();
+    let contract_bytecode = fe_yulc::compile_single_contract(
+        "Contract",
+        &yul_code,
+        &fe_yulc::OptimizerSettings {
+            enabled: false,
+            ..fe_yulc::OptimizerSettings::default()
+        },
+        fe_common::EvmVersion::default(),
+        false,
+    )
+    .expect("failed to compile Yul");
     let bytecode = hex::decode(contract_bytecode.bytecode).expect("failed to decode bytecode");
 
     if let evm::Capture::Exit((reason, _, output)) = executor.create(
@@ -0,0 +1,201 @@
+//! Tests of contracts which use the instructions of the Cancun upgrade.
+//!
+//! The executor of the other tests doesn't support Cancun, so these contracts
+//! are run on `aurora-evm`, and each call is a transaction of its own.
+
+#![cfg(feature = "solc-backend")]
+use aurora_evm::backend::{MemoryBackend, MemoryVicinity};
+use aurora_evm::executor::stack::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+use aurora_evm::{Config, CreateScheme, ExitReason};
+use fe_compiler_test_utils::{address_token, bool_token, uint_token, DEFAULT_CALLER};
+use fe_driver::{AnalyzerDb, CodegenDb};
+use primitive_types::U256;
+use std::collections::BTreeMap;
+
+type Executor<'a, 'b> = StackExecutor<'a, 'b, MemoryStackState<'a, 'a, MemoryBackend<'a>>, ()>;
+
+const GAS_LIMIT: u64 = 10_000_000;
+
+const BLOB_HASHES: [u64; 2] = [0x11, 0x22];
+
+const BLOB_BASE_FEE: u64 = 7;
+
+fn with_cancun_executor(test: &dyn Fn(&mut Executor)) {
+    let vicinity = MemoryVicinity {
+        gas_price: Default::default(),
+        effective_gas_price: Default::default(),
+        origin: Default::default(),
+        chain_id: Default::default(),
+        block_hashes: Vec::new(),
+        block_number: Default::default(),
+        block_coinbase: Default::default(),
+        block_timestamp: Default::default(),
+        block_difficulty: Default::default(),
+        block_randomness: None,
+        block_gas_limit: u64::MAX.into(),
+        block_base_fee_per_gas: Default::default(),
+        blob_gas_price: Some(BLOB_BASE_FEE.into()),
+        blob_hashes: BLOB_HASHES.iter().map(|hash| (*hash).into()).collect(),
+    };
+    let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+    let config = Config::cancun();
+    let state = MemoryStackState::new(StackSubstateMetadata::new(GAS_LIMIT, &config), &backend);
+    let mut executor = StackExecutor::new_with_precompiles(state, &config, &());
+
+    test(&mut executor)
+}
+
+/// A contract compiled for Cancun with the native backend, which doesn't
+/// depend on the EVM versions the linked solc supports.
+struct CancunContract {
+    address: [u8; 20],
+    abi: ethabi::Contract,
+}
+
+impl CancunContract {
+    fn deploy(executor: &mut Executor, fixture: &str, contract_name: &str) -> Self {
+        let mut db = fe_driver::Db::default();
+        db.set_evm_version(fe_driver::EvmVersion::Cancun);
+        db.set_codegen_native_backend(true);
+        let module = match fe_driver::compile_single_file(
+            &mut db,
+            fixture,
+            test_files::fixture(fixture),
+            true,
+            false,
+            false,
+            &fe_driver::OptimizerSettings::default(),
+            None,
+        ) {
+            Ok(module) => module,
+            Err(error) => {
+                fe_common::diagnostics::print_diagnostics(&db, &error.0);
+                panic!("failed to compile module: {fixture}")
+            }
+        };
+        let contract = &module.contracts[contract_name];
+
+        let caller = fe_compiler_test_utils::address(DEFAULT_CALLER);
+        let address = executor.create_address(CreateScheme::Legacy {
+            caller: caller.0.into(),
+        });
+        let bytecode = hex::decode(&contract.bytecode).expect("failed to decode bytecode");
+        let (reason, _) = executor.transact_create(
+            caller.0.into(),
+            Default::default(),
+            bytecode,
+            GAS_LIMIT,
+            Vec::new(),
+        );
+        assert!(
+            matches!(reason, ExitReason::Succeed(_)),
+            "failed to deploy {contract_name}: {reason:?}"
+        );
+
+        CancunContract {
+            address: address.0,
+            abi: ethabi::Contract::load(contract.json_abi.as_bytes())
+                .expect("unable to load the ABI"),
+        }
+    }
+
+    /// Calls the function `name` in a transaction and returns its output.
+    fn call(
+        &self,
+        executor: &mut Executor,
+        name: &str,
+        input: &[ethabi::Token],
+    ) -> Option<ethabi::Token> {
+        let function = &self.abi.functions[name][0];
+        let input = function
+            .encode_input(input)
+            .unwrap_or_else(|reason| panic!("Unable to encode input for {name}: {reason:?}"));
+        let (reason, output) = executor.transact_call(
+            fe_compiler_test_utils::address(DEFAULT_CALLER).0.into(),
+            self.address.into(),
+            Default::default(),
+            input,
+            GAS_LIMIT,
+            Vec::new(),
+        );
+        match reason {
+            ExitReason::Succeed(_) => function
+                .decode_output(&output)
+                .unwrap_or_else(|_| panic!("unable to decode output of {name}: {output:?}"))
+                .pop(),
+            reason => panic!("failed to run \"{name}\": {reason:?}"),
+        }
+    }
+}
+
+#[test]
+fn transient_storage() {
+    with_cancun_executor(&|executor| {
+        let vault = CancunContract::deploy(executor, "features/transient_storage.fe", "Vault");
+
+        vault.call(executor, "deposit", &[uint_token(10)]);
+        // The lock was released, so it can be taken again.
+        vault.call(executor, "deposit", &[uint_token(5)]);
+        assert_eq!(
+            vault.call(executor, "balance_of", &[address_token(DEFAULT_CALLER)]),
+            Some(uint_token(15))
+        );
+        assert_eq!(
+            vault.call(executor, "is_locked", &[]),
+            Some(bool_token(false))
+        );
+
+        // The transient fields are placed from the root slot of the ERC-7201
+        // namespace `example.vault`, so they don't collide with slot `0`.
+        let root = U256::from_str_radix(
+            "d1921ee58d28820c9487d4d5d3eec1942edd7f5897e909e18a400cd2422da100",
+            16,
+        )
+        .unwrap();
+        assert_eq!(
+            vault.call(
+                executor,
+                "transient_word_while_locked",
+                &[ethabi::Token::Uint(root)]
+            ),
+            Some(uint_token(1))
+        );
+        // `depth`
+        assert_eq!(
+            vault.call(
+                executor,
+                "transient_word_while_locked",
+                &[ethabi::Token::Uint(root + 1)]
+            ),
+            Some(uint_token(1))
+        );
+        assert_eq!(
+            vault.call(executor, "transient_word_while_locked", &[uint_token(0)]),
+            Some(uint_token(0))
+        );
+    })
+}
+
+#[test]
+fn cancun_helpers() {
+    with_cancun_executor(&|executor| {
+        let vault = CancunContract::deploy(executor, "features/transient_storage.fe", "Vault");
+
+        assert_eq!(vault.call(executor, "copy_word", &[]), Some(uint_token(42)));
+        assert_eq!(
+            vault.call(executor, "blob_base_fee", &[]),
+            Some(uint_token(BLOB_BASE_FEE))
+        );
+        for (index, hash) in BLOB_HASHES.into_iter().enumerate() {
+            assert_eq!(
+                vault.call(executor, "blob_hash", &[uint_token(index as u64)]),
+                Some(uint_token(hash))
+            );
+        }
+        // There is no blob at this index.
+        assert_eq!(
+            vault.call(executor, "blob_hash", &[uint_token(2)]),
+            Some(uint_token(0))
+        );
+    })
+}
@@ -8,7 +8,17 @@ macro_rules! test_file {
             let mut db = fe_driver::Db::default();
             let path = concat!("crashes/", stringify!($name), ".fe");
             let src = test_files::fixture(path);
-            fe_driver::compile_single_file(&mut db, path, src, true, false, true).ok();
+            fe_driver::compile_single_file(
+                &mut db,
+                path,
+                src,
+                true,
+                false,
+                false,
+                &fe_driver::OptimizerSettings::default(),
+                None,
+            )
+            .ok();
         }
     };
 }
@@ -103,6 +103,7 @@ fn simple_open_auction() {
         );
 
         // action_end test
+        harness.value = U256::zero();
         harness.test_function(&mut executor, "action_end", &[], None);
         validate_revert(
             harness.capture_call(&mut executor, "action_end", &[]),
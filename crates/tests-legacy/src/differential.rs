@@ -7,12 +7,16 @@ use fe_compiler_test_utils::{self as test_utils};
 
 struct DualHarness {
     fe_harness: ContractHarness,
+    /// The Fe contract compiled without eliding the checks which the range
+    /// analysis proves safe, whose gas is compared with `fe_harness`.
+    fe_checked_harness: ContractHarness,
     solidity_harness: ContractHarness,
 }
 
 struct CaptureResult<'a> {
     fe_capture: evm::Capture<(evm::ExitReason, Vec<u8>), std::convert::Infallible>,
     fe_used_gas: u64,
+    fe_checked_used_gas: u64,
     solidity_capture: evm::Capture<(evm::ExitReason, Vec<u8>), std::convert::Infallible>,
     solidity_used_gas: u64,
     name: &'a str,
@@ -28,6 +32,20 @@ impl<'a> CaptureResult<'a> {
         self
     }
 
+    /// Asserts that eliding the overflow and bounds checks which the range
+    /// analysis proves safe doesn't cost gas.
+    pub fn assert_check_elimination_saves_gas(&self) -> &Self {
+        assert!(
+            self.fe_used_gas <= self.fe_checked_used_gas,
+            "Fe used gas: {}, Fe used gas with all checks: {}. Called {} with input: {:?}",
+            self.fe_used_gas,
+            self.fe_checked_used_gas,
+            self.name,
+            self.input
+        );
+        self
+    }
+
     pub fn assert_perfomed_equal(&self) -> &Self {
         assert_eq!(
             self.fe_capture, self.solidity_capture,
@@ -138,6 +156,12 @@ impl<'a> DualHarness {
             contract_name,
             init_params,
         );
+        let fe_checked_harness = test_utils::deploy_contract_without_check_elimination(
+            executor,
+            &format!("differential/{fixture}.fe"),
+            contract_name,
+            init_params,
+        );
         let solidity_harness = test_utils::deploy_solidity_contract(
             executor,
             &format!("differential/{fixture}.sol"),
@@ -147,6 +171,7 @@ impl<'a> DualHarness {
         );
         DualHarness {
             fe_harness,
+            fe_checked_harness,
             solidity_harness,
         }
     }
@@ -157,15 +182,20 @@ impl<'a> DualHarness {
         name: &'a str,
         input: &'a [ethabi::Token],
     ) -> CaptureResult<'a> {
-        let initially_used = executor.used_gas();
-        let fe_capture = self.fe_harness.capture_call(executor, name, input);
-        let fe_used_gas = executor.used_gas() - initially_used;
-        let solidity_capture = self.solidity_harness.capture_call(executor, name, input);
-        let solidity_used_gas = executor.used_gas() - fe_used_gas - initially_used;
+        let (fe_capture, fe_used_gas) = measure_gas(executor, |executor| {
+            self.fe_harness.capture_call(executor, name, input)
+        });
+        let (_, fe_checked_used_gas) = measure_gas(executor, |executor| {
+            self.fe_checked_harness.capture_call(executor, name, input)
+        });
+        let (solidity_capture, solidity_used_gas) = measure_gas(executor, |executor| {
+            self.solidity_harness.capture_call(executor, name, input)
+        });
 
         CaptureResult {
             fe_capture,
             fe_used_gas,
+            fe_checked_used_gas,
             solidity_capture,
             solidity_used_gas,
             name,
@@ -174,6 +204,13 @@ impl<'a> DualHarness {
     }
 }
 
+/// Returns the result of `call` and the gas used by it.
+fn measure_gas<T>(executor: &mut Executor, call: impl FnOnce(&mut Executor) -> T) -> (T, u64) {
+    let initially_used = executor.used_gas();
+    let result = call(executor);
+    (result, executor.used_gas() - initially_used)
+}
+
 proptest! {
 
     #[test]
@@ -183,23 +220,24 @@ proptest! {
 
             let harness = DualHarness::from_fixture(&mut executor, "math_u8", "Foo", &[]);
 
-            harness.capture_call(&mut executor, "add", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(5);
-            harness.capture_call(&mut executor, "subtract", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(5);
-            harness.capture_call(&mut executor, "divide", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(100);
-            harness.capture_call(&mut executor, "multiply", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(5);
-            harness.capture_call(&mut executor, "pow", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(15);
-            harness.capture_call(&mut executor, "modulo", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(25);
-            harness.capture_call(&mut executor, "leftshift", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(25);
-            harness.capture_call(&mut executor, "rightshift", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(25);
-            harness.capture_call(&mut executor, "order_of_operation", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(50);
-            harness.capture_call(&mut executor, "invert", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(60);
-            harness.capture_call(&mut executor, "bit_and", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(15);
-            harness.capture_call(&mut executor, "bit_or", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(50);
-            harness.capture_call(&mut executor, "bit_xor", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(30);
-            harness.capture_call(&mut executor, "cast1", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(50);
-            harness.capture_call(&mut executor, "cast2", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(60);
-            harness.capture_call(&mut executor, "cast3", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(70);
-            harness.capture_call(&mut executor, "sqrt", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(120);
+            harness.capture_call(&mut executor, "add", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(5).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "subtract", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(5).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "divide", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(100).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "multiply", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(5).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "pow", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(15).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "modulo", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(25).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "leftshift", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(25).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "rightshift", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(25).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "order_of_operation", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(50).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "invert", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(60).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "bit_and", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(15).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "bit_or", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(50).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "bit_xor", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(30).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "cast1", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(50).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "cast2", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(60).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "cast3", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(70).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "sqrt", &[uint_token(val.into())]).assert_perfomed_equal().assert_fe_max_percentage_more_gas(120).assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "count_up", &[uint_token(val.into()), uint_token(val2.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
         });
     }
 
@@ -209,19 +247,19 @@ proptest! {
         with_executor(&|mut executor| {
             let harness = DualHarness::from_fixture(&mut executor, "math_i8", "Foo", &[]);
 
-            harness.capture_call(&mut executor, "add", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "subtract", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "divide", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "multiply", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "modulo", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "leftshift", &[int_token(val.into()), uint_token(val3.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "rightshift", &[int_token(val.into()), uint_token(val3.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "order_of_operation", &[int_token(val.into()), int_token(val2.into()), uint_token(val3.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "invert", &[int_token(val.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "cast1", &[int_token(val.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "cast2", &[int_token(val.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "cast3", &[int_token(val.into())]).assert_perfomed_equal();
-            harness.capture_call(&mut executor, "negate", &[int_token(val.into())]).assert_perfomed_equal();
+            harness.capture_call(&mut executor, "add", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "subtract", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "divide", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "multiply", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "modulo", &[int_token(val.into()), int_token(val2.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "leftshift", &[int_token(val.into()), uint_token(val3.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "rightshift", &[int_token(val.into()), uint_token(val3.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "order_of_operation", &[int_token(val.into()), int_token(val2.into()), uint_token(val3.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "invert", &[int_token(val.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "cast1", &[int_token(val.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "cast2", &[int_token(val.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "cast3", &[int_token(val.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
+            harness.capture_call(&mut executor, "negate", &[int_token(val.into())]).assert_perfomed_equal().assert_check_elimination_saves_gas();
         });
     }
 
@@ -8,6 +8,7 @@ use primitive_types::{H160, U256};
 use rstest::rstest;
 use std::collections::BTreeMap;
 
+use fe_common::utils::files::BuildFiles;
 use fe_common::utils::keccak;
 use fe_compiler_test_utils::*;
 use fe_compiler_test_utils::{self as test_utils};
@@ -431,7 +432,6 @@ fn return_builtin_attributes() {
         let sender = address_token("1234000000000000000000000000000000005678");
         harness.caller = sender.clone().into_address().unwrap();
         let value = 55555;
-        harness.value = U256::from(value);
         harness.test_function(&mut executor, "base_fee", &[], Some(&uint_token(basefee)));
         harness.test_function(&mut executor, "coinbase", &[], Some(&block_coinbase));
         harness.test_function(
@@ -454,7 +454,9 @@ fn return_builtin_attributes() {
         );
         harness.test_function(&mut executor, "chainid", &[], Some(&uint_token(chain_id)));
         harness.test_function(&mut executor, "sender", &[], Some(&sender));
+        harness.value = U256::from(value);
         harness.test_function(&mut executor, "value", &[], Some(&uint_token(value)));
+        harness.value = U256::zero();
         harness.test_function(&mut executor, "origin", &[], Some(&origin));
         harness.test_function(
             &mut executor,
@@ -1885,6 +1887,407 @@ fn intrinsics() {
     });
 }
 
+#[test]
+fn packed_storage() {
+    with_executor(&|mut executor| {
+        let point = |x: u64, y: u64| ethabi::Token::Tuple(vec![uint_token(x), uint_token(y)]);
+
+        let harness = deploy_contract(&mut executor, "packed_storage.fe", "Packed", &[]);
+        harness.test_function(&mut executor, "set", &[], None);
+
+        // `a`, `b` and `c` share the first slot from its least significant byte as in
+        // Solidity, and fields following `point` start a new slot.
+        harness.test_function(
+            &mut executor,
+            "slot",
+            &[uint_token(0)],
+            Some(&uint_token(255 | 1 << 8 | 2 << 16)),
+        );
+        harness.test_function(
+            &mut executor,
+            "slot",
+            &[uint_token(1)],
+            Some(&uint_token(3 | 4 << 8)),
+        );
+        harness.test_function(
+            &mut executor,
+            "slot",
+            &[uint_token(2)],
+            Some(&uint_token(5 | 0xff << 8)),
+        );
+
+        harness.test_function(&mut executor, "get_b", &[], Some(&bool_token(true)));
+        harness.test_function(&mut executor, "get_d", &[], Some(&uint_token(5)));
+        harness.test_function(&mut executor, "get_e", &[], Some(&int_token(-1)));
+
+        // Memory uses the same layout, so packed structs are copied between memory and
+        // storage slot by slot.
+        harness.test_function(&mut executor, "get_point", &[], Some(&point(3, 4)));
+        harness.test_function(&mut executor, "set_point", &[point(6, 7)], None);
+        harness.test_function(
+            &mut executor,
+            "slot",
+            &[uint_token(1)],
+            Some(&uint_token(6 | 7 << 8)),
+        );
+        harness.test_function(&mut executor, "get_point", &[], Some(&point(6, 7)));
+        harness.test_function(
+            &mut executor,
+            "make_point",
+            &[uint_token(8), uint_token(9)],
+            Some(&point(8, 9)),
+        );
+
+        let harness = deploy_contract(&mut executor, "packed_storage.fe", "Unpacked", &[]);
+        harness.test_function(&mut executor, "set", &[], None);
+
+        for (slot, value) in [(0, 1), (1, 1), (2, 2), (3, 3), (4, 4)] {
+            harness.test_function(
+                &mut executor,
+                "slot",
+                &[uint_token(slot)],
+                Some(&uint_token(value)),
+            );
+        }
+        harness.test_function(&mut executor, "get_y", &[], Some(&uint_token(4)));
+    });
+}
+
+#[test]
+fn packed_storage_layout() {
+    let mut db = fe_driver::Db::default();
+    let path = "features/packed_storage.fe";
+    let src = test_files::fixture(path);
+    let module = fe_driver::compile_single_file(
+        &mut db,
+        path,
+        src,
+        false,
+        false,
+        false,
+        &fe_driver::OptimizerSettings::default(),
+        None,
+    )
+    .unwrap();
+
+    // The layout agrees with the slots read in `packed_storage`.
+    let layout: serde_json::Value =
+        serde_json::from_str(&module.contracts["Packed"].storage_layout).unwrap();
+    let fields: Vec<_> = layout["storage"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| {
+            (
+                field["name"].as_str().unwrap(),
+                field["slot"].as_str().unwrap(),
+                field["offset"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("a", "0", 0),
+            ("b", "0", 1),
+            ("c", "0", 2),
+            ("point", "1", 0),
+            ("d", "2", 0),
+            ("e", "2", 1),
+        ]
+    );
+    assert_eq!(layout["storage"][3]["members"][1]["slot"], "1");
+    assert_eq!(layout["storage"][3]["members"][1]["offset"], 1);
+
+    let layout: serde_json::Value =
+        serde_json::from_str(&module.contracts["Unpacked"].storage_layout).unwrap();
+    assert_eq!(layout["storage"][2]["slot"], "2");
+    assert_eq!(layout["storage"][3]["members"][1]["slot"], "4");
+}
+
+#[test]
+fn namespaced_storage() {
+    with_executor(&|mut executor| {
+        // The root slot of the ERC-7201 namespace `example.main`.
+        let root = U256::from_str_radix(
+            "183a6125c38840424c4a85fa12bab2ab606c4b6d0e7cc73c0c06ba5300eab500",
+            16,
+        )
+        .unwrap();
+        let slot = |offset: u64| ethabi::Token::Uint(root + offset);
+
+        let harness = deploy_contract(&mut executor, "namespaced_storage.fe", "Namespaced", &[]);
+        harness.test_function(&mut executor, "set", &[], None);
+
+        harness.test_function(
+            &mut executor,
+            "slot",
+            &[uint_token(0)],
+            Some(&uint_token(0)),
+        );
+        harness.test_function(&mut executor, "slot", &[slot(0)], Some(&uint_token(1)));
+        harness.test_function(
+            &mut executor,
+            "slot",
+            &[slot(1)],
+            Some(&uint_token(2 | 3 << 8)),
+        );
+        harness.test_function(
+            &mut executor,
+            "slot",
+            &[slot(2)],
+            Some(&uint_token(4 | 5 << 8)),
+        );
+        // The length of `name`.
+        harness.test_function(&mut executor, "slot", &[slot(4)], Some(&uint_token(2)));
+
+        harness.test_function(&mut executor, "get_y", &[], Some(&uint_token(5)));
+        harness.test_function(
+            &mut executor,
+            "get_balance",
+            &[uint_token(6)],
+            Some(&uint_token(7)),
+        );
+        harness.test_function(&mut executor, "get_name", &[], Some(&string_token("fe")));
+    });
+}
+
+#[test]
+fn namespaced_storage_layout() {
+    let mut db = fe_driver::Db::default();
+    let path = "features/namespaced_storage.fe";
+    let src = test_files::fixture(path);
+    let module = fe_driver::compile_single_file(
+        &mut db,
+        path,
+        src,
+        false,
+        false,
+        false,
+        &fe_driver::OptimizerSettings::default(),
+        None,
+    )
+    .unwrap();
+
+    let layout: serde_json::Value =
+        serde_json::from_str(&module.contracts["Namespaced"].storage_layout).unwrap();
+    assert_eq!(layout["namespace"], "example.main");
+    // `0x183a6125c38840424c4a85fa12bab2ab606c4b6d0e7cc73c0c06ba5300eab500` in decimal.
+    assert_eq!(
+        layout["storage"][0]["slot"],
+        "10958655983261152271848436692291137275443024275653522991983264966744321209600"
+    );
+    assert_eq!(
+        layout["storage"][5]["slot"],
+        "10958655983261152271848436692291137275443024275653522991983264966744321209604"
+    );
+}
+
+#[test]
+fn metadata_trailer_ends_runtime_code() {
+    with_executor(&|mut executor| {
+        let path = "features/return_u256.fe";
+        let mut db = fe_driver::Db::default();
+        let module = fe_driver::compile_single_file(
+            &mut db,
+            path,
+            test_files::fixture(path),
+            true,
+            true,
+            false,
+            &fe_driver::OptimizerSettings::default(),
+            None,
+        )
+        .unwrap();
+        let trailer =
+            fe_yulc::metadata_trailer(&module.contracts["Foo"].metadata, env!("CARGO_PKG_VERSION"));
+
+        let harness = deploy_contract(&mut executor, "return_u256.fe", "Foo", &[]);
+        let code = executor.code(harness.address);
+        assert!(
+            code.ends_with(&trailer),
+            "the runtime code doesn't end with the metadata trailer"
+        );
+    })
+}
+
+/// Writes the ingot fixtures to the directory `dir` of the temporary directory
+/// and returns the path of the directory.
+fn write_ingot_fixtures(dir: &str) -> std::path::PathBuf {
+    let root = std::env::temp_dir().join(format!("fe-{}-{dir}", std::process::id()));
+    for (path, content) in test_files::new_fixture_dir_files("ingots") {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    root
+}
+
+#[test]
+fn metadata_independent_of_build_dir() {
+    let compile = |dir: &str| {
+        let root = write_ingot_fixtures(dir);
+        let build_files =
+            BuildFiles::load_fs(root.join("ingots/basic_ingot").to_str().unwrap()).unwrap();
+        let mut db = fe_driver::Db::default();
+        let module = fe_driver::compile_ingot(
+            &mut db,
+            &build_files,
+            true,
+            true,
+            false,
+            &fe_driver::OptimizerSettings::default(),
+            None,
+        )
+        .unwrap();
+        std::fs::remove_dir_all(root).unwrap();
+        let contract = &module.contracts["Foo"];
+        (
+            contract.metadata.clone(),
+            contract.bytecode.clone(),
+            contract.runtime_bytecode.clone(),
+        )
+    };
+
+    let first = compile("first");
+    assert_eq!(first, compile("second"));
+
+    let metadata: serde_json::Value = serde_json::from_str(&first.0).unwrap();
+    assert!(metadata["sources"]["main.fe"].is_object());
+    assert!(metadata["sources"]["basic_ingot_dep/lib.fe"].is_object());
+}
+
+#[rstest(native_backend, case(false), case(true))]
+fn source_map(native_backend: bool) {
+    use fe_driver::CodegenDb;
+
+    let mut db = fe_driver::Db::default();
+    db.set_codegen_native_backend(native_backend);
+    let path = "features/source_map.fe";
+    let src = test_files::fixture(path);
+    let module = fe_driver::compile_single_file(
+        &mut db,
+        path,
+        src,
+        true,
+        true,
+        false,
+        &fe_driver::OptimizerSettings {
+            enabled: false,
+            ..fe_driver::OptimizerSettings::default()
+        },
+        None,
+    )
+    .unwrap();
+    let contract = &module.contracts["Foo"];
+    let source_map: serde_json::Value = serde_json::from_str(&contract.source_map).unwrap();
+    let locations =
+        fe_yulc::parse_source_map(source_map["runtimeBytecode"].as_str().unwrap()).unwrap();
+
+    // The index of the only `SSTORE` instruction of the runtime code.
+    let code = hex::decode(&contract.runtime_bytecode).unwrap();
+    let mut offset = 0;
+    let mut idx = 0;
+    while code[offset] != 0x55 {
+        let push_len = match code[offset] {
+            op @ 0x60..=0x7f => (op - 0x5f) as usize,
+            _ => 0,
+        };
+        offset += 1 + push_len;
+        idx += 1;
+    }
+
+    let location = locations[idx].expect("the `SSTORE` has no source location");
+    assert_eq!(
+        source_map["sources"][location.file.to_string()],
+        serde_json::json!(path)
+    );
+    assert_eq!(&src[location.start..location.end], "__sstore(7, value)");
+}
+
+#[test]
+fn size_limits() {
+    use fe_driver::{AnalyzerDb, CodegenDb};
+
+    // String constants are stored as data of the runtime code, so a contract
+    // returning two this long exceeds both the EIP-170 and EIP-3860 limits.
+    let text_len = fe_driver::MAX_CODE_SIZE + 24;
+    let src = format!(
+        "contract Large {{\n    \
+         pub fn first() -> String<{text_len}> {{\n        return \"{}\"\n    }}\n\n    \
+         pub fn second() -> String<{text_len}> {{\n        return \"{}\"\n    }}\n}}\n",
+        "a".repeat(text_len),
+        "b".repeat(text_len)
+    );
+    let mut db = fe_driver::Db::default();
+    db.set_evm_version(fe_driver::EvmVersion::Shanghai);
+    db.set_codegen_metadata_hash(false);
+    let module = fe_driver::compile_single_file(
+        &mut db,
+        "large.fe",
+        &src,
+        true,
+        true,
+        false,
+        &fe_driver::OptimizerSettings::default(),
+        None,
+    )
+    .unwrap();
+
+    let warnings: Vec<_> = module
+        .warnings
+        .iter()
+        .map(|warning| warning.message.as_str())
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            "contract `Large` exceeds the EIP-170 code size limit",
+            "contract `Large` exceeds the EIP-3860 initcode size limit",
+        ]
+    );
+
+    let contract = &module.contracts["Large"];
+    let runtime_size = contract.runtime_bytecode.len() / 2;
+    let report = &contract.size_report;
+    assert!(report.starts_with(&format!(
+        "deploy size: {} bytes (EIP-3860 limit: 49152 bytes)\n\
+         runtime size: {runtime_size} bytes (EIP-170 limit: 24576 bytes)\n",
+        contract.bytecode.len() / 2
+    )));
+
+    // Each row is `<origin>  <size> bytes  <share>%`.
+    let rows: Vec<(String, usize)> = report
+        .lines()
+        .skip_while(|line| *line != "runtime code by origin:")
+        .skip(1)
+        .map(|line| {
+            let words: Vec<_> = line.split_whitespace().collect();
+            let (name, size) = words[..words.len() - 2].split_at(words.len() - 3);
+            (name.join(" "), size[0].parse().unwrap())
+        })
+        .collect();
+    let names: Vec<_> = rows.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "dispatcher",
+            "fn first",
+            "fn second",
+            "other functions",
+            "data"
+        ]
+    );
+    assert!(rows[0].1 > 0, "no code is attributed to the dispatcher");
+    assert!(rows[1].1 > 0, "no code is attributed to `first`");
+    assert!(rows[2].1 > 0, "no code is attributed to `second`");
+    assert_eq!(rows[4].1, 2 * text_len);
+    assert_eq!(
+        rows.iter().map(|(_, size)| size).sum::<usize>(),
+        runtime_size
+    );
+}
+
 #[rstest(
     method,
     params,
@@ -1940,6 +2343,65 @@ fn ctx_param_internal_func_call() {
     });
 }
 
+#[test]
+fn receive_fallback() {
+    with_executor(&|mut executor| {
+        let mut harness = deploy_contract(&mut executor, "receive_fallback.fe", "Wallet", &[]);
+
+        // Plain ether transfers are handled by `__receive__`.
+        harness.value = U256::from(100);
+        harness.test_call_returns(&mut executor, vec![], &[]);
+        harness.value = U256::zero();
+        harness.test_function(&mut executor, "get_received", &[], Some(&uint_token(100)));
+
+        // Unknown selectors are handled by `__fallback__`, which isn't payable.
+        harness.test_call_returns(&mut executor, vec![0xde, 0xad, 0xbe, 0xef], &[]);
+        harness.value = U256::from(1);
+        harness.test_call_reverts(&mut executor, vec![0xde, 0xad, 0xbe, 0xef], &[]);
+        harness.value = U256::zero();
+        harness.test_function(&mut executor, "get_fallbacks", &[], Some(&uint_token(1)));
+    })
+}
+
+#[test]
+fn proxy() {
+    with_executor(&|mut executor| {
+        let v1 = deploy_contract(&mut executor, "proxy.fe", "CounterV1", &[]);
+        let v2 = deploy_contract(&mut executor, "proxy.fe", "CounterV2", &[]);
+        let mut proxy = deploy_contract(
+            &mut executor,
+            "proxy.fe",
+            "Proxy",
+            &[ethabi::Token::Address(v1.address)],
+        );
+
+        // Calls to the proxy run the implementation on the storage of the proxy.
+        let counter = load_contract(proxy.address, "proxy.fe", "CounterV1");
+        counter.test_function(&mut executor, "increment", &[], None);
+        counter.test_function(&mut executor, "get", &[], Some(&uint_token(1)));
+        v1.test_function(&mut executor, "get", &[], Some(&uint_token(0)));
+
+        // The upgraded implementation keeps the storage.
+        proxy.test_function(
+            &mut executor,
+            "upgrade_to",
+            &[ethabi::Token::Address(v2.address)],
+            None,
+        );
+        counter.test_function(&mut executor, "increment", &[], None);
+        counter.test_function(&mut executor, "get", &[], Some(&uint_token(3)));
+
+        // Only the admin can upgrade the proxy.
+        proxy.caller = address(SOME_ADDRESS);
+        proxy.test_function_reverts(
+            &mut executor,
+            "upgrade_to",
+            &[ethabi::Token::Address(v1.address)],
+            &encoded_panic_assert(),
+        );
+    })
+}
+
 #[test]
 fn ctx_init_in_call() {
     with_executor(&|mut executor| {
@@ -1975,3 +2437,118 @@ fn execution_tests(fixture_file: &str) {
         assert_harness_gas_report!(harness, fixture_file);
     })
 }
+
+pub fn deploy_contract_with_native_backend(
+    executor: &mut Executor,
+    fixture: &str,
+    contract_name: &str,
+    init_params: &[ethabi::Token],
+) -> ContractHarness {
+    test_utils::deploy_contract_with_native_backend(
+        executor,
+        &format!("features/{fixture}"),
+        contract_name,
+        init_params,
+    )
+}
+
+#[test]
+fn native_backend_compiles_fixtures() {
+    use fe_driver::{AnalyzerDb, CodegenDb};
+
+    for (path, src) in test_files::fixture_dir_files("features") {
+        if !path.ends_with(".fe") {
+            continue;
+        }
+
+        let mut db = fe_driver::Db::default();
+        db.set_codegen_native_backend(true);
+        // Some fixtures use the instructions of the latest version.
+        db.set_evm_version(fe_driver::EvmVersion::Cancun);
+        if let Err(error) = fe_driver::compile_single_file(
+            &mut db,
+            path,
+            src,
+            true,
+            true,
+            false,
+            &fe_driver::OptimizerSettings::default(),
+            None,
+        ) {
+            fe_common::diagnostics::print_diagnostics(&db, &error.0);
+            panic!("failed to compile {path} with the native backend");
+        }
+    }
+}
+
+#[rstest(
+    fixture_file,
+    case::simple_traits("simple_traits.fe"),
+    case::self_type("self_type.fe"),
+    case::trait_associated_functions("trait_associated_functions.fe"),
+    case::generic_functions("generic_functions.fe"),
+    case::generic_functions_primitves("generic_functions_primitves.fe"),
+    case::contract_pure_fns("contract_pure_fns.fe")
+)]
+fn native_backend_execution_tests(fixture_file: &str) {
+    with_executor(&|mut executor| {
+        let harness =
+            deploy_contract_with_native_backend(&mut executor, fixture_file, "Example", &[]);
+        harness.test_function(&mut executor, "run_test", &[], None);
+    })
+}
+
+#[rstest(fixture_file, case("u256_u256_map.fe"), case("u8_u8_map.fe"))]
+fn native_backend_map(fixture_file: &str) {
+    with_executor(&|mut executor| {
+        let harness = deploy_contract_with_native_backend(&mut executor, fixture_file, "Foo", &[]);
+
+        harness.test_function(
+            &mut executor,
+            "write_bar",
+            &[uint_token(4), uint_token(42)],
+            None,
+        );
+        harness.test_function(
+            &mut executor,
+            "read_bar",
+            &[uint_token(4)],
+            Some(&uint_token(42)),
+        );
+    })
+}
+
+#[test]
+fn native_backend_revert() {
+    with_executor(&|mut executor| {
+        let harness = deploy_contract_with_native_backend(&mut executor, "revert.fe", "Foo", &[]);
+
+        validate_revert(
+            harness.capture_call(&mut executor, "revert_other_error", &[]),
+            &encode_revert(
+                "OtherError(uint256,bool)",
+                &[uint_token(1), bool_token(true)],
+            ),
+        );
+    })
+}
+
+#[test]
+fn many_locals() {
+    // More variables are alive at once than the stack can reach, so some are
+    // moved to memory by both backends.
+    with_executor(&|mut executor| {
+        let harness = deploy_contract(&mut executor, "many_locals.fe", "Foo", &[]);
+        let native =
+            deploy_contract_with_native_backend(&mut executor, "many_locals.fe", "Foo", &[]);
+
+        for harness in [harness, native] {
+            harness.test_function(
+                &mut executor,
+                "sum",
+                &[uint_token(3)],
+                Some(&uint_token(20 * 3 + 190)),
+            );
+        }
+    })
+}
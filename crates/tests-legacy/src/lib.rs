@@ -1,4 +1,6 @@
 #[cfg(test)]
+mod cancun;
+#[cfg(test)]
 mod crashes;
 #[cfg(test)]
 mod demo_erc20;
@@ -2,6 +2,7 @@
 
 #![cfg(feature = "solc-backend")]
 use fe_compiler_test_utils::*;
+use fe_driver::DispatcherStrategy;
 use insta::assert_snapshot;
 
 pub fn deploy_contract(
@@ -347,3 +348,56 @@ fn external_calls_stress() {
         assert_harness_gas_report!(proxy_harness);
     });
 }
+
+/// Returns the gas used by calls to each function of `many_functions.fe`
+/// when they're dispatched with `strategy`.
+fn dispatch_gas(
+    executor: &mut Executor,
+    strategy: DispatcherStrategy,
+    native_backend: bool,
+) -> Vec<u64> {
+    let harness = deploy_contract_with_dispatcher(
+        executor,
+        "stress/many_functions.fe",
+        "Foo",
+        &[],
+        strategy,
+        native_backend,
+    );
+    (0..40)
+        .map(|i| {
+            let start_gas = executor.used_gas();
+            harness.test_function(executor, &format!("get_{i}"), &[], Some(&uint_token(i)));
+            executor.used_gas() - start_gas
+        })
+        .collect()
+}
+
+#[test]
+fn dispatcher_strategies() {
+    with_executor(&|mut executor| {
+        let linear = dispatch_gas(&mut executor, DispatcherStrategy::Linear, false);
+        let binary_search = dispatch_gas(&mut executor, DispatcherStrategy::BinarySearch, false);
+        let auto = dispatch_gas(&mut executor, DispatcherStrategy::Auto, false);
+        let jump_table = dispatch_gas(&mut executor, DispatcherStrategy::JumpTable, false);
+
+        let max = |gas: &[u64]| gas.iter().copied().max().unwrap();
+        let total = |gas: &[u64]| gas.iter().sum::<u64>();
+        assert!(max(&binary_search) < max(&linear));
+        assert!(total(&binary_search) < total(&linear));
+        // Contracts with many functions use binary search by default.
+        assert_eq!(auto, binary_search);
+        // solc has no jump tables, so the jump table falls back to binary
+        // search, which still saves gas over linear dispatch.
+        assert_eq!(jump_table, binary_search);
+        assert!(max(&jump_table) < max(&linear));
+        assert!(total(&jump_table) < total(&linear));
+
+        // The native backend jumps to the bucket through a jump table.
+        let linear = dispatch_gas(&mut executor, DispatcherStrategy::Linear, true);
+        let binary_search = dispatch_gas(&mut executor, DispatcherStrategy::BinarySearch, true);
+        let jump_table = dispatch_gas(&mut executor, DispatcherStrategy::JumpTable, true);
+        assert!(total(&binary_search) < total(&linear));
+        assert!(total(&jump_table) < total(&binary_search));
+    })
+}
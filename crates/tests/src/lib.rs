@@ -15,6 +15,7 @@ fn single_file_test_run(fixture: Fixture<&str>) {
         fixture.path(),
         fixture.content(),
         true,
+        None,
     ) {
         Ok((_, tests)) => tests,
         Err(error) => {
@@ -48,7 +49,7 @@ fn ingot_test_run(fixture: Fixture<&str>) {
         BuildFiles::load_fs(input_path).expect("failed to load build files from file system");
 
     let mut db = fe_driver::Db::default();
-    match fe_driver::compile_ingot_tests(&mut db, &build_files, optimize) {
+    match fe_driver::compile_ingot_tests(&mut db, &build_files, optimize, None) {
         Ok(test_batches) => {
             let mut sink = TestSink::new(true);
             for (_, tests) in test_batches {
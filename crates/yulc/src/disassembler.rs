@@ -0,0 +1,185 @@
+//! Disassembly of EVM bytecode into a listing of its instructions.
+
+use std::fmt;
+
+/// An instruction of disassembled bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// The offset of the instruction in the bytecode.
+    pub offset: usize,
+    pub opcode: u8,
+    /// The value pushed by a `PUSH` instruction, which is cut short if the
+    /// bytecode ends before it does.
+    pub immediate: Vec<u8>,
+}
+
+impl Instruction {
+    /// Returns the number of bytes of the instruction.
+    pub fn size(&self) -> usize {
+        1 + self.immediate.len()
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}  ", self.offset)?;
+        match self.opcode {
+            0x60..=0x7f => write!(f, "PUSH{}", self.opcode - 0x5f)?,
+            0x80..=0x8f => write!(f, "DUP{}", self.opcode - 0x7f)?,
+            0x90..=0x9f => write!(f, "SWAP{}", self.opcode - 0x8f)?,
+            0xa0..=0xa4 => write!(f, "LOG{}", self.opcode - 0xa0)?,
+            opcode => match mnemonic(opcode) {
+                Some(mnemonic) => write!(f, "{mnemonic}")?,
+                None => write!(f, "UNDEFINED(0x{opcode:02x})")?,
+            },
+        }
+        if !self.immediate.is_empty() {
+            write!(f, " 0x{}", hex::encode(&self.immediate))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the instructions of `bytecode`, which is decoded up to its end,
+/// data included.
+pub fn disassemble(bytecode: &[u8]) -> Vec<Instruction> {
+    let mut instructions = vec![];
+    let mut offset = 0;
+    while let Some(&opcode) = bytecode.get(offset) {
+        let immediate_size = match opcode {
+            0x60..=0x7f => (opcode - 0x5f) as usize,
+            _ => 0,
+        };
+        let end = (offset + 1 + immediate_size).min(bytecode.len());
+        let instruction = Instruction {
+            offset,
+            opcode,
+            immediate: bytecode[offset + 1..end].to_vec(),
+        };
+        offset += instruction.size();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// Returns the mnemonic of an opcode which isn't a `PUSH`, `DUP`, `SWAP` or
+/// `LOG`, or `None` if it's undefined.
+fn mnemonic(opcode: u8) -> Option<&'static str> {
+    let mnemonic = match opcode {
+        0x00 => "STOP",
+        0x01 => "ADD",
+        0x02 => "MUL",
+        0x03 => "SUB",
+        0x04 => "DIV",
+        0x05 => "SDIV",
+        0x06 => "MOD",
+        0x07 => "SMOD",
+        0x08 => "ADDMOD",
+        0x09 => "MULMOD",
+        0x0a => "EXP",
+        0x0b => "SIGNEXTEND",
+        0x10 => "LT",
+        0x11 => "GT",
+        0x12 => "SLT",
+        0x13 => "SGT",
+        0x14 => "EQ",
+        0x15 => "ISZERO",
+        0x16 => "AND",
+        0x17 => "OR",
+        0x18 => "XOR",
+        0x19 => "NOT",
+        0x1a => "BYTE",
+        0x1b => "SHL",
+        0x1c => "SHR",
+        0x1d => "SAR",
+        0x20 => "KECCAK256",
+        0x30 => "ADDRESS",
+        0x31 => "BALANCE",
+        0x32 => "ORIGIN",
+        0x33 => "CALLER",
+        0x34 => "CALLVALUE",
+        0x35 => "CALLDATALOAD",
+        0x36 => "CALLDATASIZE",
+        0x37 => "CALLDATACOPY",
+        0x38 => "CODESIZE",
+        0x39 => "CODECOPY",
+        0x3a => "GASPRICE",
+        0x3b => "EXTCODESIZE",
+        0x3c => "EXTCODECOPY",
+        0x3d => "RETURNDATASIZE",
+        0x3e => "RETURNDATACOPY",
+        0x3f => "EXTCODEHASH",
+        0x40 => "BLOCKHASH",
+        0x41 => "COINBASE",
+        0x42 => "TIMESTAMP",
+        0x43 => "NUMBER",
+        0x44 => "PREVRANDAO",
+        0x45 => "GASLIMIT",
+        0x46 => "CHAINID",
+        0x47 => "SELFBALANCE",
+        0x48 => "BASEFEE",
+        0x49 => "BLOBHASH",
+        0x4a => "BLOBBASEFEE",
+        0x50 => "POP",
+        0x51 => "MLOAD",
+        0x52 => "MSTORE",
+        0x53 => "MSTORE8",
+        0x54 => "SLOAD",
+        0x55 => "SSTORE",
+        0x56 => "JUMP",
+        0x57 => "JUMPI",
+        0x58 => "PC",
+        0x59 => "MSIZE",
+        0x5a => "GAS",
+        0x5b => "JUMPDEST",
+        0x5c => "TLOAD",
+        0x5d => "TSTORE",
+        0x5e => "MCOPY",
+        0x5f => "PUSH0",
+        0xf0 => "CREATE",
+        0xf1 => "CALL",
+        0xf2 => "CALLCODE",
+        0xf3 => "RETURN",
+        0xf4 => "DELEGATECALL",
+        0xf5 => "CREATE2",
+        0xfa => "STATICCALL",
+        0xfd => "REVERT",
+        0xfe => "INVALID",
+        0xff => "SELFDESTRUCT",
+        _ => return None,
+    };
+    Some(mnemonic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::disassemble;
+
+    #[test]
+    fn listing() {
+        let bytecode = hex::decode("6080604052348015600e575f80fd5b0c61ff").unwrap();
+        let listing: Vec<_> = disassemble(&bytecode)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            listing,
+            [
+                "0000  PUSH1 0x80",
+                "0002  PUSH1 0x40",
+                "0004  MSTORE",
+                "0005  CALLVALUE",
+                "0006  DUP1",
+                "0007  ISZERO",
+                "0008  PUSH1 0x0e",
+                "000a  JUMPI",
+                "000b  PUSH0",
+                "000c  DUP1",
+                "000d  REVERT",
+                "000e  JUMPDEST",
+                "000f  UNDEFINED(0x0c)",
+                "0010  PUSH2 0xff",
+            ]
+        );
+    }
+}
@@ -1,4 +1,19 @@
+use fe_common::EvmVersion;
 use indexmap::map::IndexMap;
+use yultsur::yul;
+
+mod disassembler;
+mod metadata;
+mod native;
+mod optimizer;
+mod solc_binary;
+mod source_map;
+
+pub use disassembler::{disassemble, Instruction};
+pub use metadata::{metadata_data, metadata_trailer, print_metadata_as_hex, METADATA_DATA_NAME};
+pub use optimizer::OptimizerSettings;
+pub use solc_binary::{SolcBinary, SolcVersion};
+pub use source_map::{parse_source_map, SourceLocation};
 
 #[derive(Debug)]
 pub struct YulcError(pub String);
@@ -6,6 +21,11 @@ pub struct YulcError(pub String);
 pub struct ContractBytecode {
     pub bytecode: String,
     pub runtime_bytecode: String,
+    /// The solc source map of `bytecode`, whose file indices are the ones of
+    /// the `@src` comments in the Yul code.
+    pub source_map: String,
+    /// The solc source map of `runtime_bytecode`.
+    pub runtime_source_map: String,
 }
 
 /// Compile a map of Yul contracts to a map of bytecode contracts.
@@ -13,30 +33,103 @@ pub struct ContractBytecode {
 /// Returns a `contract_name -> hex_encoded_bytecode` map.
 pub fn compile(
     contracts: impl Iterator<Item = (impl AsRef<str>, impl AsRef<str>)>,
-    optimize: bool,
+    optimizer: &OptimizerSettings,
+    evm_version: EvmVersion,
 ) -> Result<IndexMap<String, ContractBytecode>, YulcError> {
     contracts
         .map(|(name, yul_src)| {
-            compile_single_contract(name.as_ref(), yul_src.as_ref(), optimize, true)
-                .map(|bytecode| (name.as_ref().to_string(), bytecode))
+            compile_single_contract(
+                name.as_ref(),
+                yul_src.as_ref(),
+                optimizer,
+                evm_version,
+                true,
+            )
+            .map(|bytecode| (name.as_ref().to_string(), bytecode))
         })
         .collect()
 }
 
+/// Compiles a single Yul object to bytecode with the native backend, which
+/// doesn't depend on solc.
+///
+/// The runtime bytecode is the bytecode of the last sub-object, which is where
+/// the runtime object of a contract is placed.
+pub fn compile_single_object(
+    object: &yul::Object,
+    evm_version: EvmVersion,
+    verify_runtime_bytecode: bool,
+) -> Result<ContractBytecode, YulcError> {
+    let assembled = native::assemble_object(object, evm_version)?;
+    let (runtime_bytecode, runtime_source_map) = match assembled.objects.last() {
+        Some(runtime) => (hex::encode(&runtime.bytecode), runtime.source_map.clone()),
+        None if verify_runtime_bytecode => {
+            return Err(YulcError(format!(
+                "object `{}` has no runtime object",
+                object.name.identifier
+            )))
+        }
+        None => (String::new(), String::new()),
+    };
+
+    Ok(ContractBytecode {
+        bytecode: hex::encode(assembled.bytecode),
+        runtime_bytecode,
+        source_map: assembled.source_map,
+        runtime_source_map,
+    })
+}
+
 #[cfg(feature = "solc-backend")]
 /// Compiles a single Yul contract to bytecode.
 pub fn compile_single_contract(
     name: &str,
     yul_src: &str,
-    optimize: bool,
+    optimizer: &OptimizerSettings,
+    evm_version: EvmVersion,
     verify_runtime_bytecode: bool,
 ) -> Result<ContractBytecode, YulcError> {
-    let solc_temp = include_str!("solc_temp.json");
-    let input = solc_temp
-        .replace("{optimizer_enabled}", &optimize.to_string())
-        .replace("{src}", yul_src);
+    let input = standard_json_input(yul_src, optimizer, evm_version);
     let raw_output = solc::compile(&input);
-    let output: serde_json::Value = serde_json::from_str(&raw_output)
+    contract_bytecode(name, &raw_output, verify_runtime_bytecode)
+}
+
+/// Compiles a single Yul contract to bytecode with a solc executable instead
+/// of the built-in backend.
+pub fn compile_single_contract_with_solc(
+    solc: &SolcBinary,
+    name: &str,
+    yul_src: &str,
+    optimizer: &OptimizerSettings,
+    evm_version: EvmVersion,
+    verify_runtime_bytecode: bool,
+) -> Result<ContractBytecode, YulcError> {
+    solc.check_evm_version(evm_version)?;
+    let input = standard_json_input(yul_src, optimizer, evm_version);
+    let raw_output = solc.compile(&input)?;
+    contract_bytecode(name, &raw_output, verify_runtime_bytecode)
+}
+
+fn standard_json_input(
+    yul_src: &str,
+    optimizer: &OptimizerSettings,
+    evm_version: EvmVersion,
+) -> String {
+    let solc_temp = include_str!("solc_temp.json");
+    solc_temp
+        .replace("{optimizer}", &optimizer.to_json().to_string())
+        .replace("{evm_version}", evm_version.name())
+        .replace("{src}", &serde_json::Value::from(yul_src).to_string())
+}
+
+/// Returns the bytecode of the contract `name` in the standard JSON output of
+/// solc, or the output as the error if it isn't there.
+fn contract_bytecode(
+    name: &str,
+    raw_output: &str,
+    verify_runtime_bytecode: bool,
+) -> Result<ContractBytecode, YulcError> {
+    let output: serde_json::Value = serde_json::from_str(raw_output)
         .map_err(|_| YulcError("JSON serialization error".into()))?;
 
     let bytecode = output["contracts"]["input.yul"][name]["evm"]["bytecode"]["object"]
@@ -55,33 +148,61 @@ pub fn compile_single_contract(
         return Err(YulcError(output.to_string()));
     }
 
+    let evm = &output["contracts"]["input.yul"][name]["evm"];
     Ok(ContractBytecode {
         bytecode,
         runtime_bytecode,
+        source_map: evm["bytecode"]["sourceMap"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        runtime_source_map: evm["deployedBytecode"]["sourceMap"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
     })
 }
 
 #[cfg(not(feature = "solc-backend"))]
 /// Compiles a single Yul contract to bytecode.
+///
+/// Without the `solc-backend` feature, Yul source can't be compiled, since the
+/// native backend compiles Yul objects rather than source. Use
+/// `compile_single_object` or `compile_single_contract_with_solc` instead.
 pub fn compile_single_contract(
-    _name: &str,
+    name: &str,
     _yul_src: &str,
-    _optimize: bool,
+    _optimizer: &OptimizerSettings,
+    _evm_version: EvmVersion,
     _verify_runtime_bytecode: bool,
 ) -> Result<ContractBytecode, YulcError> {
-    // This is ugly, but required (as far as I can tell) to make
-    // `cargo test --workspace` work without solc.
-    panic!("fe-yulc requires 'solc-backend' feature")
+    Err(YulcError(format!(
+        "unable to compile `{name}`: compiling Yul source requires the `solc-backend` feature or a solc executable"
+    )))
+}
+
+#[cfg(not(feature = "solc-backend"))]
+#[test]
+fn compile_without_solc() {
+    let err = compile(
+        [("Foo", "object \"Foo\" { code { } }")].into_iter(),
+        &OptimizerSettings::default(),
+        EvmVersion::default(),
+    )
+    .err()
+    .unwrap();
+    assert!(err.0.contains("`solc-backend`"));
 }
 
 #[cfg(feature = "solc-backend")]
 #[test]
 fn test_solc_sanity() {
     let yul_src = "{ sstore(0,0) }";
-    let solc_temp = include_str!("solc_temp.json");
-    let input = solc_temp
-        .replace("{optimizer_enabled}", "false")
-        .replace("{src}", yul_src);
+    let optimizer = OptimizerSettings {
+        enabled: false,
+        ..OptimizerSettings::default()
+    };
+    let input = standard_json_input(yul_src, &optimizer, EvmVersion::default());
 
     let raw_output = solc::compile(&input);
     let output: serde_json::Value = serde_json::from_str(&raw_output).unwrap();
@@ -0,0 +1,146 @@
+//! The CBOR trailer of the runtime bytecode, which refers to the metadata of
+//! the contract by its IPFS hash, as solc does.
+//!
+//! The trailer is the data object [`METADATA_DATA_NAME`] of the runtime
+//! object, which is placed after the rest of the bytecode.
+
+use sha2::{Digest, Sha256};
+use yultsur::yul;
+
+/// The name of the data object which holds the metadata trailer. solc places
+/// data with this name after all other data.
+pub const METADATA_DATA_NAME: &str = ".metadata";
+
+/// The size of the data of an IPFS block.
+const IPFS_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Returns the CBOR trailer of a contract whose metadata JSON is `metadata`:
+/// a map of the IPFS hash of the metadata and of the compiler version,
+/// followed by its length as two big-endian bytes.
+///
+/// Metadata larger than an IPFS block has no `ipfs` entry.
+pub fn metadata_trailer(metadata: &str, compiler_version: &str) -> Vec<u8> {
+    let ipfs = (metadata.len() <= IPFS_CHUNK_SIZE).then(|| ipfs_hash(metadata.as_bytes()));
+
+    // A map of one or two entries.
+    let mut cbor = vec![if ipfs.is_some() { 0xa2 } else { 0xa1 }];
+    if let Some(ipfs) = ipfs {
+        cbor_text(&mut cbor, "ipfs");
+        cbor_bytes(&mut cbor, &ipfs);
+    }
+    cbor_text(&mut cbor, "fe");
+    cbor_bytes(&mut cbor, &version_bytes(compiler_version));
+
+    let len = cbor.len() as u16;
+    cbor.extend(len.to_be_bytes());
+    cbor
+}
+
+/// Returns the data object of `trailer`, whose value is the hex encoding of
+/// the trailer.
+pub fn metadata_data(trailer: &[u8]) -> yul::Data {
+    yul::Data {
+        name: METADATA_DATA_NAME.to_string(),
+        value: hex::encode(trailer),
+    }
+}
+
+/// Prints the value of the metadata data object of printed Yul code as a hex
+/// literal, as which it's given.
+pub fn print_metadata_as_hex(yul: &str) -> String {
+    yul.replace(
+        &format!("data \"{METADATA_DATA_NAME}\" \""),
+        &format!("data \"{METADATA_DATA_NAME}\" hex\""),
+    )
+}
+
+/// Returns the multihash of the IPFS file of `content`, which fits in one
+/// block.
+fn ipfs_hash(content: &[u8]) -> Vec<u8> {
+    // The unixfs `Data` of a file: its type, content and size.
+    let mut data = vec![0x08, 0x02];
+    if !content.is_empty() {
+        data.push(0x12);
+        varint(&mut data, content.len());
+        data.extend(content);
+    }
+    data.push(0x18);
+    varint(&mut data, content.len());
+
+    // The `PBNode` of the block, which has the `Data` and no links.
+    let mut node = vec![0x0a];
+    varint(&mut node, data.len());
+    node.extend(data);
+
+    // The sha2-256 multihash.
+    let mut hash = vec![0x12, 0x20];
+    hash.extend(Sha256::digest(&node));
+    hash
+}
+
+fn varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn cbor_text(out: &mut Vec<u8>, text: &str) {
+    cbor_head(out, 0x60, text.len());
+    out.extend(text.as_bytes());
+}
+
+fn cbor_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    cbor_head(out, 0x40, bytes.len());
+    out.extend(bytes);
+}
+
+/// Encodes the major type and the length of a string shorter than 256 bytes.
+fn cbor_head(out: &mut Vec<u8>, major_type: u8, len: usize) {
+    if len < 24 {
+        out.push(major_type + len as u8);
+    } else {
+        out.extend([major_type + 24, len as u8]);
+    }
+}
+
+/// Returns the major, minor and patch version of a version like `0.26.0`.
+fn version_bytes(version: &str) -> [u8; 3] {
+    let mut parts = version
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse().unwrap_or_default());
+    [(); 3].map(|_| parts.next().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ipfs_hash, metadata_trailer, print_metadata_as_hex};
+
+    #[test]
+    fn ipfs_hash_of_empty_file() {
+        // `QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH`
+        assert_eq!(
+            hex::encode(ipfs_hash(b"")),
+            "1220bfccda787baba32b59c78450ac3d20b633360b43992c77289f9ed46d843561e6"
+        );
+    }
+
+    #[test]
+    fn trailer() {
+        let trailer = metadata_trailer("{}", "0.26.0");
+        let len = trailer.len();
+        assert_eq!(len, 51);
+        assert_eq!(&trailer[len - 2..], [0, 49]);
+        assert_eq!(hex::encode(&trailer[..7]), "a2646970667358");
+        assert_eq!(hex::encode(&trailer[len - 9..len - 2]), "62666543001a00");
+    }
+
+    #[test]
+    fn print_hex() {
+        assert_eq!(
+            print_metadata_as_hex(r#"object "A" { code { } data ".metadata" "a1" }"#),
+            r#"object "A" { code { } data ".metadata" hex"a1" }"#
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use fe_common::EvmVersion;
+
+use super::opcode::{JUMPDEST, PUSH0, PUSH1};
+use crate::{
+    source_map::{self, SourceLocation},
+    YulcError,
+};
+
+/// The number of bytes of the offsets pushed by [`Assembly::push_wide_label`].
+pub(super) const WIDE_LABEL_WIDTH: usize = 2;
+
+/// A jump target in an [`Assembly`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Label(usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Op(u8),
+    /// Pushes a big-endian value without leading zero bytes.
+    Push(Vec<u8>),
+    PushLabel(Label),
+    PushWideLabel(Label),
+    /// Pushes the offset of the data at the given offset of the appendix,
+    /// which is placed right after the code.
+    PushDataOffset(usize),
+    Label(Label),
+}
+
+/// EVM code whose jump targets and data offsets aren't resolved yet.
+#[derive(Debug)]
+pub(super) struct Assembly {
+    items: Vec<Item>,
+    /// The source locations of the items, each of which is one instruction.
+    sources: Vec<Option<SourceLocation>>,
+    /// The source location of the items added next.
+    source: Option<SourceLocation>,
+    label_count: usize,
+    /// Whether zero is pushed with `push0`.
+    has_push0: bool,
+}
+
+impl Assembly {
+    pub(super) fn new(evm_version: EvmVersion) -> Self {
+        Self {
+            items: vec![],
+            sources: vec![],
+            source: None,
+            label_count: 0,
+            has_push0: evm_version.has_push0(),
+        }
+    }
+
+    pub(super) fn new_label(&mut self) -> Label {
+        self.label_count += 1;
+        Label(self.label_count - 1)
+    }
+
+    /// Attributes the items added next to `source`.
+    pub(super) fn set_source(&mut self, source: Option<SourceLocation>) {
+        self.source = source;
+    }
+
+    pub(super) fn op(&mut self, opcode: u8) {
+        self.item(Item::Op(opcode))
+    }
+
+    /// Pushes a big-endian value of at most 32 bytes.
+    pub(super) fn push(&mut self, value: &[u8]) {
+        let start = value
+            .iter()
+            .position(|byte| *byte != 0)
+            .unwrap_or(value.len());
+        self.item(Item::Push(value[start..].to_vec()))
+    }
+
+    pub(super) fn push_label(&mut self, label: Label) {
+        self.item(Item::PushLabel(label))
+    }
+
+    /// Pushes the offset of `label` with [`WIDE_LABEL_WIDTH`] bytes, so that
+    /// the size of the push is known before the labels are resolved.
+    pub(super) fn push_wide_label(&mut self, label: Label) {
+        self.item(Item::PushWideLabel(label))
+    }
+
+    pub(super) fn push_data_offset(&mut self, offset: usize) {
+        self.item(Item::PushDataOffset(offset))
+    }
+
+    /// Places `label` at the current position.
+    pub(super) fn label(&mut self, label: Label) {
+        self.item(Item::Label(label))
+    }
+
+    fn item(&mut self, item: Item) {
+        self.items.push(item);
+        self.sources.push(self.source);
+    }
+
+    /// Returns the solc source map of the bytecode.
+    pub(super) fn source_map(&self) -> String {
+        source_map::compress(&self.sources)
+    }
+
+    /// Resolves the jump targets and data offsets, and returns the bytecode
+    /// followed by `appendix`.
+    ///
+    /// The pushed offsets start with one byte and are widened until every
+    /// offset fits; widening a push only ever moves the following items
+    /// forward, so this terminates. It fails if a jump table entry can't
+    /// reach its target.
+    pub(super) fn assemble(&self, appendix: &[u8]) -> Result<Vec<u8>, YulcError> {
+        let mut widths: Vec<usize> = self
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::PushWideLabel(_) => WIDE_LABEL_WIDTH,
+                _ => 1,
+            })
+            .collect();
+        let (label_offsets, code_size) = loop {
+            let (label_offsets, code_size) = self.layout(&widths);
+            let mut changed = false;
+            for (item, width) in self.items.iter().zip(widths.iter_mut()) {
+                let value = match item {
+                    Item::PushLabel(label) => label_offsets[label.0],
+                    Item::PushDataOffset(offset) => code_size + offset,
+                    _ => continue,
+                };
+                let needed = byte_len(value);
+                if needed > *width {
+                    *width = needed;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break (label_offsets, code_size);
+            }
+        };
+
+        let mut bytecode = Vec::with_capacity(code_size + appendix.len());
+        for (item, width) in self.items.iter().zip(widths.iter()) {
+            match item {
+                Item::Op(opcode) => bytecode.push(*opcode),
+                Item::Push(value) if value.is_empty() && self.has_push0 => bytecode.push(PUSH0),
+                Item::Push(value) if value.is_empty() => bytecode.extend([PUSH1, 0]),
+                Item::Push(value) => {
+                    bytecode.push(PUSH1 + value.len() as u8 - 1);
+                    bytecode.extend(value);
+                }
+                Item::PushLabel(label) => {
+                    push_offset(&mut bytecode, label_offsets[label.0], *width)
+                }
+                Item::PushWideLabel(label) => {
+                    let offset = label_offsets[label.0];
+                    if byte_len(offset) > WIDE_LABEL_WIDTH {
+                        return Err(YulcError(format!(
+                            "the code is too large for a jump table: offset {offset:#x} \
+                             doesn't fit in {WIDE_LABEL_WIDTH} bytes"
+                        )));
+                    }
+                    push_offset(&mut bytecode, offset, *width)
+                }
+                Item::PushDataOffset(offset) => {
+                    push_offset(&mut bytecode, code_size + offset, *width)
+                }
+                Item::Label(_) => bytecode.push(JUMPDEST),
+            }
+        }
+        debug_assert_eq!(bytecode.len(), code_size);
+
+        bytecode.extend(appendix);
+        Ok(bytecode)
+    }
+
+    /// Returns the offsets of the labels and the size of the code when the
+    /// pushed offsets have the given widths.
+    fn layout(&self, widths: &[usize]) -> (Vec<usize>, usize) {
+        let mut label_offsets = vec![0; self.label_count];
+        let mut offset = 0;
+        for (item, width) in self.items.iter().zip(widths) {
+            offset += match item {
+                Item::Op(_) => 1,
+                Item::Push(value) if value.is_empty() && self.has_push0 => 1,
+                Item::Push(value) => 1 + value.len().max(1),
+                Item::PushLabel(_) | Item::PushWideLabel(_) | Item::PushDataOffset(_) => 1 + width,
+                Item::Label(label) => {
+                    label_offsets[label.0] = offset;
+                    1
+                }
+            };
+        }
+
+        (label_offsets, offset)
+    }
+}
+
+fn push_offset(bytecode: &mut Vec<u8>, offset: usize, width: usize) {
+    bytecode.push(PUSH1 + width as u8 - 1);
+    bytecode.extend(&offset.to_be_bytes()[std::mem::size_of::<usize>() - width..]);
+}
+
+/// Returns the number of bytes needed to push `value`.
+fn byte_len(value: usize) -> usize {
+    let bits = (usize::BITS - value.leading_zeros()) as usize;
+    bits.div_ceil(8).max(1)
+}
@@ -0,0 +1,975 @@
+//! Generates EVM code from Yul code whose names are resolved.
+//!
+//! Variables live on the stack. Each variable takes a slot when it's
+//! declared, and gives it up after its last use: the slot is then reused by the
+//! next declaration, or popped once it's on the top of the stack. A variable
+//! declared outside a loop and used inside it is alive until the loop ends.
+//!
+//! Functions are called with the return label below the arguments, the first
+//! argument being on the top. They return with their return values in place
+//! of the return label and the arguments, the last one being on the top.
+//!
+//! A variable which `DUP` or `SWAP` can't reach is moved to memory, and the
+//! code is generated again. The variables in memory are put at the address
+//! given to `memoryguard`, so this is only done if the program calls it,
+//! always with the same address, and never for the variables of recursive
+//! functions.
+
+use std::iter;
+
+use fe_common::EvmVersion;
+
+use super::{
+    asm::{Assembly, Label, WIDE_LABEL_WIDTH},
+    ir::{Block, Expr, FuncId, Function, Program, Stmt, VarId},
+    opcode::{
+        ADD, DUP1, DUP2, EQ, ISZERO, JUMP, JUMPI, LT, MAX_STACK_DEPTH, MLOAD, MSTORE, MUL, POP,
+        STOP, SWAP1,
+    },
+};
+use crate::YulcError;
+
+/// Switches with at least this many cases are compiled into jump tables if
+/// their values are small.
+const JUMP_TABLE_MIN_CASES: usize = 4;
+/// The maximum number of jump table entries per case.
+const JUMP_TABLE_MAX_ENTRIES_PER_CASE: usize = 4;
+/// The size of a jump table entry: a `JUMPDEST`, the push of the target and
+/// a `JUMP`.
+const JUMP_TABLE_ENTRY_SIZE: usize = WIDE_LABEL_WIDTH + 3;
+
+/// The size of a variable in memory.
+const WORD_SIZE: usize = 32;
+
+/// Generates the code of `program`. The top-level code comes first, and the
+/// functions follow it.
+pub(super) fn generate(program: &Program, evm_version: EvmVersion) -> Result<Assembly, YulcError> {
+    let memory_start = memory_guard(program);
+    let recursive = recursive_functions(program);
+    let mut spills = vec![None; program.vars.len()];
+    let mut spill_count = 0;
+
+    loop {
+        let mut gen = CodeGenerator::new(program, evm_version, &spills, spill_count);
+        let error = match gen.program() {
+            Ok(()) => return Ok(gen.asm),
+            Err(error) => error,
+        };
+
+        // Move a variable which is out of reach to memory, and start over.
+        let var = gen
+            .unreachable
+            .iter()
+            .copied()
+            .find(|&var| spills[var].is_none());
+        let is_recursive = matches!(&gen.func, Some(scope) if recursive[scope.id]);
+        match (memory_start, var) {
+            (Some(start), Some(var)) if !is_recursive => {
+                spills[var] = Some(start + spill_count * WORD_SIZE);
+                spill_count += 1;
+            }
+            _ => return Err(error),
+        }
+    }
+}
+
+/// Returns the address given to the `memoryguard` calls of `program`, if
+/// there's any and they agree.
+fn memory_guard(program: &Program) -> Option<usize> {
+    let mut values = vec![];
+    let mut collect = |expr: &Expr| {
+        if let Expr::MemoryGuard(value) = expr {
+            values.push(small_value(value));
+        }
+    };
+    for_each_expr(&program.code, &mut collect);
+    for func in &program.functions {
+        for_each_expr(&func.body, &mut collect);
+    }
+
+    match values.split_first() {
+        Some((first, rest)) if rest.iter().all(|value| value == first) => *first,
+        _ => None,
+    }
+}
+
+/// Returns whether each function of `program` can end up calling itself.
+fn recursive_functions(program: &Program) -> Vec<bool> {
+    let callees: Vec<Vec<FuncId>> = program
+        .functions
+        .iter()
+        .map(|func| {
+            let mut callees = vec![];
+            for_each_expr(&func.body, &mut |expr| {
+                if let Expr::Call(callee, _) = expr {
+                    callees.push(*callee);
+                }
+            });
+            callees
+        })
+        .collect();
+
+    (0..program.functions.len())
+        .map(|func| {
+            let mut visited = vec![false; program.functions.len()];
+            let mut worklist = callees[func].clone();
+            while let Some(callee) = worklist.pop() {
+                if callee == func {
+                    return true;
+                }
+                if !visited[callee] {
+                    visited[callee] = true;
+                    worklist.extend(&callees[callee]);
+                }
+            }
+            false
+        })
+        .collect()
+}
+
+/// A value on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Var(VarId),
+    /// The return label of the current function.
+    ReturnLabel,
+    /// An intermediate value of an expression.
+    Temp,
+    /// A value which is never used again.
+    Junk,
+}
+
+struct BlockScope {
+    height: usize,
+    vars: Vec<VarId>,
+}
+
+struct LoopScope {
+    height: usize,
+    break_label: Label,
+    continue_label: Label,
+    /// The uses of the variables declared outside the loop, which are counted
+    /// when the loop ends.
+    deferred_uses: Vec<VarId>,
+}
+
+struct FuncScope {
+    id: FuncId,
+    height: usize,
+    exit_label: Label,
+}
+
+struct CodeGenerator<'a> {
+    program: &'a Program,
+    asm: Assembly,
+    func_labels: Vec<Label>,
+    /// The memory addresses of the variables which are moved to memory.
+    spills: &'a [Option<usize>],
+    /// The number of variables in memory, which `memoryguard` moves past.
+    spill_count: usize,
+    /// The number of uses left of each variable.
+    uses: Vec<usize>,
+    /// The number of loops around the declaration of each variable.
+    loop_depths: Vec<usize>,
+    /// The stack of the current function, whose top is the last item.
+    stack: Vec<Slot>,
+    blocks: Vec<BlockScope>,
+    loops: Vec<LoopScope>,
+    func: Option<FuncScope>,
+    /// The variables whose values were out of reach. Moving any of them to
+    /// memory brings the others closer.
+    unreachable: Vec<VarId>,
+}
+
+impl<'a> CodeGenerator<'a> {
+    fn new(
+        program: &'a Program,
+        evm_version: EvmVersion,
+        spills: &'a [Option<usize>],
+        spill_count: usize,
+    ) -> Self {
+        let mut asm = Assembly::new(evm_version);
+        let func_labels = program.functions.iter().map(|_| asm.new_label()).collect();
+
+        let mut uses = vec![0; program.vars.len()];
+        count_uses(&program.code, &mut uses);
+        for func in &program.functions {
+            count_uses(&func.body, &mut uses);
+            // The return values are used when the function returns.
+            for &ret in &func.returns {
+                uses[ret] += 1;
+            }
+        }
+
+        Self {
+            program,
+            asm,
+            func_labels,
+            spills,
+            spill_count,
+            uses,
+            loop_depths: vec![0; program.vars.len()],
+            stack: vec![],
+            blocks: vec![],
+            loops: vec![],
+            func: None,
+            unreachable: vec![],
+        }
+    }
+
+    fn program(&mut self) -> Result<(), YulcError> {
+        let program = self.program;
+        self.block(&program.code)?;
+        self.asm.op(STOP);
+        for func_id in 0..program.functions.len() {
+            self.function(func_id)?;
+        }
+        Ok(())
+    }
+
+    fn function(&mut self, id: FuncId) -> Result<(), YulcError> {
+        let program = self.program;
+        let func: &Function = &program.functions[id];
+        // The source of the function is given by the comments in its body.
+        self.asm.set_source(None);
+        self.asm.label(self.func_labels[id]);
+
+        self.stack = vec![Slot::ReturnLabel];
+        for &param in func.params.iter().rev() {
+            let slot = self.var_slot(param);
+            self.stack.push(slot);
+        }
+        for &param in &func.params {
+            if let (Some(address), Some(pos)) = (self.spills[param], self.position(param)) {
+                self.dup(pos)?;
+                self.store_to_memory(address);
+                self.stack[pos] = Slot::Junk;
+            }
+        }
+        for &ret in &func.returns {
+            self.asm.push(&[]);
+            match self.spills[ret] {
+                Some(address) => {
+                    self.stack.push(Slot::Temp);
+                    self.store_to_memory(address);
+                }
+                None => self.stack.push(Slot::Var(ret)),
+            }
+        }
+
+        let exit_label = self.asm.new_label();
+        self.func = Some(FuncScope {
+            id,
+            height: self.stack.len(),
+            exit_label,
+        });
+        self.block(&func.body)?;
+
+        self.asm.label(exit_label);
+        self.stack = iter::once(Slot::ReturnLabel)
+            .chain(iter::repeat_n(Slot::Junk, func.params.len()))
+            .chain(
+                func.returns
+                    .iter()
+                    .filter(|&&ret| self.spills[ret].is_none())
+                    .map(|&ret| Slot::Var(ret)),
+            )
+            .collect();
+        for &ret in &func.returns {
+            if let Some(address) = self.spills[ret] {
+                self.load_from_memory(address);
+                *self.stack.last_mut().unwrap() = Slot::Var(ret);
+            }
+        }
+        let target: Vec<_> = func
+            .returns
+            .iter()
+            .map(|&ret| Slot::Var(ret))
+            .chain(iter::once(Slot::ReturnLabel))
+            .collect();
+        self.shuffle(&target)?;
+        self.asm.op(JUMP);
+
+        self.func = None;
+        Ok(())
+    }
+
+    fn block(&mut self, block: &Block) -> Result<(), YulcError> {
+        self.enter_block();
+        self.statements(block)?;
+        self.leave_block();
+        Ok(())
+    }
+
+    fn enter_block(&mut self) {
+        self.blocks.push(BlockScope {
+            height: self.stack.len(),
+            vars: vec![],
+        });
+    }
+
+    fn leave_block(&mut self) {
+        let scope = self.blocks.pop().unwrap();
+        while self.stack.len() > scope.height {
+            self.pop();
+        }
+        // The variables which were put in the slots of unused variables below
+        // the block stay there.
+        for var in scope.vars {
+            if let Some(pos) = self.position(var) {
+                self.stack[pos] = Slot::Junk;
+            }
+        }
+    }
+
+    /// Generates the statements in the current block.
+    fn statements(&mut self, block: &Block) -> Result<(), YulcError> {
+        for stmt in &block.0 {
+            self.statement(stmt)?;
+
+            let height = self.blocks.last().unwrap().height;
+            while self.stack.len() > height && self.stack.last() == Some(&Slot::Junk) {
+                self.pop();
+            }
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<(), YulcError> {
+        match stmt {
+            Stmt::Expr(expr) => {
+                for _ in 0..self.expr(expr)? {
+                    self.pop();
+                }
+            }
+
+            Stmt::Let(vars, value) => self.declare(vars, value.as_ref())?,
+
+            Stmt::Assign(vars, value) => {
+                self.values(value, vars.len())?;
+                // The last value is on the top.
+                for &var in vars.iter().rev() {
+                    self.store(var)?;
+                }
+            }
+
+            Stmt::If(cond, body) => {
+                let end = self.asm.new_label();
+                self.value(cond)?;
+                self.asm.op(ISZERO);
+                self.jump_if(end);
+                self.block(body)?;
+                self.asm.label(end);
+            }
+
+            Stmt::Switch(scrutinee, cases) => self.switch(scrutinee, cases)?,
+
+            Stmt::For {
+                pre,
+                cond,
+                post,
+                body,
+            } => self.for_loop(pre, cond, post, body)?,
+
+            Stmt::Break | Stmt::Continue => {
+                let (height, label) = match self.loops.last() {
+                    Some(scope) if matches!(stmt, Stmt::Break) => (scope.height, scope.break_label),
+                    Some(scope) => (scope.height, scope.continue_label),
+                    None => return Err(YulcError("`break` or `continue` outside a loop".into())),
+                };
+                self.jump_out(height, label);
+            }
+
+            Stmt::Leave => {
+                let (height, label) = match &self.func {
+                    Some(scope) => (scope.height, scope.exit_label),
+                    None => return Err(YulcError("`leave` outside a function".into())),
+                };
+                self.jump_out(height, label);
+            }
+
+            Stmt::Block(block) => self.block(block)?,
+
+            Stmt::Src(location) => self.asm.set_source(*location),
+        }
+
+        Ok(())
+    }
+
+    fn declare(&mut self, vars: &[VarId], value: Option<&Expr>) -> Result<(), YulcError> {
+        match value {
+            Some(value) => self.values(value, vars.len())?,
+            None => {
+                for _ in vars {
+                    self.asm.push(&[]);
+                    self.stack.push(Slot::Temp);
+                }
+            }
+        }
+
+        let base = self.stack.len() - vars.len();
+        for (i, &var) in vars.iter().enumerate() {
+            self.loop_depths[var] = self.loops.len();
+            self.blocks.last_mut().unwrap().vars.push(var);
+            self.stack[base + i] = self.var_slot(var);
+        }
+
+        // The variables in memory are stored from the top down.
+        for &var in vars.iter().rev() {
+            if let (Some(address), Some(pos)) = (self.spills[var], self.position(var)) {
+                if pos != self.stack.len() - 1 {
+                    self.swap(pos)?;
+                }
+                self.store_to_memory(address);
+            }
+        }
+
+        // Put a single variable in the slot of an unused one if there's any.
+        let top = self.stack.len() - 1;
+        if vars.len() == 1 && self.stack[top] == Slot::Var(vars[0]) {
+            let junk = (top.saturating_sub(MAX_STACK_DEPTH)..top)
+                .rev()
+                .find(|&pos| self.stack[pos] == Slot::Junk);
+            if let Some(pos) = junk {
+                self.swap(pos)?;
+                self.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn switch(
+        &mut self,
+        scrutinee: &Expr,
+        cases: &[(Option<Vec<u8>>, Block)],
+    ) -> Result<(), YulcError> {
+        self.value(scrutinee)?;
+        if let Some(size) = jump_table_size(cases) {
+            return self.jump_table(cases, size);
+        }
+
+        let mut targets = vec![];
+        let mut default = None;
+        for (value, body) in cases {
+            match value {
+                Some(value) => {
+                    let label = self.asm.new_label();
+                    self.asm.op(DUP1);
+                    self.asm.push(value);
+                    self.asm.op(EQ);
+                    self.asm.push_label(label);
+                    self.asm.op(JUMPI);
+                    targets.push((label, body));
+                }
+                None => default = Some(body),
+            }
+        }
+
+        let end = self.asm.new_label();
+        self.pop();
+        if let Some(body) = default {
+            self.block(body)?;
+        }
+        for (label, body) in targets {
+            self.asm.push_label(end);
+            self.asm.op(JUMP);
+
+            self.asm.label(label);
+            self.stack.push(Slot::Temp);
+            self.pop();
+            self.block(body)?;
+        }
+        self.asm.label(end);
+
+        Ok(())
+    }
+
+    /// Jumps to the entry of the scrutinee in a table with an entry for each
+    /// value below `size`, which jumps to the case of the value. The
+    /// scrutinee is on the top of the stack.
+    fn jump_table(
+        &mut self,
+        cases: &[(Option<Vec<u8>>, Block)],
+        size: usize,
+    ) -> Result<(), YulcError> {
+        let in_range = self.asm.new_label();
+        let default_label = self.asm.new_label();
+        let end = self.asm.new_label();
+        let entries: Vec<_> = (0..size).map(|_| self.asm.new_label()).collect();
+
+        self.asm.push(&size.to_be_bytes());
+        self.asm.op(DUP2);
+        self.asm.op(LT);
+        self.asm.push_label(in_range);
+        self.asm.op(JUMPI);
+        self.pop();
+        self.asm.push_label(default_label);
+        self.asm.op(JUMP);
+
+        // The scrutinee is consumed by the jump to its entry.
+        self.asm.label(in_range);
+        self.asm.push(&[JUMP_TABLE_ENTRY_SIZE as u8]);
+        self.asm.op(MUL);
+        self.asm.push_label(entries[0]);
+        self.asm.op(ADD);
+        self.asm.op(JUMP);
+
+        let mut targets = vec![default_label; size];
+        let mut bodies = vec![];
+        let mut default = None;
+        for (value, body) in cases {
+            match value {
+                Some(value) => {
+                    let label = self.asm.new_label();
+                    targets[small_value(value).unwrap()] = label;
+                    bodies.push((label, body));
+                }
+                None => default = Some(body),
+            }
+        }
+        for (entry, target) in entries.into_iter().zip(targets) {
+            self.asm.label(entry);
+            self.asm.push_wide_label(target);
+            self.asm.op(JUMP);
+        }
+
+        self.asm.label(default_label);
+        if let Some(body) = default {
+            self.block(body)?;
+        }
+        for (label, body) in bodies {
+            self.asm.push_label(end);
+            self.asm.op(JUMP);
+
+            self.asm.label(label);
+            self.block(body)?;
+        }
+        self.asm.label(end);
+
+        Ok(())
+    }
+
+    fn for_loop(
+        &mut self,
+        pre: &Block,
+        cond: &Expr,
+        post: &Block,
+        body: &Block,
+    ) -> Result<(), YulcError> {
+        // The variables of `pre` are visible in the whole loop.
+        self.enter_block();
+        self.statements(pre)?;
+
+        let start = self.asm.new_label();
+        let continue_label = self.asm.new_label();
+        let break_label = self.asm.new_label();
+        self.loops.push(LoopScope {
+            height: self.stack.len(),
+            break_label,
+            continue_label,
+            deferred_uses: vec![],
+        });
+
+        self.asm.label(start);
+        self.value(cond)?;
+        self.asm.op(ISZERO);
+        self.jump_if(break_label);
+        self.block(body)?;
+        self.asm.label(continue_label);
+        self.block(post)?;
+        self.asm.push_label(start);
+        self.asm.op(JUMP);
+        self.asm.label(break_label);
+
+        let scope = self.loops.pop().unwrap();
+        for var in scope.deferred_uses {
+            if self.use_var(var) {
+                if let Some(pos) = self.position(var) {
+                    self.stack[pos] = Slot::Junk;
+                }
+            }
+        }
+
+        self.leave_block();
+        Ok(())
+    }
+
+    /// Generates `expr`, and returns the number of values it leaves on the
+    /// stack.
+    fn expr(&mut self, expr: &Expr) -> Result<usize, YulcError> {
+        match expr {
+            Expr::Literal(value) => {
+                self.asm.push(value);
+                self.stack.push(Slot::Temp);
+                Ok(1)
+            }
+
+            Expr::DataOffset(offset) => {
+                self.asm.push_data_offset(*offset);
+                self.stack.push(Slot::Temp);
+                Ok(1)
+            }
+
+            Expr::Var(var) => {
+                self.load(*var)?;
+                Ok(1)
+            }
+
+            Expr::MemoryGuard(value) => {
+                match (small_value(value), self.spill_count) {
+                    (Some(start), count) if count > 0 => {
+                        self.asm.push(&(start + count * WORD_SIZE).to_be_bytes())
+                    }
+                    _ => self.asm.push(value),
+                }
+                self.stack.push(Slot::Temp);
+                Ok(1)
+            }
+
+            Expr::Builtin(inst, args) => {
+                // The arguments are evaluated from right to left, which
+                // leaves the first one on the top.
+                for arg in args.iter().rev() {
+                    self.value(arg)?;
+                }
+                self.asm.op(inst.opcode);
+                self.stack.truncate(self.stack.len() - inst.inputs);
+                self.stack.extend(iter::repeat_n(Slot::Temp, inst.outputs));
+                Ok(inst.outputs)
+            }
+
+            Expr::Call(func, args) => {
+                let return_label = self.asm.new_label();
+                self.asm.push_label(return_label);
+                self.stack.push(Slot::Temp);
+                for arg in args.iter().rev() {
+                    self.value(arg)?;
+                }
+                self.asm.push_label(self.func_labels[*func]);
+                self.asm.op(JUMP);
+                self.asm.label(return_label);
+
+                let outputs = self.program.functions[*func].returns.len();
+                self.stack.truncate(self.stack.len() - args.len() - 1);
+                self.stack.extend(iter::repeat_n(Slot::Temp, outputs));
+                Ok(outputs)
+            }
+        }
+    }
+
+    fn value(&mut self, expr: &Expr) -> Result<(), YulcError> {
+        self.values(expr, 1)
+    }
+
+    fn values(&mut self, expr: &Expr, expected: usize) -> Result<(), YulcError> {
+        let count = self.expr(expr)?;
+        if count != expected {
+            return Err(YulcError(format!(
+                "expected {expected} values, but the expression has {count}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Pushes the value of `var`.
+    fn load(&mut self, var: VarId) -> Result<(), YulcError> {
+        if let Some(address) = self.spills[var] {
+            self.load_from_memory(address);
+            return Ok(());
+        }
+
+        let pos = self.position(var).expect("variable is alive");
+        let last_use = self.use_var(var);
+
+        let top = self.stack.len() - 1;
+        if last_use && pos == top && pos >= self.blocks.last().unwrap().height {
+            // The variable is on the top and isn't needed anymore, so its
+            // value is taken as is.
+            self.stack[pos] = Slot::Temp;
+            return Ok(());
+        }
+
+        self.dup(pos)?;
+        if last_use {
+            self.stack[pos] = Slot::Junk;
+        }
+        Ok(())
+    }
+
+    /// Pops the value on the top into `var`.
+    fn store(&mut self, var: VarId) -> Result<(), YulcError> {
+        if let Some(address) = self.spills[var] {
+            self.store_to_memory(address);
+            return Ok(());
+        }
+
+        // The value is dropped if the variable is never used again.
+        if let Some(pos) = self.position(var) {
+            let depth = self.stack.len() - 1 - pos;
+            if depth > MAX_STACK_DEPTH {
+                return Err(self.stack_too_deep(pos));
+            }
+            self.asm.op(SWAP1 + depth as u8 - 1);
+        }
+        self.pop();
+        Ok(())
+    }
+
+    /// Pushes a copy of the value at `pos`.
+    fn dup(&mut self, pos: usize) -> Result<(), YulcError> {
+        let depth = self.stack.len() - pos;
+        if depth > MAX_STACK_DEPTH {
+            return Err(self.stack_too_deep(pos));
+        }
+        self.asm.op(DUP1 + depth as u8 - 1);
+        self.stack.push(Slot::Temp);
+        Ok(())
+    }
+
+    /// Pushes the value at `address` in memory.
+    fn load_from_memory(&mut self, address: usize) {
+        self.asm.push(&address.to_be_bytes());
+        self.asm.op(MLOAD);
+        self.stack.push(Slot::Temp);
+    }
+
+    /// Pops the value on the top to `address` in memory.
+    fn store_to_memory(&mut self, address: usize) {
+        self.asm.push(&address.to_be_bytes());
+        self.asm.op(MSTORE);
+        self.stack.pop();
+    }
+
+    /// Records a use of `var`, and returns `true` if it was the last one.
+    fn use_var(&mut self, var: VarId) -> bool {
+        let loop_depth = self.loop_depths[var];
+        if loop_depth < self.loops.len() {
+            // The variable is used again in the next iteration.
+            self.loops[loop_depth].deferred_uses.push(var);
+            return false;
+        }
+
+        self.uses[var] -= 1;
+        self.uses[var] == 0
+    }
+
+    fn var_slot(&self, var: VarId) -> Slot {
+        if self.uses[var] == 0 {
+            Slot::Junk
+        } else {
+            Slot::Var(var)
+        }
+    }
+
+    fn position(&self, var: VarId) -> Option<usize> {
+        self.stack.iter().rposition(|slot| *slot == Slot::Var(var))
+    }
+
+    /// Rearranges the stack into `target`, and drops the other values.
+    fn shuffle(&mut self, target: &[Slot]) -> Result<(), YulcError> {
+        while matches!(self.stack.last(), Some(slot) if !target.contains(slot)) {
+            self.pop();
+        }
+
+        for (i, slot) in target.iter().enumerate() {
+            if self.stack[i] == *slot {
+                continue;
+            }
+
+            let top = self.stack.len() - 1;
+            let pos = self.stack.iter().position(|s| s == slot).unwrap();
+            if pos == top {
+                self.swap(i)?;
+            } else if i == top {
+                self.swap(pos)?;
+            } else {
+                self.swap(i)?;
+                self.swap(pos)?;
+                self.swap(i)?;
+            }
+        }
+
+        while self.stack.len() > target.len() {
+            self.pop();
+        }
+        Ok(())
+    }
+
+    /// Swaps the top with the value at `pos`.
+    fn swap(&mut self, pos: usize) -> Result<(), YulcError> {
+        let top = self.stack.len() - 1;
+        let depth = top - pos;
+        if depth > MAX_STACK_DEPTH {
+            let error = self.stack_too_deep(pos);
+            if let Slot::Var(var) = self.stack[top] {
+                self.unreachable.push(var);
+            }
+            return Err(error);
+        }
+        self.asm.op(SWAP1 + depth as u8 - 1);
+        self.stack.swap(pos, top);
+        Ok(())
+    }
+
+    fn pop(&mut self) {
+        self.asm.op(POP);
+        self.stack.pop();
+    }
+
+    fn jump_if(&mut self, label: Label) {
+        self.asm.push_label(label);
+        self.asm.op(JUMPI);
+        self.stack.pop();
+    }
+
+    /// Jumps to `label` after popping the stack down to `height`. The stack
+    /// is kept as is for the code which follows.
+    fn jump_out(&mut self, height: usize, label: Label) {
+        for _ in height..self.stack.len() {
+            self.asm.op(POP);
+        }
+        self.asm.push_label(label);
+        self.asm.op(JUMP);
+    }
+
+    /// Returns the error for the value at `pos` being out of reach, and
+    /// records the variable it holds.
+    fn stack_too_deep(&mut self, pos: usize) -> YulcError {
+        let func = self.func.as_ref().map(|scope| scope.id);
+        let what = match self.stack[pos] {
+            Slot::Var(var) => {
+                self.unreachable.push(var);
+                format!("`{}`", self.program.vars[var])
+            }
+            Slot::ReturnLabel => "the return label".to_string(),
+            Slot::Temp | Slot::Junk => "a value".to_string(),
+        };
+        let location = match func {
+            Some(id) => format!("function `{}`", self.program.functions[id].name),
+            None => "the top-level code".to_string(),
+        };
+        YulcError(format!(
+            "stack too deep: {what} is out of reach in {location}"
+        ))
+    }
+}
+
+/// Returns the number of entries of the jump table of a switch, if its cases
+/// are many and their values are small.
+fn jump_table_size(cases: &[(Option<Vec<u8>>, Block)]) -> Option<usize> {
+    let mut count = 0;
+    let mut size = 0;
+    for value in cases.iter().filter_map(|(value, _)| value.as_ref()) {
+        count += 1;
+        size = size.max(small_value(value)? + 1);
+    }
+
+    if count >= JUMP_TABLE_MIN_CASES && size <= count * JUMP_TABLE_MAX_ENTRIES_PER_CASE {
+        Some(size)
+    } else {
+        None
+    }
+}
+
+/// Returns a big-endian value if it fits in two bytes.
+fn small_value(value: &[u8]) -> Option<usize> {
+    let start = value
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(value.len());
+    match &value[start..] {
+        [] => Some(0),
+        [low] => Some(*low as usize),
+        [high, low] => Some((*high as usize) << 8 | *low as usize),
+        _ => None,
+    }
+}
+
+fn count_uses(block: &Block, uses: &mut [usize]) {
+    for stmt in &block.0 {
+        match stmt {
+            Stmt::Expr(expr) | Stmt::Assign(_, expr) | Stmt::Let(_, Some(expr)) => {
+                count_expr_uses(expr, uses)
+            }
+            Stmt::Let(_, None) | Stmt::Break | Stmt::Continue | Stmt::Leave | Stmt::Src(_) => {}
+            Stmt::If(cond, body) => {
+                count_expr_uses(cond, uses);
+                count_uses(body, uses);
+            }
+            Stmt::Switch(scrutinee, cases) => {
+                count_expr_uses(scrutinee, uses);
+                for (_, body) in cases {
+                    count_uses(body, uses);
+                }
+            }
+            Stmt::For {
+                pre,
+                cond,
+                post,
+                body,
+            } => {
+                count_uses(pre, uses);
+                count_expr_uses(cond, uses);
+                count_uses(post, uses);
+                count_uses(body, uses);
+            }
+            Stmt::Block(block) => count_uses(block, uses),
+        }
+    }
+}
+
+fn count_expr_uses(expr: &Expr, uses: &mut [usize]) {
+    match expr {
+        Expr::Literal(_) | Expr::DataOffset(_) | Expr::MemoryGuard(_) => {}
+        Expr::Var(var) => uses[*var] += 1,
+        Expr::Call(_, args) | Expr::Builtin(_, args) => {
+            for arg in args {
+                count_expr_uses(arg, uses);
+            }
+        }
+    }
+}
+
+/// Calls `f` on every expression in `block`, including the nested ones.
+fn for_each_expr(block: &Block, f: &mut impl FnMut(&Expr)) {
+    for stmt in &block.0 {
+        match stmt {
+            Stmt::Expr(expr) | Stmt::Assign(_, expr) | Stmt::Let(_, Some(expr)) => {
+                for_each_nested_expr(expr, f)
+            }
+            Stmt::Let(_, None) | Stmt::Break | Stmt::Continue | Stmt::Leave | Stmt::Src(_) => {}
+            Stmt::If(cond, body) => {
+                for_each_nested_expr(cond, f);
+                for_each_expr(body, f);
+            }
+            Stmt::Switch(scrutinee, cases) => {
+                for_each_nested_expr(scrutinee, f);
+                for (_, body) in cases {
+                    for_each_expr(body, f);
+                }
+            }
+            Stmt::For {
+                pre,
+                cond,
+                post,
+                body,
+            } => {
+                for_each_expr(pre, f);
+                for_each_nested_expr(cond, f);
+                for_each_expr(post, f);
+                for_each_expr(body, f);
+            }
+            Stmt::Block(block) => for_each_expr(block, f),
+        }
+    }
+}
+
+fn for_each_nested_expr(expr: &Expr, f: &mut impl FnMut(&Expr)) {
+    f(expr);
+    if let Expr::Call(_, args) | Expr::Builtin(_, args) = expr {
+        for arg in args {
+            for_each_nested_expr(arg, f);
+        }
+    }
+}
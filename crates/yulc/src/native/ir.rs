@@ -0,0 +1,67 @@
+//! Yul code whose names are resolved, which is what the code generator works
+//! on.
+
+use super::opcode::Instruction;
+use crate::source_map::SourceLocation;
+
+pub(super) type VarId = usize;
+pub(super) type FuncId = usize;
+
+#[derive(Debug, Default)]
+pub(super) struct Program {
+    /// The top-level code of the object.
+    pub(super) code: Block,
+    /// The functions defined anywhere in the object.
+    pub(super) functions: Vec<Function>,
+    /// The names of the variables, indexed by `VarId`.
+    pub(super) vars: Vec<String>,
+}
+
+#[derive(Debug)]
+pub(super) struct Function {
+    pub(super) name: String,
+    pub(super) params: Vec<VarId>,
+    pub(super) returns: Vec<VarId>,
+    pub(super) body: Block,
+}
+
+/// A block with its function definitions hoisted into [`Program::functions`].
+#[derive(Debug, Default)]
+pub(super) struct Block(pub(super) Vec<Stmt>);
+
+#[derive(Debug)]
+pub(super) enum Stmt {
+    Expr(Expr),
+    Let(Vec<VarId>, Option<Expr>),
+    Assign(Vec<VarId>, Expr),
+    If(Expr, Block),
+    Switch(Expr, Vec<(Option<Vec<u8>>, Block)>),
+    For {
+        pre: Block,
+        cond: Expr,
+        post: Block,
+        body: Block,
+    },
+    Break,
+    Continue,
+    Leave,
+    Block(Block),
+    /// Attributes the code that follows to a span of a source file, or marks
+    /// it as generated.
+    Src(Option<SourceLocation>),
+}
+
+#[derive(Debug)]
+pub(super) enum Expr {
+    /// A big-endian value of at most 32 bytes.
+    Literal(Vec<u8>),
+    Var(VarId),
+    Call(FuncId, Vec<Expr>),
+    Builtin(Instruction, Vec<Expr>),
+    /// The offset of the data at the given offset of the appendix.
+    DataOffset(usize),
+    /// The start of the memory which is free to use, given as a big-endian
+    /// value by `memoryguard`. The variables which are moved to memory are
+    /// put there, and the start is moved past them.
+    MemoryGuard(Vec<u8>),
+}
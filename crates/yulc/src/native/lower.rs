@@ -0,0 +1,380 @@
+use fxhash::FxHashMap;
+use num_bigint::BigUint;
+use yultsur::yul;
+
+use super::{
+    ir::{Block, Expr, FuncId, Function, Program, Stmt, VarId},
+    opcode, Appendix,
+};
+use crate::{source_map, YulcError};
+
+/// Resolves the names in the code of an object.
+pub(super) fn lower_code(code: &yul::Block, appendix: &Appendix) -> Result<Program, YulcError> {
+    let mut lowerer = Lowerer {
+        appendix,
+        program: Program::default(),
+        functions: vec![],
+        scopes: vec![],
+    };
+    let code = lowerer.block(code)?;
+
+    let mut program = lowerer.program;
+    program.code = code;
+    program.functions = lowerer
+        .functions
+        .into_iter()
+        .map(|func| func.expect("function is lowered"))
+        .collect();
+    Ok(program)
+}
+
+struct Lowerer<'a> {
+    appendix: &'a Appendix,
+    program: Program,
+    functions: Vec<Option<Function>>,
+    scopes: Vec<Scope>,
+}
+
+#[derive(Default)]
+struct Scope {
+    vars: FxHashMap<String, VarId>,
+    /// The function ids and the numbers of their parameters.
+    functions: FxHashMap<String, (FuncId, usize)>,
+    /// The variables of the enclosing scopes aren't visible in a function.
+    is_function: bool,
+}
+
+impl<'a> Lowerer<'a> {
+    fn block(&mut self, block: &yul::Block) -> Result<Block, YulcError> {
+        self.scopes.push(Scope::default());
+        let block = self.statements(&block.statements);
+        self.scopes.pop();
+        block
+    }
+
+    /// Lowers the statements in the current scope.
+    fn statements(&mut self, statements: &[yul::Statement]) -> Result<Block, YulcError> {
+        // Functions can be called before they're defined.
+        for stmt in statements {
+            if let yul::Statement::FunctionDefinition(def) = stmt {
+                let name = &def.name.identifier;
+                let scope = self.scopes.last_mut().unwrap();
+                if scope.functions.contains_key(name) {
+                    return Err(YulcError(format!("function `{name}` is already defined")));
+                }
+                scope
+                    .functions
+                    .insert(name.clone(), (self.functions.len(), def.parameters.len()));
+                self.functions.push(None);
+            }
+        }
+
+        let mut lowered = vec![];
+        for stmt in statements {
+            match stmt {
+                yul::Statement::FunctionDefinition(def) => self.function(def)?,
+                _ => lowered.push(self.statement(stmt)?),
+            }
+        }
+        Ok(Block(lowered))
+    }
+
+    fn function(&mut self, def: &yul::FunctionDefinition) -> Result<(), YulcError> {
+        let (func_id, _) = self.lookup_function(&def.name.identifier)?;
+        self.scopes.push(Scope {
+            is_function: true,
+            ..Scope::default()
+        });
+        let params = def
+            .parameters
+            .iter()
+            .map(|param| self.declare(param))
+            .collect::<Result<_, _>>()?;
+        let returns = def
+            .returns
+            .iter()
+            .map(|ret| self.declare(ret))
+            .collect::<Result<_, _>>()?;
+        let body = self.block(&def.block);
+        self.scopes.pop();
+
+        self.functions[func_id] = Some(Function {
+            name: def.name.identifier.clone(),
+            params,
+            returns,
+            body: body?,
+        });
+        Ok(())
+    }
+
+    fn statement(&mut self, stmt: &yul::Statement) -> Result<Stmt, YulcError> {
+        Ok(match stmt {
+            yul::Statement::Expression(yul::Expression::Identifier(ident))
+                if source_map::is_comment(&ident.identifier) =>
+            {
+                match source_map::parse_src_comment(&ident.identifier) {
+                    Some(location) => Stmt::Src(location),
+                    None => {
+                        return Err(YulcError(format!(
+                            "unsupported comment `{}`",
+                            ident.identifier
+                        )))
+                    }
+                }
+            }
+            yul::Statement::Expression(expr) => Stmt::Expr(self.expr(expr)?),
+            yul::Statement::VariableDeclaration(decl) => {
+                // The variables aren't visible in their own initial value.
+                let value = match &decl.expression {
+                    Some(expr) => Some(self.expr(expr)?),
+                    None => None,
+                };
+                let vars = decl
+                    .identifiers
+                    .iter()
+                    .map(|ident| self.declare(ident))
+                    .collect::<Result<_, _>>()?;
+                Stmt::Let(vars, value)
+            }
+            yul::Statement::Assignment(assign) => {
+                let vars = assign
+                    .identifiers
+                    .iter()
+                    .map(|ident| self.lookup_var(&ident.identifier))
+                    .collect::<Result<_, _>>()?;
+                Stmt::Assign(vars, self.expr(&assign.expression)?)
+            }
+            yul::Statement::If(if_) => {
+                Stmt::If(self.expr(&if_.expression)?, self.block(&if_.block)?)
+            }
+            yul::Statement::Switch(switch) => {
+                let scrutinee = self.expr(&switch.expression)?;
+                let mut cases = Vec::with_capacity(switch.cases.len());
+                for case in &switch.cases {
+                    let value = match &case.literal {
+                        Some(lit) => Some(literal(&lit.literal)?),
+                        None => None,
+                    };
+                    cases.push((value, self.block(&case.block)?));
+                }
+                Stmt::Switch(scrutinee, cases)
+            }
+            yul::Statement::ForLoop(for_) => {
+                // The variables of `pre` are visible in the whole loop.
+                self.scopes.push(Scope::default());
+                let lowered = self.for_loop(for_);
+                self.scopes.pop();
+                lowered?
+            }
+            yul::Statement::Break => Stmt::Break,
+            yul::Statement::Continue => Stmt::Continue,
+            yul::Statement::Leave => Stmt::Leave,
+            yul::Statement::Block(block) => Stmt::Block(self.block(block)?),
+            yul::Statement::FunctionDefinition(_) => unreachable!(),
+        })
+    }
+
+    fn for_loop(&mut self, for_: &yul::ForLoop) -> Result<Stmt, YulcError> {
+        let pre = self.statements(&for_.pre.statements)?;
+        let cond = self.expr(&for_.condition)?;
+        let post = self.block(&for_.post)?;
+        let body = self.block(&for_.body)?;
+        Ok(Stmt::For {
+            pre,
+            cond,
+            post,
+            body,
+        })
+    }
+
+    fn expr(&mut self, expr: &yul::Expression) -> Result<Expr, YulcError> {
+        Ok(match expr {
+            yul::Expression::Literal(lit) => Expr::Literal(literal(&lit.literal)?),
+            yul::Expression::Identifier(ident) => Expr::Var(self.lookup_var(&ident.identifier)?),
+            yul::Expression::FunctionCall(call) => self.call(call)?,
+        })
+    }
+
+    fn call(&mut self, call: &yul::FunctionCall) -> Result<Expr, YulcError> {
+        let name = call.identifier.identifier.as_str();
+        match name {
+            "datasize" | "dataoffset" => {
+                let (offset, size) = self.data(call)?;
+                return Ok(if name == "datasize" { size } else { offset });
+            }
+            "datacopy" => {
+                let codecopy = opcode::instruction("codecopy").unwrap();
+                let args = self.args(name, &call.arguments, codecopy.inputs)?;
+                return Ok(Expr::Builtin(codecopy, args));
+            }
+            // The variables moved to memory are put at the guarded address,
+            // which is moved past them.
+            "memoryguard" => {
+                let mut args = self.args(name, &call.arguments, 1)?;
+                return match args.pop() {
+                    Some(Expr::Literal(value)) => Ok(Expr::MemoryGuard(value)),
+                    _ => Err(YulcError(
+                        "the argument of `memoryguard` must be a literal".into(),
+                    )),
+                };
+            }
+            _ => {}
+        }
+
+        if let Some(inst) = opcode::instruction(name) {
+            let args = self.args(name, &call.arguments, inst.inputs)?;
+            return Ok(Expr::Builtin(inst, args));
+        }
+
+        let (func_id, params) = self.lookup_function(name)?;
+        let args = self.args(name, &call.arguments, params)?;
+        Ok(Expr::Call(func_id, args))
+    }
+
+    fn args(
+        &mut self,
+        name: &str,
+        args: &[yul::Expression],
+        expected: usize,
+    ) -> Result<Vec<Expr>, YulcError> {
+        if args.len() != expected {
+            return Err(YulcError(format!(
+                "`{name}` takes {expected} arguments, but {} were given",
+                args.len()
+            )));
+        }
+        args.iter().map(|arg| self.expr(arg)).collect()
+    }
+
+    /// Returns the offset and the size of the object or data named by the
+    /// argument of `datasize` or `dataoffset`.
+    fn data(&self, call: &yul::FunctionCall) -> Result<(Expr, Expr), YulcError> {
+        let name = match call.arguments.as_slice() {
+            [yul::Expression::Literal(yul::Literal { literal, .. })]
+            | [yul::Expression::Identifier(yul::Identifier {
+                identifier: literal,
+                ..
+            })] => literal.trim_matches('"'),
+            _ => {
+                return Err(YulcError(format!(
+                    "the argument of `{}` must be a string literal",
+                    call.identifier.identifier
+                )))
+            }
+        };
+
+        // The object itself spans its code and the whole appendix.
+        if name == self.appendix.name {
+            return Ok((
+                Expr::Literal(vec![]),
+                Expr::DataOffset(self.appendix.bytes.len()),
+            ));
+        }
+        match self.appendix.entries.get(name) {
+            Some(&(offset, size)) => Ok((Expr::DataOffset(offset), Expr::Literal(word(size)))),
+            None => Err(YulcError(format!("undefined object or data `{name}`"))),
+        }
+    }
+
+    fn declare(&mut self, ident: &yul::Identifier) -> Result<VarId, YulcError> {
+        let name = &ident.identifier;
+        if self.lookup_var(name).is_ok() {
+            return Err(YulcError(format!("variable `{name}` is already declared")));
+        }
+
+        let var = self.program.vars.len();
+        self.program.vars.push(name.clone());
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .vars
+            .insert(name.clone(), var);
+        Ok(var)
+    }
+
+    fn lookup_var(&self, name: &str) -> Result<VarId, YulcError> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(var) = scope.vars.get(name) {
+                return Ok(*var);
+            }
+            if scope.is_function {
+                break;
+            }
+        }
+        Err(YulcError(format!("undefined variable `{name}`")))
+    }
+
+    fn lookup_function(&self, name: &str) -> Result<(FuncId, usize), YulcError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.functions.get(name).copied())
+            .ok_or_else(|| YulcError(format!("undefined function `{name}`")))
+    }
+}
+
+/// Returns the value of a number, boolean or string literal as big-endian
+/// bytes.
+fn literal(lit: &str) -> Result<Vec<u8>, YulcError> {
+    let invalid = || YulcError(format!("invalid literal `{lit}`"));
+
+    let value = if lit == "true" {
+        word(1)
+    } else if lit == "false" {
+        word(0)
+    } else if let Some(hex) = lit.strip_prefix("0x") {
+        BigUint::parse_bytes(hex.as_bytes(), 16)
+            .ok_or_else(invalid)?
+            .to_bytes_be()
+    } else if lit.len() >= 2 && lit.starts_with('"') && lit.ends_with('"') {
+        // String literals are left-aligned.
+        let mut bytes = unescape(&lit[1..lit.len() - 1]).ok_or_else(invalid)?;
+        if bytes.len() > 32 {
+            return Err(YulcError(format!(
+                "string literal `{lit}` is longer than 32 bytes"
+            )));
+        }
+        bytes.resize(32, 0);
+        bytes
+    } else {
+        BigUint::parse_bytes(lit.as_bytes(), 10)
+            .ok_or_else(invalid)?
+            .to_bytes_be()
+    };
+
+    if value.len() > 32 {
+        return Err(YulcError(format!(
+            "literal `{lit}` doesn't fit in 32 bytes"
+        )));
+    }
+    Ok(value)
+}
+
+fn unescape(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        match chars.next()? {
+            'n' => bytes.push(b'\n'),
+            'r' => bytes.push(b'\r'),
+            't' => bytes.push(b'\t'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                bytes.push(u8::from_str_radix(&hex, 16).ok()?);
+            }
+            c @ ('\\' | '"' | '\'') => bytes.push(c as u8),
+            _ => return None,
+        }
+    }
+    Some(bytes)
+}
+
+fn word(value: usize) -> Vec<u8> {
+    value.to_be_bytes().to_vec()
+}
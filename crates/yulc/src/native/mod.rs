@@ -0,0 +1,358 @@
+//! A backend which assembles Yul objects into EVM bytecode without solc.
+//!
+//! The code of an object is lowered into Yul whose names are resolved, from
+//! which the EVM code is generated. The sub-objects and the data of the
+//! object are appended to its code.
+
+mod asm;
+mod codegen;
+mod ir;
+mod lower;
+mod opcode;
+
+use fe_common::EvmVersion;
+use fxhash::FxHashMap;
+use yultsur::yul;
+
+use crate::{YulcError, METADATA_DATA_NAME};
+
+/// The bytecode of an object and of its sub-objects.
+pub(crate) struct AssembledObject {
+    pub(crate) bytecode: Vec<u8>,
+    /// The solc source map of the code of the object.
+    pub(crate) source_map: String,
+    pub(crate) objects: Vec<AssembledObject>,
+}
+
+pub(crate) fn assemble_object(
+    object: &yul::Object,
+    evm_version: EvmVersion,
+) -> Result<AssembledObject, YulcError> {
+    let mut appendix = Appendix {
+        name: object.name.identifier.clone(),
+        ..Appendix::default()
+    };
+
+    let mut objects = Vec::with_capacity(object.objects.len());
+    for sub_object in &object.objects {
+        let assembled = assemble_object(sub_object, evm_version)?;
+        appendix.insert(&sub_object.name.identifier, &assembled.bytecode);
+        objects.push(assembled);
+    }
+    // The metadata trailer follows the rest of the data, and can't be referred
+    // to by the code.
+    let mut metadata = vec![];
+    for data in &object.data {
+        if data.name == METADATA_DATA_NAME {
+            metadata = hex::decode(&data.value)
+                .map_err(|_| YulcError(format!("invalid hex in data `{METADATA_DATA_NAME}`")))?;
+        } else {
+            appendix.insert(&data.name, data.value.as_bytes());
+        }
+    }
+
+    let program = lower::lower_code(&object.code.block, &appendix)?;
+    let assembly = codegen::generate(&program, evm_version)?;
+    let mut bytecode = assembly.assemble(&appendix.bytes)?;
+    bytecode.extend(metadata);
+
+    Ok(AssembledObject {
+        bytecode,
+        source_map: assembly.source_map(),
+        objects,
+    })
+}
+
+/// The sub-objects and the data of an object, which follow its code.
+#[derive(Debug, Default)]
+struct Appendix {
+    /// The name of the object itself.
+    name: String,
+    /// The offsets and the sizes of the sub-objects and the data in `bytes`.
+    entries: FxHashMap<String, (usize, usize)>,
+    bytes: Vec<u8>,
+}
+
+impl Appendix {
+    fn insert(&mut self, name: &str, bytes: &[u8]) {
+        self.entries
+            .insert(name.to_string(), (self.bytes.len(), bytes.len()));
+        self.bytes.extend(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe_common::EvmVersion;
+    use yultsur::*;
+
+    use super::assemble_object;
+    use crate::compile_single_object;
+
+    fn object(name: &str, code: yul::Code, objects: Vec<yul::Object>) -> yul::Object {
+        yul::Object {
+            name: identifier! {(name)},
+            code,
+            objects,
+            data: vec![],
+        }
+    }
+
+    #[test]
+    fn assemble_builtin_call() {
+        let code = code! { (sstore(0, 0)) };
+        let bytecode = assemble_object(&object("test", code, vec![]), EvmVersion::Paris)
+            .unwrap()
+            .bytecode;
+
+        // push1 0, push1 0, sstore, stop
+        assert_eq!(hex::encode(bytecode), "600060005500");
+    }
+
+    #[test]
+    fn assemble_push0() {
+        let code = code! { (sstore(0, 0)) };
+        let bytecode = assemble_object(&object("test", code, vec![]), EvmVersion::Shanghai)
+            .unwrap()
+            .bytecode;
+
+        // push0, push0, sstore, stop
+        assert_eq!(hex::encode(bytecode), "5f5f5500");
+    }
+
+    #[test]
+    fn assemble_transient_storage() {
+        let code = code! { (tstore(0, (tload(1)))) };
+        let bytecode = assemble_object(&object("test", code, vec![]), EvmVersion::Cancun)
+            .unwrap()
+            .bytecode;
+
+        // push1 1, tload, push0, tstore, stop
+        assert_eq!(hex::encode(bytecode), "60015c5f5d00");
+    }
+
+    #[test]
+    fn assemble_source_map() {
+        let src = |comment: &str| {
+            yul::Statement::Expression(yul::Expression::Identifier(identifier! {(comment)}))
+        };
+        let (start, end) = (src("/** @src 0:5:9 */"), src("/** @src -1:-1:-1 */"));
+        let code = code! {
+            [start]
+            (sstore(0, 0))
+            [end]
+        };
+        let assembled = assemble_object(&object("test", code, vec![]), EvmVersion::Paris).unwrap();
+
+        // push1 0, push1 0 and sstore are attributed to the span, stop isn't
+        assert_eq!(hex::encode(assembled.bytecode), "600060005500");
+        assert_eq!(assembled.source_map, "5:4:0:-;;;-1:-1:-1");
+    }
+
+    #[test]
+    fn assemble_metadata_trailer() {
+        let mut object = object("test", code! { (sstore(0, 0)) }, vec![]);
+        object.data = vec![
+            crate::metadata_data(&[0xa1, 0x00, 0x02]),
+            yul::Data {
+                name: "x".to_string(),
+                value: "ab".to_string(),
+            },
+        ];
+        let bytecode = assemble_object(&object, EvmVersion::Paris)
+            .unwrap()
+            .bytecode;
+
+        // the trailer follows the other data
+        assert_eq!(hex::encode(bytecode), "6000600055006162a10002");
+    }
+
+    #[test]
+    fn assemble_function_call() {
+        let (inc, a, r) = (identifier! {inc}, identifier! {a}, identifier! {r});
+        let func = function_definition! {
+            function [inc]([a]) -> [r] {
+                (r := add(a, 1))
+            }
+        };
+        let code = code! {
+            [func]
+            (sstore(0, (inc(2))))
+        };
+        let bytecode = assemble_object(&object("test", code, vec![]), EvmVersion::Paris)
+            .unwrap()
+            .bytecode;
+
+        assert_eq!(
+            hex::encode(bytecode),
+            concat!(
+                // push the return label and the argument, and jump to `inc`
+                "60076002600c565b",
+                // store the return value
+                "60005500",
+                // `inc`: push `r`, and compute `add(a, 1)` into it
+                "5b6000600182019050",
+                // put `r` in place of the return label and `a`, and return
+                "5b91905056",
+            )
+        );
+    }
+
+    #[test]
+    fn assemble_jump_table() {
+        let switch = switch! {
+            switch (calldataload(0))
+            (case 0 { (sstore(0, 10)) })
+            (case 1 { (sstore(0, 11)) })
+            (case 2 { (sstore(0, 12)) })
+            (case 3 { (sstore(0, 13)) })
+        };
+        let code = code! { [switch] };
+        let bytecode = assemble_object(&object("test", code, vec![]), EvmVersion::Paris)
+            .unwrap()
+            .bytecode;
+
+        assert_eq!(
+            hex::encode(bytecode),
+            concat!(
+                // jump to the default case unless the scrutinee is below 4
+                "60003560048110600e5750602a56",
+                // jump to the entry of the scrutinee
+                "5b60050260160156",
+                // the entries, which jump to the cases
+                "5b61002e56",
+                "5b61003756",
+                "5b61004056",
+                "5b61004956",
+                // the default case is empty
+                "5b604f56",
+                // the cases, which jump to the end
+                "5b600a600055604f56",
+                "5b600b600055604f56",
+                "5b600c600055604f56",
+                "5b600d600055",
+                "5b00",
+            )
+        );
+    }
+
+    #[test]
+    fn jump_table_out_of_reach() {
+        // The first case is larger than the offsets of the table can reach.
+        let store = statement! { sstore(0, 0) };
+        let large = vec![store; 14000];
+        let switch = switch! {
+            switch (calldataload(0))
+            (case 0 { [large...] })
+            (case 1 { (sstore(0, 11)) })
+            (case 2 { (sstore(0, 12)) })
+            (case 3 { (sstore(0, 13)) })
+        };
+        let code = code! { [switch] };
+        let error = assemble_object(&object("test", code, vec![]), EvmVersion::Paris)
+            .err()
+            .unwrap();
+
+        assert!(
+            error
+                .0
+                .starts_with("the code is too large for a jump table"),
+            "{}",
+            error.0
+        );
+    }
+
+    /// Returns code which sets the free memory pointer with `memoryguard`, if
+    /// `guarded`, and calls a function with more variables alive at once than
+    /// the stack can reach.
+    fn deep_code(guarded: bool) -> yul::Code {
+        let (f, p, r) = (identifier! {f}, identifier! {p}, identifier! {r});
+        let vars: Vec<_> = (0..20).map(|i| identifier! {(format!("v{i}"))}).collect();
+        let decls: Vec<_> = vars
+            .iter()
+            .enumerate()
+            .map(|(i, var)| {
+                let i = literal_expression! {(i)};
+                statement! { let [var.clone()] := add(p, [i]) }
+            })
+            .collect();
+        // The variables are added from the last one, so they're all alive
+        // until then.
+        let sum = vars.iter().rev().skip(1).fold(
+            yul::Expression::Identifier(vars[19].clone()),
+            |sum, var| {
+                let var = yul::Expression::Identifier(var.clone());
+                expression! { add([var], [sum]) }
+            },
+        );
+        let func = function_definition! {
+            function [f]([p]) -> [r] {
+                [decls...]
+                (r := [sum])
+            }
+        };
+        let guard = if guarded {
+            statements! { (mstore(64, (memoryguard(128)))) }
+        } else {
+            vec![]
+        };
+
+        code! {
+            [func]
+            [guard...]
+            (sstore(0, (f(1))))
+        }
+    }
+
+    #[test]
+    fn move_variables_to_memory() {
+        let bytecode = assemble_object(&object("test", deep_code(true), vec![]), EvmVersion::Paris)
+            .unwrap()
+            .bytecode;
+
+        // Six variables are moved to memory, past which the guarded memory
+        // starts: push2 0x140, push1 64, mstore
+        assert!(hex::encode(bytecode).starts_with("610140604052"));
+    }
+
+    #[test]
+    fn stack_too_deep_without_memoryguard() {
+        let error = assemble_object(&object("test", deep_code(false), vec![]), EvmVersion::Paris)
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            error.0,
+            "stack too deep: `p` is out of reach in function `f`"
+        );
+    }
+
+    #[test]
+    fn compile_deployable_object() {
+        let runtime = object(
+            "runtime",
+            code! {
+                (mstore(0, 42))
+                (return(0, 32))
+            },
+            vec![],
+        );
+        let symbol = literal_expression! {("\"runtime\"".to_string())};
+        let deployer = object(
+            "deployer",
+            code! {
+                (datacopy(0, (dataoffset([symbol.clone()])), (datasize([symbol.clone()]))))
+                (return(0, (datasize([symbol]))))
+            },
+            vec![runtime],
+        );
+
+        let compiled = compile_single_object(&deployer, EvmVersion::Paris, true).unwrap();
+        assert_eq!(compiled.runtime_bytecode, "602a60005260206000f300");
+        // The runtime object follows the 13 bytes of code.
+        assert_eq!(
+            compiled.bytecode,
+            "600b600d600039600b6000f300602a60005260206000f300"
+        );
+    }
+}
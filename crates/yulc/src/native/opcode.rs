@@ -0,0 +1,130 @@
+pub(super) const STOP: u8 = 0x00;
+pub(super) const ADD: u8 = 0x01;
+pub(super) const MUL: u8 = 0x02;
+pub(super) const LT: u8 = 0x10;
+pub(super) const EQ: u8 = 0x14;
+pub(super) const ISZERO: u8 = 0x15;
+pub(super) const POP: u8 = 0x50;
+pub(super) const MLOAD: u8 = 0x51;
+pub(super) const MSTORE: u8 = 0x52;
+pub(super) const JUMP: u8 = 0x56;
+pub(super) const JUMPI: u8 = 0x57;
+pub(super) const JUMPDEST: u8 = 0x5b;
+pub(super) const PUSH0: u8 = 0x5f;
+pub(super) const PUSH1: u8 = 0x60;
+pub(super) const DUP1: u8 = 0x80;
+pub(super) const DUP2: u8 = 0x81;
+pub(super) const SWAP1: u8 = 0x90;
+
+/// The deepest stack item which `DUP` and `SWAP` can reach.
+pub(super) const MAX_STACK_DEPTH: usize = 16;
+
+/// An EVM instruction which is available as a Yul builtin function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Instruction {
+    pub(super) opcode: u8,
+    pub(super) inputs: usize,
+    pub(super) outputs: usize,
+}
+
+impl Instruction {
+    const fn new(opcode: u8, inputs: usize, outputs: usize) -> Self {
+        Self {
+            opcode,
+            inputs,
+            outputs,
+        }
+    }
+}
+
+/// Returns the instruction of the Yul builtin `name`.
+///
+/// `datasize`, `dataoffset` and `datacopy` aren't instructions, and are
+/// handled when the code is lowered.
+pub(super) fn instruction(name: &str) -> Option<Instruction> {
+    let inst = match name {
+        "stop" => Instruction::new(0x00, 0, 0),
+        "add" => Instruction::new(0x01, 2, 1),
+        "mul" => Instruction::new(0x02, 2, 1),
+        "sub" => Instruction::new(0x03, 2, 1),
+        "div" => Instruction::new(0x04, 2, 1),
+        "sdiv" => Instruction::new(0x05, 2, 1),
+        "mod" => Instruction::new(0x06, 2, 1),
+        "smod" => Instruction::new(0x07, 2, 1),
+        "addmod" => Instruction::new(0x08, 3, 1),
+        "mulmod" => Instruction::new(0x09, 3, 1),
+        "exp" => Instruction::new(0x0a, 2, 1),
+        "signextend" => Instruction::new(0x0b, 2, 1),
+        "lt" => Instruction::new(0x10, 2, 1),
+        "gt" => Instruction::new(0x11, 2, 1),
+        "slt" => Instruction::new(0x12, 2, 1),
+        "sgt" => Instruction::new(0x13, 2, 1),
+        "eq" => Instruction::new(0x14, 2, 1),
+        "iszero" => Instruction::new(0x15, 1, 1),
+        "and" => Instruction::new(0x16, 2, 1),
+        "or" => Instruction::new(0x17, 2, 1),
+        "xor" => Instruction::new(0x18, 2, 1),
+        "not" => Instruction::new(0x19, 1, 1),
+        "byte" => Instruction::new(0x1a, 2, 1),
+        "shl" => Instruction::new(0x1b, 2, 1),
+        "shr" => Instruction::new(0x1c, 2, 1),
+        "sar" => Instruction::new(0x1d, 2, 1),
+        "keccak256" => Instruction::new(0x20, 2, 1),
+        "address" => Instruction::new(0x30, 0, 1),
+        "balance" => Instruction::new(0x31, 1, 1),
+        "origin" => Instruction::new(0x32, 0, 1),
+        "caller" => Instruction::new(0x33, 0, 1),
+        "callvalue" => Instruction::new(0x34, 0, 1),
+        "calldataload" => Instruction::new(0x35, 1, 1),
+        "calldatasize" => Instruction::new(0x36, 0, 1),
+        "calldatacopy" => Instruction::new(0x37, 3, 0),
+        "codesize" => Instruction::new(0x38, 0, 1),
+        "codecopy" => Instruction::new(0x39, 3, 0),
+        "gasprice" => Instruction::new(0x3a, 0, 1),
+        "extcodesize" => Instruction::new(0x3b, 1, 1),
+        "extcodecopy" => Instruction::new(0x3c, 4, 0),
+        "returndatasize" => Instruction::new(0x3d, 0, 1),
+        "returndatacopy" => Instruction::new(0x3e, 3, 0),
+        "extcodehash" => Instruction::new(0x3f, 1, 1),
+        "blockhash" => Instruction::new(0x40, 1, 1),
+        "coinbase" => Instruction::new(0x41, 0, 1),
+        "timestamp" => Instruction::new(0x42, 0, 1),
+        "number" => Instruction::new(0x43, 0, 1),
+        "difficulty" | "prevrandao" => Instruction::new(0x44, 0, 1),
+        "gaslimit" => Instruction::new(0x45, 0, 1),
+        "chainid" => Instruction::new(0x46, 0, 1),
+        "selfbalance" => Instruction::new(0x47, 0, 1),
+        "basefee" => Instruction::new(0x48, 0, 1),
+        "blobhash" => Instruction::new(0x49, 1, 1),
+        "blobbasefee" => Instruction::new(0x4a, 0, 1),
+        "pop" => Instruction::new(0x50, 1, 0),
+        "mload" => Instruction::new(0x51, 1, 1),
+        "mstore" => Instruction::new(0x52, 2, 0),
+        "mstore8" => Instruction::new(0x53, 2, 0),
+        "sload" => Instruction::new(0x54, 1, 1),
+        "sstore" => Instruction::new(0x55, 2, 0),
+        "msize" => Instruction::new(0x59, 0, 1),
+        "gas" => Instruction::new(0x5a, 0, 1),
+        "tload" => Instruction::new(0x5c, 1, 1),
+        "tstore" => Instruction::new(0x5d, 2, 0),
+        "mcopy" => Instruction::new(0x5e, 3, 0),
+        "log0" => Instruction::new(0xa0, 2, 0),
+        "log1" => Instruction::new(0xa1, 3, 0),
+        "log2" => Instruction::new(0xa2, 4, 0),
+        "log3" => Instruction::new(0xa3, 5, 0),
+        "log4" => Instruction::new(0xa4, 6, 0),
+        "create" => Instruction::new(0xf0, 3, 1),
+        "call" => Instruction::new(0xf1, 7, 1),
+        "callcode" => Instruction::new(0xf2, 7, 1),
+        "return" => Instruction::new(0xf3, 2, 0),
+        "delegatecall" => Instruction::new(0xf4, 6, 1),
+        "create2" => Instruction::new(0xf5, 4, 1),
+        "staticcall" => Instruction::new(0xfa, 6, 1),
+        "revert" => Instruction::new(0xfd, 2, 0),
+        "invalid" => Instruction::new(0xfe, 0, 0),
+        "selfdestruct" => Instruction::new(0xff, 1, 0),
+        _ => return None,
+    };
+
+    Some(inst)
+}
@@ -0,0 +1,78 @@
+use serde_json::{json, Value};
+
+/// The default number of `runs` of the solc optimizer.
+const DEFAULT_RUNS: u32 = 200;
+
+/// The settings of the solc optimizer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OptimizerSettings {
+    pub enabled: bool,
+    /// How often the deployed code is expected to run. Lower values make the
+    /// code smaller, higher values make calls cheaper.
+    pub runs: u32,
+    /// The sequence of Yul optimizer steps to run instead of the default
+    /// sequence of solc, e.g. `"dhfoDgvulfnTUtnIf"`.
+    pub yul_steps: Option<String>,
+}
+
+impl Default for OptimizerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            runs: DEFAULT_RUNS,
+            yul_steps: None,
+        }
+    }
+}
+
+impl OptimizerSettings {
+    /// Returns the `optimizer` settings of the solc standard JSON input.
+    pub fn to_json(&self) -> Value {
+        let mut settings = json!({
+            "enabled": self.enabled,
+            "runs": self.runs,
+        });
+        if let Some(steps) = &self.yul_steps {
+            settings["details"] = json!({
+                "yul": true,
+                "yulDetails": { "optimizerSteps": steps },
+            });
+        }
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::OptimizerSettings;
+
+    #[test]
+    fn default_settings_json() {
+        assert_eq!(
+            OptimizerSettings::default().to_json(),
+            json!({ "enabled": true, "runs": 200 })
+        );
+    }
+
+    #[test]
+    fn yul_steps_json() {
+        let settings = OptimizerSettings {
+            enabled: false,
+            runs: 1,
+            yul_steps: Some("dhfoDgvulfnTUtnIf".into()),
+        };
+        assert_eq!(
+            settings.to_json(),
+            json!({
+                "enabled": false,
+                "runs": 1,
+                "details": {
+                    "yul": true,
+                    "yulDetails": { "optimizerSteps": "dhfoDgvulfnTUtnIf" },
+                },
+            })
+        );
+    }
+}
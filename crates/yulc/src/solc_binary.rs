@@ -0,0 +1,259 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use fe_common::EvmVersion;
+
+use crate::YulcError;
+
+/// A version of solc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SolcVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SolcVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses the output of `solc --version`, e.g.
+    /// `Version: 0.8.19+commit.7dd6d404.Linux.g++`.
+    pub fn parse(version_output: &str) -> Option<Self> {
+        let version = version_output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Version:"))?
+            .trim();
+        let end = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+
+        let numbers: Vec<u32> = version[..end]
+            .split('.')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        match numbers[..] {
+            [major, minor, patch] => Some(Self::new(major, minor, patch)),
+            _ => None,
+        }
+    }
+
+    /// Returns the first version of solc which supports the EVM version.
+    pub fn min_for(evm_version: EvmVersion) -> Self {
+        match evm_version {
+            EvmVersion::Constantinople | EvmVersion::Petersburg | EvmVersion::Istanbul => {
+                Self::new(0, 8, 0)
+            }
+            EvmVersion::Berlin => Self::new(0, 8, 5),
+            EvmVersion::London => Self::new(0, 8, 7),
+            EvmVersion::Paris => Self::new(0, 8, 18),
+            EvmVersion::Shanghai => Self::new(0, 8, 20),
+            EvmVersion::Cancun => Self::new(0, 8, 24),
+        }
+    }
+}
+
+impl fmt::Display for SolcVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A solc executable, which compiles standard JSON input read from stdin.
+#[derive(Debug, Clone)]
+pub struct SolcBinary {
+    path: PathBuf,
+    version: SolcVersion,
+}
+
+impl SolcBinary {
+    /// Runs `solc --version` with the executable at `path` to detect its
+    /// version. A path without a directory is looked up in `PATH`.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, YulcError> {
+        let path = path.into();
+        let output = Command::new(&path)
+            .arg("--version")
+            .output()
+            .map_err(|err| run_error(&path, err))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match SolcVersion::parse(&stdout) {
+            Some(version) if output.status.success() => Ok(Self { path, version }),
+            _ => Err(YulcError(format!(
+                "unable to detect the version of solc `{}`, which printed:\n{}",
+                path.display(),
+                stdout.trim()
+            ))),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn version(&self) -> SolcVersion {
+        self.version
+    }
+
+    /// Returns an error if this version of solc doesn't support the EVM
+    /// version.
+    pub fn check_evm_version(&self, evm_version: EvmVersion) -> Result<(), YulcError> {
+        let min_version = SolcVersion::min_for(evm_version);
+        if self.version < min_version {
+            return Err(YulcError(format!(
+                "solc {} doesn't support the `{evm_version}` EVM version, which requires solc {min_version} or later",
+                self.version
+            )));
+        }
+        Ok(())
+    }
+
+    /// Compiles standard JSON input, and returns the standard JSON output.
+    pub fn compile(&self, input: &str) -> Result<String, YulcError> {
+        let mut child = Command::new(&self.path)
+            .arg("--standard-json")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| run_error(&self.path, err))?;
+
+        // solc reads the whole input before it writes any output, so the input
+        // is written before the output is read.
+        child
+            .stdin
+            .take()
+            .expect("stdin of solc is piped")
+            .write_all(input.as_bytes())
+            .map_err(|err| run_error(&self.path, err))?;
+        let output = child
+            .wait_with_output()
+            .map_err(|err| run_error(&self.path, err))?;
+
+        if !output.status.success() {
+            return Err(YulcError(format!(
+                "solc `{}` failed with {}:\n{}",
+                self.path.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout).map_err(|_| {
+            YulcError(format!(
+                "solc `{}` printed invalid UTF-8",
+                self.path.display()
+            ))
+        })
+    }
+}
+
+fn run_error(path: &Path, err: io::Error) -> YulcError {
+    if err.kind() == io::ErrorKind::NotFound {
+        YulcError(format!(
+            "solc executable `{}` not found. Install solc, or compile with the built-in backend by not setting `--solc`, `FE_SOLC` or `solc` in fe.toml",
+            path.display()
+        ))
+    } else {
+        YulcError(format!("unable to run solc `{}`: {err}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe_common::EvmVersion;
+
+    use super::{SolcBinary, SolcVersion};
+
+    #[test]
+    fn parse_version() {
+        let output = "solc, the solidity compiler commandline interface\nVersion: 0.8.19+commit.7dd6d404.Linux.g++\n";
+        assert_eq!(SolcVersion::parse(output), Some(SolcVersion::new(0, 8, 19)));
+        assert_eq!(
+            SolcVersion::parse("Version: 0.8.24-nightly.2024.1.1+commit.1a2b3c4d"),
+            Some(SolcVersion::new(0, 8, 24))
+        );
+        assert_eq!(SolcVersion::parse("Version: 0.8"), None);
+        assert_eq!(SolcVersion::parse("solc 0.8.19"), None);
+    }
+
+    #[test]
+    fn check_evm_version() {
+        let solc = SolcBinary {
+            path: "solc".into(),
+            version: SolcVersion::new(0, 8, 20),
+        };
+        assert!(solc.check_evm_version(EvmVersion::Shanghai).is_ok());
+        assert_eq!(
+            solc.check_evm_version(EvmVersion::Cancun).unwrap_err().0,
+            "solc 0.8.20 doesn't support the `cancun` EVM version, which requires solc 0.8.24 or later"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn compile_with_binary() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        // A stand-in for solc, which prints a version and compiles any input
+        // to the same output.
+        let dir = std::env::temp_dir().join(format!("fe-yulc-solc-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("solc");
+        fs::write(
+            &path,
+            r#"#!/bin/sh
+if [ "$1" = "--version" ]; then
+  echo "Version: 0.8.20+commit.a1b79de6.Linux.g++"
+else
+  cat > /dev/null
+  echo '{"contracts":{"input.yul":{"Foo":{"evm":{"bytecode":{"object":"6000"},"deployedBytecode":{"object":"00"}}}}}}'
+fi
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let solc = SolcBinary::new(&path).unwrap();
+        assert_eq!(solc.version(), SolcVersion::new(0, 8, 20));
+        let compiled = crate::compile_single_contract_with_solc(
+            &solc,
+            "Foo",
+            "object \"Foo\" { code { } }",
+            &Default::default(),
+            EvmVersion::Shanghai,
+            true,
+        )
+        .unwrap();
+        assert_eq!(compiled.bytecode, "6000");
+        assert_eq!(compiled.runtime_bytecode, "00");
+        assert!(crate::compile_single_contract_with_solc(
+            &solc,
+            "Foo",
+            "",
+            &Default::default(),
+            EvmVersion::Cancun,
+            true,
+        )
+        .is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_binary() {
+        let err = SolcBinary::new("/nonexistent/solc").unwrap_err();
+        assert!(err
+            .0
+            .starts_with("solc executable `/nonexistent/solc` not found"));
+    }
+}
@@ -0,0 +1,152 @@
+//! Source maps in the format of solc, which map each instruction of the
+//! bytecode to a span of a source file.
+//!
+//! The spans come from `@src <file>:<start>:<end>` comments in the Yul code,
+//! which attribute the code that follows them to the byte range
+//! `start..end` of the source file with the index `file`. `@src -1:-1:-1`
+//! marks the code that follows it as generated.
+
+/// A span of a source file, as given by an `@src` comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    pub file: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Returns `true` if `text`, which the Fe code generator places where an
+/// expression statement is expected, is a comment.
+pub(crate) fn is_comment(text: &str) -> bool {
+    text.starts_with("/*")
+}
+
+/// Parses a `/** @src ... */` comment, returning `Some(None)` for generated
+/// code.
+pub(crate) fn parse_src_comment(text: &str) -> Option<Option<SourceLocation>> {
+    let src = text
+        .strip_prefix("/**")?
+        .strip_suffix("*/")?
+        .trim()
+        .strip_prefix("@src ")?;
+    if src == "-1:-1:-1" {
+        return Some(None);
+    }
+
+    let mut parts = src.split(':');
+    let location = SourceLocation {
+        file: parts.next()?.parse().ok()?,
+        start: parts.next()?.parse().ok()?,
+        end: parts.next()?.parse().ok()?,
+    };
+    if parts.next().is_some() || location.end < location.start {
+        return None;
+    }
+    Some(Some(location))
+}
+
+/// Returns the solc source map of instructions with the given locations.
+///
+/// Each entry is `start:length:file:jump`, where a field is left empty if
+/// it's the same as in the previous entry, and trailing empty fields are
+/// dropped. Generated code has the location `-1:-1:-1`.
+pub(crate) fn compress(locations: &[Option<SourceLocation>]) -> String {
+    let mut prev: Option<[String; 4]> = None;
+    let entries: Vec<_> = locations
+        .iter()
+        .map(|location| {
+            let fields = match location {
+                Some(loc) => [
+                    loc.start.to_string(),
+                    (loc.end - loc.start).to_string(),
+                    loc.file.to_string(),
+                    "-".to_string(),
+                ],
+                None => ["-1", "-1", "-1", "-"].map(ToString::to_string),
+            };
+
+            let mut entry: Vec<&str> = fields
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| match &prev {
+                    Some(prev) if prev[idx] == *field => "",
+                    _ => field.as_str(),
+                })
+                .collect();
+            while entry.last() == Some(&"") {
+                entry.pop();
+            }
+            let entry = entry.join(":");
+            prev = Some(fields);
+            entry
+        })
+        .collect();
+    entries.join(";")
+}
+
+/// Returns the locations of the instructions of a solc source map, which
+/// `compress` returns.
+pub fn parse_source_map(source_map: &str) -> Result<Vec<Option<SourceLocation>>, String> {
+    if source_map.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut fields = ["-1", "-1", "-1"].map(ToString::to_string);
+    source_map
+        .split(';')
+        .map(|entry| {
+            for (field, value) in fields.iter_mut().zip(entry.split(':')) {
+                if !value.is_empty() {
+                    *field = value.to_string();
+                }
+            }
+            if fields[2] == "-1" {
+                return Ok(None);
+            }
+
+            let invalid = |_| format!("invalid source map entry `{entry}`");
+            let start: usize = fields[0].parse().map_err(invalid)?;
+            let len: usize = fields[1].parse().map_err(invalid)?;
+            Ok(Some(SourceLocation {
+                file: fields[2].parse().map_err(invalid)?,
+                start,
+                end: start + len,
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, parse_source_map, parse_src_comment, SourceLocation};
+
+    #[test]
+    fn parse_comments() {
+        assert_eq!(
+            parse_src_comment("/** @src 3:10:25 */"),
+            Some(Some(SourceLocation {
+                file: 3,
+                start: 10,
+                end: 25
+            }))
+        );
+        assert_eq!(parse_src_comment("/** @src -1:-1:-1 */"), Some(None));
+        assert_eq!(parse_src_comment("/** @src 3:25:10 */"), None);
+        assert_eq!(parse_src_comment("/** @use-src 3:\"a.fe\" */"), None);
+    }
+
+    #[test]
+    fn compress_locations() {
+        let loc = |file, start, end| Some(SourceLocation { file, start, end });
+        let locations = [
+            loc(0, 10, 25),
+            loc(0, 10, 25),
+            loc(0, 30, 35),
+            loc(1, 30, 35),
+            None,
+            None,
+            loc(1, 2, 4),
+        ];
+        assert_eq!(compress(&locations), "10:15:0:-;;30:5;::1;-1:-1:-1;;2:2:1");
+        assert_eq!(parse_source_map(&compress(&locations)).unwrap(), locations);
+    }
+}